@@ -1,7 +1,6 @@
 use num_bigint::BigUint;
 use num_traits::{One, Zero};
 use serde::{Deserialize, Serialize};
-use std::cmp::Ordering::{Equal, Greater, Less};
 use std::ops::{Add, Mul, Sub};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -126,15 +125,17 @@ impl Add for DyadicRational {
 
 impl PartialOrd for DyadicRational {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for DyadicRational {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
         // Make sure self.exponent is the larger exponent
         if self.exponent < other.exponent {
             // Flip result when this function is called recursively with flipped
             // arguments.
-            return match other.partial_cmp(self) {
-                Some(Greater) => Some(Less),
-                Some(Less) => Some(Greater),
-                _ => Some(Equal),
-            };
+            return other.cmp(self).reverse();
         }
 
         // Put on same denominator
@@ -149,13 +150,7 @@ impl PartialOrd for DyadicRational {
             shift -= 1;
         }
 
-        self.mantissa.partial_cmp(&shifted_other_mantissa)
-    }
-}
-
-impl Ord for DyadicRational {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.partial_cmp(other).unwrap()
+        self.mantissa.cmp(&shifted_other_mantissa)
     }
 }
 
@@ -1,7 +1,10 @@
 #![deny(clippy::shadow_unrelated)]
 pub mod amount;
+pub mod parallel;
 pub mod shared_math;
+#[cfg(feature = "std")]
 pub mod test_shared;
+#[cfg(feature = "std")]
 pub mod timing_reporter;
 pub mod util_types;
 pub mod utils;
@@ -0,0 +1,182 @@
+//! Parallel-iterator support behind the `parallel` feature.
+//!
+//! With the feature enabled (the default) this re-exports the rayon entry
+//! points used throughout the crate. Without it, the same names resolve to
+//! sequential stand-ins built on plain [`Iterator`]s, so the crate -- in
+//! particular the FRI verifier -- compiles and runs on targets without
+//! threads, such as wasm32 in browsers and smart-contract runtimes.
+//!
+//! The module also hosts the crate-wide [`Determinism`] mode, which pins
+//! down the scheduling of the prover's commit-phase loops for performance
+//! debugging and differential testing.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+pub mod prelude {
+    #[cfg(feature = "parallel")]
+    pub use rayon::iter::{
+        IndexedParallelIterator, IntoParallelIterator, IntoParallelRefIterator,
+        IntoParallelRefMutIterator, ParallelIterator,
+    };
+
+    #[cfg(not(feature = "parallel"))]
+    pub use super::sequential::{
+        IndexedParallelIterator, IntoParallelIterator, IntoParallelRefIterator,
+        IntoParallelRefMutIterator, ParallelIterator,
+    };
+}
+
+#[cfg(not(feature = "parallel"))]
+mod sequential {
+    /// Sequential stand-in for rayon's `IntoParallelIterator`.
+    pub trait IntoParallelIterator {
+        type Item;
+        type Iter: Iterator<Item = Self::Item>;
+
+        fn into_par_iter(self) -> Self::Iter;
+    }
+
+    impl<I: IntoIterator> IntoParallelIterator for I {
+        type Item = I::Item;
+        type Iter = I::IntoIter;
+
+        fn into_par_iter(self) -> Self::Iter {
+            self.into_iter()
+        }
+    }
+
+    /// Sequential stand-in for rayon's `IntoParallelRefIterator`.
+    pub trait IntoParallelRefIterator<'data> {
+        type Item;
+        type Iter: Iterator<Item = Self::Item>;
+
+        fn par_iter(&'data self) -> Self::Iter;
+    }
+
+    impl<'data, I: 'data + ?Sized> IntoParallelRefIterator<'data> for I
+    where
+        &'data I: IntoIterator,
+    {
+        type Item = <&'data I as IntoIterator>::Item;
+        type Iter = <&'data I as IntoIterator>::IntoIter;
+
+        fn par_iter(&'data self) -> Self::Iter {
+            self.into_iter()
+        }
+    }
+
+    /// Sequential stand-in for rayon's `IntoParallelRefMutIterator`.
+    pub trait IntoParallelRefMutIterator<'data> {
+        type Item;
+        type Iter: Iterator<Item = Self::Item>;
+
+        fn par_iter_mut(&'data mut self) -> Self::Iter;
+    }
+
+    impl<'data, I: 'data + ?Sized> IntoParallelRefMutIterator<'data> for I
+    where
+        &'data mut I: IntoIterator,
+    {
+        type Item = <&'data mut I as IntoIterator>::Item;
+        type Iter = <&'data mut I as IntoIterator>::IntoIter;
+
+        fn par_iter_mut(&'data mut self) -> Self::Iter {
+            self.into_iter()
+        }
+    }
+
+    /// Sequential stand-in for rayon's `ParallelIterator`. The adapter
+    /// methods the crate uses (`map`, `sum`, `collect`, ...) all exist on
+    /// [`Iterator`] already, so nothing needs a definition here.
+    pub trait ParallelIterator: Iterator {}
+
+    impl<I: Iterator> ParallelIterator for I {}
+
+    /// Sequential stand-in for rayon's `IndexedParallelIterator`, carrying
+    /// the one adapter without a std equivalent.
+    pub trait IndexedParallelIterator: Iterator + Sized {
+        fn collect_into_vec(self, target: &mut Vec<Self::Item>) {
+            target.clear();
+            target.extend(self);
+        }
+    }
+
+    impl<I: Iterator> IndexedParallelIterator for I {}
+}
+
+/// How the crate schedules its data-parallel loops; see [`set_determinism`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Determinism {
+    /// Let the thread pool schedule freely -- the default and the fastest.
+    /// Results are identical across runs, but work stealing makes memory
+    /// access order and allocation patterns differ from run to run.
+    #[default]
+    None = 0,
+    /// Run every loop sequentially on the calling thread.
+    Sequential = 1,
+    /// Process loops as fixed-size chunks, each sequential internally and
+    /// collected into its own buffer, so memory access order within every
+    /// chunk and the allocation pattern are the same in every run. The
+    /// chunks still run concurrently.
+    FixedChunks = 2,
+}
+
+/// The chunk size [`Determinism::FixedChunks`] splits loops into.
+const FIXED_CHUNK_SIZE: usize = 1 << 12;
+
+static DETERMINISM: AtomicU8 = AtomicU8::new(0);
+
+/// Set the crate-wide scheduling mode. This affects the loops the prover's
+/// commit phase runs -- leaf hashing, folding, and Merkle-node hashing.
+/// Outputs are identical in every mode; only scheduling, memory access
+/// order, and allocation patterns change.
+pub fn set_determinism(determinism: Determinism) {
+    DETERMINISM.store(determinism as u8, Ordering::Relaxed);
+}
+
+/// The current crate-wide scheduling mode.
+pub fn determinism() -> Determinism {
+    match DETERMINISM.load(Ordering::Relaxed) {
+        1 => Determinism::Sequential,
+        2 => Determinism::FixedChunks,
+        _ => Determinism::None,
+    }
+}
+
+/// Map `f` over `0..length` into a vector, scheduled according to the
+/// crate-wide [`Determinism`] mode.
+pub fn map_collect_range<U, F>(length: usize, f: F) -> Vec<U>
+where
+    U: Send,
+    F: Fn(usize) -> U + Send + Sync,
+{
+    use crate::parallel::prelude::*;
+
+    match determinism() {
+        Determinism::None => (0..length).into_par_iter().map(f).collect(),
+        Determinism::Sequential => (0..length).map(f).collect(),
+        Determinism::FixedChunks => {
+            let chunk_starts: Vec<usize> = (0..length).step_by(FIXED_CHUNK_SIZE).collect();
+            let chunks: Vec<Vec<U>> = chunk_starts
+                .into_par_iter()
+                .map(|start| {
+                    (start..(start + FIXED_CHUNK_SIZE).min(length))
+                        .map(&f)
+                        .collect()
+                })
+                .collect();
+            chunks.into_iter().flatten().collect()
+        }
+    }
+}
+
+/// Map `f` over a slice into a vector, scheduled according to the
+/// crate-wide [`Determinism`] mode.
+pub fn map_collect<T, U, F>(items: &[T], f: F) -> Vec<U>
+where
+    T: Sync,
+    U: Send,
+    F: Fn(&T) -> U + Send + Sync,
+{
+    map_collect_range(items.len(), |i| f(&items[i]))
+}
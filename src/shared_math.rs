@@ -1,11 +1,19 @@
 pub mod b_field_element;
+pub mod data_availability;
+pub mod extension_field_element;
 pub mod fri;
+#[cfg(feature = "std")]
 pub mod mpolynomial;
 pub mod ntt;
 pub mod other;
 pub mod polynomial;
+pub mod poseidon2;
+pub mod rational_function;
 pub mod rescue_prime_digest;
+pub mod rescue_prime_generic;
 pub mod rescue_prime_regular;
+#[cfg(feature = "std")]
 pub mod stark;
+pub mod tip5;
 pub mod traits;
 pub mod x_field_element;
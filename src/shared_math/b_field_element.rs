@@ -163,7 +163,7 @@ impl BFieldElement {
     }
 
     pub fn legendre_symbol(&self) -> i8 {
-        let elem = self.mod_pow((Self::QUOTIENT - 1) as u64 / 2).0;
+        let elem = self.mod_pow((Self::QUOTIENT - 1) / 2).0;
 
         // Ugly hack to force a result in {-1,0,1}
         if elem == Self::QUOTIENT - 1 {
@@ -188,7 +188,7 @@ impl BFieldElement {
         // expressions, so we have to accept a vector as output.
         // Cf. https://github.com/rust-lang/rust/issues/76560
         let bytes_per_element = 7;
-        let output_length = N / 7 + if N % 7 == 0 { 0 } else { 1 };
+        let output_length = N / 7 + if N.is_multiple_of(7) { 0 } else { 1 };
         let full_iterations = N / 7;
         let mut ret: Vec<BFieldElement> = Vec::with_capacity(output_length);
         for i in 0..full_iterations {
@@ -319,7 +319,6 @@ impl From<[u8; 8]> for BFieldElement {
 }
 
 impl Inverse for BFieldElement {
-    #[must_use]
     #[inline]
     fn inverse(&self) -> Self {
         let x = *self;
@@ -523,8 +522,8 @@ impl ModPowU64 for BFieldElement {
 impl PrimitiveRootOfUnity for BFieldElement {
     fn primitive_root_of_unity(n: u64) -> Option<BFieldElement> {
         // Check if n is one of the values for which we have pre-calculated roots
-        if PRIMITIVE_ROOTS.contains_key(&(n as u64)) {
-            Some(BFieldElement::new(PRIMITIVE_ROOTS[&(n as u64)]))
+        if PRIMITIVE_ROOTS.contains_key(&{ n }) {
+            Some(BFieldElement::new(PRIMITIVE_ROOTS[&{ n }]))
         } else if n <= 1 {
             Some(BFieldElement::one())
         } else {
@@ -861,7 +860,7 @@ mod b_prime_field_element_test {
         for i in 0..test_iterations {
             let rands: Vec<BFieldElement> = random_elements(i);
             let rands_inv: Vec<BFieldElement> = BFieldElement::batch_inversion(rands.clone());
-            assert_eq!(i as usize, rands_inv.len());
+            assert_eq!(i, rands_inv.len());
             for (mut rand, rand_inv) in izip!(rands, rands_inv) {
                 assert!((rand * rand_inv).is_one());
                 assert!((rand_inv * rand).is_one());
@@ -1036,7 +1035,7 @@ mod b_prime_field_element_test {
         let b: Polynomial<BFieldElement> = Polynomial::new(vec![
             bfield_elem!(2),
             bfield_elem!(5),
-            bfield_elem!(BFieldElement::MAX as u64),
+            bfield_elem!(BFieldElement::MAX),
         ]);
 
         let expected: Polynomial<BFieldElement> =
@@ -1083,8 +1082,8 @@ mod b_prime_field_element_test {
                 None => println!("Found no primitive root of unity for n = {}", power),
             };
             let root = root_result.unwrap();
-            assert!(root.mod_pow(power as u64).is_one());
-            assert!(!root.mod_pow(power as u64 / 2).is_one());
+            assert!(root.mod_pow(power).is_one());
+            assert!(!root.mod_pow(power / 2).is_one());
         }
     }
 
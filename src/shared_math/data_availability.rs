@@ -0,0 +1,311 @@
+use std::error::Error;
+use std::fmt;
+
+use super::b_field_element::BFieldElement;
+use super::fri::Fri;
+use super::other::roundup_npo2;
+use super::polynomial::Polynomial;
+use super::rescue_prime_digest::Digest;
+use super::traits::PrimitiveRootOfUnity;
+use super::x_field_element::XFieldElement;
+use crate::util_types::algebraic_hasher::{AlgebraicHasher, Hashable};
+use crate::util_types::merkle_tree::{domain_separated_leaf_digest, MerkleTree};
+use crate::util_types::proof_stream::ProofStream;
+
+/// Number of blob bytes packed into each base field element. Seven bytes
+/// always fit since the field modulus is larger than 2^56.
+pub const BYTES_PER_ELEMENT: usize = 7;
+
+#[derive(PartialEq, Eq, Debug)]
+pub enum BlobProofError {
+    EmptyBlob,
+    BadChunkProof,
+    WrongMerkleRootInFriProof,
+}
+
+impl Error for BlobProofError {}
+
+impl fmt::Display for BlobProofError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Blob proof error: {:?}", self)
+    }
+}
+
+/// The public commitment to a blob: the Merkle root of the Reed-Solomon
+/// extended codeword, along with the parameters needed to re-derive the
+/// evaluation domain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlobCommitment {
+    pub merkle_root: Digest,
+    pub blob_byte_length: usize,
+    pub codeword_length: usize,
+}
+
+/// An opening of a single codeword position against a [`BlobCommitment`],
+/// as used for data-availability sampling.
+#[derive(Debug, Clone)]
+pub struct BlobChunkProof {
+    pub index: usize,
+    pub value: XFieldElement,
+    pub auth_path: Vec<Digest>,
+}
+
+/// A committed blob retained by the prover. Holds the full codeword and its
+/// Merkle tree so that chunk openings can be produced after committing.
+#[derive(Debug, Clone)]
+pub struct CommittedBlob<H: AlgebraicHasher> {
+    pub commitment: BlobCommitment,
+    codeword: Vec<XFieldElement>,
+    merkle_tree: MerkleTree<H>,
+}
+
+/// Ties together byte encoding, Reed-Solomon extension over a `FriDomain`,
+/// Merkle commitment, and FRI low-degreeness -- the full data-availability
+/// workflow that otherwise requires stitching the individual modules
+/// together by hand.
+#[derive(Debug, Clone)]
+pub struct BlobProver<H> {
+    pub fri: Fri<H>,
+    data_length: usize,
+}
+
+/// Pack a byte blob into base field elements, [`BYTES_PER_ELEMENT`] bytes
+/// per element, little endian. The final element may be partially filled.
+pub fn encode_blob(blob: &[u8]) -> Vec<BFieldElement> {
+    blob.chunks(BYTES_PER_ELEMENT)
+        .map(|chunk| {
+            let mut bytes = [0u8; 8];
+            bytes[..chunk.len()].copy_from_slice(chunk);
+            BFieldElement::new(u64::from_le_bytes(bytes))
+        })
+        .collect()
+}
+
+/// Inverse of [`encode_blob`]. The original byte length must be supplied
+/// since the last element may contain fewer than [`BYTES_PER_ELEMENT`] bytes.
+pub fn decode_blob(elements: &[BFieldElement], blob_byte_length: usize) -> Vec<u8> {
+    let mut blob: Vec<u8> = Vec::with_capacity(elements.len() * BYTES_PER_ELEMENT);
+    for element in elements {
+        blob.extend_from_slice(&element.value().to_le_bytes()[..BYTES_PER_ELEMENT]);
+    }
+    blob.truncate(blob_byte_length);
+    blob
+}
+
+impl<H> BlobProver<H>
+where
+    H: AlgebraicHasher,
+{
+    /// Derive FRI parameters for blobs of (up to) `blob_byte_length` bytes.
+    /// The data length is the number of packed field elements rounded up to
+    /// the next power of two, and the codeword is `expansion_factor` times
+    /// longer than that.
+    pub fn new(
+        blob_byte_length: usize,
+        expansion_factor: usize,
+        colinearity_checks_count: usize,
+    ) -> Self {
+        assert!(blob_byte_length > 0, "Blob must be non-empty");
+        let element_count = blob_byte_length.div_ceil(BYTES_PER_ELEMENT);
+        let data_length = roundup_npo2(element_count as u64) as usize;
+        let codeword_length = data_length * expansion_factor;
+        let omega = BFieldElement::primitive_root_of_unity(codeword_length as u64)
+            .expect("Codeword length must divide the order of the multiplicative group");
+        let fri: Fri<H> = Fri::new(
+            BFieldElement::generator(),
+            omega,
+            codeword_length,
+            expansion_factor,
+            colinearity_checks_count,
+            2,
+        )
+        .expect("FRI parameters derived from the blob length must be valid");
+        Self { fri, data_length }
+    }
+
+    /// Encode the blob, Reed-Solomon extend it over the FRI domain, and
+    /// commit to the resulting codeword with a Merkle tree.
+    ///
+    /// The packed field elements are interpreted as the coefficients of a
+    /// polynomial of degree less than the data length; the codeword is its
+    /// evaluation over the coset domain. The Merkle leaves coincide with
+    /// those of the first FRI round, so the commitment root equals the first
+    /// root on a FRI proof stream for this codeword.
+    pub fn commit(&self, blob: &[u8]) -> Result<CommittedBlob<H>, Box<dyn Error>> {
+        if blob.is_empty() {
+            return Err(Box::new(BlobProofError::EmptyBlob));
+        }
+        assert!(
+            encode_blob(blob).len() <= self.data_length,
+            "Blob must fit in the data length set in the BlobProver object"
+        );
+
+        let coefficients: Vec<XFieldElement> =
+            encode_blob(blob).into_iter().map(|b| b.lift()).collect();
+        let codeword = self.fri.domain.x_evaluate(&Polynomial::new(coefficients));
+
+        let leaf_digests: Vec<Digest> = codeword
+            .iter()
+            .map(|x| domain_separated_leaf_digest::<H>(&x.to_sequence()))
+            .collect();
+        let merkle_tree: MerkleTree<H> = MerkleTree::from_digests(&leaf_digests);
+
+        let commitment = BlobCommitment {
+            merkle_root: merkle_tree.get_root(),
+            blob_byte_length: blob.len(),
+            codeword_length: codeword.len(),
+        };
+
+        Ok(CommittedBlob {
+            commitment,
+            codeword,
+            merkle_tree,
+        })
+    }
+
+    /// Produce a FRI proof that the committed codeword is of low degree,
+    /// i.e. that the blob is available and correctly Reed-Solomon encoded.
+    pub fn prove_availability(
+        &self,
+        committed_blob: &CommittedBlob<H>,
+        proof_stream: &mut ProofStream,
+    ) -> Result<(), Box<dyn Error>> {
+        self.fri.prove(&committed_blob.codeword, proof_stream)?;
+        Ok(())
+    }
+
+    /// Verify a FRI availability proof against a blob commitment. Checks
+    /// both low-degreeness and that the codeword the FRI prover committed to
+    /// is the one behind the blob commitment's Merkle root.
+    pub fn verify_availability(
+        &self,
+        commitment: &BlobCommitment,
+        proof_stream: &mut ProofStream,
+    ) -> Result<(), Box<dyn Error>> {
+        let first_root: Digest = proof_stream.dequeue(Digest::BYTES)?;
+        if first_root != commitment.merkle_root {
+            return Err(Box::new(BlobProofError::WrongMerkleRootInFriProof));
+        }
+
+        proof_stream.set_index(0);
+        self.fri.verify(proof_stream)?;
+        Ok(())
+    }
+}
+
+impl<H> CommittedBlob<H>
+where
+    H: AlgebraicHasher,
+{
+    /// Open the codeword at the given indices for data-availability sampling.
+    pub fn open_chunks(&self, indices: &[usize]) -> Vec<BlobChunkProof> {
+        indices
+            .iter()
+            .map(|&index| BlobChunkProof {
+                index,
+                value: self.codeword[index],
+                auth_path: self.merkle_tree.get_authentication_path(index),
+            })
+            .collect()
+    }
+
+    pub fn codeword(&self) -> &[XFieldElement] {
+        &self.codeword
+    }
+}
+
+impl BlobChunkProof {
+    /// Verify this chunk opening against a blob commitment.
+    pub fn verify<H: AlgebraicHasher>(
+        &self,
+        commitment: &BlobCommitment,
+    ) -> Result<(), Box<dyn Error>> {
+        let leaf_hash = domain_separated_leaf_digest::<H>(&self.value.to_sequence());
+        let valid = MerkleTree::<H>::verify_authentication_path_from_leaf_hash(
+            commitment.merkle_root,
+            self.index as u32,
+            leaf_hash,
+            self.auth_path.clone(),
+        );
+        if valid {
+            Ok(())
+        } else {
+            Err(Box::new(BlobProofError::BadChunkProof))
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod data_availability_tests {
+    use rand::RngCore;
+
+    use super::*;
+    use crate::test_shared::corrupt_digest;
+
+    fn random_blob(byte_length: usize) -> Vec<u8> {
+        let mut blob = vec![0u8; byte_length];
+        rand::thread_rng().fill_bytes(&mut blob);
+        blob
+    }
+
+    #[test]
+    fn encode_decode_blob_test() {
+        for byte_length in [1, 6, 7, 8, 100, 1000] {
+            let blob = random_blob(byte_length);
+            let encoding = encode_blob(&blob);
+            assert_eq!(blob, decode_blob(&encoding, byte_length));
+        }
+    }
+
+    #[test]
+    fn blob_commitment_and_availability_proof_test() {
+        type H = blake3::Hasher;
+
+        let blob_byte_length = 1000;
+        let expansion_factor = 4;
+        let colinearity_checks_count = 6;
+        let prover: BlobProver<H> =
+            BlobProver::new(blob_byte_length, expansion_factor, colinearity_checks_count);
+
+        let blob = random_blob(blob_byte_length);
+        let committed_blob = prover.commit(&blob).unwrap();
+        let commitment = committed_blob.commitment.clone();
+        assert_eq!(blob_byte_length, commitment.blob_byte_length);
+
+        // Prove and verify availability
+        let mut proof_stream = ProofStream::default();
+        prover
+            .prove_availability(&committed_blob, &mut proof_stream)
+            .unwrap();
+        assert!(prover
+            .verify_availability(&commitment, &mut proof_stream)
+            .is_ok());
+
+        // Negative: Proof for a different blob must not verify against this commitment
+        let other_blob = random_blob(blob_byte_length);
+        let other_committed_blob = prover.commit(&other_blob).unwrap();
+        let mut other_proof_stream = ProofStream::default();
+        prover
+            .prove_availability(&other_committed_blob, &mut other_proof_stream)
+            .unwrap();
+        assert!(prover
+            .verify_availability(&commitment, &mut other_proof_stream)
+            .is_err());
+
+        // Open and verify some chunks
+        let indices = [0, 1, 17, commitment.codeword_length - 1];
+        let chunk_proofs = committed_blob.open_chunks(&indices);
+        for chunk_proof in chunk_proofs.iter() {
+            assert!(chunk_proof.verify::<H>(&commitment).is_ok());
+        }
+
+        // Negative: Chunk proofs must not verify against a corrupted root
+        let bad_commitment = BlobCommitment {
+            merkle_root: corrupt_digest(&commitment.merkle_root),
+            ..commitment
+        };
+        for chunk_proof in chunk_proofs.iter() {
+            assert!(chunk_proof.verify::<H>(&bad_commitment).is_err());
+        }
+    }
+}
@@ -0,0 +1,479 @@
+use num_traits::{One, Zero};
+use rand::Rng;
+use rand_distr::{Distribution, Standard};
+use serde::{Deserialize, Serialize};
+use serde_big_array::BigArray;
+use std::fmt::Display;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use super::rescue_prime_digest::Digest;
+use crate::shared_math::b_field_element::BFieldElement;
+use crate::shared_math::polynomial::Polynomial;
+use crate::shared_math::traits::{CyclicGroupGenerator, FiniteField, ModPowU32, ModPowU64, New};
+use crate::shared_math::traits::{FromVecu8, Inverse, PrimitiveRootOfUnity};
+
+/// An extension field of the base field of configurable degree, reduced
+/// modulo `x^DEGREE - 7`.
+///
+/// That modulus is irreducible over the base field for degrees 2 and 4 --
+/// 7 is neither a square nor of the form `-4c^4` -- which are the two
+/// degrees this type is meant for: a cheaper challenge field than
+/// [`XFieldElement`] when less soundness per query suffices, and a larger
+/// one when more is wanted. Degree 3 keeps its dedicated implementation in
+/// [`XFieldElement`], with its own modulus and an optimized multiplication.
+///
+/// [`XFieldElement`]: crate::shared_math::x_field_element::XFieldElement
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Hash, Serialize, Deserialize)]
+pub struct ExtensionFieldElement<const DEGREE: usize> {
+    #[serde(with = "BigArray")]
+    pub coefficients: [BFieldElement; DEGREE],
+}
+
+/// The degree-2 extension field, for when soundness per query is worth
+/// trading against arithmetic cost.
+pub type QuadFieldElement = ExtensionFieldElement<2>;
+
+/// The degree-4 extension field, for when more soundness per query is
+/// worth paying extra arithmetic for.
+pub type QuarticFieldElement = ExtensionFieldElement<4>;
+
+impl<const DEGREE: usize> Default for ExtensionFieldElement<DEGREE> {
+    fn default() -> Self {
+        Self::one()
+    }
+}
+
+impl<const DEGREE: usize> Sum for ExtensionFieldElement<DEGREE> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.reduce(|a, b| a + b).unwrap_or_else(Self::zero)
+    }
+}
+
+impl<const DEGREE: usize> From<ExtensionFieldElement<DEGREE>> for Polynomial<BFieldElement> {
+    fn from(item: ExtensionFieldElement<DEGREE>) -> Self {
+        Self {
+            coefficients: item.coefficients.to_vec(),
+        }
+    }
+}
+
+impl<const DEGREE: usize> From<Polynomial<BFieldElement>> for ExtensionFieldElement<DEGREE> {
+    fn from(poly: Polynomial<BFieldElement>) -> Self {
+        let (_, rem) = poly.divide(Self::modulus_polynomial());
+        let mut coefficients = [BFieldElement::zero(); DEGREE];
+
+        for i in 0..rem.degree() + 1 {
+            coefficients[i as usize] = rem.coefficients[i as usize];
+        }
+
+        Self { coefficients }
+    }
+}
+
+impl<const DEGREE: usize> ExtensionFieldElement<DEGREE> {
+    /// The constant term of the modulus, negated: `x^DEGREE = 7` in this
+    /// field.
+    const W: u64 = 7;
+
+    #[inline]
+    pub fn new(coefficients: [BFieldElement; DEGREE]) -> Self {
+        Self { coefficients }
+    }
+
+    #[inline]
+    pub fn new_u64(coeffs: [u64; DEGREE]) -> Self {
+        Self {
+            coefficients: coeffs.map(BFieldElement::new),
+        }
+    }
+
+    #[inline]
+    pub fn new_const(element: BFieldElement) -> Self {
+        let mut coefficients = [BFieldElement::zero(); DEGREE];
+        coefficients[0] = element;
+        Self { coefficients }
+    }
+
+    /// The modulus `x^DEGREE - 7` this field reduces by.
+    #[inline]
+    pub fn modulus_polynomial() -> Polynomial<BFieldElement> {
+        let mut coefficients = vec![BFieldElement::zero(); DEGREE + 1];
+        coefficients[0] = -BFieldElement::new(Self::W);
+        coefficients[DEGREE] = BFieldElement::one();
+        Polynomial::new(coefficients)
+    }
+
+    pub fn unlift(&self) -> Option<BFieldElement> {
+        if self.coefficients[1..].iter().all(|c| c.is_zero()) {
+            Some(self.coefficients[0])
+        } else {
+            None
+        }
+    }
+
+    pub fn sample(digest: &Digest) -> Self {
+        let elements = digest.values();
+        let mut coefficients = [BFieldElement::zero(); DEGREE];
+        coefficients.copy_from_slice(&elements[0..DEGREE]);
+        Self { coefficients }
+    }
+}
+
+impl<const DEGREE: usize> Inverse for ExtensionFieldElement<DEGREE> {
+    fn inverse(&self) -> Self {
+        let self_as_poly: Polynomial<BFieldElement> = self.to_owned().into();
+        let (_, a, _) = Polynomial::<BFieldElement>::xgcd(self_as_poly, Self::modulus_polynomial());
+        a.into()
+    }
+}
+
+impl<const DEGREE: usize> PrimitiveRootOfUnity for ExtensionFieldElement<DEGREE> {
+    fn primitive_root_of_unity(n: u64) -> Option<Self> {
+        let b_root = BFieldElement::primitive_root_of_unity(n);
+        b_root.map(Self::new_const)
+    }
+}
+
+impl<const DEGREE: usize> Distribution<ExtensionFieldElement<DEGREE>> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> ExtensionFieldElement<DEGREE> {
+        let mut coefficients = [BFieldElement::zero(); DEGREE];
+        for coefficient in coefficients.iter_mut() {
+            *coefficient = rng.gen::<BFieldElement>();
+        }
+        ExtensionFieldElement { coefficients }
+    }
+}
+
+impl<const DEGREE: usize> CyclicGroupGenerator for ExtensionFieldElement<DEGREE> {
+    fn get_cyclic_group_elements(&self, max: Option<usize>) -> Vec<Self> {
+        let mut val = *self;
+        let mut ret: Vec<Self> = vec![Self::one()];
+
+        loop {
+            ret.push(val);
+            val *= *self;
+            if val.is_one() || max.is_some() && ret.len() >= max.unwrap() {
+                break;
+            }
+        }
+        ret
+    }
+}
+
+impl<const DEGREE: usize> Display for ExtensionFieldElement<DEGREE> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let coefficients: Vec<String> = self
+            .coefficients
+            .iter()
+            .map(|coefficient| coefficient.to_string())
+            .collect();
+        write!(f, "({})_efe{}", coefficients.join(", "), DEGREE)
+    }
+}
+
+impl<const DEGREE: usize> FromVecu8 for ExtensionFieldElement<DEGREE> {
+    fn from_vecu8(bytes: Vec<u8>) -> Self {
+        let bytesize = std::mem::size_of::<u64>();
+        let mut coefficients = [BFieldElement::zero(); DEGREE];
+        for (coefficient, chunk) in coefficients.iter_mut().zip(bytes.chunks(bytesize)) {
+            *coefficient = BFieldElement::from_vecu8(chunk.to_vec());
+        }
+        Self { coefficients }
+    }
+}
+
+impl<const DEGREE: usize> Zero for ExtensionFieldElement<DEGREE> {
+    fn zero() -> Self {
+        Self {
+            coefficients: [BFieldElement::zero(); DEGREE],
+        }
+    }
+
+    fn is_zero(&self) -> bool {
+        self.coefficients.iter().all(|c| c.is_zero())
+    }
+}
+
+impl<const DEGREE: usize> One for ExtensionFieldElement<DEGREE> {
+    fn one() -> Self {
+        Self::new_const(BFieldElement::one())
+    }
+
+    fn is_one(&self) -> bool {
+        self.coefficients[0].is_one() && self.coefficients[1..].iter().all(|c| c.is_zero())
+    }
+}
+
+impl<const DEGREE: usize> FiniteField for ExtensionFieldElement<DEGREE> {}
+
+impl<const DEGREE: usize> New for ExtensionFieldElement<DEGREE> {
+    fn new_from_usize(&self, value: usize) -> Self {
+        Self::new_const(BFieldElement::new(value as u64))
+    }
+}
+
+impl<const DEGREE: usize> Add<ExtensionFieldElement<DEGREE>> for ExtensionFieldElement<DEGREE> {
+    type Output = Self;
+
+    #[inline]
+    fn add(mut self, other: Self) -> Self {
+        self += other;
+        self
+    }
+}
+
+/// Multiplication is schoolbook polynomial multiplication followed by the
+/// reduction `x^DEGREE = 7`: every overflowing term wraps around to degree
+/// `DEGREE` lower, scaled by 7.
+impl<const DEGREE: usize> Mul<ExtensionFieldElement<DEGREE>> for ExtensionFieldElement<DEGREE> {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, other: Self) -> Self {
+        let w = BFieldElement::new(Self::W);
+        let mut coefficients = [BFieldElement::zero(); DEGREE];
+        for i in 0..DEGREE {
+            for j in 0..DEGREE {
+                let product = self.coefficients[i] * other.coefficients[j];
+                if i + j < DEGREE {
+                    coefficients[i + j] += product;
+                } else {
+                    coefficients[i + j - DEGREE] += w * product;
+                }
+            }
+        }
+        Self { coefficients }
+    }
+}
+
+/// Scalar multiplication of the BFieldElement onto each coefficient.
+impl<const DEGREE: usize> Mul<BFieldElement> for ExtensionFieldElement<DEGREE> {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, other: BFieldElement) -> Self {
+        Self {
+            coefficients: self.coefficients.map(|coefficient| coefficient * other),
+        }
+    }
+}
+
+impl<const DEGREE: usize> Neg for ExtensionFieldElement<DEGREE> {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self {
+        Self {
+            coefficients: self.coefficients.map(|coefficient| -coefficient),
+        }
+    }
+}
+
+impl<const DEGREE: usize> Sub<ExtensionFieldElement<DEGREE>> for ExtensionFieldElement<DEGREE> {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, other: Self) -> Self {
+        self + (-other)
+    }
+}
+
+impl<const DEGREE: usize> AddAssign<ExtensionFieldElement<DEGREE>>
+    for ExtensionFieldElement<DEGREE>
+{
+    #[inline]
+    fn add_assign(&mut self, rhs: Self) {
+        for (coefficient, rhs_coefficient) in self.coefficients.iter_mut().zip(rhs.coefficients) {
+            *coefficient += rhs_coefficient;
+        }
+    }
+}
+
+impl<const DEGREE: usize> MulAssign<ExtensionFieldElement<DEGREE>>
+    for ExtensionFieldElement<DEGREE>
+{
+    #[inline]
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl<const DEGREE: usize> MulAssign<BFieldElement> for ExtensionFieldElement<DEGREE> {
+    #[inline]
+    fn mul_assign(&mut self, rhs: BFieldElement) {
+        *self = *self * rhs;
+    }
+}
+
+impl<const DEGREE: usize> SubAssign<ExtensionFieldElement<DEGREE>>
+    for ExtensionFieldElement<DEGREE>
+{
+    #[inline]
+    fn sub_assign(&mut self, rhs: Self) {
+        for (coefficient, rhs_coefficient) in self.coefficients.iter_mut().zip(rhs.coefficients) {
+            *coefficient -= rhs_coefficient;
+        }
+    }
+}
+
+impl<const DEGREE: usize> Div for ExtensionFieldElement<DEGREE> {
+    type Output = Self;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, other: Self) -> Self {
+        self * other.inverse()
+    }
+}
+
+impl<const DEGREE: usize> ModPowU64 for ExtensionFieldElement<DEGREE> {
+    #[inline]
+    fn mod_pow_u64(&self, exponent: u64) -> Self {
+        // Special case for handling 0^0 = 1
+        if exponent == 0 {
+            return Self::one();
+        }
+
+        let mut x = *self;
+        let mut result = Self::one();
+        let mut i = exponent;
+
+        while i > 0 {
+            if i % 2 == 1 {
+                result *= x;
+            }
+
+            x *= x;
+            i >>= 1;
+        }
+
+        result
+    }
+}
+
+impl<const DEGREE: usize> ModPowU32 for ExtensionFieldElement<DEGREE> {
+    #[inline]
+    fn mod_pow_u32(&self, exp: u32) -> Self {
+        self.mod_pow_u64(exp as u64)
+    }
+}
+
+#[cfg(test)]
+mod extension_field_element_test {
+    use itertools::izip;
+
+    use crate::shared_math::other::random_elements;
+    use crate::shared_math::{b_field_element::*, extension_field_element::*};
+
+    #[test]
+    fn one_zero_test() {
+        let one = QuadFieldElement::one();
+        assert!(one.is_one());
+        let zero = QuadFieldElement::zero();
+        assert!(zero.is_zero());
+        assert!(!QuadFieldElement::new_u64([2, 0]).is_one());
+        assert!(!QuadFieldElement::new_u64([1, 1]).is_one());
+        assert!(!QuadFieldElement::new_u64([0, 1]).is_zero());
+
+        assert!(QuarticFieldElement::one().is_one());
+        assert!(QuarticFieldElement::zero().is_zero());
+        assert!(!QuarticFieldElement::new_u64([1, 0, 0, 1]).is_one());
+        assert!(!QuarticFieldElement::new_u64([0, 0, 0, 1]).is_zero());
+    }
+
+    #[test]
+    fn modulus_reduction_test() {
+        // x * x = 7 in the degree-2 field
+        let x2 = QuadFieldElement::new_u64([0, 1]);
+        assert_eq!(QuadFieldElement::new_u64([7, 0]), x2 * x2);
+
+        // x^2 * x^2 = 7 in the degree-4 field, and x^3 * x^2 = 7x
+        let x4_squared = QuarticFieldElement::new_u64([0, 0, 1, 0]);
+        let x4_cubed = QuarticFieldElement::new_u64([0, 0, 0, 1]);
+        assert_eq!(
+            QuarticFieldElement::new_u64([7, 0, 0, 0]),
+            x4_squared * x4_squared
+        );
+        assert_eq!(
+            QuarticFieldElement::new_u64([0, 7, 0, 0]),
+            x4_cubed * x4_squared
+        );
+    }
+
+    #[test]
+    fn quad_field_inversion_pbt() {
+        let test_iterations = 100;
+        let rands: Vec<QuadFieldElement> = random_elements(test_iterations);
+        for rand in rands.iter() {
+            let inverse = rand.inverse();
+            assert!((*rand * inverse).is_one());
+            assert!((inverse * *rand).is_one());
+        }
+
+        // Test batch inversion
+        let inverses = QuadFieldElement::batch_inversion(rands.clone());
+        for (val, inv) in izip!(rands, inverses) {
+            assert!((val * inv).is_one());
+        }
+    }
+
+    #[test]
+    fn quartic_field_inversion_pbt() {
+        let test_iterations = 100;
+        let rands: Vec<QuarticFieldElement> = random_elements(test_iterations);
+        for rand in rands.iter() {
+            let inverse = rand.inverse();
+            assert!((*rand * inverse).is_one());
+            assert!((inverse * *rand).is_one());
+        }
+    }
+
+    #[test]
+    fn division_mul_pbt() {
+        let test_iterations = 100;
+        let rands_a: Vec<QuarticFieldElement> = random_elements(test_iterations);
+        let rands_b: Vec<QuarticFieldElement> = random_elements(test_iterations);
+        for (a, b) in izip!(rands_a, rands_b) {
+            let ab = a * b;
+            assert_eq!(ab, b * a);
+            assert_eq!(ab / b, a);
+            assert_eq!(ab / a, b);
+
+            let mut a_mul_b = a;
+            a_mul_b *= b;
+            assert_eq!(ab, a_mul_b);
+
+            let mut a_plus_b = a;
+            a_plus_b += b;
+            assert_eq!(a + b, a_plus_b);
+
+            let mut a_minus_b = a;
+            a_minus_b -= b;
+            assert_eq!(a - b, a_minus_b);
+        }
+    }
+
+    #[test]
+    fn scalar_mul_test() {
+        let test_iterations = 100;
+        let rands_x: Vec<QuadFieldElement> = random_elements(test_iterations);
+        let rands_b: Vec<BFieldElement> = random_elements(test_iterations);
+        for (x, b) in izip!(rands_x, rands_b) {
+            let res_mul = x * b;
+            assert_eq!(res_mul.coefficients[0], x.coefficients[0] * b);
+            assert_eq!(res_mul.coefficients[1], x.coefficients[1] * b);
+            assert_eq!(res_mul, x * QuadFieldElement::new_const(b));
+        }
+    }
+
+    #[test]
+    fn mod_pow_test() {
+        let three = QuarticFieldElement::new_const(BFieldElement::new(3));
+        for (exponent, expected) in izip!([0u64, 1, 2, 3, 4, 5], [1u64, 3, 9, 27, 81, 243]) {
+            assert_eq!(
+                QuarticFieldElement::new_const(BFieldElement::new(expected)),
+                three.mod_pow_u64(exponent)
+            );
+        }
+    }
+}
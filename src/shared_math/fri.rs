@@ -1,23 +1,60 @@
+use crate::parallel::prelude::*;
+use crate::parallel::{map_collect, map_collect_range};
 use itertools::Itertools;
 use num_traits::{One, Zero};
-use rayon::prelude::{IntoParallelIterator, IntoParallelRefIterator, ParallelIterator};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
 use std::fmt;
 use std::marker::PhantomData;
+use std::ops::MulAssign;
+#[cfg(feature = "tokio")]
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(feature = "tokio")]
+use std::sync::Arc;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "tokio")]
+use tokio::sync::mpsc::UnboundedSender;
 
 use super::b_field_element::BFieldElement;
-use super::other::{log_2_ceil, log_2_floor};
+use super::extension_field_element::{QuadFieldElement, QuarticFieldElement};
+use super::other::{is_power_of_two, log_2_ceil, log_2_floor, random_elements_array};
 use super::polynomial::Polynomial;
-use super::traits::{CyclicGroupGenerator, ModPowU32};
-use super::x_field_element::XFieldElement;
-use crate::shared_math::ntt::{intt, ntt};
+use super::traits::{CyclicGroupGenerator, PrimitiveRootOfUnity};
+use super::x_field_element::{XFieldElement, EXTENSION_DEGREE};
+use crate::shared_math::ntt::{intt, ntt, ntt_twiddles, ntt_with_twiddles};
 use crate::shared_math::traits::FiniteField;
-use crate::util_types::algebraic_hasher::{AlgebraicHasher, Hashable};
-use crate::util_types::merkle_tree::{MerkleTree, PartialAuthenticationPath};
+use crate::util_types::algebraic_hasher::{AlgebraicHasher, Hashable, WEIGHT_SAMPLING_DOMAIN_TAG};
+use crate::util_types::index_sampler::IndexSampler;
+#[cfg(feature = "std")]
+use crate::util_types::merkle_tree::DiskBackedMerkleTree;
+use crate::util_types::merkle_tree::{
+    domain_separated_leaf_digest, AuthenticationStructureError, MerkleTree,
+    PartialAuthenticationPath,
+};
 use crate::util_types::proof_stream::ProofStream;
+#[cfg(feature = "std")]
+use rusty_leveldb::DB;
 
 use super::rescue_prime_digest::Digest;
 
+/// An entered `tracing` span when the `tracing` feature is enabled, a no-op
+/// otherwise. Keeps the instrumentation sites free of `cfg` clutter.
+#[cfg(feature = "tracing")]
+macro_rules! fri_span {
+    ($($args:tt)*) => {
+        tracing::info_span!($($args)*).entered()
+    };
+}
+#[cfg(not(feature = "tracing"))]
+macro_rules! fri_span {
+    ($($args:tt)*) => {
+        ()
+    };
+}
+
 impl Error for ValidationError {}
 
 impl fmt::Display for ValidationError {
@@ -29,632 +66,4593 @@ impl fmt::Display for ValidationError {
 #[derive(PartialEq, Eq, Debug)]
 pub enum ValidationError {
     BadMerkleProof,
+    BadAuthenticationStructure(AuthenticationStructureError),
     BadSizedProof,
     NonPostiveRoundCount,
     NotColinear(usize),
     LastIterationTooHighDegree,
     BadMerkleRootForLastCodeword,
+    GrindingTargetNotMet,
+    BadOutOfDomainEvaluation,
+    MalformedProof,
 }
 
-#[derive(Debug, Clone)]
-pub struct FriDomain {
-    pub offset: BFieldElement,
-    pub omega: BFieldElement,
-    pub length: usize,
+/// The soundness regime a FRI configuration is parameterized under. The
+/// regime determines how many bits of security a single colinearity check
+/// buys, and thereby how many checks [`FriBuilder`] picks for a target
+/// security level -- which in turn bounds how many fold rounds
+/// [`Fri::num_rounds`] derives, since the last codeword must stay long
+/// enough to sample all check indices from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SoundnessRegime {
+    /// Only what has been formally proven about FRI soundness, via the
+    /// Johnson list-decoding bound: each colinearity check contributes half
+    /// a bit per bit of rate.
+    Proven,
+    /// The widely conjectured soundness: each colinearity check contributes
+    /// one full bit per bit of rate.
+    #[default]
+    Conjectured,
 }
 
-impl FriDomain {
-    pub fn x_evaluate(&self, polynomial: &Polynomial<XFieldElement>) -> Vec<XFieldElement> {
-        polynomial.fast_coset_evaluate(&self.offset, self.omega, self.length as usize)
+impl SoundnessRegime {
+    /// The number of security bits a single colinearity check contributes
+    /// at the given rate.
+    pub fn bits_per_colinearity_check(self, expansion_factor: usize) -> f64 {
+        let bits_of_rate = (expansion_factor as f64).log2();
+        match self {
+            SoundnessRegime::Proven => bits_of_rate / 2.0,
+            SoundnessRegime::Conjectured => bits_of_rate,
+        }
     }
+}
 
-    pub fn x_interpolate(&self, values: &[XFieldElement]) -> Polynomial<XFieldElement> {
-        Polynomial::<XFieldElement>::fast_coset_interpolate(&self.offset, self.omega, values)
+/// Errors raised by [`Fri::new`] and the proving entry points. Unlike
+/// [`ValidationError`], these indicate a misconfigured or misused prover,
+/// not a bad proof.
+#[derive(PartialEq, Eq, Debug)]
+pub enum FriProverError {
+    CodewordLengthMismatch { expected: usize, actual: usize },
+    UnsupportedFoldingFactor(usize),
+    DomainLengthNotPowerOfTwo,
+    TooManyColinearityChecks,
+    EmptyBatch,
+    ProofStreamFailure(String),
+    MissingDomainLength,
+    TargetSecurityUnreachable,
+    Cancelled,
+    UnsupportedFoldingSchedule,
+    WrongTreeDatabaseCount { expected: usize, actual: usize },
+    LeafDigestCountMismatch { expected: usize, actual: usize },
+    PrecomputedDigestsWithBlinding,
+    StreamedCodewordWithBlinding,
+    ExcessiveGrindingBits(u8),
+}
+
+impl Error for FriProverError {}
+
+impl fmt::Display for FriProverError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "FRI prover error: {:?}", self)
     }
+}
 
-    pub fn b_domain_value(&self, index: u32) -> BFieldElement {
-        self.omega.mod_pow_u32(index) * self.offset
+impl From<Box<dyn Error>> for FriProverError {
+    fn from(err: Box<dyn Error>) -> Self {
+        FriProverError::ProofStreamFailure(err.to_string())
     }
+}
 
-    pub fn b_domain_values(&self) -> Vec<BFieldElement> {
-        (0..self.length)
-            .map(|i| self.omega.mod_pow_u32(i as u32) * self.offset)
-            .collect()
+/// What FRI needs from a codeword field beyond [`FiniteField`] arithmetic:
+/// embedding domain points and deriving folding challenges from Fiat-Shamir
+/// digests.
+///
+/// Implemented for [`XFieldElement`] (the default), for [`BFieldElement`] --
+/// so that base-field-only applications can run the protocol without lifting
+/// every codeword value into the extension field -- and for the degree-2 and
+/// degree-4 extension fields [`QuadFieldElement`] and [`QuarticFieldElement`],
+/// so that callers can trade soundness per query against arithmetic cost.
+/// Note that smaller challenge fields sample challenges from a smaller
+/// space; the commit phase loses roughly 64 bits of soundness per degree
+/// dropped.
+pub trait FriFieldElement: FiniteField + Hashable + MulAssign<BFieldElement> {
+    /// Embed a domain point into the codeword field.
+    fn from_base(element: BFieldElement) -> Self;
+
+    /// Derive a folding challenge from a Fiat-Shamir digest.
+    fn sample_challenge(digest: &Digest) -> Self;
+}
+
+impl FriFieldElement for XFieldElement {
+    fn from_base(element: BFieldElement) -> Self {
+        element.lift()
     }
 
-    pub fn b_evaluate(&self, polynomial: &Polynomial<BFieldElement>) -> Vec<BFieldElement> {
-        let zero = BFieldElement::zero();
-        let mut polynomial_representation: Vec<BFieldElement> =
-            polynomial.scale(&self.offset).coefficients;
-        polynomial_representation.resize(self.length as usize, zero);
-        ntt(
-            &mut polynomial_representation,
-            self.omega,
-            log_2_ceil(self.length as u128) as u32,
-        );
+    fn sample_challenge(digest: &Digest) -> Self {
+        XFieldElement::sample(digest)
+    }
+}
 
-        polynomial_representation
+impl FriFieldElement for BFieldElement {
+    fn from_base(element: BFieldElement) -> Self {
+        element
     }
 
-    pub fn b_interpolate(&self, values: &[BFieldElement]) -> Polynomial<BFieldElement> {
-        Polynomial::<BFieldElement>::fast_coset_interpolate(&self.offset, self.omega, values)
+    fn sample_challenge(digest: &Digest) -> Self {
+        digest.values()[0]
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct Fri<H> {
-    pub expansion_factor: usize,         // = domain_length / trace_length
-    pub colinearity_checks_count: usize, // number of colinearity checks in each round
-    pub domain: FriDomain,
-    _hasher: PhantomData<H>,
+impl FriFieldElement for QuadFieldElement {
+    fn from_base(element: BFieldElement) -> Self {
+        Self::new_const(element)
+    }
+
+    fn sample_challenge(digest: &Digest) -> Self {
+        QuadFieldElement::sample(digest)
+    }
 }
 
-type CodewordEvaluation<T> = (usize, T);
+impl FriFieldElement for QuarticFieldElement {
+    fn from_base(element: BFieldElement) -> Self {
+        Self::new_const(element)
+    }
 
-impl<H> Fri<H>
+    fn sample_challenge(digest: &Digest) -> Self {
+        QuarticFieldElement::sample(digest)
+    }
+}
+
+/// A re-readable source of codeword values for [`Fri::prove_stream`].
+///
+/// The streaming prover makes several passes over the codeword -- hashing,
+/// folding, and opening queried positions -- so a source must be able to
+/// start a fresh pass at any position. For values that do fit in memory,
+/// slices implement this trait; for very large traces, implement it on top
+/// of a chunked file reader or similar.
+pub trait CodewordSource<FF> {
+    /// The number of values this source yields in a full pass.
+    fn length(&self) -> usize;
+
+    /// Open a fresh pass over the values, starting at position `start`.
+    fn stream_from(&self, start: usize) -> Box<dyn Iterator<Item = FF> + '_>;
+}
+
+impl<FF: FriFieldElement> CodewordSource<FF> for &[FF] {
+    fn length(&self) -> usize {
+        self.len()
+    }
+
+    fn stream_from(&self, start: usize) -> Box<dyn Iterator<Item = FF> + '_> {
+        Box::new(self[start..].iter().copied())
+    }
+}
+
+/// Offload hook for the heavy parts of the FRI commit phase.
+///
+/// For large domains (2^22 values and up) the per-round fold and the
+/// hashing of codeword values into Merkle leaves dominate prover time. An
+/// application with a CUDA or wgpu context can implement this trait on top
+/// of it and hand it to [`Fri::prove_with_backend`] (behind the `gpu`
+/// feature) to run both off the CPU. [`RayonCommitBackend`] is the
+/// thread-pool fallback the prover uses when no device backend is supplied.
+pub trait CommitBackend<FF: FriFieldElement, H: AlgebraicHasher> {
+    /// Hash every codeword value into a Merkle leaf digest, laid out
+    /// according to `encoding`; see [`LeafEncoding`].
+    fn hash_leaves(&self, codeword: &[FF], encoding: LeafEncoding) -> Vec<Digest>;
+
+    /// Fold `codeword` with the round challenge `alpha`. The result must
+    /// match the CPU fold exactly; transcripts may not depend on which
+    /// backend produced them.
+    fn fold_codeword(
+        &self,
+        codeword: &[FF],
+        generator: BFieldElement,
+        offset: BFieldElement,
+        alpha: FF,
+        folding_factor: usize,
+    ) -> Vec<FF>;
+}
+
+/// The CPU fallback [`CommitBackend`]: leaf hashing and folding on the
+/// rayon thread pool, exactly as the plain prover does it.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RayonCommitBackend;
+
+impl<FF, H> CommitBackend<FF, H> for RayonCommitBackend
 where
+    FF: FriFieldElement,
     H: AlgebraicHasher + Send + Sync,
 {
-    pub fn new(
+    fn hash_leaves(&self, codeword: &[FF], encoding: LeafEncoding) -> Vec<Digest> {
+        map_collect(codeword, |x| Fri::<H>::leaf_digest(encoding, x))
+    }
+
+    fn fold_codeword(
+        &self,
+        codeword: &[FF],
+        generator: BFieldElement,
         offset: BFieldElement,
-        omega: BFieldElement,
-        domain_length: usize,
-        expansion_factor: usize,
-        colinearity_checks_count: usize,
-    ) -> Self {
-        let domain = FriDomain {
+        alpha: FF,
+        folding_factor: usize,
+    ) -> Vec<FF> {
+        Fri::<H>::fold_codeword(codeword, generator, offset, alpha, folding_factor)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FriDomain {
+    pub offset: BFieldElement,
+    pub omega: BFieldElement,
+    pub length: usize,
+
+    // Lazily filled caches; see `precompute`. Not serialized; a
+    // deserialized domain starts with empty caches and refills them on
+    // demand.
+    #[serde(skip)]
+    values: OnceLock<Vec<BFieldElement>>,
+    #[serde(skip)]
+    twiddles: OnceLock<Vec<BFieldElement>>,
+}
+
+impl FriDomain {
+    pub fn new(offset: BFieldElement, omega: BFieldElement, length: usize) -> Self {
+        Self {
             offset,
             omega,
-            length: domain_length,
-        };
-        let _hasher = PhantomData;
-        Self {
-            domain,
-            expansion_factor,
-            colinearity_checks_count,
-            _hasher,
+            length,
+            values: OnceLock::new(),
+            twiddles: OnceLock::new(),
         }
     }
 
-    /// Build the (deduplicated) Merkle authentication paths for the codeword at the given indices
-    /// and enqueue the corresponding values and (partial) authentication paths on the proof stream.
-    fn enqueue_auth_pairs(
-        indices: &[usize],
-        codeword: &[XFieldElement],
-        merkle_tree: &MerkleTree<H>,
-        proof_stream: &mut ProofStream,
-    ) {
-        let value_ap_pairs: Vec<(PartialAuthenticationPath<Digest>, XFieldElement)> = merkle_tree
-            .get_authentication_structure(indices)
-            .into_iter()
-            .zip(indices.iter())
-            .map(|(ap, i)| (ap, codeword[*i]))
-            .collect_vec();
-        proof_stream
-            .enqueue_length_prepended(&value_ap_pairs)
-            .expect("Enqueuing must succeed")
-    }
+    /// Construct a domain of the given length, looking up the primitive
+    /// root of unity of matching order. Uses the standard coset offset --
+    /// the field generator -- unless an explicit one is given. Errors if
+    /// the length is not a power of two supported by the field.
+    pub fn of_length(
+        length: usize,
+        offset: Option<BFieldElement>,
+    ) -> Result<FriDomain, FriProverError> {
+        let omega = BFieldElement::primitive_root_of_unity(length as u64)
+            .ok_or(FriProverError::DomainLengthNotPowerOfTwo)?;
+        let offset = offset.unwrap_or_else(BFieldElement::generator);
 
-    /// Given a set of `indices`, a merkle `root`, and the (correctly set) `proof_stream`, verify
-    /// whether the values at the `indices` are members of the set committed to by the merkle `root`
-    /// and return these values if they are. Fails otherwise.
-    fn dequeue_and_authenticate(
-        indices: &[usize],
-        root: Digest,
-        proof_stream: &mut ProofStream,
-    ) -> Result<Vec<XFieldElement>, Box<dyn Error>> {
-        let (paths, values): (Vec<PartialAuthenticationPath<Digest>>, Vec<XFieldElement>) = proof_stream
-            .dequeue_length_prepended::<Vec<(PartialAuthenticationPath<Digest>, XFieldElement)>>()?
-            .into_iter()
-            .unzip();
-        let digests: Vec<Digest> = values
-            .par_iter()
-            .map(|v| H::hash_slice(&v.to_sequence()))
-            .collect();
-        let path_digest_pairs = paths.into_iter().zip(digests).collect_vec();
+        Ok(FriDomain::new(offset, omega, length))
+    }
 
-        if MerkleTree::<H>::verify_authentication_structure(root, indices, &path_digest_pairs) {
-            Ok(values)
-        } else {
-            Err(Box::new(ValidationError::BadMerkleProof))
-        }
+    /// Fill the lazy caches of domain values and NTT twiddles up front, so
+    /// that the first evaluate or interpolate call does not pay for them.
+    /// The caches are also filled on demand, so calling this is optional.
+    pub fn precompute(&self) {
+        self.domain_values();
+        self.cached_twiddles();
     }
 
-    pub fn prove(
-        &self,
-        codeword: &[XFieldElement],
-        proof_stream: &mut ProofStream,
-    ) -> Result<Vec<usize>, Box<dyn Error>> {
-        assert_eq!(
-            self.domain.length,
-            codeword.len(),
-            "Initial codeword length must match that set in FRI object"
-        );
+    /// All domain values `offset * omega^i`, computed incrementally once and
+    /// cached for the lifetime of the domain.
+    pub fn domain_values(&self) -> &[BFieldElement] {
+        self.values.get_or_init(|| {
+            let mut values = Vec::with_capacity(self.length);
+            let mut value = self.offset;
+            for _ in 0..self.length {
+                values.push(value);
+                value *= self.omega;
+            }
+            values
+        })
+    }
 
-        // Commit phase
-        let (codewords, merkle_trees): (Vec<Vec<XFieldElement>>, Vec<MerkleTree<H>>) =
-            self.commit(codeword, proof_stream)?.into_iter().unzip();
+    fn cached_twiddles(&self) -> &[BFieldElement] {
+        self.twiddles
+            .get_or_init(|| ntt_twiddles(self.omega, log_2_ceil(self.length as u128) as u32))
+    }
+    pub fn x_evaluate(&self, polynomial: &Polynomial<XFieldElement>) -> Vec<XFieldElement> {
+        self.evaluate_with_twiddles(polynomial)
+    }
 
-        // fiat-shamir phase (get indices)
-        let top_level_indices = self.sample_indices(&proof_stream.prover_fiat_shamir());
+    /// Evaluate many polynomials over the domain, sharing the cached NTT
+    /// twiddles and running the transforms in parallel.
+    pub fn x_evaluate_many(
+        &self,
+        polynomials: &[Polynomial<XFieldElement>],
+    ) -> Vec<Vec<XFieldElement>> {
+        self.evaluate_many(polynomials)
+    }
 
-        // query phase
-        let initial_a_indices: Vec<usize> = top_level_indices.clone();
-        Self::enqueue_auth_pairs(&initial_a_indices, codeword, &merkle_trees[0], proof_stream);
-        let mut current_domain_len = self.domain.length;
-        let mut b_indices: Vec<usize> = initial_a_indices;
+    pub fn x_interpolate(&self, values: &[XFieldElement]) -> Polynomial<XFieldElement> {
+        Polynomial::<XFieldElement>::fast_coset_interpolate(&self.offset, self.omega, values)
+    }
 
-        for r in 0..merkle_trees.len() - 1 {
-            debug_assert_eq!(
-                codewords[r].len(),
-                current_domain_len,
-                "The current domain length needs to be the same as the length of the \
-                current codeword"
-            );
-            b_indices = b_indices
-                .iter()
-                .map(|x| (x + current_domain_len / 2) % current_domain_len)
-                .collect();
-            Self::enqueue_auth_pairs(&b_indices, &codewords[r], &merkle_trees[r], proof_stream);
-            current_domain_len /= 2;
+    pub fn b_domain_value(&self, index: u64) -> BFieldElement {
+        match self.values.get() {
+            Some(values) => values[index as usize],
+            None => self.omega.mod_pow(index) * self.offset,
         }
-
-        Ok(top_level_indices)
     }
 
-    #[allow(clippy::type_complexity)]
-    fn commit(
-        &self,
-        codeword: &[XFieldElement],
-        proof_stream: &mut ProofStream,
-    ) -> Result<Vec<(Vec<XFieldElement>, MerkleTree<H>)>, Box<dyn Error>> {
-        let mut generator = self.domain.omega;
-        let mut offset = self.domain.offset;
-        let mut codeword_local = codeword.to_vec();
+    pub fn b_domain_values(&self) -> Vec<BFieldElement> {
+        self.domain_values().to_vec()
+    }
 
-        let one: XFieldElement = XFieldElement::one();
-        let two: XFieldElement = one + one;
-        let two_inv = one / two;
+    /// Iterate over the domain values `offset * omega^i` by successive
+    /// multiplication, without materializing them. Useful for streaming
+    /// through domains too large to hold as a vector; callers that need
+    /// repeated random access should use [`domain_values`] instead.
+    ///
+    /// [`domain_values`]: FriDomain::domain_values
+    pub fn iter_values(&self) -> impl Iterator<Item = BFieldElement> {
+        let omega = self.omega;
+        std::iter::successors(Some(self.offset), move |&value| Some(value * omega))
+            .take(self.length)
+    }
 
-        // Compute and send Merkle root
-        let mut digests: Vec<Digest> = codeword_local
-            .par_iter()
-            .map(|x| H::hash_slice(&x.to_sequence()))
-            .collect();
-        let mut mt = MerkleTree::from_digests(&digests);
-        proof_stream.enqueue(&mt.get_root())?;
-        let mut values_and_merkle_trees = vec![(codeword_local.clone(), mt)];
+    pub fn b_evaluate(&self, polynomial: &Polynomial<BFieldElement>) -> Vec<BFieldElement> {
+        self.evaluate_with_twiddles(polynomial)
+    }
 
-        let (num_rounds, _) = self.num_rounds();
-        for _ in 0..num_rounds {
-            let n = codeword_local.len();
+    /// The base-field analogue of [`x_evaluate_many`]: evaluate many
+    /// polynomials over the domain, sharing the cached NTT twiddles and
+    /// running the transforms in parallel.
+    ///
+    /// [`x_evaluate_many`]: FriDomain::x_evaluate_many
+    pub fn b_evaluate_many(
+        &self,
+        polynomials: &[Polynomial<BFieldElement>],
+    ) -> Vec<Vec<BFieldElement>> {
+        self.evaluate_many(polynomials)
+    }
 
-            // Sanity check to verify that generator has the right order; requires ModPowU64
-            //assert!(generator.inv() == generator.mod_pow((n - 1).into())); // TODO: REMOVE
+    fn evaluate_with_twiddles<FF>(&self, polynomial: &Polynomial<FF>) -> Vec<FF>
+    where
+        FF: FiniteField + MulAssign<BFieldElement>,
+    {
+        let mut coefficients: Vec<FF> = polynomial.scale(&self.offset).coefficients;
+        coefficients.resize(self.length, FF::zero());
+        ntt_with_twiddles(&mut coefficients, self.cached_twiddles());
 
-            // Get challenge, one just acts as *any* element in this field -- the field element
-            // is completely determined from the byte stream.
-            let challenge: Digest = proof_stream.prover_fiat_shamir();
-            let alpha: XFieldElement = XFieldElement::sample(&challenge);
+        coefficients
+    }
 
-            let x_offset: Vec<BFieldElement> = generator
-                .get_cyclic_group_elements(None)
-                .into_par_iter()
-                .map(|x| x * offset)
-                .collect();
+    fn evaluate_many<FF>(&self, polynomials: &[Polynomial<FF>]) -> Vec<Vec<FF>>
+    where
+        FF: FiniteField + MulAssign<BFieldElement>,
+    {
+        // Fill the twiddle cache once, outside the parallel region
+        self.cached_twiddles();
+        map_collect(polynomials, |polynomial| {
+            self.evaluate_with_twiddles(polynomial)
+        })
+    }
 
-            let x_offset_inverses = BFieldElement::batch_inversion(x_offset);
-            codeword_local = (0..n / 2)
-                .into_par_iter()
-                .map(|i| {
-                    two_inv
-                        * ((one + alpha * x_offset_inverses[i]) * codeword_local[i]
-                            + (one - alpha * x_offset_inverses[i]) * codeword_local[n / 2 + i])
-                })
-                .collect();
+    pub fn b_interpolate(&self, values: &[BFieldElement]) -> Polynomial<BFieldElement> {
+        Polynomial::<BFieldElement>::fast_coset_interpolate(&self.offset, self.omega, values)
+    }
 
-            // Compute and send Merkle root
-            digests = codeword_local
-                .par_iter()
-                .map(|x| H::hash_slice(&x.to_sequence()))
-                .collect();
-            mt = MerkleTree::from_digests(&digests);
-            proof_stream.enqueue(&mt.get_root())?;
-            values_and_merkle_trees.push((codeword_local.clone(), mt));
+    /// The same subgroup shifted to a different coset offset. The NTT
+    /// twiddle cache carries over, since twiddles depend only on `omega`;
+    /// the domain-value cache starts empty.
+    pub fn with_offset(&self, offset: BFieldElement) -> FriDomain {
+        let domain = FriDomain::new(offset, self.omega, self.length);
+        let _ = domain.twiddles.set(self.cached_twiddles().to_vec());
+        domain
+    }
 
-            // Update subgroup generator and offset
-            generator = generator * generator;
-            offset = offset * offset;
-        }
+    /// Re-interpret `codeword`, the evaluations of some polynomial over
+    /// this domain, as its evaluations over the same subgroup shifted to
+    /// `new_offset`: interpolate, change the coset by scaling in
+    /// coefficient space, and evaluate back. Needed when a trace domain
+    /// and an evaluation domain differ by a coset shift, as in quotient
+    /// computations.
+    pub fn b_change_offset(
+        &self,
+        codeword: &[BFieldElement],
+        new_offset: BFieldElement,
+    ) -> Vec<BFieldElement> {
+        self.change_offset(codeword, new_offset)
+    }
 
-        // Send the last codeword
-        let last_codeword = codeword_local;
-        proof_stream.enqueue_length_prepended(&last_codeword)?;
+    /// The x-field analogue of [`b_change_offset`].
+    ///
+    /// [`b_change_offset`]: FriDomain::b_change_offset
+    pub fn x_change_offset(
+        &self,
+        codeword: &[XFieldElement],
+        new_offset: BFieldElement,
+    ) -> Vec<XFieldElement> {
+        self.change_offset(codeword, new_offset)
+    }
 
-        Ok(values_and_merkle_trees)
+    fn change_offset<FF>(&self, codeword: &[FF], new_offset: BFieldElement) -> Vec<FF>
+    where
+        FF: FiniteField + MulAssign<BFieldElement>,
+    {
+        let interpolant =
+            Polynomial::<FF>::fast_coset_interpolate(&self.offset, self.omega, codeword);
+        self.with_offset(new_offset)
+            .evaluate_with_twiddles(&interpolant)
     }
 
-    // Return the c-indices for the 1st round of FRI
-    fn sample_indices(&self, seed: &Digest) -> Vec<usize> {
-        // This algorithm starts with the inner-most indices to pick up
-        // to `last_codeword_length` indices from the codeword in the last round.
-        // It then calculates the indices in the subsequent rounds by choosing
-        // between the two possible next indices in the next round until we get
-        // the c-indices for the first round.
-        let num_rounds = self.num_rounds().0;
-        let last_codeword_length = self.domain.length >> num_rounds;
+    /// The domain of a codeword folded once by a factor of two: the same
+    /// coset squared, at half the length.
+    pub fn halve(&self) -> FriDomain {
         assert!(
-            self.colinearity_checks_count <= last_codeword_length,
-            "Requested number of indices must not exceed length of last codeword"
+            self.length.is_multiple_of(2) && self.length > 0,
+            "Can only halve a domain of even, positive length; got {}",
+            self.length
         );
+        FriDomain::new(
+            self.offset * self.offset,
+            self.omega * self.omega,
+            self.length / 2,
+        )
+    }
 
-        let mut last_indices: Vec<usize> = vec![];
-        let mut remaining_last_round_exponents: Vec<usize> = (0..last_codeword_length).collect();
-        let mut counter = 0u32;
-        for _ in 0..self.colinearity_checks_count {
-            let mut seed_local = seed.to_sequence();
-            seed_local.append(&mut counter.to_sequence());
-            let hash = H::hash_slice(&seed_local);
-            let index: usize =
-                H::sample_index_not_power_of_two(&hash, remaining_last_round_exponents.len());
-            last_indices.push(remaining_last_round_exponents.remove(index));
-            counter += 1;
+    /// The domain of the codeword committed to in the given round: this
+    /// domain halved `log2(folding_factor)` times per round. Round zero is
+    /// the domain itself. The returned domain carries its own `omega`,
+    /// `offset`, and `length`, so callers need not re-derive them round by
+    /// round.
+    pub fn round_domain(&self, round: usize, folding_factor: usize) -> FriDomain {
+        let halvings_per_round = log_2_floor(folding_factor as u128) as usize;
+        let mut domain = self.clone();
+        for _ in 0..round * halvings_per_round {
+            domain = domain.halve();
         }
+        domain
+    }
+}
 
-        // Use last indices to derive first c-indices
-        let mut indices = last_indices;
-        for i in 1..num_rounds {
-            let codeword_length = last_codeword_length << i;
-
-            let mut new_indices: Vec<usize> = vec![];
-            for index in indices {
-                let mut seed_local = seed.to_sequence();
-                seed_local.append(&mut counter.to_sequence());
-                let hash = H::hash_slice(&seed_local);
-                let reduce_modulo: bool = H::sample_index(&hash, 2) == 0;
-                let new_index = if reduce_modulo {
-                    index + codeword_length / 2
-                } else {
-                    index
-                };
-                new_indices.push(new_index);
+/// The values and (partial) authentication paths sent for one round of the
+/// FRI query phase.
+pub type FriQueryRound = Vec<(PartialAuthenticationPath<Digest>, XFieldElement)>;
 
-                counter += 1;
-            }
+/// Write one batch of query-phase openings to the proof stream, with every
+/// authentication path in the compact bitmask encoding of
+/// [`PartialAuthenticationPath::encode`] rather than serde's per-node
+/// `Option` tags.
+fn enqueue_query_pairs<FF: Serialize>(
+    pairs: &[(PartialAuthenticationPath<Digest>, FF)],
+    proof_stream: &mut ProofStream,
+) -> Result<(), Box<dyn Error>> {
+    let encoded_pairs: Vec<(Vec<u8>, &FF)> = pairs
+        .iter()
+        .map(|(path, value)| (path.encode(), value))
+        .collect();
+    proof_stream.enqueue_length_prepended(&encoded_pairs)?;
 
-            indices = new_indices;
-        }
+    Ok(())
+}
 
-        indices
-    }
+/// Read back a batch written by [`enqueue_query_pairs`], failing with
+/// [`ValidationError::BadMerkleProof`] on a malformed path encoding.
+#[allow(clippy::type_complexity)]
+fn dequeue_query_pairs<FF: serde::de::DeserializeOwned>(
+    proof_stream: &mut ProofStream,
+) -> Result<Vec<(PartialAuthenticationPath<Digest>, FF)>, Box<dyn Error>> {
+    let encoded_pairs: Vec<(Vec<u8>, FF)> = proof_stream.dequeue_length_prepended()?;
+    encoded_pairs
+        .into_iter()
+        .map(|(bytes, value)| {
+            let path =
+                PartialAuthenticationPath::decode(&bytes).ok_or(ValidationError::BadMerkleProof)?;
+            Ok((path, value))
+        })
+        .collect()
+}
 
-    pub fn verify(
-        &self,
-        proof_stream: &mut ProofStream,
-    ) -> Result<Vec<CodewordEvaluation<XFieldElement>>, Box<dyn Error>> {
-        let mut omega = self.domain.omega;
-        let mut offset = self.domain.offset;
-        let (num_rounds, degree_of_last_round) = self.num_rounds();
+/// A structured representation of a FRI proof: the Merkle roots of all
+/// rounds, the plain codeword of the last round, and the values and
+/// authentication paths revealed in the query phase.
+///
+/// This is the same data that [`Fri::prove`] writes into a [`ProofStream`],
+/// but in a form that can be stored, transmitted, and inspected without
+/// knowing the raw stream layout. Converting back with [`to_proof_stream`]
+/// reproduces the transcript byte for byte, so the Fiat-Shamir challenges
+/// derived during verification are unaffected by a serialization roundtrip.
+///
+/// [`to_proof_stream`]: FriProof::to_proof_stream
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FriProof {
+    pub merkle_roots: Vec<Digest>,
+    pub last_codeword: Vec<XFieldElement>,
+    pub query_rounds: Vec<FriQueryRound>,
+}
 
-        // Extract all roots and calculate alpha, the challenges
-        let mut roots: Vec<Digest> = vec![];
-        let mut alphas: Vec<XFieldElement> = vec![];
-        let first_root: Digest = proof_stream.dequeue(Digest::BYTES)?;
-        roots.push(first_root);
+impl FriProof {
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Box<dyn Error>> {
+        Ok(bincode::serialize(self)?)
+    }
 
-        for _ in 0..num_rounds {
-            // Get a challenge from the proof stream
-            let challenge: Digest = proof_stream.verifier_fiat_shamir();
-            let alpha: XFieldElement = XFieldElement::sample(&challenge);
-            alphas.push(alpha);
-            roots.push(proof_stream.dequeue(Digest::BYTES)?);
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Box<dyn Error>> {
+        Ok(bincode::deserialize(bytes)?)
+    }
+
+    /// Reconstruct the proof stream that [`Fri::prove`] would have produced
+    /// for this proof.
+    pub fn to_proof_stream(&self) -> Result<ProofStream, Box<dyn Error>> {
+        let mut proof_stream = ProofStream::default();
+        for root in self.merkle_roots.iter() {
+            proof_stream.enqueue(root)?;
+        }
+        proof_stream.enqueue_length_prepended(&self.last_codeword)?;
+        for query_round in self.query_rounds.iter() {
+            enqueue_query_pairs(query_round, &mut proof_stream)?;
         }
 
-        // Extract last codeword
-        let mut last_codeword: Vec<XFieldElement> =
-            proof_stream.dequeue_length_prepended::<Vec<XFieldElement>>()?;
+        Ok(proof_stream)
+    }
 
-        // Check if last codeword matches the given root
-        let leaves: Vec<_> = last_codeword
-            .iter()
-            .map(|x| H::hash_slice(&x.to_sequence()))
-            .collect();
-        let last_codeword_mt = MerkleTree::<H>::from_digests(&leaves);
-        let last_root = roots.last().unwrap();
-        if *last_root != last_codeword_mt.get_root() {
-            return Err(Box::new(ValidationError::BadMerkleRootForLastCodeword));
+    /// Parse a FRI proof from a proof stream. Since the stream itself is not
+    /// self-describing, the `num_rounds` and `folding_factor` of the FRI
+    /// instance that produced the proof must be supplied; see
+    /// [`Fri::extract_proof`].
+    pub fn from_proof_stream(
+        proof_stream: &mut ProofStream,
+        num_rounds: usize,
+        folding_factor: usize,
+    ) -> Result<Self, Box<dyn Error>> {
+        let mut merkle_roots: Vec<Digest> = Vec::with_capacity(num_rounds + 1);
+        for _ in 0..num_rounds + 1 {
+            merkle_roots.push(proof_stream.dequeue(Digest::BYTES)?);
         }
-
-        // Verify that last codeword is of sufficiently low degree
-        let mut last_omega = omega;
-        let mut last_offset = offset;
-        for _ in 0..num_rounds {
-            last_omega = last_omega * last_omega;
-            last_offset = last_offset * last_offset;
+        let last_codeword: Vec<XFieldElement> = proof_stream.dequeue_length_prepended()?;
+        let num_query_rounds = 1 + num_rounds * (folding_factor - 1);
+        let mut query_rounds: Vec<FriQueryRound> = Vec::with_capacity(num_query_rounds);
+        for _ in 0..num_query_rounds {
+            query_rounds.push(dequeue_query_pairs(proof_stream)?);
         }
 
-        // Compute interpolant to get the degree of the last codeword
-        // Note that we don't have to scale the polynomial back to the
-        // trace subgroup since we only check its degree and don't use
-        // it further.
-        let log_2_of_n = log_2_floor(last_codeword.len() as u128) as u32;
-        intt::<XFieldElement>(&mut last_codeword, last_omega, log_2_of_n);
-        let last_poly_degree: isize = (Polynomial::<XFieldElement> {
-            coefficients: last_codeword,
+        Ok(Self {
+            merkle_roots,
+            last_codeword,
+            query_rounds,
         })
-        .degree();
-        if last_poly_degree > degree_of_last_round as isize {
-            return Err(Box::new(ValidationError::LastIterationTooHighDegree));
+    }
+}
+
+/// Structured output of a successful FRI verification.
+///
+/// Outer protocols -- a STARK verifier, say -- need more than the top-level
+/// evaluations to link FRI to their own consistency checks: the Fiat-Shamir
+/// challenges, the per-round commitments, and the low-degree polynomial the
+/// proof boils down to. This struct exposes all of it without requiring the
+/// caller to re-parse the proof stream.
+#[derive(Debug, Clone)]
+pub struct FriVerifyReport<FF: FiniteField> {
+    /// The first-round codeword evaluations revealed in the query phase, as
+    /// `(index, value)` pairs.
+    pub codeword_evaluations: Vec<CodewordEvaluation<FF>>,
+    /// The folding challenge of every round.
+    pub alphas: Vec<FF>,
+    /// The Merkle root of every round, the first-round root first.
+    pub merkle_roots: Vec<Digest>,
+    /// The interpolant of the last-round codeword over the unshifted
+    /// subgroup of the last round's domain; its degree is at most the
+    /// last-round degree bound.
+    pub last_round_polynomial: Polynomial<FF>,
+}
+
+/// The shape of every proof a given [`Fri`] configuration produces:
+/// everything a transcript parser, an on-chain verifier, or a fuzzer needs
+/// to walk a proof without re-deriving the protocol logic. Obtained from
+/// [`Fri::proof_shape`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FriProofShape {
+    /// The number of fold rounds; see [`Fri::num_rounds`].
+    pub num_rounds: u8,
+    /// The degree bound on the last-round codeword.
+    pub last_round_max_degree: u32,
+    /// The codeword length of every committed Merkle tree, the first-round
+    /// (full-domain) length first; `num_rounds + 1` entries.
+    pub round_domain_lengths: Vec<usize>,
+    /// The length of the plain last-round codeword.
+    pub last_codeword_length: usize,
+    /// The number of authentication paths opened in the query phase: one
+    /// batch of `colinearity_checks_count` paths per queried coset arm.
+    /// An upper bound on what hits the transcript, since indices that
+    /// repeat within a batch are opened only once.
+    pub num_auth_paths: usize,
+    /// Whether the transcript carries a proof-of-work nonce between the
+    /// last codeword and the query-phase openings.
+    pub has_grinding_nonce: bool,
+    /// The number of out-of-domain evaluations interleaved with the commit
+    /// phase: one per round under the STIR schedule, zero otherwise.
+    pub num_out_of_domain_samples: usize,
+}
+
+/// How much intermediate state the FRI prover is allowed to retain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ProverMemoryProfile {
+    /// Keep every folded codeword and Merkle tree alive until the query
+    /// phase is done. Fastest, but memory grows with the domain size times
+    /// the number of rounds.
+    #[default]
+    Standard,
+    /// Drop intermediate codewords and Merkle trees after committing and
+    /// recompute them round by round during the query phase. Peak memory is
+    /// a single codeword plus a single Merkle tree, at the cost of folding
+    /// and hashing everything twice.
+    Lean,
+}
+
+/// The folding schedule run by the prover and verifier. Both parties must
+/// agree on the schedule; it changes the transcript.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum FoldingSchedule {
+    /// Classic FRI: fold by `folding_factor` every round.
+    #[default]
+    Classic,
+    /// STIR-style folding: after every fold the prover answers an
+    /// out-of-domain sample of the folded polynomial, and the next round
+    /// runs on the quotient by that sample, so each committed codeword is
+    /// bound to a unique low-degree polynomial rather than merely to a
+    /// list of leaves. Under the STIR soundness analysis this lets callers
+    /// run markedly fewer colinearity checks -- and thereby emit smaller
+    /// proofs -- at the same conjectured security; the check count itself
+    /// remains a caller choice.
+    ///
+    /// Out-of-domain sampling is interleaved with the commit phase, so
+    /// this schedule always uses the standard prover: the lean and
+    /// streaming provers reject it, and batched colinearity checks fall
+    /// back to individual ones.
+    Stir,
+}
+
+/// How codeword values are encoded into Merkle leaf digests. Both parties
+/// must agree on the encoding; it changes the transcript.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum LeafEncoding {
+    /// Hash the value's base-field coefficients through the configured
+    /// [`AlgebraicHasher`], as `hash_slice` lays them out.
+    #[default]
+    Algebraic,
+    /// Commit each base-field coefficient as its own 32-byte big-endian
+    /// word and hash the concatenation with SHA-256 -- the layout a
+    /// Solidity verifier gets from `abi.encode` and the hash the EVM
+    /// exposes as a precompile. Query openings can then be checked on
+    /// chain for a few hundred gas each, instead of running an algebraic
+    /// hash in contract code. Interior tree nodes still use the configured
+    /// hasher.
+    BaseFieldWords,
+}
+
+/// A progress report from [`Fri::prove_async`]: the number of commit-phase
+/// fold rounds completed so far, out of the total for this configuration.
+#[cfg(feature = "tokio")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FriProverProgress {
+    pub rounds_done: u8,
+    pub num_rounds: u8,
+}
+
+/// Hooks into the prover's progress, invoked by
+/// [`Fri::prove_with_observer`]. Implementations can drive progress bars or
+/// collect per-phase metrics without patching the crate; every method has an
+/// empty default body, so an observer only overrides the events it cares
+/// about.
+pub trait FriProverObserver {
+    /// Called after each commit-phase round. Round `0` covers hashing and
+    /// committing the initial codeword; round `r > 0` covers the `r`-th
+    /// fold, including hashing and committing its result. The reported
+    /// length is that of the codeword committed in the round.
+    fn on_commit_round(&mut self, round: usize, codeword_length: usize, elapsed: Duration) {
+        let _ = (round, codeword_length, elapsed);
+    }
+
+    /// Called once after the query phase, with the number of top-level
+    /// indices opened. The elapsed time includes grinding and index
+    /// sampling.
+    fn on_query_phase(&mut self, num_indices: usize, elapsed: Duration) {
+        let _ = (num_indices, elapsed);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Fri<H> {
+    pub expansion_factor: usize,         // = domain_length / trace_length
+    pub colinearity_checks_count: usize, // number of colinearity checks in each round
+    pub folding_factor: usize,           // arity of the fold in each round: 2, 4, or 8
+    // Number of leading zero bits the query-phase challenge hash must have.
+    // The prover grinds for a nonce that meets this target; zero disables
+    // grinding entirely.
+    pub grinding_bits: u8,
+    // Blind the initial codeword with a random multiple of the trace-domain
+    // zerofier before proving, so that query responses leak nothing about
+    // the underlying codeword. Doubles the degree bound the verifier
+    // accepts.
+    pub zero_knowledge: bool,
+    // Stop folding once the last round's degree bound fits under this cap,
+    // trading a larger plain last codeword for fewer rounds. `None` folds
+    // as far down as the parameters allow.
+    pub max_last_round_degree: Option<u32>,
+    // Which soundness bound -- proven or conjectured -- the security
+    // estimates assume; see [`SoundnessRegime`].
+    pub soundness_regime: SoundnessRegime,
+    // Replace the per-query colinearity checks of each round with a single
+    // random-linear-combination equation. The prover sends the claimed
+    // folded values alongside the sibling openings, and the verifier checks
+    // one weighted sum per round instead of interpolating every query. Only
+    // in effect for folding factor 2; higher arities fall back to the
+    // individual checks.
+    pub batched_colinearity_checks: bool,
+    // Which folding schedule both parties run; see [`FoldingSchedule`].
+    pub folding_schedule: FoldingSchedule,
+    // How codeword values are hashed into Merkle leaves; see
+    // [`LeafEncoding`].
+    pub leaf_encoding: LeafEncoding,
+    pub memory_profile: ProverMemoryProfile,
+    pub domain: FriDomain,
+    _hasher: PhantomData<H>,
+}
+
+/// The protocol label under which all FRI Fiat-Shamir challenges are
+/// derived; see [`Fri::protocol_tag`].
+const FRI_PROTOCOL_LABEL: &[u8] = b"twenty-first/fri/v1";
+
+pub type CodewordEvaluation<T> = (usize, T);
+
+/// The largest permitted proof-of-work difficulty, in leading zero bits of
+/// the query-phase challenge. The target is tested against a single 64-bit
+/// limb, so anything approaching 64 bits is unreachable and would spin the
+/// grinding loop forever; already 32 bits cost billions of hashes per proof.
+/// Configurations above this bound are rejected as misconfigurations.
+pub const MAX_GRINDING_BITS: u8 = 32;
+
+/// A hasher-independent, serializable description of a [`Fri`] instance:
+/// every configuration knob, but no `PhantomData` hasher. Useful for
+/// persisting prover configurations or shipping them between services;
+/// rehydrate with [`to_fri`], which picks the hasher back up as a type
+/// parameter.
+///
+/// [`to_fri`]: FriParameters::to_fri
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FriParameters {
+    pub offset: BFieldElement,
+    pub omega: BFieldElement,
+    pub domain_length: usize,
+    pub expansion_factor: usize,
+    pub colinearity_checks_count: usize,
+    pub folding_factor: usize,
+    pub grinding_bits: u8,
+    pub zero_knowledge: bool,
+    pub max_last_round_degree: Option<u32>,
+    pub soundness_regime: SoundnessRegime,
+    pub batched_colinearity_checks: bool,
+    pub folding_schedule: FoldingSchedule,
+    pub leaf_encoding: LeafEncoding,
+    pub memory_profile: ProverMemoryProfile,
+}
+
+impl FriParameters {
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Box<dyn Error>> {
+        Ok(bincode::serialize(self)?)
+    }
+
+    /// Deserialize and validate a parameter set; see [`validate`].
+    ///
+    /// [`validate`]: FriParameters::validate
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Box<dyn Error>> {
+        let parameters: Self = bincode::deserialize(bytes)?;
+        parameters.validate()?;
+
+        Ok(parameters)
+    }
+
+    /// Check the invariants [`Fri::new`] enforces, so that a deserialized
+    /// parameter set cannot smuggle in an unusable configuration.
+    pub fn validate(&self) -> Result<(), FriProverError> {
+        if ![2, 4, 8].contains(&self.folding_factor) {
+            return Err(FriProverError::UnsupportedFoldingFactor(
+                self.folding_factor,
+            ));
+        }
+        if !is_power_of_two(self.domain_length) {
+            return Err(FriProverError::DomainLengthNotPowerOfTwo);
+        }
+        if self.colinearity_checks_count > self.domain_length {
+            return Err(FriProverError::TooManyColinearityChecks);
+        }
+        if self.grinding_bits > MAX_GRINDING_BITS {
+            return Err(FriProverError::ExcessiveGrindingBits(self.grinding_bits));
         }
 
-        let mut a_indices: Vec<usize> = self.sample_indices(&proof_stream.verifier_fiat_shamir());
+        Ok(())
+    }
 
-        // for every round, check consistency of subsequent layers
-        let mut codeword_evaluations: Vec<CodewordEvaluation<XFieldElement>> = vec![];
-        let mut a_values = Self::dequeue_and_authenticate(&a_indices, roots[0], proof_stream)?;
+    /// Construct the [`Fri`] instance this parameter set describes.
+    pub fn to_fri<H>(&self) -> Result<Fri<H>, FriProverError>
+    where
+        H: AlgebraicHasher + Send + Sync,
+    {
+        let mut fri = Fri::new(
+            self.offset,
+            self.omega,
+            self.domain_length,
+            self.expansion_factor,
+            self.colinearity_checks_count,
+            self.folding_factor,
+        )?;
+        fri.grinding_bits = self.grinding_bits;
+        fri.zero_knowledge = self.zero_knowledge;
+        fri.max_last_round_degree = self.max_last_round_degree;
+        fri.soundness_regime = self.soundness_regime;
+        fri.batched_colinearity_checks = self.batched_colinearity_checks;
+        fri.folding_schedule = self.folding_schedule;
+        fri.leaf_encoding = self.leaf_encoding;
+        fri.memory_profile = self.memory_profile;
 
-        // set up "B" for offsetting inside loop.  Note that "B" and "A" indices
-        // can be calcuated from each other.
-        let mut b_indices = a_indices.clone();
-        let mut current_domain_len = self.domain.length;
+        Ok(fri)
+    }
+}
 
-        for r in 0..num_rounds as usize {
-            // get "B" indices and verify set membership of corresponding values
-            b_indices = b_indices
-                .iter()
-                .map(|x| (x + current_domain_len / 2) % current_domain_len)
+/// Derives a sound FRI parameter set from a target security level, so that
+/// callers need not hand-pick `expansion_factor` and
+/// `colinearity_checks_count`. Construct via [`Fri::builder`].
+#[derive(Debug, Clone)]
+pub struct FriBuilder<H> {
+    domain_length: Option<usize>,
+    offset: BFieldElement,
+    expansion_factor: usize,
+    folding_factor: usize,
+    grinding_bits: u8,
+    target_security_bits: usize,
+    soundness_regime: SoundnessRegime,
+    _hasher: PhantomData<H>,
+}
+
+impl<H> FriBuilder<H>
+where
+    H: AlgebraicHasher + Send + Sync,
+{
+    pub fn domain_length(mut self, domain_length: usize) -> Self {
+        self.domain_length = Some(domain_length);
+        self
+    }
+
+    pub fn offset(mut self, offset: BFieldElement) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    pub fn expansion_factor(mut self, expansion_factor: usize) -> Self {
+        self.expansion_factor = expansion_factor;
+        self
+    }
+
+    pub fn folding_factor(mut self, folding_factor: usize) -> Self {
+        self.folding_factor = folding_factor;
+        self
+    }
+
+    pub fn grinding_bits(mut self, grinding_bits: u8) -> Self {
+        self.grinding_bits = grinding_bits;
+        self
+    }
+
+    pub fn target_security_bits(mut self, target_security_bits: usize) -> Self {
+        self.target_security_bits = target_security_bits;
+        self
+    }
+
+    pub fn soundness_regime(mut self, soundness_regime: SoundnessRegime) -> Self {
+        self.soundness_regime = soundness_regime;
+        self
+    }
+
+    /// Compute the colinearity check count that meets the target security
+    /// level and construct the corresponding [`Fri`] object. Errors if no
+    /// domain length was set, or if the target cannot be reached with the
+    /// chosen rate and domain.
+    pub fn build(self) -> Result<Fri<H>, FriProverError> {
+        let domain_length = self
+            .domain_length
+            .ok_or(FriProverError::MissingDomainLength)?;
+        let omega = BFieldElement::primitive_root_of_unity(domain_length as u64)
+            .ok_or(FriProverError::DomainLengthNotPowerOfTwo)?;
+        if self.grinding_bits > MAX_GRINDING_BITS {
+            return Err(FriProverError::ExcessiveGrindingBits(self.grinding_bits));
+        }
+
+        let bits_per_check = self
+            .soundness_regime
+            .bits_per_colinearity_check(self.expansion_factor);
+        let query_phase_bits = self
+            .target_security_bits
+            .saturating_sub(self.grinding_bits as usize);
+        let colinearity_checks_count = (query_phase_bits as f64 / bits_per_check).ceil() as usize;
+        if colinearity_checks_count > domain_length {
+            return Err(FriProverError::TargetSecurityUnreachable);
+        }
+
+        let mut fri = Fri::new(
+            self.offset,
+            omega,
+            domain_length,
+            self.expansion_factor,
+            colinearity_checks_count,
+            self.folding_factor,
+        )?;
+        fri.grinding_bits = self.grinding_bits;
+        fri.soundness_regime = self.soundness_regime;
+
+        if fri.security_bits() < self.target_security_bits as f64 {
+            return Err(FriProverError::TargetSecurityUnreachable);
+        }
+
+        Ok(fri)
+    }
+}
+
+impl<H> Fri<H>
+where
+    H: AlgebraicHasher + Send + Sync,
+{
+    /// A [`FriBuilder`] with an expansion factor of 4, a folding factor of 2,
+    /// no grinding, and conjectured soundness.
+    pub fn builder() -> FriBuilder<H> {
+        FriBuilder {
+            domain_length: None,
+            offset: BFieldElement::generator(),
+            expansion_factor: 4,
+            folding_factor: 2,
+            grinding_bits: 0,
+            target_security_bits: 0,
+            soundness_regime: SoundnessRegime::Conjectured,
+            _hasher: PhantomData,
+        }
+    }
+
+    pub fn new(
+        offset: BFieldElement,
+        omega: BFieldElement,
+        domain_length: usize,
+        expansion_factor: usize,
+        colinearity_checks_count: usize,
+        folding_factor: usize,
+    ) -> Result<Self, FriProverError> {
+        if ![2, 4, 8].contains(&folding_factor) {
+            return Err(FriProverError::UnsupportedFoldingFactor(folding_factor));
+        }
+        if !is_power_of_two(domain_length) {
+            return Err(FriProverError::DomainLengthNotPowerOfTwo);
+        }
+        if colinearity_checks_count > domain_length {
+            return Err(FriProverError::TooManyColinearityChecks);
+        }
+        let domain = FriDomain::new(offset, omega, domain_length);
+        let _hasher = PhantomData;
+        Ok(Self {
+            domain,
+            expansion_factor,
+            colinearity_checks_count,
+            folding_factor,
+            grinding_bits: 0,
+            zero_knowledge: false,
+            max_last_round_degree: None,
+            soundness_regime: SoundnessRegime::default(),
+            batched_colinearity_checks: false,
+            folding_schedule: FoldingSchedule::default(),
+            leaf_encoding: LeafEncoding::default(),
+            memory_profile: ProverMemoryProfile::default(),
+            _hasher,
+        })
+    }
+
+    /// The hasher-independent [`FriParameters`] describing this instance.
+    pub fn parameters(&self) -> FriParameters {
+        FriParameters {
+            offset: self.domain.offset,
+            omega: self.domain.omega,
+            domain_length: self.domain.length,
+            expansion_factor: self.expansion_factor,
+            colinearity_checks_count: self.colinearity_checks_count,
+            folding_factor: self.folding_factor,
+            grinding_bits: self.grinding_bits,
+            zero_knowledge: self.zero_knowledge,
+            max_last_round_degree: self.max_last_round_degree,
+            soundness_regime: self.soundness_regime,
+            batched_colinearity_checks: self.batched_colinearity_checks,
+            folding_schedule: self.folding_schedule,
+            leaf_encoding: self.leaf_encoding,
+            memory_profile: self.memory_profile,
+        }
+    }
+
+    /// A domain-separation tag for a protocol-wide challenge kind: the FRI
+    /// protocol label followed by the kind. Mixed into every Fiat-Shamir
+    /// derivation so that FRI challenges can never collide with those of
+    /// another protocol reading the same bytes.
+    fn protocol_tag(kind: &[u8]) -> Vec<u8> {
+        [FRI_PROTOCOL_LABEL, b"/", kind].concat()
+    }
+
+    /// A domain-separation tag for a per-round challenge kind: the protocol
+    /// tag followed by the round number.
+    fn round_tag(kind: &[u8], round: usize) -> Vec<u8> {
+        let mut tag = Self::protocol_tag(kind);
+        tag.extend((round as u32).to_le_bytes());
+        tag
+    }
+
+    /// The sorted, duplicate-free version of a list of query indices. Prover
+    /// and verifier derive this list independently, so only the unique
+    /// positions ever hit the proof stream.
+    fn unique_indices(indices: &[usize]) -> Vec<usize> {
+        indices.iter().copied().sorted_unstable().dedup().collect()
+    }
+
+    /// Build the (deduplicated) Merkle authentication paths for the codeword at the given indices
+    /// and enqueue the corresponding values and (partial) authentication paths on the proof stream.
+    ///
+    /// Indices that repeat in `indices` are opened only once.
+    fn enqueue_auth_pairs<FF: FriFieldElement>(
+        indices: &[usize],
+        codeword: &[FF],
+        merkle_tree: &MerkleTree<H>,
+        proof_stream: &mut ProofStream,
+    ) -> Result<(), Box<dyn Error>> {
+        let unique_indices = Self::unique_indices(indices);
+        let value_ap_pairs: Vec<(PartialAuthenticationPath<Digest>, FF)> = merkle_tree
+            .get_authentication_structure(&unique_indices)
+            .into_iter()
+            .zip(unique_indices.iter())
+            .map(|(ap, i)| (ap, codeword[*i]))
+            .collect_vec();
+        enqueue_query_pairs(&value_ap_pairs, proof_stream)?;
+
+        Ok(())
+    }
+
+    /// Hash a codeword value into its Merkle leaf under `encoding`; see
+    /// [`LeafEncoding`]. The algebraic encoding prepends
+    /// [`MERKLE_LEAF_DOMAIN_TAG`], separating the leaf domain from the
+    /// internal-node domain of the commitment trees. The EVM word
+    /// encoding keeps its locked-down layout: its leaves are SHA-256
+    /// digests while the nodes are compressed with the algebraic hasher,
+    /// so the two domains are already disjoint.
+    fn leaf_digest<FF: FriFieldElement>(encoding: LeafEncoding, value: &FF) -> Digest {
+        match encoding {
+            LeafEncoding::Algebraic => domain_separated_leaf_digest::<H>(&value.to_sequence()),
+            LeafEncoding::BaseFieldWords => {
+                let coefficients = value.to_sequence();
+                let mut words: Vec<u8> = Vec::with_capacity(32 * coefficients.len());
+                for coefficient in coefficients {
+                    words.extend_from_slice(&[0u8; 24]);
+                    words.extend_from_slice(&coefficient.value().to_be_bytes());
+                }
+                let hash = ring::digest::digest(&ring::digest::SHA256, &words);
+                let bytes = hash.as_ref();
+                Digest::new([
+                    BFieldElement::from_ne_bytes(&bytes[0..8]),
+                    BFieldElement::from_ne_bytes(&bytes[8..16]),
+                    BFieldElement::from_ne_bytes(&bytes[16..24]),
+                    BFieldElement::from_ne_bytes(&bytes[24..32]),
+                    BFieldElement::zero(),
+                ])
+            }
+        }
+    }
+
+    /// [`enqueue_auth_pairs`] against a [`DiskBackedMerkleTree`]. The tree
+    /// handle is mutable because database reads are.
+    ///
+    /// [`enqueue_auth_pairs`]: Fri::enqueue_auth_pairs
+    #[cfg(feature = "std")]
+    fn enqueue_auth_pairs_from_disk<FF: FriFieldElement>(
+        indices: &[usize],
+        codeword: &[FF],
+        merkle_tree: &mut DiskBackedMerkleTree<H>,
+        proof_stream: &mut ProofStream,
+    ) -> Result<(), Box<dyn Error>> {
+        let unique_indices = Self::unique_indices(indices);
+        let value_ap_pairs: Vec<(PartialAuthenticationPath<Digest>, FF)> = merkle_tree
+            .get_authentication_structure(&unique_indices)
+            .into_iter()
+            .zip(unique_indices.iter())
+            .map(|(ap, i)| (ap, codeword[*i]))
+            .collect_vec();
+        enqueue_query_pairs(&value_ap_pairs, proof_stream)?;
+
+        Ok(())
+    }
+
+    /// Given a set of `indices`, a merkle `root`, and the (correctly set) `proof_stream`, verify
+    /// whether the values at the `indices` are members of the set committed to by the merkle `root`
+    /// and return these values if they are. Fails otherwise.
+    ///
+    /// Mirrors the deduplication performed by [`enqueue_auth_pairs`]: the
+    /// proof stream holds one opening per unique index, and the returned
+    /// values are mapped back onto the order (and multiplicity) of `indices`.
+    ///
+    /// [`enqueue_auth_pairs`]: Fri::enqueue_auth_pairs
+    fn dequeue_and_authenticate<FF: FriFieldElement>(
+        indices: &[usize],
+        root: Digest,
+        encoding: LeafEncoding,
+        proof_stream: &mut ProofStream,
+    ) -> Result<Vec<FF>, Box<dyn Error>> {
+        let unique_indices = Self::unique_indices(indices);
+        let (paths, values): (Vec<PartialAuthenticationPath<Digest>>, Vec<FF>) =
+            dequeue_query_pairs::<FF>(proof_stream)?.into_iter().unzip();
+        if values.len() != unique_indices.len() {
+            return Err(Box::new(ValidationError::BadMerkleProof));
+        }
+        let digests: Vec<Digest> = values
+            .par_iter()
+            .map(|v| Self::leaf_digest(encoding, v))
+            .collect();
+        let path_digest_pairs = paths.into_iter().zip(digests).collect_vec();
+
+        MerkleTree::<H>::check_authentication_structure(root, &unique_indices, &path_digest_pairs)
+            .map_err(ValidationError::BadAuthenticationStructure)?;
+
+        let value_of_index: HashMap<usize, FF> = unique_indices.into_iter().zip(values).collect();
+        Ok(indices.iter().map(|i| value_of_index[i]).collect())
+    }
+
+    pub fn prove(
+        &self,
+        codeword: &[XFieldElement],
+        proof_stream: &mut ProofStream,
+    ) -> Result<Vec<usize>, FriProverError> {
+        self.prove_in_field(codeword, proof_stream)
+    }
+
+    /// Run the prover on a codeword over any [`FriFieldElement`] field. In
+    /// particular, base-field codewords can be proven directly, without
+    /// lifting every value into the extension field first.
+    pub fn prove_in_field<FF: FriFieldElement>(
+        &self,
+        codeword: &[FF],
+        proof_stream: &mut ProofStream,
+    ) -> Result<Vec<usize>, FriProverError> {
+        if self.domain.length != codeword.len() {
+            return Err(FriProverError::CodewordLengthMismatch {
+                expected: self.domain.length,
+                actual: codeword.len(),
+            });
+        }
+
+        let blinded_codeword: Vec<FF>;
+        let codeword = if self.zero_knowledge {
+            blinded_codeword = self.blind_codeword(codeword);
+            &blinded_codeword
+        } else {
+            codeword
+        };
+
+        match self.memory_profile {
+            // The STIR schedule interleaves out-of-domain sampling with the
+            // commit phase; only the standard prover implements it.
+            ProverMemoryProfile::Lean if !self.stir_active() => {
+                self.prove_lean(codeword, proof_stream)
+            }
+            _ => self.prove_standard(codeword, proof_stream),
+        }
+    }
+
+    /// Run the prover with per-phase progress reported to `observer`; see
+    /// [`FriProverObserver`]. The observer only watches: the standard
+    /// prover is always used, and the transcript is byte-identical to
+    /// [`prove`]'s.
+    ///
+    /// [`prove`]: Fri::prove
+    pub fn prove_with_observer(
+        &self,
+        codeword: &[XFieldElement],
+        proof_stream: &mut ProofStream,
+        observer: &mut dyn FriProverObserver,
+    ) -> Result<Vec<usize>, FriProverError> {
+        self.prove_in_field_with_observer(codeword, proof_stream, observer)
+    }
+
+    /// Like [`prove_with_observer`], over any [`FriFieldElement`] field.
+    ///
+    /// [`prove_with_observer`]: Fri::prove_with_observer
+    pub fn prove_in_field_with_observer<FF: FriFieldElement>(
+        &self,
+        codeword: &[FF],
+        proof_stream: &mut ProofStream,
+        observer: &mut dyn FriProverObserver,
+    ) -> Result<Vec<usize>, FriProverError> {
+        if self.domain.length != codeword.len() {
+            return Err(FriProverError::CodewordLengthMismatch {
+                expected: self.domain.length,
+                actual: codeword.len(),
+            });
+        }
+
+        let blinded_codeword: Vec<FF>;
+        let codeword = if self.zero_knowledge {
+            blinded_codeword = self.blind_codeword(codeword);
+            &blinded_codeword
+        } else {
+            codeword
+        };
+
+        let (codewords, merkle_trees): (Vec<Vec<FF>>, Vec<MerkleTree<H>>) = self
+            .commit(codeword, proof_stream, None, Some(observer))?
+            .into_iter()
+            .unzip();
+
+        let query_phase_start = Instant::now();
+        let top_level_indices =
+            self.standard_query_phase(codeword, &codewords, &merkle_trees, proof_stream)?;
+        observer.on_query_phase(top_level_indices.len(), query_phase_start.elapsed());
+
+        Ok(top_level_indices)
+    }
+
+    /// Run the prover with the commit phase offloaded to `backend`.
+    ///
+    /// Leaf hashing and the per-round fold go through the backend;
+    /// Fiat-Shamir, grinding, and the query phase are unchanged, and the
+    /// transcript is byte-identical to [`prove`]'s. All intermediate
+    /// codewords and Merkle trees are retained for the query phase, so the
+    /// configured [`ProverMemoryProfile`] is ignored.
+    ///
+    /// [`prove`]: Fri::prove
+    #[cfg(feature = "gpu")]
+    pub fn prove_with_backend(
+        &self,
+        codeword: &[XFieldElement],
+        proof_stream: &mut ProofStream,
+        backend: &impl CommitBackend<XFieldElement, H>,
+    ) -> Result<Vec<usize>, FriProverError> {
+        self.prove_in_field_with_backend(codeword, proof_stream, backend)
+    }
+
+    /// Like [`prove_with_backend`], over any [`FriFieldElement`] field.
+    ///
+    /// [`prove_with_backend`]: Fri::prove_with_backend
+    #[cfg(feature = "gpu")]
+    pub fn prove_in_field_with_backend<FF: FriFieldElement>(
+        &self,
+        codeword: &[FF],
+        proof_stream: &mut ProofStream,
+        backend: &impl CommitBackend<FF, H>,
+    ) -> Result<Vec<usize>, FriProverError> {
+        if self.domain.length != codeword.len() {
+            return Err(FriProverError::CodewordLengthMismatch {
+                expected: self.domain.length,
+                actual: codeword.len(),
+            });
+        }
+
+        let blinded_codeword: Vec<FF>;
+        let codeword = if self.zero_knowledge {
+            blinded_codeword = self.blind_codeword(codeword);
+            &blinded_codeword
+        } else {
+            codeword
+        };
+
+        let (codewords, merkle_trees): (Vec<Vec<FF>>, Vec<MerkleTree<H>>) = self
+            .commit_with_backend(codeword, proof_stream, backend, None, None)?
+            .into_iter()
+            .unzip();
+
+        self.standard_query_phase(codeword, &codewords, &merkle_trees, proof_stream)
+    }
+
+    /// Run the prover with the first round's Merkle leaves taken from
+    /// `leaf_digests` instead of re-hashing the codeword.
+    ///
+    /// Callers that have already hashed every codeword value -- a STARK
+    /// committing to trace columns, say -- can hand those digests in and
+    /// skip the most expensive hashing pass of the commit phase. The
+    /// digests must be what [`hash_leaves`] would produce for this codeword
+    /// under the configured [`LeafEncoding`]; the transcript is
+    /// byte-identical to [`prove`]'s. The standard prover is always used,
+    /// and since blinding rewrites the codeword, configurations with
+    /// `zero_knowledge` set are rejected.
+    ///
+    /// [`hash_leaves`]: CommitBackend::hash_leaves
+    /// [`prove`]: Fri::prove
+    pub fn prove_with_digests(
+        &self,
+        codeword: &[XFieldElement],
+        leaf_digests: &[Digest],
+        proof_stream: &mut ProofStream,
+    ) -> Result<Vec<usize>, FriProverError> {
+        self.prove_in_field_with_digests(codeword, leaf_digests, proof_stream)
+    }
+
+    /// Like [`prove_with_digests`], over any [`FriFieldElement`] field.
+    ///
+    /// [`prove_with_digests`]: Fri::prove_with_digests
+    pub fn prove_in_field_with_digests<FF: FriFieldElement>(
+        &self,
+        codeword: &[FF],
+        leaf_digests: &[Digest],
+        proof_stream: &mut ProofStream,
+    ) -> Result<Vec<usize>, FriProverError> {
+        if self.domain.length != codeword.len() {
+            return Err(FriProverError::CodewordLengthMismatch {
+                expected: self.domain.length,
+                actual: codeword.len(),
+            });
+        }
+        if leaf_digests.len() != codeword.len() {
+            return Err(FriProverError::LeafDigestCountMismatch {
+                expected: codeword.len(),
+                actual: leaf_digests.len(),
+            });
+        }
+        if self.zero_knowledge {
+            return Err(FriProverError::PrecomputedDigestsWithBlinding);
+        }
+
+        let (codewords, merkle_trees): (Vec<Vec<FF>>, Vec<MerkleTree<H>>) = self
+            .commit(codeword, proof_stream, Some(leaf_digests), None)?
+            .into_iter()
+            .unzip();
+
+        self.standard_query_phase(codeword, &codewords, &merkle_trees, proof_stream)
+    }
+
+    /// Run the prover with every commit-phase Merkle tree backed by one of
+    /// the supplied databases instead of RAM; see [`DiskBackedMerkleTree`].
+    ///
+    /// For domains of 2^26 leaves and beyond, holding every round's tree in
+    /// memory until the query phase exhausts RAM long before the codewords
+    /// themselves do. This prover writes each tree's nodes to its database as
+    /// they are computed and reads back only the nodes the query phase
+    /// reveals. One empty database per tree is required --
+    /// [`tree_database_count`] says how many. Intermediate codewords are
+    /// still held in memory, and the transcript is byte-identical to
+    /// [`prove`]'s.
+    ///
+    /// [`prove`]: Fri::prove
+    /// [`tree_database_count`]: Fri::tree_database_count
+    #[cfg(feature = "std")]
+    pub fn prove_disk_backed(
+        &self,
+        codeword: &[XFieldElement],
+        proof_stream: &mut ProofStream,
+        tree_databases: Vec<DB>,
+    ) -> Result<Vec<usize>, FriProverError> {
+        self.prove_in_field_disk_backed(codeword, proof_stream, tree_databases)
+    }
+
+    /// Like [`prove_disk_backed`], over any [`FriFieldElement`] field.
+    ///
+    /// [`prove_disk_backed`]: Fri::prove_disk_backed
+    #[cfg(feature = "std")]
+    pub fn prove_in_field_disk_backed<FF: FriFieldElement>(
+        &self,
+        codeword: &[FF],
+        proof_stream: &mut ProofStream,
+        tree_databases: Vec<DB>,
+    ) -> Result<Vec<usize>, FriProverError> {
+        if self.domain.length != codeword.len() {
+            return Err(FriProverError::CodewordLengthMismatch {
+                expected: self.domain.length,
+                actual: codeword.len(),
+            });
+        }
+        if tree_databases.len() != self.tree_database_count() {
+            return Err(FriProverError::WrongTreeDatabaseCount {
+                expected: self.tree_database_count(),
+                actual: tree_databases.len(),
+            });
+        }
+
+        let blinded_codeword: Vec<FF>;
+        let codeword = if self.zero_knowledge {
+            blinded_codeword = self.blind_codeword(codeword);
+            &blinded_codeword
+        } else {
+            codeword
+        };
+
+        // Commit phase: intermediate codewords stay in memory, Merkle nodes
+        // go to the databases
+        let _commit_span = fri_span!("fri_commit_phase", codeword_length = codeword.len());
+        let (num_rounds, _) = self.num_rounds();
+        let mut databases = tree_databases.into_iter();
+        let mut codeword_local = codeword.to_vec();
+        let mut codewords: Vec<Vec<FF>> = Vec::with_capacity(num_rounds as usize + 1);
+        let mut merkle_trees: Vec<DiskBackedMerkleTree<H>> =
+            Vec::with_capacity(num_rounds as usize + 1);
+
+        {
+            let _merkle_span = fri_span!("merkle_tree_construction", leaf_count = codeword.len());
+            let leaf_digests: Vec<Digest> = codeword_local
+                .par_iter()
+                .map(|x| Self::leaf_digest(self.leaf_encoding, x))
                 .collect();
+            let mut mt =
+                DiskBackedMerkleTree::from_digests(&leaf_digests, databases.next().unwrap());
+            proof_stream.enqueue(&mt.get_root())?;
+            codewords.push(codeword_local.clone());
+            merkle_trees.push(mt);
+        }
 
-            let b_values = Self::dequeue_and_authenticate(&b_indices, roots[r], proof_stream)?;
+        for round in 0..num_rounds {
+            let _round_span = fri_span!(
+                "fri_fold_round",
+                round = round,
+                codeword_length = codeword_local.len()
+            );
+            let challenge: Digest =
+                proof_stream.prover_fiat_shamir_tagged(&Self::round_tag(b"alpha", round as usize));
+            let alpha: FF = FF::sample_challenge(&challenge);
 
-            debug_assert_eq!(
-                self.colinearity_checks_count,
-                a_indices.len(),
-                "There must be equally many 'a indices' as there are colinearity checks."
+            let round_domain = self
+                .domain
+                .round_domain(round as usize, self.folding_factor);
+            codeword_local = Self::fold_codeword(
+                &codeword_local,
+                round_domain.omega,
+                round_domain.offset,
+                alpha,
+                self.folding_factor,
             );
-            debug_assert_eq!(
-                self.colinearity_checks_count,
-                b_indices.len(),
-                "There must be equally many 'b indices' as there are colinearity checks."
+
+            let folded_digests: Vec<Digest> = codeword_local
+                .par_iter()
+                .map(|x| Self::leaf_digest(self.leaf_encoding, x))
+                .collect();
+            let mut mt =
+                DiskBackedMerkleTree::from_digests(&folded_digests, databases.next().unwrap());
+            proof_stream.enqueue(&mt.get_root())?;
+            codewords.push(codeword_local.clone());
+            merkle_trees.push(mt);
+
+            // STIR schedule, mirroring `commit_with_backend`: answer an
+            // out-of-domain sample of the freshly committed codeword and run
+            // the next round on the quotient
+            if self.stir_active() {
+                let z: FF = FF::sample_challenge(
+                    &proof_stream
+                        .prover_fiat_shamir_tagged(&Self::round_tag(b"ood", round as usize)),
+                );
+                let folded_omega = self
+                    .domain
+                    .round_domain(round as usize + 1, self.folding_factor)
+                    .omega;
+                let y = Self::normalized_interpolant(&codeword_local, folded_omega).evaluate(&z);
+                proof_stream.enqueue_length_prepended(&y)?;
+                if round + 1 < num_rounds {
+                    codeword_local = Self::quotient_codeword(&codeword_local, folded_omega, z, y);
+                }
+            }
+        }
+        proof_stream.enqueue_length_prepended(&codeword_local)?;
+
+        // fiat-shamir phase (get indices), preceded by proof-of-work
+        // grinding if so configured
+        if self.grinding_bits > 0 {
+            let nonce = self.grind_nonce(proof_stream)?;
+            proof_stream.enqueue(&nonce)?;
+        }
+        let top_level_indices = self.sample_indices(
+            &proof_stream.prover_fiat_shamir_tagged(&Self::protocol_tag(b"indices")),
+        );
+
+        // Query phase, opening the committed values against the databases
+        let _query_span = fri_span!(
+            "fri_query_phase",
+            colinearity_checks = self.colinearity_checks_count
+        );
+        Self::enqueue_auth_pairs_from_disk(
+            &top_level_indices,
+            &codewords[0],
+            &mut merkle_trees[0],
+            proof_stream,
+        )?;
+        let mut current_domain_len = self.domain.length;
+        let mut a_indices: Vec<usize> = top_level_indices.clone();
+
+        for r in 0..num_rounds as usize {
+            for t in 1..self.folding_factor {
+                let sibling_indices: Vec<usize> = a_indices
+                    .iter()
+                    .map(|x| {
+                        (x + t * current_domain_len / self.folding_factor) % current_domain_len
+                    })
+                    .collect();
+                Self::enqueue_auth_pairs_from_disk(
+                    &sibling_indices,
+                    &codewords[r],
+                    &mut merkle_trees[r],
+                    proof_stream,
+                )?;
+            }
+            current_domain_len /= self.folding_factor;
+            a_indices = a_indices.iter().map(|x| x % current_domain_len).collect();
+            if self.batched_checks_active() {
+                let claimed_c_values: Vec<FF> =
+                    a_indices.iter().map(|i| codewords[r + 1][*i]).collect();
+                proof_stream.enqueue_length_prepended(&claimed_c_values)?;
+            }
+        }
+
+        Ok(top_level_indices)
+    }
+
+    /// The number of databases [`prove_disk_backed`] consumes: one per
+    /// commit-phase Merkle tree.
+    ///
+    /// [`prove_disk_backed`]: Fri::prove_disk_backed
+    #[cfg(feature = "std")]
+    pub fn tree_database_count(&self) -> usize {
+        self.num_rounds().0 as usize + 1
+    }
+
+    /// Run the standard prover on the tokio blocking pool, one commit-phase
+    /// chunk per blocking task, so that long proofs can be awaited, observed,
+    /// and aborted from a service context. A progress report is sent through
+    /// `progress` after every completed fold round, and `cancel` is checked
+    /// between chunks; setting it aborts the run with
+    /// [`FriProverError::Cancelled`]. The transcript is byte-identical to
+    /// [`prove`]'s.
+    ///
+    /// The blocking tasks require owned data, so the codeword and proof
+    /// stream are taken and returned by value. All intermediate codewords
+    /// and Merkle trees are retained for the query phase; the configured
+    /// [`ProverMemoryProfile`] is ignored.
+    ///
+    /// [`prove`]: Fri::prove
+    #[cfg(feature = "tokio")]
+    pub async fn prove_async<FF>(
+        &self,
+        codeword: Vec<FF>,
+        mut proof_stream: ProofStream,
+        cancel: Arc<AtomicBool>,
+        progress: Option<UnboundedSender<FriProverProgress>>,
+    ) -> Result<(Vec<usize>, ProofStream), FriProverError>
+    where
+        FF: FriFieldElement + Send + Sync + 'static,
+        H: 'static,
+    {
+        if self.domain.length != codeword.len() {
+            return Err(FriProverError::CodewordLengthMismatch {
+                expected: self.domain.length,
+                actual: codeword.len(),
+            });
+        }
+        if self.stir_active() {
+            return Err(FriProverError::UnsupportedFoldingSchedule);
+        }
+
+        let (num_rounds, _) = self.num_rounds();
+        let report = |rounds_done: u8| {
+            if let Some(sender) = &progress {
+                let _ = sender.send(FriProverProgress {
+                    rounds_done,
+                    num_rounds,
+                });
+            }
+        };
+
+        // First chunk: blinding (if configured) and the first Merkle tree.
+        if cancel.load(Ordering::Relaxed) {
+            return Err(FriProverError::Cancelled);
+        }
+        let fri = self.clone();
+        let (mut codeword_local, mut values_and_merkle_trees, mut proof_stream) =
+            tokio::task::spawn_blocking(move || {
+                let codeword = if fri.zero_knowledge {
+                    fri.blind_codeword(&codeword)
+                } else {
+                    codeword
+                };
+                let digests = <RayonCommitBackend as CommitBackend<FF, H>>::hash_leaves(
+                    &RayonCommitBackend,
+                    &codeword,
+                    fri.leaf_encoding,
+                );
+                let mt: MerkleTree<H> = MerkleTree::from_digests(&digests);
+                proof_stream.enqueue(&mt.get_root())?;
+                let values_and_merkle_trees = vec![(codeword.clone(), mt)];
+                Ok::<_, FriProverError>((codeword, values_and_merkle_trees, proof_stream))
+            })
+            .await
+            .expect("FRI prover task panicked")?;
+        report(0);
+
+        // One chunk per fold round; Fiat-Shamir stays on the async side.
+        for round in 0..num_rounds {
+            if cancel.load(Ordering::Relaxed) {
+                return Err(FriProverError::Cancelled);
+            }
+            let challenge: Digest =
+                proof_stream.prover_fiat_shamir_tagged(&Self::round_tag(b"alpha", round as usize));
+            let alpha: FF = FF::sample_challenge(&challenge);
+            let folding_factor = self.folding_factor;
+            let leaf_encoding = self.leaf_encoding;
+            let round_domain = self.domain.round_domain(round as usize, folding_factor);
+            let (folded, mt) = tokio::task::spawn_blocking(move || {
+                let folded = Self::fold_codeword(
+                    &codeword_local,
+                    round_domain.omega,
+                    round_domain.offset,
+                    alpha,
+                    folding_factor,
+                );
+                let digests = <RayonCommitBackend as CommitBackend<FF, H>>::hash_leaves(
+                    &RayonCommitBackend,
+                    &folded,
+                    leaf_encoding,
+                );
+                let mt: MerkleTree<H> = MerkleTree::from_digests(&digests);
+                (folded, mt)
+            })
+            .await
+            .expect("FRI prover task panicked");
+            proof_stream.enqueue(&mt.get_root())?;
+            codeword_local = folded;
+            values_and_merkle_trees.push((codeword_local.clone(), mt));
+            report(round + 1);
+        }
+        proof_stream.enqueue_length_prepended(&codeword_local)?;
+
+        // Last chunk: grinding (if configured) and the query phase.
+        if cancel.load(Ordering::Relaxed) {
+            return Err(FriProverError::Cancelled);
+        }
+        let query_fri = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let (codewords, merkle_trees): (Vec<Vec<FF>>, Vec<MerkleTree<H>>) =
+                values_and_merkle_trees.into_iter().unzip();
+            let top_level_indices = query_fri.standard_query_phase(
+                &codewords[0],
+                &codewords,
+                &merkle_trees,
+                &mut proof_stream,
+            )?;
+            Ok((top_level_indices, proof_stream))
+        })
+        .await
+        .expect("FRI prover task panicked")
+    }
+
+    /// Add a uniformly random codeword that vanishes on the trace domain:
+    /// the evaluation of `r(x) * (x^d - 1)` over the FRI domain, for a
+    /// random polynomial `r` of degree less than `d`. The blinded codeword
+    /// agrees with the original on the trace domain but reveals nothing
+    /// about it anywhere on the (disjoint) FRI domain.
+    fn blind_codeword<FF: FriFieldElement>(&self, codeword: &[FF]) -> Vec<FF> {
+        let data_length = self.domain.length / self.expansion_factor;
+        let randomizer = Polynomial::<FF>::new(
+            (0..data_length)
+                .map(|_| FF::sample_challenge(&Digest::new(random_elements_array())))
+                .collect(),
+        );
+        let mut randomizer_codeword: Vec<FF> = randomizer.scale(&self.domain.offset).coefficients;
+        randomizer_codeword.resize(self.domain.length, FF::zero());
+        ntt(
+            &mut randomizer_codeword,
+            self.domain.omega,
+            log_2_ceil(self.domain.length as u128) as u32,
+        );
+
+        // The zerofier `x^d - 1` of the trace domain is cheap to evaluate
+        // over the FRI domain since `omega^d` has small order.
+        let offset_pow_d = self.domain.offset.mod_pow(data_length as u64);
+        let omega_pow_d = self.domain.omega.mod_pow(data_length as u64);
+        let mut zerofier_value = offset_pow_d;
+        codeword
+            .iter()
+            .zip(randomizer_codeword.iter())
+            .map(|(&value, &randomizer_value)| {
+                let blinder =
+                    randomizer_value * FF::from_base(zerofier_value - BFieldElement::one());
+                zerofier_value *= omega_pow_d;
+                value + blinder
+            })
+            .collect()
+    }
+
+    /// Run the prover against a [`CodewordSource`] without ever holding the
+    /// full first-round codeword in memory. The first Merkle tree is built
+    /// incrementally from streamed values, the first fold reads
+    /// `folding_factor` interleaved passes of the source, and only the (much
+    /// smaller) folded codewords are materialized. With `zero_knowledge`
+    /// disabled, the transcript is identical to that of [`prove`]; blinding
+    /// would have to rewrite the streamed values, so provers with
+    /// `zero_knowledge` set are rejected rather than silently committing the
+    /// codeword unblinded.
+    ///
+    /// [`prove`]: Fri::prove
+    pub fn prove_stream<FF: FriFieldElement>(
+        &self,
+        source: &impl CodewordSource<FF>,
+        proof_stream: &mut ProofStream,
+    ) -> Result<Vec<usize>, FriProverError> {
+        if self.domain.length != source.length() {
+            return Err(FriProverError::CodewordLengthMismatch {
+                expected: self.domain.length,
+                actual: source.length(),
+            });
+        }
+        if self.stir_active() {
+            return Err(FriProverError::UnsupportedFoldingSchedule);
+        }
+        if self.zero_knowledge {
+            return Err(FriProverError::StreamedCodewordWithBlinding);
+        }
+
+        let (num_rounds, _) = self.num_rounds();
+        let n = self.domain.length;
+        let m = self.folding_factor;
+
+        // Commit phase, first round: hash streamed values into leaf digests
+        let first_digests: Vec<Digest> = source
+            .stream_from(0)
+            .map(|value| Self::leaf_digest(self.leaf_encoding, &value))
+            .collect();
+        let first_tree: MerkleTree<H> = MerkleTree::from_digests(&first_digests);
+        proof_stream.enqueue(&first_tree.get_root())?;
+
+        // First fold: zip `folding_factor` interleaved passes of the source
+        // so that each fold group is complete without buffering the codeword
+        let mut codewords: Vec<Vec<FF>> = Vec::with_capacity(num_rounds as usize);
+        let mut merkle_trees: Vec<MerkleTree<H>> = vec![];
+        if num_rounds > 0 {
+            let challenge: Digest =
+                proof_stream.prover_fiat_shamir_tagged(&Self::round_tag(b"alpha", 0));
+            let alpha: FF = FF::sample_challenge(&challenge);
+
+            let mut arms: Vec<_> = (0..m).map(|t| source.stream_from(t * (n / m))).collect();
+            let mut x_arms: Vec<BFieldElement> = (0..m)
+                .map(|t| self.domain.offset * self.domain.omega.mod_pow((t * (n / m)) as u64))
+                .collect();
+            let mut folded: Vec<FF> = Vec::with_capacity(n / m);
+            for _ in 0..n / m {
+                let points: Vec<(FF, FF)> = arms
+                    .iter_mut()
+                    .zip(x_arms.iter())
+                    .map(|(arm, &x)| (FF::from_base(x), arm.next().unwrap()))
+                    .collect();
+                folded.push(Polynomial::lagrange_interpolate_zipped(&points).evaluate(&alpha));
+                for x in x_arms.iter_mut() {
+                    *x *= self.domain.omega;
+                }
+            }
+
+            let digests: Vec<Digest> = folded
+                .par_iter()
+                .map(|x| Self::leaf_digest(self.leaf_encoding, x))
+                .collect();
+            let mt = MerkleTree::from_digests(&digests);
+            proof_stream.enqueue(&mt.get_root())?;
+            codewords.push(folded);
+            merkle_trees.push(mt);
+        }
+
+        // Commit phase, remaining rounds: the folded codewords fit in memory
+        for round in 1..num_rounds {
+            let challenge: Digest =
+                proof_stream.prover_fiat_shamir_tagged(&Self::round_tag(b"alpha", round as usize));
+            let alpha: FF = FF::sample_challenge(&challenge);
+            let round_domain = self.domain.round_domain(round as usize, m);
+            let folded = Self::fold_codeword(
+                codewords.last().unwrap(),
+                round_domain.omega,
+                round_domain.offset,
+                alpha,
+                m,
             );
+            let digests: Vec<Digest> = folded
+                .par_iter()
+                .map(|x| Self::leaf_digest(self.leaf_encoding, x))
+                .collect();
+            let mt = MerkleTree::from_digests(&digests);
+            proof_stream.enqueue(&mt.get_root())?;
+            codewords.push(folded);
+            merkle_trees.push(mt);
+        }
+
+        let last_codeword: Vec<FF> = match codewords.last() {
+            Some(codeword) => codeword.clone(),
+            None => source.stream_from(0).collect(),
+        };
+        proof_stream.enqueue_length_prepended(&last_codeword)?;
+
+        // fiat-shamir phase (get indices), preceded by proof-of-work
+        // grinding if so configured
+        if self.grinding_bits > 0 {
+            let nonce = self.grind_nonce(proof_stream)?;
+            proof_stream.enqueue(&nonce)?;
+        }
+        let top_level_indices = self.sample_indices(
+            &proof_stream.prover_fiat_shamir_tagged(&Self::protocol_tag(b"indices")),
+        );
+
+        // Query phase, first round: fetch the queried positions and their
+        // fold siblings in one more pass over the source
+        let first_round_sibling_indices: Vec<Vec<usize>> = (1..if num_rounds > 0 { m } else { 1 })
+            .map(|t| {
+                top_level_indices
+                    .iter()
+                    .map(|x| (x + t * n / m) % n)
+                    .collect()
+            })
+            .collect();
+        let needed: HashSet<usize> = top_level_indices
+            .iter()
+            .chain(first_round_sibling_indices.iter().flatten())
+            .copied()
+            .collect();
+        let mut first_round_values: HashMap<usize, FF> = HashMap::new();
+        for (index, value) in source.stream_from(0).enumerate() {
+            if needed.contains(&index) {
+                first_round_values.insert(index, value);
+            }
+        }
+        let enqueue_first_round_pairs =
+            |indices: &[usize], stream: &mut ProofStream| -> Result<(), FriProverError> {
+                let unique_indices = Self::unique_indices(indices);
+                let value_ap_pairs: Vec<(PartialAuthenticationPath<Digest>, FF)> = first_tree
+                    .get_authentication_structure(&unique_indices)
+                    .into_iter()
+                    .zip(unique_indices.iter())
+                    .map(|(ap, i)| (ap, first_round_values[i]))
+                    .collect_vec();
+                enqueue_query_pairs(&value_ap_pairs, stream)?;
+                Ok(())
+            };
+        enqueue_first_round_pairs(&top_level_indices, proof_stream)?;
+        for sibling_indices in first_round_sibling_indices.iter() {
+            enqueue_first_round_pairs(sibling_indices, proof_stream)?;
+        }
+
+        // Query phase, remaining rounds: identical to the in-memory prover
+        let mut current_domain_len = n / m;
+        let mut a_indices: Vec<usize> = top_level_indices
+            .iter()
+            .map(|x| x % current_domain_len.max(1))
+            .collect();
+        if self.batched_checks_active() && num_rounds > 0 {
+            let claimed_c_values: Vec<FF> = a_indices.iter().map(|i| codewords[0][*i]).collect();
+            proof_stream.enqueue_length_prepended(&claimed_c_values)?;
+        }
+        for r in 0..merkle_trees.len().saturating_sub(1) {
+            for t in 1..m {
+                let sibling_indices: Vec<usize> = a_indices
+                    .iter()
+                    .map(|x| (x + t * current_domain_len / m) % current_domain_len)
+                    .collect();
+                Self::enqueue_auth_pairs(
+                    &sibling_indices,
+                    &codewords[r],
+                    &merkle_trees[r],
+                    proof_stream,
+                )?;
+            }
+            current_domain_len /= m;
+            a_indices = a_indices.iter().map(|x| x % current_domain_len).collect();
+            if self.batched_checks_active() {
+                let claimed_c_values: Vec<FF> =
+                    a_indices.iter().map(|i| codewords[r + 1][*i]).collect();
+                proof_stream.enqueue_length_prepended(&claimed_c_values)?;
+            }
+        }
+
+        Ok(top_level_indices)
+    }
+
+    /// The default prover: commit once, keep all intermediate codewords and
+    /// Merkle trees around for the query phase.
+    fn prove_standard<FF: FriFieldElement>(
+        &self,
+        codeword: &[FF],
+        proof_stream: &mut ProofStream,
+    ) -> Result<Vec<usize>, FriProverError> {
+        // Commit phase
+        let (codewords, merkle_trees): (Vec<Vec<FF>>, Vec<MerkleTree<H>>) = self
+            .commit(codeword, proof_stream, None, None)?
+            .into_iter()
+            .unzip();
+
+        self.standard_query_phase(codeword, &codewords, &merkle_trees, proof_stream)
+    }
+
+    /// Fiat-Shamir index sampling (with optional grinding) and the query
+    /// phase of the standard prover, starting from a completed commit
+    /// phase.
+    fn standard_query_phase<FF: FriFieldElement>(
+        &self,
+        codeword: &[FF],
+        codewords: &[Vec<FF>],
+        merkle_trees: &[MerkleTree<H>],
+        proof_stream: &mut ProofStream,
+    ) -> Result<Vec<usize>, FriProverError> {
+        // fiat-shamir phase (get indices), preceded by proof-of-work
+        // grinding if so configured
+        if self.grinding_bits > 0 {
+            let nonce = self.grind_nonce(proof_stream)?;
+            proof_stream.enqueue(&nonce)?;
+        }
+        let top_level_indices = self.sample_indices(
+            &proof_stream.prover_fiat_shamir_tagged(&Self::protocol_tag(b"indices")),
+        );
+
+        // query phase
+        let _query_span = fri_span!(
+            "fri_query_phase",
+            colinearity_checks = self.colinearity_checks_count
+        );
+        let initial_a_indices: Vec<usize> = top_level_indices.clone();
+        Self::enqueue_auth_pairs(&initial_a_indices, codeword, &merkle_trees[0], proof_stream)?;
+        let mut current_domain_len = self.domain.length;
+        let mut a_indices: Vec<usize> = initial_a_indices;
+
+        for r in 0..merkle_trees.len() - 1 {
             debug_assert_eq!(
-                self.colinearity_checks_count,
-                a_values.len(),
-                "There must be equally many 'a values' as there are colinearity checks."
+                codewords[r].len(),
+                current_domain_len,
+                "The current domain length needs to be the same as the length of the \
+                current codeword"
             );
-            debug_assert_eq!(
-                self.colinearity_checks_count,
-                b_values.len(),
-                "There must be equally many 'b values' as there are colinearity checks."
+            // For every queried position, open the `folding_factor - 1`
+            // sibling positions that fold into the same next-round position.
+            for t in 1..self.folding_factor {
+                let sibling_indices: Vec<usize> = a_indices
+                    .iter()
+                    .map(|x| {
+                        (x + t * current_domain_len / self.folding_factor) % current_domain_len
+                    })
+                    .collect();
+                Self::enqueue_auth_pairs(
+                    &sibling_indices,
+                    &codewords[r],
+                    &merkle_trees[r],
+                    proof_stream,
+                )?;
+            }
+            current_domain_len /= self.folding_factor;
+            a_indices = a_indices.iter().map(|x| x % current_domain_len).collect();
+            if self.batched_checks_active() {
+                let claimed_c_values: Vec<FF> =
+                    a_indices.iter().map(|i| codewords[r + 1][*i]).collect();
+                proof_stream.enqueue_length_prepended(&claimed_c_values)?;
+            }
+        }
+
+        Ok(top_level_indices)
+    }
+
+    /// Prove low-degreeness of many codewords at once by taking a random
+    /// linear combination and running a single FRI instance on the result.
+    ///
+    /// The batching challenges are sampled from the proof stream's current
+    /// transcript, so any commitments to the individual codewords that the
+    /// caller has already enqueued are bound by the combination. Returns the
+    /// weights used for the combination along with the top-level indices.
+    pub fn prove_batch(
+        &self,
+        codewords: &[Vec<XFieldElement>],
+        proof_stream: &mut ProofStream,
+    ) -> Result<(Vec<XFieldElement>, Vec<usize>), FriProverError> {
+        if codewords.is_empty() {
+            return Err(FriProverError::EmptyBatch);
+        }
+        for codeword in codewords.iter() {
+            if self.domain.length != codeword.len() {
+                return Err(FriProverError::CodewordLengthMismatch {
+                    expected: self.domain.length,
+                    actual: codeword.len(),
+                });
+            }
+        }
+
+        let weights = Self::sample_batch_weights(
+            &proof_stream.prover_fiat_shamir_tagged(&Self::protocol_tag(b"batch-weights")),
+            codewords.len(),
+        );
+        let combined_codeword: Vec<XFieldElement> = (0..self.domain.length)
+            .into_par_iter()
+            .map(|i| {
+                codewords
+                    .iter()
+                    .zip(weights.iter())
+                    .map(|(codeword, weight)| *weight * codeword[i])
+                    .sum()
+            })
+            .collect();
+
+        let top_level_indices = self.prove(&combined_codeword, proof_stream)?;
+
+        Ok((weights, top_level_indices))
+    }
+
+    /// Verify a proof produced by [`prove_batch`]. Returns the batching
+    /// weights and the evaluations of the combined codeword so the caller
+    /// can check them against openings of the individual codewords.
+    ///
+    /// [`prove_batch`]: Fri::prove_batch
+    #[allow(clippy::type_complexity)]
+    pub fn verify_batch(
+        &self,
+        codeword_count: usize,
+        proof_stream: &mut ProofStream,
+    ) -> Result<(Vec<XFieldElement>, Vec<CodewordEvaluation<XFieldElement>>), Box<dyn Error>> {
+        let weights = Self::sample_batch_weights(
+            &proof_stream.verifier_fiat_shamir_tagged(&Self::protocol_tag(b"batch-weights")),
+            codeword_count,
+        );
+        let codeword_evaluations = self.verify(proof_stream)?;
+
+        Ok((weights, codeword_evaluations))
+    }
+
+    /// Run the prover as the low-degree test of a DEEP-ALI STARK: sample an
+    /// out-of-domain point from the transcript, send the claimed evaluation
+    /// of the codeword there, and prove the quotient by that sample instead
+    /// of the codeword itself.
+    ///
+    /// The quotient `(f_i - y) / (omega^i - z)` is of low degree exactly
+    /// when `y` is the true out-of-domain evaluation, so a single FRI run
+    /// attests to both the codeword's degree and the claimed value. The
+    /// point is sampled from the transcript as enqueued so far; the caller
+    /// must enqueue its commitment to the codeword first, so that the
+    /// sample is bound to it. Returns the sampled point, the claimed
+    /// evaluation, and the top-level indices -- which index the quotient
+    /// codeword, as do the evaluations [`verify_deep`] hands back.
+    ///
+    /// [`verify_deep`]: Fri::verify_deep
+    pub fn prove_deep(
+        &self,
+        codeword: &[XFieldElement],
+        proof_stream: &mut ProofStream,
+    ) -> Result<(XFieldElement, XFieldElement, Vec<usize>), FriProverError> {
+        self.prove_in_field_deep(codeword, proof_stream)
+    }
+
+    /// Like [`prove_deep`], over any [`FriFieldElement`] field.
+    ///
+    /// [`prove_deep`]: Fri::prove_deep
+    pub fn prove_in_field_deep<FF: FriFieldElement>(
+        &self,
+        codeword: &[FF],
+        proof_stream: &mut ProofStream,
+    ) -> Result<(FF, FF, Vec<usize>), FriProverError> {
+        if self.domain.length != codeword.len() {
+            return Err(FriProverError::CodewordLengthMismatch {
+                expected: self.domain.length,
+                actual: codeword.len(),
+            });
+        }
+
+        let z: FF = FF::sample_challenge(
+            &proof_stream.prover_fiat_shamir_tagged(&Self::protocol_tag(b"deep")),
+        );
+        let y = Self::normalized_interpolant(codeword, self.domain.omega).evaluate(&z);
+        proof_stream.enqueue_length_prepended(&y)?;
+
+        let quotient = Self::quotient_codeword(codeword, self.domain.omega, z, y);
+        let top_level_indices = self.prove_in_field(&quotient, proof_stream)?;
+
+        Ok((z, y, top_level_indices))
+    }
+
+    /// Verify a proof produced by [`prove_deep`]. Returns the out-of-domain
+    /// sample `(z, y)` and the evaluations of the quotient codeword at the
+    /// top-level indices, so a DEEP-ALI caller can check each one against
+    /// an opening `f_i` of its own commitment via
+    /// `quotient_i = (f_i - y) / (omega^i - z)`.
+    ///
+    /// [`prove_deep`]: Fri::prove_deep
+    #[allow(clippy::type_complexity)]
+    pub fn verify_deep(
+        &self,
+        proof_stream: &mut ProofStream,
+    ) -> Result<
+        (
+            XFieldElement,
+            XFieldElement,
+            Vec<CodewordEvaluation<XFieldElement>>,
+        ),
+        Box<dyn Error>,
+    > {
+        self.verify_in_field_deep::<XFieldElement>(proof_stream)
+    }
+
+    /// Like [`verify_deep`], over any [`FriFieldElement`] field.
+    ///
+    /// [`verify_deep`]: Fri::verify_deep
+    #[allow(clippy::type_complexity)]
+    pub fn verify_in_field_deep<FF: FriFieldElement>(
+        &self,
+        proof_stream: &mut ProofStream,
+    ) -> Result<(FF, FF, Vec<CodewordEvaluation<FF>>), Box<dyn Error>> {
+        let z: FF = FF::sample_challenge(
+            &proof_stream.verifier_fiat_shamir_tagged(&Self::protocol_tag(b"deep")),
+        );
+        let y: FF = proof_stream.dequeue_length_prepended()?;
+        let codeword_evaluations = self.verify_in_field(proof_stream)?;
+
+        Ok((z, y, codeword_evaluations))
+    }
+
+    /// Search for a nonce that, once appended to the transcript, makes the
+    /// query-phase challenge hash meet the configured difficulty target.
+    /// Candidates go through the stream's own mode-aware challenge
+    /// derivation, so the mined digest matches the tagged query seed the
+    /// verifier recomputes in either [`TranscriptMode`].
+    ///
+    /// [`TranscriptMode`]: crate::util_types::proof_stream::TranscriptMode
+    fn grind_nonce(&self, proof_stream: &ProofStream) -> Result<u64, FriProverError> {
+        // `grinding_bits` is a public field, so the unreachable-target guard
+        // from `validate` has to be repeated where the search would hang.
+        if self.grinding_bits > MAX_GRINDING_BITS {
+            return Err(FriProverError::ExcessiveGrindingBits(self.grinding_bits));
+        }
+
+        let tag = Self::protocol_tag(b"indices");
+        let mut nonce = 0u64;
+        loop {
+            let nonce_bytes = bincode::serialize(&nonce)
+                .map_err(|err| FriProverError::ProofStreamFailure(err.to_string()))?;
+            let digest = proof_stream.prover_fiat_shamir_tagged_after(&tag, &nonce_bytes);
+            if Self::meets_grinding_target(&digest, self.grinding_bits) {
+                return Ok(nonce);
+            }
+            nonce += 1;
+        }
+    }
+
+    /// Whether a challenge digest has the required number of leading zero
+    /// bits. Trivially true when grinding is disabled.
+    fn meets_grinding_target(digest: &Digest, grinding_bits: u8) -> bool {
+        digest.values()[0].value().leading_zeros() >= grinding_bits as u32
+    }
+
+    /// Sample one batching challenge per codeword from a Fiat-Shamir seed.
+    fn sample_batch_weights(seed: &Digest, count: usize) -> Vec<XFieldElement> {
+        H::sample_scalars(seed, count)
+    }
+
+    /// Whether the batched colinearity-check transcript layout is in effect.
+    /// The single-equation form of the check only exists for arity-2 folds.
+    fn batched_checks_active(&self) -> bool {
+        self.batched_colinearity_checks && self.folding_factor == 2 && !self.stir_active()
+    }
+
+    fn stir_active(&self) -> bool {
+        self.folding_schedule == FoldingSchedule::Stir
+    }
+
+    /// The interpolant of a round codeword in the normalized domain variable
+    /// `x = omega^i`, i.e. with the coset offset left in place. Both the
+    /// prover's out-of-domain evaluations and the verifier's check against
+    /// the last-round polynomial use this basis, so neither side ever has to
+    /// divide the offset out.
+    fn normalized_interpolant<FF: FriFieldElement>(
+        codeword: &[FF],
+        omega: BFieldElement,
+    ) -> Polynomial<FF> {
+        let mut coefficients = codeword.to_vec();
+        intt::<FF>(
+            &mut coefficients,
+            omega,
+            log_2_floor(codeword.len() as u128) as u32,
+        );
+        Polynomial { coefficients }
+    }
+
+    /// The quotient of a round codeword by its out-of-domain sample `(z, y)`,
+    /// in the normalized domain variable: `h_i = (f_i - y) / (omega^i - z)`.
+    /// If `y` is the true out-of-domain evaluation, the quotient is again of
+    /// low degree; if not, the subsequent fold rounds run on a function with
+    /// a pole and fail.
+    fn quotient_codeword<FF: FriFieldElement>(
+        codeword: &[FF],
+        omega: BFieldElement,
+        z: FF,
+        y: FF,
+    ) -> Vec<FF> {
+        let mut x = BFieldElement::one();
+        codeword
+            .iter()
+            .map(|&value| {
+                let quotient = (value - y) / (FF::from_base(x) - z);
+                x *= omega;
+                quotient
+            })
+            .collect()
+    }
+
+    /// The same quotient map applied to individual opened values, addressed
+    /// by their domain index.
+    fn quotient_opened_values<FF: FriFieldElement>(
+        values: &[FF],
+        indices: &[usize],
+        omega: BFieldElement,
+        z: FF,
+        y: FF,
+    ) -> Vec<FF> {
+        indices
+            .iter()
+            .zip(values.iter())
+            .map(|(&index, &value)| (value - y) / (FF::from_base(omega.mod_pow(index as u64)) - z))
+            .collect()
+    }
+
+    /// Sample one colinearity-check weight per query from a Fiat-Shamir seed.
+    fn sample_colinearity_weights<FF: FriFieldElement>(seed: &Digest, count: usize) -> Vec<FF> {
+        H::get_n_tagged_hash_rounds(WEIGHT_SAMPLING_DOMAIN_TAG, seed, count)
+            .iter()
+            .map(FF::sample_challenge)
+            .collect()
+    }
+
+    /// Parse a [`FriProof`] produced by this FRI instance from a proof
+    /// stream. Reads from the stream's current index.
+    pub fn extract_proof(
+        &self,
+        proof_stream: &mut ProofStream,
+    ) -> Result<FriProof, Box<dyn Error>> {
+        let (num_rounds, _) = self.num_rounds();
+        FriProof::from_proof_stream(proof_stream, num_rounds as usize, self.folding_factor)
+    }
+
+    /// Memory-lean prover: identical transcript to [`prove_standard`], but
+    /// intermediate codewords and Merkle trees are dropped after the commit
+    /// phase and recomputed round by round in the query phase.
+    ///
+    /// [`prove_standard`]: Fri::prove_standard
+    fn prove_lean<FF: FriFieldElement>(
+        &self,
+        codeword: &[FF],
+        proof_stream: &mut ProofStream,
+    ) -> Result<Vec<usize>, FriProverError> {
+        let (num_rounds, _) = self.num_rounds();
+
+        // Commit phase, retaining only the round challenges
+        let mut codeword_local = codeword.to_vec();
+
+        let mut digests: Vec<Digest> = codeword_local
+            .par_iter()
+            .map(|x| Self::leaf_digest(self.leaf_encoding, x))
+            .collect();
+        proof_stream.enqueue(&MerkleTree::<H>::from_digests(&digests).get_root())?;
+
+        let mut alphas: Vec<FF> = Vec::with_capacity(num_rounds as usize);
+        for round in 0..num_rounds {
+            let challenge: Digest =
+                proof_stream.prover_fiat_shamir_tagged(&Self::round_tag(b"alpha", round as usize));
+            let alpha: FF = FF::sample_challenge(&challenge);
+            alphas.push(alpha);
+
+            let round_domain = self
+                .domain
+                .round_domain(round as usize, self.folding_factor);
+            codeword_local = Self::fold_codeword(
+                &codeword_local,
+                round_domain.omega,
+                round_domain.offset,
+                alpha,
+                self.folding_factor,
             );
+            digests = codeword_local
+                .par_iter()
+                .map(|x| Self::leaf_digest(self.leaf_encoding, x))
+                .collect();
+            proof_stream.enqueue(&MerkleTree::<H>::from_digests(&digests).get_root())?;
+        }
+        proof_stream.enqueue_length_prepended(&codeword_local)?;
+
+        // fiat-shamir phase (get indices), preceded by proof-of-work
+        // grinding if so configured
+        if self.grinding_bits > 0 {
+            let nonce = self.grind_nonce(proof_stream)?;
+            proof_stream.enqueue(&nonce)?;
+        }
+        let top_level_indices = self.sample_indices(
+            &proof_stream.prover_fiat_shamir_tagged(&Self::protocol_tag(b"indices")),
+        );
+
+        // Query phase: refold from the initial codeword, materializing one
+        // codeword and one Merkle tree at a time
+        codeword_local = codeword.to_vec();
+        let mut current_domain_len = self.domain.length;
+        let mut a_indices: Vec<usize> = top_level_indices.clone();
+
+        #[allow(clippy::needless_range_loop)]
+        for r in 0..num_rounds.max(1) as usize {
+            digests = codeword_local
+                .par_iter()
+                .map(|x| Self::leaf_digest(self.leaf_encoding, x))
+                .collect();
+            let merkle_tree: MerkleTree<H> = MerkleTree::from_digests(&digests);
+
+            if r == 0 {
+                Self::enqueue_auth_pairs(&a_indices, &codeword_local, &merkle_tree, proof_stream)?;
+            }
+            if r == num_rounds as usize {
+                break;
+            }
+            for t in 1..self.folding_factor {
+                let sibling_indices: Vec<usize> = a_indices
+                    .iter()
+                    .map(|x| {
+                        (x + t * current_domain_len / self.folding_factor) % current_domain_len
+                    })
+                    .collect();
+                Self::enqueue_auth_pairs(
+                    &sibling_indices,
+                    &codeword_local,
+                    &merkle_tree,
+                    proof_stream,
+                )?;
+            }
+
+            let round_domain = self.domain.round_domain(r, self.folding_factor);
+            codeword_local = Self::fold_codeword(
+                &codeword_local,
+                round_domain.omega,
+                round_domain.offset,
+                alphas[r],
+                self.folding_factor,
+            );
+            current_domain_len /= self.folding_factor;
+            a_indices = a_indices.iter().map(|x| x % current_domain_len).collect();
+            if self.batched_checks_active() {
+                let claimed_c_values: Vec<FF> =
+                    a_indices.iter().map(|i| codeword_local[*i]).collect();
+                proof_stream.enqueue_length_prepended(&claimed_c_values)?;
+            }
+        }
+
+        Ok(top_level_indices)
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn commit<FF: FriFieldElement>(
+        &self,
+        codeword: &[FF],
+        proof_stream: &mut ProofStream,
+        first_leaf_digests: Option<&[Digest]>,
+        observer: Option<&mut dyn FriProverObserver>,
+    ) -> Result<Vec<(Vec<FF>, MerkleTree<H>)>, FriProverError> {
+        self.commit_with_backend(
+            codeword,
+            proof_stream,
+            &RayonCommitBackend,
+            first_leaf_digests,
+            observer,
+        )
+    }
+
+    /// The commit phase with leaf hashing and folding routed through a
+    /// [`CommitBackend`]. [`commit`] uses the rayon fallback backend.
+    ///
+    /// [`commit`]: Fri::commit
+    #[allow(clippy::type_complexity)]
+    fn commit_with_backend<FF: FriFieldElement>(
+        &self,
+        codeword: &[FF],
+        proof_stream: &mut ProofStream,
+        backend: &impl CommitBackend<FF, H>,
+        first_leaf_digests: Option<&[Digest]>,
+        mut observer: Option<&mut dyn FriProverObserver>,
+    ) -> Result<Vec<(Vec<FF>, MerkleTree<H>)>, FriProverError> {
+        let _commit_span = fri_span!("fri_commit_phase", codeword_length = codeword.len());
+        let mut codeword_local = codeword.to_vec();
+        let mut round_start = Instant::now();
+
+        // Compute and send Merkle root
+        let mut digests: Vec<Digest>;
+        let mut mt: MerkleTree<H>;
+        {
+            let _merkle_span = fri_span!("merkle_tree_construction", leaf_count = codeword.len());
+            digests = match first_leaf_digests {
+                Some(precomputed) => precomputed.to_vec(),
+                None => backend.hash_leaves(&codeword_local, self.leaf_encoding),
+            };
+            mt = MerkleTree::from_digests(&digests);
+        }
+        proof_stream.enqueue(&mt.get_root())?;
+        let mut values_and_merkle_trees = vec![(codeword_local.clone(), mt)];
+        if let Some(observer) = observer.as_deref_mut() {
+            observer.on_commit_round(0, codeword.len(), round_start.elapsed());
+        }
+
+        let (num_rounds, _) = self.num_rounds();
+        for _round in 0..num_rounds {
+            round_start = Instant::now();
+            let _round_span = fri_span!(
+                "fri_fold_round",
+                round = _round,
+                codeword_length = codeword_local.len()
+            );
+            // Get challenge, one just acts as *any* element in this field -- the field element
+            // is completely determined from the byte stream.
+            let challenge: Digest =
+                proof_stream.prover_fiat_shamir_tagged(&Self::round_tag(b"alpha", _round as usize));
+            let alpha: FF = FF::sample_challenge(&challenge);
+
+            let round_domain = self
+                .domain
+                .round_domain(_round as usize, self.folding_factor);
+            codeword_local = backend.fold_codeword(
+                &codeword_local,
+                round_domain.omega,
+                round_domain.offset,
+                alpha,
+                self.folding_factor,
+            );
+
+            // Compute and send Merkle root
+            digests = backend.hash_leaves(&codeword_local, self.leaf_encoding);
+            mt = MerkleTree::from_digests(&digests);
+            proof_stream.enqueue(&mt.get_root())?;
+            values_and_merkle_trees.push((codeword_local.clone(), mt));
+            if let Some(observer) = observer.as_deref_mut() {
+                observer.on_commit_round(
+                    _round as usize + 1,
+                    codeword_local.len(),
+                    round_start.elapsed(),
+                );
+            }
+
+            // STIR schedule: answer an out-of-domain sample of the freshly
+            // committed codeword and run the next round on the quotient. The
+            // final round's sample is checked directly against the plain
+            // last codeword, so no quotient is taken there.
+            if self.stir_active() {
+                let z: FF = FF::sample_challenge(
+                    &proof_stream
+                        .prover_fiat_shamir_tagged(&Self::round_tag(b"ood", _round as usize)),
+                );
+                let folded_omega = self
+                    .domain
+                    .round_domain(_round as usize + 1, self.folding_factor)
+                    .omega;
+                let y = Self::normalized_interpolant(&codeword_local, folded_omega).evaluate(&z);
+                proof_stream.enqueue_length_prepended(&y)?;
+                if _round + 1 < num_rounds {
+                    codeword_local = Self::quotient_codeword(&codeword_local, folded_omega, z, y);
+                }
+            }
+        }
+
+        // Send the last codeword
+        let last_codeword = codeword_local;
+        proof_stream.enqueue_length_prepended(&last_codeword)?;
+
+        Ok(values_and_merkle_trees)
+    }
+
+    /// Fold a codeword by `folding_factor`. Each output value is the
+    /// degree-`folding_factor - 1` interpolant through the `folding_factor`
+    /// positions that share an image under `x -> x^folding_factor`,
+    /// evaluated in the challenge `alpha`.
+    fn fold_codeword<FF: FriFieldElement>(
+        codeword: &[FF],
+        generator: BFieldElement,
+        offset: BFieldElement,
+        alpha: FF,
+        folding_factor: usize,
+    ) -> Vec<FF> {
+        let n = codeword.len();
+        let x_offset: Vec<BFieldElement> =
+            map_collect(&generator.get_cyclic_group_elements(None), |&x| x * offset);
+
+        if folding_factor == 2 {
+            // Binary folding admits a closed form that is cheaper than
+            // interpolation.
+            let one: FF = FF::one();
+            let two_inv = one / (one + one);
+            let x_offset_inverses = BFieldElement::batch_inversion(x_offset);
+            return map_collect_range(n / 2, |i| {
+                let alpha_over_x = alpha * FF::from_base(x_offset_inverses[i]);
+                two_inv
+                    * ((one + alpha_over_x) * codeword[i]
+                        + (one - alpha_over_x) * codeword[n / 2 + i])
+            });
+        }
+
+        map_collect_range(n / folding_factor, |i| {
+            let points: Vec<(FF, FF)> = (0..folding_factor)
+                .map(|t| {
+                    let index = i + t * (n / folding_factor);
+                    (FF::from_base(x_offset[index]), codeword[index])
+                })
+                .collect();
+            Polynomial::lagrange_interpolate_zipped(&points).evaluate(&alpha)
+        })
+    }
+
+    // Return the c-indices for the 1st round of FRI
+    fn sample_indices(&self, seed: &Digest) -> Vec<usize> {
+        // This algorithm starts with the inner-most indices to pick up
+        // to `last_codeword_length` indices from the codeword in the last round.
+        // It then calculates the indices in the subsequent rounds by choosing
+        // between the two possible next indices in the next round until we get
+        // the c-indices for the first round.
+        let num_rounds = self.num_rounds().0;
+        let last_codeword_length = self.domain.length / self.folding_factor.pow(num_rounds as u32);
+        assert!(
+            self.colinearity_checks_count <= last_codeword_length,
+            "Requested number of indices must not exceed length of last codeword"
+        );
+
+        let mut sampler = IndexSampler::<H>::new(seed);
+        let mut indices =
+            sampler.sample_distinct_indices(self.colinearity_checks_count, last_codeword_length);
+
+        // Use last indices to derive first c-indices
+        for i in 1..num_rounds {
+            let codeword_length = last_codeword_length * self.folding_factor.pow(i as u32);
+
+            indices = indices
+                .into_iter()
+                .map(|index| {
+                    // Pick uniformly between the `folding_factor` positions in
+                    // this round that fold onto `index` in the next round.
+                    let position = sampler.sample_index(self.folding_factor);
+                    index + position * (codeword_length / self.folding_factor)
+                })
+                .collect();
+        }
+
+        indices
+    }
+
+    pub fn verify(
+        &self,
+        proof_stream: &mut ProofStream,
+    ) -> Result<Vec<CodewordEvaluation<XFieldElement>>, Box<dyn Error>> {
+        self.verify_in_field::<XFieldElement>(proof_stream)
+    }
+
+    /// Verify a proof produced by [`prove_in_field`] over the same codeword
+    /// field.
+    ///
+    /// [`prove_in_field`]: Fri::prove_in_field
+    pub fn verify_in_field<FF: FriFieldElement>(
+        &self,
+        proof_stream: &mut ProofStream,
+    ) -> Result<Vec<CodewordEvaluation<FF>>, Box<dyn Error>> {
+        Ok(self.verify_report(proof_stream)?.codeword_evaluations)
+    }
+
+    /// Like [`verify_in_field`], but return the full [`FriVerifyReport`]
+    /// rather than just the top-level codeword evaluations.
+    ///
+    /// [`verify_in_field`]: Fri::verify_in_field
+    pub fn verify_report<FF: FriFieldElement>(
+        &self,
+        proof_stream: &mut ProofStream,
+    ) -> Result<FriVerifyReport<FF>, Box<dyn Error>> {
+        let _verify_span = fri_span!("fri_verify", domain_length = self.domain.length);
+        let (num_rounds, degree_of_last_round) = self.num_rounds();
+
+        // Extract all roots and calculate alpha, the challenges
+        let mut roots: Vec<Digest> = vec![];
+        let mut alphas: Vec<FF> = vec![];
+        let first_root: Digest = proof_stream.dequeue(Digest::BYTES)?;
+        roots.push(first_root);
+
+        let mut ood_samples: Vec<(FF, FF)> = vec![];
+        for round in 0..num_rounds {
+            // Get a challenge from the proof stream
+            let challenge: Digest = proof_stream
+                .verifier_fiat_shamir_tagged(&Self::round_tag(b"alpha", round as usize));
+            let alpha: FF = FF::sample_challenge(&challenge);
+            alphas.push(alpha);
+            roots.push(proof_stream.dequeue(Digest::BYTES)?);
+            if self.stir_active() {
+                let z: FF = FF::sample_challenge(
+                    &proof_stream
+                        .verifier_fiat_shamir_tagged(&Self::round_tag(b"ood", round as usize)),
+                );
+                let y: FF = proof_stream.dequeue_length_prepended()?;
+                ood_samples.push((z, y));
+            }
+        }
+
+        // Extract last codeword
+        let mut last_codeword: Vec<FF> = proof_stream.dequeue_length_prepended::<Vec<FF>>()?;
+
+        // Check if last codeword matches the given root
+        let leaves: Vec<_> = last_codeword
+            .iter()
+            .map(|x| Self::leaf_digest(self.leaf_encoding, x))
+            .collect();
+        let last_codeword_mt = MerkleTree::<H>::from_digests(&leaves);
+        let last_root = roots.last().unwrap();
+        if *last_root != last_codeword_mt.get_root() {
+            return Err(Box::new(ValidationError::BadMerkleRootForLastCodeword));
+        }
+
+        // Verify that last codeword is of sufficiently low degree
+        let last_round_domain = self
+            .domain
+            .round_domain(num_rounds as usize, self.folding_factor);
+
+        // Compute interpolant to get the degree of the last codeword
+        // Note that we don't have to scale the polynomial back to the
+        // trace subgroup since we only check its degree and don't use
+        // it further.
+        let log_2_of_n = log_2_floor(last_codeword.len() as u128) as u32;
+        intt::<FF>(&mut last_codeword, last_round_domain.omega, log_2_of_n);
+        let last_round_polynomial = Polynomial::<FF> {
+            coefficients: last_codeword,
+        };
+        if last_round_polynomial.degree() > degree_of_last_round as isize {
+            return Err(Box::new(ValidationError::LastIterationTooHighDegree));
+        }
+
+        // The last round's out-of-domain sample can be checked directly
+        // against the last-round polynomial; the earlier rounds' samples are
+        // enforced through the low-degreeness of their quotients.
+        if let Some(&(last_z, last_y)) = ood_samples.last() {
+            if last_round_polynomial.evaluate(&last_z) != last_y {
+                return Err(Box::new(ValidationError::BadOutOfDomainEvaluation));
+            }
+        }
+
+        if self.grinding_bits > 0 {
+            let _nonce: u64 = proof_stream.dequeue(std::mem::size_of::<u64>())?;
+        }
+        let query_seed = proof_stream.verifier_fiat_shamir_tagged(&Self::protocol_tag(b"indices"));
+        if !Self::meets_grinding_target(&query_seed, self.grinding_bits) {
+            return Err(Box::new(ValidationError::GrindingTargetNotMet));
+        }
+        let mut a_indices: Vec<usize> = self.sample_indices(&query_seed);
+
+        // for every round, check consistency of subsequent layers
+        let mut codeword_evaluations: Vec<CodewordEvaluation<FF>> = vec![];
+        let mut a_values =
+            Self::dequeue_and_authenticate(&a_indices, roots[0], self.leaf_encoding, proof_stream)?;
+
+        let mut current_domain_len = self.domain.length;
+
+        for r in 0..num_rounds as usize {
+            debug_assert_eq!(
+                self.colinearity_checks_count,
+                a_indices.len(),
+                "There must be equally many 'a indices' as there are colinearity checks."
+            );
+            debug_assert_eq!(
+                self.colinearity_checks_count,
+                a_values.len(),
+                "There must be equally many 'a values' as there are colinearity checks."
+            );
+
+            // STIR schedule: from the second round on, the fold runs on the
+            // quotient of the committed codeword by its out-of-domain
+            // sample, applied here to the carried-forward values and below
+            // to every freshly opened sibling value.
+            let round_domain = self.domain.round_domain(r, self.folding_factor);
+            if self.stir_active() && r > 0 {
+                let (z, y) = ood_samples[r - 1];
+                a_values =
+                    Self::quotient_opened_values(&a_values, &a_indices, round_domain.omega, z, y);
+            }
+
+            // Collect, per fold position, the sibling indices that fold onto
+            // the same next-round position, and verify set membership of the
+            // corresponding values.
+            let mut sibling_indices: Vec<Vec<usize>> = vec![a_indices.clone()];
+            let mut sibling_values: Vec<Vec<FF>> = vec![a_values.clone()];
+            for t in 1..self.folding_factor {
+                let t_indices: Vec<usize> = a_indices
+                    .iter()
+                    .map(|x| {
+                        (x + t * current_domain_len / self.folding_factor) % current_domain_len
+                    })
+                    .collect();
+                let mut t_values = Self::dequeue_and_authenticate(
+                    &t_indices,
+                    roots[r],
+                    self.leaf_encoding,
+                    proof_stream,
+                )?;
+                if self.stir_active() && r > 0 {
+                    let (z, y) = ood_samples[r - 1];
+                    t_values = Self::quotient_opened_values(
+                        &t_values,
+                        &t_indices,
+                        round_domain.omega,
+                        z,
+                        y,
+                    );
+                }
+                sibling_indices.push(t_indices);
+                sibling_values.push(t_values);
+            }
+
+            // compute "C" indices and values for next round by evaluating, in
+            // alpha, the interpolant through all sibling positions
+            current_domain_len /= self.folding_factor;
+            let c_indices = a_indices.iter().map(|x| x % current_domain_len).collect();
+            let c_values: Vec<FF> = if self.batched_checks_active() {
+                // Batched mode: the prover supplies the claimed folded
+                // values, and all checks of this round collapse into one
+                // random linear combination of the cross-multiplied
+                // two-point colinearity equations -- no interpolation and
+                // no per-check inversion.
+                let claimed: Vec<FF> = proof_stream.dequeue_length_prepended::<Vec<FF>>()?;
+                if claimed.len() != self.colinearity_checks_count {
+                    return Err(Box::new(ValidationError::BadSizedProof));
+                }
+                let weights: Vec<FF> = Self::sample_colinearity_weights(
+                    &proof_stream.verifier_fiat_shamir_tagged(&Self::round_tag(b"weights", r)),
+                    self.colinearity_checks_count,
+                );
+                let mut combined = FF::zero();
+                for i in 0..self.colinearity_checks_count {
+                    let x0 = FF::from_base(self.get_evaluation_argument(sibling_indices[0][i], r));
+                    let x1 = FF::from_base(self.get_evaluation_argument(sibling_indices[1][i], r));
+                    let y0 = sibling_values[0][i];
+                    let y1 = sibling_values[1][i];
+                    combined +=
+                        weights[i] * ((claimed[i] - y0) * (x1 - x0) - (alphas[r] - x0) * (y1 - y0));
+                }
+                if !combined.is_zero() {
+                    return Err(Box::new(ValidationError::NotColinear(r)));
+                }
+                claimed
+            } else {
+                (0..self.colinearity_checks_count)
+                    .into_par_iter()
+                    .map(|i| {
+                        let points: Vec<(FF, FF)> = (0..self.folding_factor)
+                            .map(|t| {
+                                (
+                                    FF::from_base(
+                                        self.get_evaluation_argument(sibling_indices[t][i], r),
+                                    ),
+                                    sibling_values[t][i],
+                                )
+                            })
+                            .collect();
+                        Polynomial::lagrange_interpolate_zipped(&points).evaluate(&alphas[r])
+                    })
+                    .collect()
+            };
+
+            // Return top-level values to caller
+            if r == 0 {
+                for s in 0..self.colinearity_checks_count {
+                    for t in 0..self.folding_factor {
+                        codeword_evaluations.push((sibling_indices[t][s], sibling_values[t][s]));
+                    }
+                }
+            }
+
+            // Notice that next rounds "A"s correspond to current rounds "C":
+            a_indices = c_indices;
+            a_values = c_values;
+        }
+
+        Ok(FriVerifyReport {
+            codeword_evaluations,
+            alphas,
+            merkle_roots: roots,
+            last_round_polynomial,
+        })
+    }
+
+    /// Verify many independent proofs from this FRI instance at once,
+    /// returning one result per proof, in order.
+    ///
+    /// The dominant per-proof work -- recomputing Merkle digests, the
+    /// last-codeword interpolation, and the colinearity checks -- is spread
+    /// across the rayon pool, so a backlog of proofs verifies at much
+    /// higher throughput than a loop over [`verify_report`]. Each proof
+    /// carries its own Fiat-Shamir transcript, so challenges and query
+    /// indices are necessarily derived per proof. Errors that originate in
+    /// a malformed transcript rather than a failing check are collapsed
+    /// into [`ValidationError::MalformedProof`].
+    ///
+    /// [`verify_report`]: Fri::verify_report
+    pub fn verify_many<FF: FriFieldElement + Send + Sync>(
+        &self,
+        proof_streams: &mut [ProofStream],
+    ) -> Vec<Result<FriVerifyReport<FF>, ValidationError>> {
+        proof_streams
+            .par_iter_mut()
+            .map(|proof_stream| {
+                self.verify_report(proof_stream).map_err(|err| {
+                    err.downcast::<ValidationError>()
+                        .map(|validation_error| *validation_error)
+                        .unwrap_or(ValidationError::MalformedProof)
+                })
+            })
+            .collect()
+    }
+
+    fn get_evaluation_argument(&self, idx: usize, round: usize) -> BFieldElement {
+        (self.domain.offset * self.domain.omega.mod_pow(idx as u64))
+            .mod_pow((self.folding_factor as u64).pow(round as u32))
+    }
+
+    pub fn get_evaluation_domain(&self) -> Vec<BFieldElement> {
+        let omega_domain = self.domain.omega.get_cyclic_group_elements(None);
+        omega_domain
+            .into_iter()
+            .map(|x| x * self.domain.offset)
+            .collect()
+    }
+
+    /// The shape of every proof this configuration produces; see
+    /// [`FriProofShape`].
+    pub fn proof_shape(&self) -> FriProofShape {
+        let (num_rounds, last_round_max_degree) = self.num_rounds();
+        let round_domain_lengths: Vec<usize> = (0..=num_rounds as u32)
+            .map(|round| self.domain.length / self.folding_factor.pow(round))
+            .collect();
+        let last_codeword_length = *round_domain_lengths.last().unwrap();
+        let num_auth_paths =
+            self.colinearity_checks_count * (1 + num_rounds as usize * (self.folding_factor - 1));
+        let num_out_of_domain_samples = if self.stir_active() {
+            num_rounds as usize
+        } else {
+            0
+        };
+
+        FriProofShape {
+            num_rounds,
+            last_round_max_degree,
+            round_domain_lengths,
+            last_codeword_length,
+            num_auth_paths,
+            has_grinding_nonce: self.grinding_bits > 0,
+            num_out_of_domain_samples,
+        }
+    }
+
+    /// Estimate the size in bytes of a proof produced with this
+    /// configuration, before actually proving anything.
+    ///
+    /// Merkle roots, the grinding nonce, and the plain last-round codeword
+    /// are exact; the query-phase openings are an expectation, since the
+    /// deduplicated authentication structure depends on the sampled indices.
+    /// Sampling `k` indices from a codeword of length `n` yields about
+    /// `n * (1 - (1 - 1/n)^k)` distinct openings, whose paths are assumed to
+    /// have merged `log2(distinct)` levels below the root, revealing about
+    /// `h - log2(distinct)` digests each in a tree of height `h`.
+    pub fn proof_size_estimate(&self) -> usize {
+        let (num_rounds, _) = self.num_rounds();
+        let num_rounds = num_rounds as usize;
+        let digest_size = Digest::BYTES;
+        let value_size = EXTENSION_DEGREE * std::mem::size_of::<u64>();
+        let length_prefix_size = std::mem::size_of::<u32>();
+        let vec_header_size = std::mem::size_of::<u64>();
+        let checks = self.colinearity_checks_count;
+
+        // Merkle roots, one per round plus one for the initial codeword
+        let mut estimate = (num_rounds + 1) * digest_size;
+
+        // The plain last-round codeword
+        let last_codeword_length = self.domain.length / self.folding_factor.pow(num_rounds as u32);
+        estimate += length_prefix_size + vec_header_size + last_codeword_length * value_size;
+
+        // The grinding nonce, if grinding is configured
+        if self.grinding_bits > 0 {
+            estimate += std::mem::size_of::<u64>();
+        }
+
+        // One length-prefixed out-of-domain evaluation per round under the
+        // STIR schedule
+        if self.folding_schedule == FoldingSchedule::Stir {
+            estimate += num_rounds * (length_prefix_size + value_size);
+        }
+
+        // Query-phase openings: `folding_factor` items on the first round's
+        // tree, `folding_factor - 1` on every later round's
+        let mut codeword_length = self.domain.length;
+        for round in 0..num_rounds.max(1) {
+            let items = match round {
+                0 if num_rounds == 0 => 1,
+                0 => self.folding_factor,
+                _ => self.folding_factor - 1,
+            };
+            let height = log_2_floor(codeword_length as u128) as f64;
+            let distinct = codeword_length as f64
+                * (1.0 - (1.0 - 1.0 / codeword_length as f64).powi(checks as i32));
+            let revealed_digests = (height - distinct.log2()).max(0.0);
+            let per_index = (vec_header_size + value_size) as f64
+                + 1.0
+                + (height / 8.0).ceil()
+                + revealed_digests * digest_size as f64;
+            estimate += items * (length_prefix_size + vec_header_size)
+                + (items as f64 * distinct * per_index) as usize;
+            codeword_length /= self.folding_factor;
+        }
+
+        estimate
+    }
+
+    /// Estimate the security level of this FRI configuration in bits.
+    ///
+    /// The query phase contributes `log2(expansion_factor)` bits per
+    /// colinearity check under [`SoundnessRegime::Conjectured`], and half
+    /// of that under [`SoundnessRegime::Proven`]. Grinding adds its bit
+    /// count on top. The result is capped by the soundness error of sampling
+    /// the folding challenges from the extension field, which is bounded by
+    /// `domain_length / |F|` per round; the cap assumes challenges drawn
+    /// from the default degree-3 extension field.
+    pub fn security_bits(&self) -> f64 {
+        let (query_phase_bits, commit_phase_bits) = self.phase_security_bits();
+        query_phase_bits.min(commit_phase_bits)
+    }
+
+    /// The soundness error of this FRI configuration under its
+    /// [`SoundnessRegime`]: an upper bound on the probability that a
+    /// cheating prover convinces the verifier. This adds the query-phase
+    /// and commit-phase error terms whose larger one [`Self::security_bits`]
+    /// reports as a bit count, so `-soundness_error().log2()` is at most one
+    /// bit below `security_bits()`.
+    pub fn soundness_error(&self) -> f64 {
+        let (query_phase_bits, commit_phase_bits) = self.phase_security_bits();
+        2f64.powf(-query_phase_bits) + 2f64.powf(-commit_phase_bits)
+    }
+
+    fn phase_security_bits(&self) -> (f64, f64) {
+        let bits_per_check = self
+            .soundness_regime
+            .bits_per_colinearity_check(self.expansion_factor);
+        let query_phase_bits =
+            self.colinearity_checks_count as f64 * bits_per_check + self.grinding_bits as f64;
+
+        let field_bits = 64.0 * EXTENSION_DEGREE as f64;
+        let (num_rounds, _) = self.num_rounds();
+        let commit_phase_bits =
+            field_bits - (self.domain.length as f64).log2() - (num_rounds.max(1) as f64).log2();
+
+        (query_phase_bits, commit_phase_bits)
+    }
+
+    /// The number of fold rounds this configuration runs, and the degree
+    /// bound on the last-round codeword.
+    ///
+    /// The check count feeds both the early-stop heuristic and the
+    /// index-sampling floor, so a [`SoundnessRegime`] that demands more
+    /// checks for the same security level also folds fewer rounds.
+    pub fn num_rounds(&self) -> (u8, u32) {
+        let data_length = self.domain.length / self.expansion_factor;
+        // Blinding adds a randomizer multiple of the trace-domain zerofier,
+        // doubling the degree bound both parties must accept.
+        let max_degree = if self.zero_knowledge {
+            2 * data_length - 1
+        } else {
+            data_length - 1
+        };
+        let bits_per_round = log_2_floor(self.folding_factor as u128) as u8;
+        let total_degree_bits = log_2_ceil(max_degree as u128 + 1) as u8;
+        let mut missed_bits = 0u8;
+        if self.expansion_factor < self.colinearity_checks_count {
+            missed_bits = log_2_ceil(
+                (self.colinearity_checks_count as f64 / self.expansion_factor as f64).ceil()
+                    as u128,
+            ) as u8;
+        }
+
+        let mut rounds_count = total_degree_bits.saturating_sub(missed_bits) / bits_per_round;
+        // Make sure that the last codeword is long enough to sample all
+        // colinearity check indices from.
+        while rounds_count > 0
+            && self.domain.length / self.folding_factor.pow(rounds_count as u32)
+                < self.colinearity_checks_count
+        {
+            rounds_count -= 1;
+        }
+
+        // Stop folding early if a larger last-round degree is acceptable.
+        if let Some(degree_cap) = self.max_last_round_degree {
+            while rounds_count > 0
+                && 2u32.pow((total_degree_bits - bits_per_round * (rounds_count - 1)) as u32) - 1
+                    <= degree_cap
+            {
+                rounds_count -= 1;
+            }
+        }
+
+        let max_degree_of_last_round =
+            2u32.pow((total_degree_bits - bits_per_round * rounds_count) as u32) - 1;
+
+        (rounds_count, max_degree_of_last_round)
+    }
+}
+
+#[cfg(test)]
+mod fri_domain_tests {
+    use num_traits::One;
+
+    use super::*;
+    use crate::shared_math::b_field_element::BFieldElement;
+    use crate::shared_math::traits::PrimitiveRootOfUnity;
+    use crate::shared_math::x_field_element::XFieldElement;
+
+    #[test]
+    fn x_values_test() {
+        // pol = x^3
+        let x_squared_coefficients = vec![
+            BFieldElement::zero(),
+            BFieldElement::zero(),
+            BFieldElement::zero(),
+            BFieldElement::one(),
+        ];
+
+        for order in [4, 8, 32] {
+            let omega = BFieldElement::primitive_root_of_unity(order).unwrap();
+            let domain = FriDomain::new(BFieldElement::generator(), omega, order as usize);
+            let expected_x_values: Vec<BFieldElement> = (0..order)
+                .map(|i| BFieldElement::generator() * omega.mod_pow(i))
+                .collect();
+            let x_values = domain.b_domain_values();
+            assert_eq!(expected_x_values, x_values);
+
+            // Verify that `x_value` also returns expected values
+            for i in 0..order {
+                assert_eq!(expected_x_values[i as usize], domain.b_domain_value(i));
+            }
+
+            let pol = Polynomial::<BFieldElement>::new(x_squared_coefficients.clone());
+            let values = domain.b_evaluate(&pol);
+            assert_ne!(values, x_squared_coefficients);
+            let interpolant = domain.b_interpolate(&values);
+            assert_eq!(pol, interpolant);
+
+            // Verify that batch-evaluated values match a manual evaluation
+            for i in 0..order {
+                assert_eq!(pol.evaluate(&domain.b_domain_value(i)), values[i as usize]);
+            }
+
+            let x_squared_coefficients_lifted: Vec<XFieldElement> = x_squared_coefficients
+                .clone()
+                .into_iter()
+                .map(|x| x.lift())
+                .collect();
+            let xpol = Polynomial::new(x_squared_coefficients_lifted.clone());
+            let x_field_x_values = domain.x_evaluate(&xpol);
+            assert_ne!(x_field_x_values, x_squared_coefficients_lifted);
+            let x_interpolant = domain.x_interpolate(&x_field_x_values);
+            assert_eq!(xpol, x_interpolant);
+        }
+    }
+}
+
+#[cfg(feature = "test-vectors")]
+pub mod test_vectors {
+    //! Canonical FRI transcripts for cross-implementation checking.
+    //!
+    //! A [`FriTestVector`] pins down a full protocol run -- domain
+    //! parameters, the raw transcript bytes, the Fiat-Shamir challenges and
+    //! roots derived from them, the sampled query indices, and the expected
+    //! verdict -- in a JSON format that verifiers in other languages can be
+    //! tested against byte for byte.
+
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct FriTestVector {
+        pub domain_offset: u64,
+        pub domain_omega: u64,
+        pub domain_length: usize,
+        pub expansion_factor: usize,
+        pub colinearity_checks_count: usize,
+        pub folding_factor: usize,
+        /// The full proof stream, hex encoded.
+        pub transcript: String,
+        /// The Merkle root of every round, hex encoded, first round first.
+        pub merkle_roots: Vec<String>,
+        /// The folding challenge of every round, as extension field
+        /// coefficients in little-endian order.
+        pub alphas: Vec<[u64; EXTENSION_DEGREE]>,
+        /// The top-level query indices the Fiat-Shamir phase must yield.
+        pub query_indices: Vec<usize>,
+        /// Whether a conforming verifier must accept this transcript.
+        pub expected_verdict: bool,
+    }
+
+    fn to_hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    /// Run the prover on the given codeword and record the resulting
+    /// transcript together with everything a verifier must reproduce.
+    pub fn generate<H: AlgebraicHasher + Send + Sync>(
+        fri: &Fri<H>,
+        codeword: &[XFieldElement],
+    ) -> Result<FriTestVector, Box<dyn Error>> {
+        let mut proof_stream = ProofStream::default();
+        let query_indices = fri.prove(codeword, &mut proof_stream)?;
+
+        let expected_verdict = fri.verify(&mut proof_stream).is_ok();
+        proof_stream.set_index(0);
+        let report_result = fri.verify_report::<XFieldElement>(&mut proof_stream);
+        let (merkle_roots, alphas) = match report_result {
+            Ok(report) => (
+                report
+                    .merkle_roots
+                    .iter()
+                    .map(|root| to_hex(&bincode::serialize(root).unwrap()))
+                    .collect(),
+                report
+                    .alphas
+                    .iter()
+                    .map(|alpha| {
+                        let mut coefficients = [0u64; EXTENSION_DEGREE];
+                        for (limb, coefficient) in
+                            coefficients.iter_mut().zip(alpha.coefficients.iter())
+                        {
+                            *limb = coefficient.value();
+                        }
+                        coefficients
+                    })
+                    .collect(),
+            ),
+            Err(_) => (vec![], vec![]),
+        };
+
+        Ok(FriTestVector {
+            domain_offset: fri.domain.offset.value(),
+            domain_omega: fri.domain.omega.value(),
+            domain_length: fri.domain.length,
+            expansion_factor: fri.expansion_factor,
+            colinearity_checks_count: fri.colinearity_checks_count,
+            folding_factor: fri.folding_factor,
+            transcript: to_hex(&proof_stream.serialize()),
+            merkle_roots,
+            alphas,
+            query_indices,
+            expected_verdict,
+        })
+    }
+
+    impl FriTestVector {
+        pub fn to_json(&self) -> String {
+            serde_json::to_string_pretty(self).expect("Test vector serialization must succeed")
+        }
+
+        pub fn from_json(json: &str) -> Result<Self, Box<dyn Error>> {
+            Ok(serde_json::from_str(json)?)
+        }
+    }
+
+    #[cfg(test)]
+    mod test_vector_tests {
+        use super::*;
+        use crate::shared_math::traits::PrimitiveRootOfUnity;
+
+        #[test]
+        fn test_vector_roundtrip_test() {
+            type H = blake3::Hasher;
+
+            let omega = BFieldElement::primitive_root_of_unity(1024).unwrap();
+            let fri: Fri<H> = Fri::new(BFieldElement::new(7), omega, 1024, 4, 6, 2).unwrap();
+            let codeword: Vec<XFieldElement> =
+                fri.domain.omega.lift().get_cyclic_group_elements(None);
+
+            let vector = generate(&fri, &codeword).unwrap();
+            assert!(vector.expected_verdict);
+            assert_eq!(fri.num_rounds().0 as usize, vector.alphas.len());
+            assert_eq!(fri.num_rounds().0 as usize + 1, vector.merkle_roots.len());
+
+            // JSON roundtrip is lossless
+            let roundtrip = FriTestVector::from_json(&vector.to_json()).unwrap();
+            assert_eq!(vector, roundtrip);
+
+            // Generation is deterministic: same configuration, same bytes
+            let second_vector = generate(&fri, &codeword).unwrap();
+            assert_eq!(vector.transcript, second_vector.transcript);
+        }
+    }
+}
+
+#[cfg(test)]
+mod fri_tests {
+    use super::*;
+    use crate::shared_math::b_field_element::BFieldElement;
+    use crate::shared_math::other::random_elements;
+    use crate::shared_math::rescue_prime_regular::RescuePrimeRegular;
+    use crate::shared_math::traits::PrimitiveRootOfUnity;
+    use crate::shared_math::traits::{CyclicGroupGenerator, ModPowU32};
+    use crate::shared_math::x_field_element::XFieldElement;
+    use itertools::Itertools;
+
+    #[test]
+    fn get_rounds_count_test() {
+        type H = blake3::Hasher;
+
+        let subgroup_order = 512;
+        let expansion_factor = 4;
+        let mut fri: Fri<H> = get_x_field_fri_test_object::<H>(subgroup_order, expansion_factor, 2);
+
+        assert_eq!((7, 0), fri.num_rounds());
+        fri.colinearity_checks_count = 8;
+        assert_eq!((6, 1), fri.num_rounds());
+        fri.colinearity_checks_count = 10;
+        assert_eq!((5, 3), fri.num_rounds());
+        fri.colinearity_checks_count = 16;
+        assert_eq!((5, 3), fri.num_rounds());
+        fri.colinearity_checks_count = 17;
+        assert_eq!((4, 7), fri.num_rounds());
+        fri.colinearity_checks_count = 18;
+        assert_eq!((4, 7), fri.num_rounds());
+        fri.colinearity_checks_count = 31;
+        assert_eq!((4, 7), fri.num_rounds());
+        fri.colinearity_checks_count = 32;
+        assert_eq!((4, 7), fri.num_rounds());
+        fri.colinearity_checks_count = 33;
+        assert_eq!((3, 15), fri.num_rounds());
+
+        fri.domain.length = 256;
+        assert_eq!((2, 15), fri.num_rounds());
+        fri.colinearity_checks_count = 32;
+        assert_eq!((3, 7), fri.num_rounds());
+
+        fri.colinearity_checks_count = 32;
+        fri.domain.length = 1048576;
+        fri.expansion_factor = 8;
+        assert_eq!((15, 3), fri.num_rounds());
+
+        fri.colinearity_checks_count = 33;
+        fri.domain.length = 1048576;
+        fri.expansion_factor = 8;
+        assert_eq!((14, 7), fri.num_rounds());
+
+        fri.colinearity_checks_count = 63;
+        fri.domain.length = 1048576;
+        fri.expansion_factor = 8;
+        assert_eq!((14, 7), fri.num_rounds());
+
+        fri.colinearity_checks_count = 64;
+        fri.domain.length = 1048576;
+        fri.expansion_factor = 8;
+        assert_eq!((14, 7), fri.num_rounds());
+
+        fri.colinearity_checks_count = 65;
+        fri.domain.length = 1048576;
+        fri.expansion_factor = 8;
+        assert_eq!((13, 15), fri.num_rounds());
+
+        fri.domain.length = 256;
+        fri.expansion_factor = 4;
+        fri.colinearity_checks_count = 17;
+        assert_eq!((3, 7), fri.num_rounds());
+    }
+
+    #[test]
+    fn fri_verify_report_test() {
+        type Hasher = blake3::Hasher;
+
+        let fri = get_x_field_fri_test_object::<Hasher>(1024, 4, 6);
+        let codeword: Vec<XFieldElement> = fri.domain.omega.lift().get_cyclic_group_elements(None);
+        let mut proof_stream = ProofStream::default();
+        fri.prove(&codeword, &mut proof_stream).unwrap();
+
+        let report: FriVerifyReport<XFieldElement> = fri.verify_report(&mut proof_stream).unwrap();
+        let (num_rounds, degree_of_last_round) = fri.num_rounds();
+        assert_eq!(num_rounds as usize, report.alphas.len());
+        assert_eq!(num_rounds as usize + 1, report.merkle_roots.len());
+        assert!(report.last_round_polynomial.degree() <= degree_of_last_round as isize);
+        for (index, value) in report.codeword_evaluations.iter() {
+            assert_eq!(codeword[*index], *value);
+        }
+
+        // The roots in the report are the ones on the transcript
+        proof_stream.set_index(0);
+        let first_root: Digest = proof_stream.dequeue(Digest::BYTES).unwrap();
+        assert_eq!(first_root, report.merkle_roots[0]);
+    }
+
+    #[test]
+    fn fri_zero_knowledge_test() {
+        type Hasher = blake3::Hasher;
+
+        let mut fri = get_x_field_fri_test_object::<Hasher>(1024, 4, 6);
+        fri.zero_knowledge = true;
+
+        // Same codeword, two proof runs: blinding randomizes the transcript
+        let codeword: Vec<XFieldElement> = fri.domain.omega.lift().get_cyclic_group_elements(None);
+        let mut first_proof_stream = ProofStream::default();
+        fri.prove(&codeword, &mut first_proof_stream).unwrap();
+        let mut second_proof_stream = ProofStream::default();
+        fri.prove(&codeword, &mut second_proof_stream).unwrap();
+        assert_ne!(
+            first_proof_stream.serialize(),
+            second_proof_stream.serialize()
+        );
+
+        // Both proofs verify under the widened degree bound
+        assert!(fri.verify(&mut first_proof_stream).is_ok());
+        assert!(fri.verify(&mut second_proof_stream).is_ok());
+
+        // A too-high-degree codeword is still rejected
+        let high_degree_codeword: Vec<XFieldElement> = random_elements(1024);
+        let mut bad_proof_stream = ProofStream::default();
+        fri.prove(&high_degree_codeword, &mut bad_proof_stream)
+            .unwrap();
+        assert!(fri.verify(&mut bad_proof_stream).is_err());
+
+        // The streaming prover cannot blind and must say so instead of
+        // committing the codeword unblinded
+        assert_eq!(
+            Err(FriProverError::StreamedCodewordWithBlinding),
+            fri.prove_stream(&codeword.as_slice(), &mut ProofStream::default())
+        );
+    }
+
+    #[test]
+    fn proof_size_estimate_test() {
+        type Hasher = blake3::Hasher;
+
+        for (subgroup_order, checks, grinding_bits) in [(1024, 6, 0), (4096, 20, 8)] {
+            let mut fri = get_x_field_fri_test_object::<Hasher>(subgroup_order, 4, checks);
+            fri.grinding_bits = grinding_bits;
+            let estimate = fri.proof_size_estimate();
+
+            let codeword: Vec<XFieldElement> =
+                fri.domain.omega.lift().get_cyclic_group_elements(None);
+            let mut proof_stream = ProofStream::default();
+            fri.prove(&codeword, &mut proof_stream).unwrap();
+            let actual = proof_stream.len();
+
+            // The estimate is only statistical in the authentication paths;
+            // it must be in the right ballpark, not exact.
+            assert!(
+                estimate as f64 > 0.75 * actual as f64 && (estimate as f64) < 1.25 * actual as f64,
+                "estimate {} too far from actual {}",
+                estimate,
+                actual
+            );
+        }
+    }
+
+    #[test]
+    fn fri_max_last_round_degree_test() {
+        type Hasher = blake3::Hasher;
+
+        let mut fri = get_x_field_fri_test_object::<Hasher>(1024, 4, 6);
+        let (full_rounds, full_last_degree) = fri.num_rounds();
+        assert_eq!((7, 1), (full_rounds, full_last_degree));
+
+        // Capping the last-round degree trades rounds for last-codeword size
+        fri.max_last_round_degree = Some(15);
+        let (capped_rounds, capped_last_degree) = fri.num_rounds();
+        assert!(capped_rounds < full_rounds);
+        assert_eq!(15, capped_last_degree);
+
+        // A cap below the uncapped degree changes nothing
+        fri.max_last_round_degree = Some(0);
+        assert_eq!((full_rounds, full_last_degree), fri.num_rounds());
+
+        // Proofs with an early stop still verify
+        fri.max_last_round_degree = Some(15);
+        let codeword: Vec<XFieldElement> = fri.domain.omega.lift().get_cyclic_group_elements(None);
+        let mut proof_stream = ProofStream::default();
+        fri.prove(&codeword, &mut proof_stream).unwrap();
+        assert!(fri.verify(&mut proof_stream).is_ok());
+    }
+
+    #[test]
+    fn fri_stream_prover_transcript_equivalence_test() {
+        type Hasher = blake3::Hasher;
+
+        for folding_factor in [2, 4] {
+            let fri = get_x_field_fri_test_object_with_folding_factor::<Hasher>(
+                1024,
+                4,
+                6,
+                folding_factor,
+            );
+            let codeword: Vec<XFieldElement> =
+                fri.domain.omega.lift().get_cyclic_group_elements(None);
+
+            let mut standard_proof_stream = ProofStream::default();
+            let standard_indices = fri.prove(&codeword, &mut standard_proof_stream).unwrap();
+
+            let mut stream_proof_stream = ProofStream::default();
+            let stream_indices = fri
+                .prove_stream(&codeword.as_slice(), &mut stream_proof_stream)
+                .unwrap();
+
+            assert_eq!(standard_indices, stream_indices);
+            assert_eq!(
+                standard_proof_stream.serialize(),
+                stream_proof_stream.serialize()
+            );
+            assert!(fri.verify(&mut stream_proof_stream).is_ok());
+        }
+    }
+
+    #[test]
+    fn fri_on_b_field_test() {
+        type Hasher = blake3::Hasher;
+
+        let subgroup_order = 1024;
+        let expansion_factor = 4;
+        let colinearity_count = 6;
+        let fri = get_x_field_fri_test_object::<Hasher>(
+            subgroup_order,
+            expansion_factor,
+            colinearity_count,
+        );
+
+        // A low-degree base-field codeword passes without any lifting
+        let polynomial = Polynomial::new(random_elements::<BFieldElement>(
+            subgroup_order as usize / expansion_factor,
+        ));
+        let codeword: Vec<BFieldElement> = fri.domain.b_evaluate(&polynomial);
+        let mut proof_stream = ProofStream::default();
+        fri.prove_in_field(&codeword, &mut proof_stream).unwrap();
+        let verify_result = fri.verify_in_field::<BFieldElement>(&mut proof_stream);
+        assert!(verify_result.is_ok(), "{:?}", verify_result);
+        for (index, value) in verify_result.unwrap() {
+            assert_eq!(codeword[index], value);
+        }
+
+        // A high-degree codeword is still rejected
+        let high_degree_codeword: Vec<BFieldElement> = random_elements(subgroup_order as usize);
+        let mut bad_proof_stream = ProofStream::default();
+        fri.prove_in_field(&high_degree_codeword, &mut bad_proof_stream)
+            .unwrap();
+        assert!(fri
+            .verify_in_field::<BFieldElement>(&mut bad_proof_stream)
+            .is_err());
+    }
+
+    #[test]
+    fn fri_configurable_extension_degree_test() {
+        type Hasher = blake3::Hasher;
+
+        let subgroup_order = 1024;
+        let fri = get_x_field_fri_test_object::<Hasher>(subgroup_order, 4, 6);
+        let domain_points: Vec<BFieldElement> = fri.domain.omega.get_cyclic_group_elements(None);
+
+        // The same configuration runs over the degree-2 challenge field
+        let quad_codeword: Vec<QuadFieldElement> = domain_points
+            .iter()
+            .map(|&x| QuadFieldElement::from_base(x))
+            .collect();
+        let mut quad_stream = ProofStream::default();
+        fri.prove_in_field(&quad_codeword, &mut quad_stream)
+            .unwrap();
+        let quad_result = fri.verify_in_field::<QuadFieldElement>(&mut quad_stream);
+        assert!(quad_result.is_ok(), "{:?}", quad_result);
+        for (index, value) in quad_result.unwrap() {
+            assert_eq!(quad_codeword[index], value);
+        }
+
+        // ... and over the degree-4 challenge field
+        let quartic_codeword: Vec<QuarticFieldElement> = domain_points
+            .iter()
+            .map(|&x| QuarticFieldElement::from_base(x))
+            .collect();
+        let mut quartic_stream = ProofStream::default();
+        fri.prove_in_field(&quartic_codeword, &mut quartic_stream)
+            .unwrap();
+        let quartic_result = fri.verify_in_field::<QuarticFieldElement>(&mut quartic_stream);
+        assert!(quartic_result.is_ok(), "{:?}", quartic_result);
+
+        // High-degree codewords are still rejected
+        let high_degree_codeword: Vec<QuarticFieldElement> =
+            random_elements(subgroup_order as usize);
+        let mut bad_proof_stream = ProofStream::default();
+        fri.prove_in_field(&high_degree_codeword, &mut bad_proof_stream)
+            .unwrap();
+        assert!(fri
+            .verify_in_field::<QuarticFieldElement>(&mut bad_proof_stream)
+            .is_err());
+    }
+
+    #[test]
+    fn fri_builder_test() {
+        type Hasher = blake3::Hasher;
+
+        let fri: Fri<Hasher> = Fri::builder()
+            .domain_length(1 << 20)
+            .target_security_bits(128)
+            .build()
+            .unwrap();
+        assert_eq!(1 << 20, fri.domain.length);
+        assert_eq!(64, fri.colinearity_checks_count);
+        assert!(fri.security_bits() >= 128.0);
+
+        // Grinding reduces the required check count
+        let grinding_fri: Fri<Hasher> = Fri::builder()
+            .domain_length(1 << 20)
+            .grinding_bits(16)
+            .target_security_bits(128)
+            .build()
+            .unwrap();
+        assert_eq!(56, grinding_fri.colinearity_checks_count);
+        assert!(grinding_fri.security_bits() >= 128.0);
+
+        // Unreachable targets are rejected rather than silently under-delivered
+        assert_eq!(
+            Err(FriProverError::TargetSecurityUnreachable),
+            Fri::<Hasher>::builder()
+                .domain_length(64)
+                .target_security_bits(1000)
+                .build()
+                .map(|_| ())
+        );
+        assert_eq!(
+            Err(FriProverError::MissingDomainLength),
+            Fri::<Hasher>::builder()
+                .target_security_bits(128)
+                .build()
+                .map(|_| ())
+        );
+    }
+
+    #[test]
+    fn security_bits_test() {
+        type Hasher = blake3::Hasher;
+
+        let mut fri = get_x_field_fri_test_object::<Hasher>(1024, 4, 64);
+        // 64 checks at 2 bits of rate each
+        assert_eq!(128.0, fri.security_bits());
+        // The proven regime only counts half of that
+        fri.soundness_regime = SoundnessRegime::Proven;
+        assert_eq!(64.0, fri.security_bits());
+        fri.soundness_regime = SoundnessRegime::Conjectured;
+
+        // Grinding adds its bit count on top
+        fri.grinding_bits = 20;
+        assert_eq!(148.0, fri.security_bits());
+        fri.grinding_bits = 0;
+
+        // The estimate is capped by the size of the extension field
+        fri.colinearity_checks_count = 1 << 10;
+        assert!(fri.security_bits() < 192.0);
+    }
+
+    #[test]
+    fn soundness_regime_test() {
+        type Hasher = blake3::Hasher;
+
+        let fri = get_x_field_fri_test_object::<Hasher>(1024, 4, 6);
+        assert_eq!(SoundnessRegime::Conjectured, fri.soundness_regime);
+
+        // For the same target, the proven regime needs twice the checks of
+        // the conjectured one, and the index-sampling floor then stops the
+        // folding one round earlier
+        let conjectured: Fri<Hasher> = Fri::builder()
+            .domain_length(1 << 14)
+            .expansion_factor(16)
+            .target_security_bits(128)
+            .build()
+            .unwrap();
+        let proven: Fri<Hasher> = Fri::builder()
+            .domain_length(1 << 14)
+            .expansion_factor(16)
+            .soundness_regime(SoundnessRegime::Proven)
+            .target_security_bits(128)
+            .build()
+            .unwrap();
+        assert_eq!(SoundnessRegime::Proven, proven.soundness_regime);
+        assert_eq!(32, conjectured.colinearity_checks_count);
+        assert_eq!(64, proven.colinearity_checks_count);
+        assert_eq!((9, 1), conjectured.num_rounds());
+        assert_eq!((8, 3), proven.num_rounds());
+
+        // The soundness error is the probability behind the bit count:
+        // summing the two phase terms costs at most one bit
+        for fri_object in [&conjectured, &proven] {
+            let bits = fri_object.security_bits();
+            let error_bits = -fri_object.soundness_error().log2();
+            assert!(error_bits <= bits);
+            assert!(error_bits >= bits - 1.0);
+        }
+    }
+
+    #[test]
+    fn fri_prover_error_test() {
+        type Hasher = blake3::Hasher;
+
+        let fri = get_x_field_fri_test_object::<Hasher>(1024, 4, 6);
+        let mut proof_stream = ProofStream::default();
+
+        let too_short: Vec<XFieldElement> = random_elements(512);
+        assert_eq!(
+            Err(FriProverError::CodewordLengthMismatch {
+                expected: 1024,
+                actual: 512,
+            }),
+            fri.prove(&too_short, &mut proof_stream)
+        );
+        assert_eq!(
+            Err(FriProverError::EmptyBatch),
+            fri.prove_batch(&[], &mut proof_stream)
+        );
+
+        let omega = BFieldElement::primitive_root_of_unity(1024).unwrap();
+        let offset = BFieldElement::new(7);
+        assert_eq!(
+            Err(FriProverError::UnsupportedFoldingFactor(3)),
+            Fri::<Hasher>::new(offset, omega, 1024, 4, 6, 3).map(|_| ())
+        );
+        assert_eq!(
+            Err(FriProverError::DomainLengthNotPowerOfTwo),
+            Fri::<Hasher>::new(offset, omega, 1000, 4, 6, 2).map(|_| ())
+        );
+        assert_eq!(
+            Err(FriProverError::TooManyColinearityChecks),
+            Fri::<Hasher>::new(offset, omega, 1024, 4, 2048, 2).map(|_| ())
+        );
+    }
+
+    #[test]
+    fn fri_on_x_field_test() {
+        type Hasher = RescuePrimeRegular;
+
+        let subgroup_order = 1024;
+        let expansion_factor = 4;
+        let colinearity_check_count = 6;
+        let fri: Fri<Hasher> =
+            get_x_field_fri_test_object(subgroup_order, expansion_factor, colinearity_check_count);
+        let mut proof_stream: ProofStream = ProofStream::default();
+        let subgroup = fri.domain.omega.lift().get_cyclic_group_elements(None);
+
+        let ret = fri.prove(&subgroup, &mut proof_stream).unwrap();
+        assert_eq!(fri.colinearity_checks_count, ret.len());
+        assert_eq!(colinearity_check_count, ret.len());
+        let verify_result = fri.verify(&mut proof_stream);
+        assert!(verify_result.is_ok());
+    }
+
+    #[test]
+    fn fri_lean_prover_transcript_equivalence_test() {
+        type Hasher = blake3::Hasher;
+
+        let subgroup_order = 1024;
+        let expansion_factor = 4;
+        let colinearity_check_count = 6;
+        let mut fri: Fri<Hasher> =
+            get_x_field_fri_test_object(subgroup_order, expansion_factor, colinearity_check_count);
+        let subgroup = fri.domain.omega.lift().get_cyclic_group_elements(None);
+
+        let mut standard_proof_stream: ProofStream = ProofStream::default();
+        fri.prove(&subgroup, &mut standard_proof_stream).unwrap();
+
+        fri.memory_profile = ProverMemoryProfile::Lean;
+        let mut lean_proof_stream: ProofStream = ProofStream::default();
+        fri.prove(&subgroup, &mut lean_proof_stream).unwrap();
+
+        assert_eq!(
+            standard_proof_stream.serialize(),
+            lean_proof_stream.serialize(),
+            "Lean and standard prover must produce identical transcripts"
+        );
+        assert!(fri.verify(&mut lean_proof_stream).is_ok());
+    }
+
+    #[cfg(feature = "gpu")]
+    #[test]
+    fn fri_commit_backend_transcript_equivalence_test() {
+        type Hasher = blake3::Hasher;
+
+        let fri: Fri<Hasher> = get_x_field_fri_test_object(1024, 4, 6);
+        let subgroup = fri.domain.omega.lift().get_cyclic_group_elements(None);
+
+        let mut standard_proof_stream: ProofStream = ProofStream::default();
+        fri.prove(&subgroup, &mut standard_proof_stream).unwrap();
+
+        let mut backend_proof_stream: ProofStream = ProofStream::default();
+        fri.prove_with_backend(&subgroup, &mut backend_proof_stream, &RayonCommitBackend)
+            .unwrap();
+
+        assert_eq!(
+            standard_proof_stream.serialize(),
+            backend_proof_stream.serialize(),
+            "Backend and standard prover must produce identical transcripts"
+        );
+        assert!(fri.verify(&mut backend_proof_stream).is_ok());
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn fri_prove_async_test() {
+        type Hasher = blake3::Hasher;
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let fri: Fri<Hasher> = get_x_field_fri_test_object(1024, 4, 6);
+        let subgroup: Vec<XFieldElement> = fri.domain.omega.lift().get_cyclic_group_elements(None);
+
+        let mut sync_proof_stream: ProofStream = ProofStream::default();
+        fri.prove(&subgroup, &mut sync_proof_stream).unwrap();
+
+        let (progress_sender, mut progress_receiver) = tokio::sync::mpsc::unbounded_channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let (indices, mut async_proof_stream) = runtime
+            .block_on(fri.prove_async(
+                subgroup.clone(),
+                ProofStream::default(),
+                cancel.clone(),
+                Some(progress_sender),
+            ))
+            .unwrap();
+        assert_eq!(
+            sync_proof_stream.serialize(),
+            async_proof_stream.serialize(),
+            "Async and sync prover must produce identical transcripts"
+        );
+        assert!(!indices.is_empty());
+        async_proof_stream.set_index(0);
+        assert!(fri.verify(&mut async_proof_stream).is_ok());
+
+        // Progress runs from zero to the full round count
+        let (num_rounds, _) = fri.num_rounds();
+        let mut last_report = None;
+        while let Ok(progress_report) = progress_receiver.try_recv() {
+            last_report = Some(progress_report);
+        }
+        assert_eq!(
+            Some(FriProverProgress {
+                rounds_done: num_rounds,
+                num_rounds
+            }),
+            last_report
+        );
+
+        // A set cancellation flag aborts the run
+        cancel.store(true, Ordering::Relaxed);
+        let cancelled =
+            runtime.block_on(fri.prove_async(subgroup, ProofStream::default(), cancel, None));
+        assert!(matches!(cancelled, Err(FriProverError::Cancelled)));
+    }
+
+    #[test]
+    fn fri_batched_colinearity_checks_test() {
+        type Hasher = blake3::Hasher;
+
+        let mut fri: Fri<Hasher> = get_x_field_fri_test_object(1024, 4, 6);
+        fri.batched_colinearity_checks = true;
+        let subgroup = fri.domain.omega.lift().get_cyclic_group_elements(None);
+
+        let mut proof_stream: ProofStream = ProofStream::default();
+        fri.prove(&subgroup, &mut proof_stream).unwrap();
+        assert!(fri.verify(&mut proof_stream).is_ok());
+
+        // All prover modes emit the identical batched transcript
+        let mut lean_fri = fri.clone();
+        lean_fri.memory_profile = ProverMemoryProfile::Lean;
+        let mut lean_proof_stream: ProofStream = ProofStream::default();
+        lean_fri.prove(&subgroup, &mut lean_proof_stream).unwrap();
+        assert_eq!(proof_stream.serialize(), lean_proof_stream.serialize());
+
+        let mut streamed_proof_stream: ProofStream = ProofStream::default();
+        fri.prove_stream(&subgroup.as_slice(), &mut streamed_proof_stream)
+            .unwrap();
+        assert_eq!(proof_stream.serialize(), streamed_proof_stream.serialize());
+
+        // A too-high-degree codeword fails the batched check as well
+        let high_degree_codeword: Vec<XFieldElement> = random_elements(1024);
+        let mut bad_proof_stream: ProofStream = ProofStream::default();
+        fri.prove(&high_degree_codeword, &mut bad_proof_stream)
+            .unwrap();
+        assert!(fri.verify(&mut bad_proof_stream).is_err());
+
+        // A verifier that does not expect the batched layout rejects it
+        let mut plain_fri = fri.clone();
+        plain_fri.batched_colinearity_checks = false;
+        proof_stream.set_index(0);
+        assert!(plain_fri.verify(&mut proof_stream).is_err());
+    }
+
+    #[test]
+    fn fri_verify_many_test() {
+        type Hasher = blake3::Hasher;
+
+        let fri: Fri<Hasher> = get_x_field_fri_test_object(1024, 4, 6);
+        let subgroup: Vec<XFieldElement> = fri.domain.omega.lift().get_cyclic_group_elements(None);
+        let squared: Vec<XFieldElement> = subgroup.iter().map(|x| *x * *x).collect();
+        let high_degree_codeword: Vec<XFieldElement> = random_elements(1024);
+
+        let mut proof_streams: Vec<ProofStream> = vec![];
+        for codeword in [&subgroup, &squared, &high_degree_codeword] {
+            let mut proof_stream = ProofStream::default();
+            fri.prove(codeword, &mut proof_stream).unwrap();
+            proof_streams.push(proof_stream);
+        }
+        // A truncated transcript on top
+        proof_streams.push(ProofStream::from(
+            proof_streams[0].serialize()[0..100].to_vec(),
+        ));
+
+        let results: Vec<Result<FriVerifyReport<XFieldElement>, ValidationError>> =
+            fri.verify_many(&mut proof_streams);
+        assert_eq!(4, results.len());
+        assert!(results[0].is_ok());
+        assert!(results[1].is_ok());
+        assert!(matches!(
+            results[2],
+            Err(ValidationError::LastIterationTooHighDegree)
+        ));
+        assert!(matches!(results[3], Err(ValidationError::MalformedProof)));
+
+        // Results line up with the sequential verifier's
+        for (codeword, result) in [&subgroup, &squared].into_iter().zip(results.iter()) {
+            for (index, value) in result.as_ref().unwrap().codeword_evaluations.iter() {
+                assert_eq!(codeword[*index], *value);
+            }
+        }
+    }
+
+    #[test]
+    fn fri_stir_folding_test() {
+        type Hasher = blake3::Hasher;
+
+        let mut fri: Fri<Hasher> = get_x_field_fri_test_object(1024, 4, 6);
+        fri.folding_schedule = FoldingSchedule::Stir;
+        let subgroup: Vec<XFieldElement> = fri.domain.omega.lift().get_cyclic_group_elements(None);
+
+        let mut proof_stream: ProofStream = ProofStream::default();
+        fri.prove(&subgroup, &mut proof_stream).unwrap();
+        assert!(fri.verify(&mut proof_stream).is_ok());
+
+        // The schedule changes the transcript, and a classic verifier must
+        // not accept a STIR proof
+        let mut classic_fri = fri.clone();
+        classic_fri.folding_schedule = FoldingSchedule::Classic;
+        let mut classic_proof_stream: ProofStream = ProofStream::default();
+        classic_fri
+            .prove(&subgroup, &mut classic_proof_stream)
+            .unwrap();
+        assert_ne!(classic_proof_stream.serialize(), proof_stream.serialize());
+        proof_stream.set_index(0);
+        assert!(classic_fri.verify(&mut proof_stream).is_err());
+
+        // The lean profile falls back to the standard prover and emits the
+        // identical transcript; batched checks fall back to individual ones
+        let mut lean_fri = fri.clone();
+        lean_fri.memory_profile = ProverMemoryProfile::Lean;
+        lean_fri.batched_colinearity_checks = true;
+        let mut lean_proof_stream: ProofStream = ProofStream::default();
+        lean_fri.prove(&subgroup, &mut lean_proof_stream).unwrap();
+        assert_eq!(proof_stream.serialize(), lean_proof_stream.serialize());
+
+        // The streaming prover rejects the schedule outright
+        let mut streamed_proof_stream: ProofStream = ProofStream::default();
+        assert_eq!(
+            Err(FriProverError::UnsupportedFoldingSchedule),
+            fri.prove_stream(&subgroup.as_slice(), &mut streamed_proof_stream)
+        );
+
+        // A too-high-degree codeword is still rejected
+        let high_degree_codeword: Vec<XFieldElement> = random_elements(1024);
+        let mut bad_proof_stream: ProofStream = ProofStream::default();
+        fri.prove(&high_degree_codeword, &mut bad_proof_stream)
+            .unwrap();
+        assert!(fri.verify(&mut bad_proof_stream).is_err());
+
+        // The proof size estimate accounts for the out-of-domain samples
+        assert!(fri.proof_size_estimate() > classic_fri.proof_size_estimate());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn fri_disk_backed_prover_test() {
+        type Hasher = blake3::Hasher;
+
+        let mut fri: Fri<Hasher> = get_x_field_fri_test_object(1024, 4, 6);
+        let subgroup: Vec<XFieldElement> = fri.domain.omega.lift().get_cyclic_group_elements(None);
+        let open_databases = |fri_object: &Fri<Hasher>| -> Vec<rusty_leveldb::DB> {
+            (0..fri_object.tree_database_count())
+                .map(|_| rusty_leveldb::DB::open("fri-tree", rusty_leveldb::in_memory()).unwrap())
+                .collect()
+        };
+
+        // Byte-identical to the in-memory prover, and the proof verifies
+        let mut in_memory_proof_stream: ProofStream = ProofStream::default();
+        fri.prove(&subgroup, &mut in_memory_proof_stream).unwrap();
+        let mut proof_stream: ProofStream = ProofStream::default();
+        fri.prove_disk_backed(&subgroup, &mut proof_stream, open_databases(&fri))
+            .unwrap();
+        assert_eq!(in_memory_proof_stream.serialize(), proof_stream.serialize());
+        assert!(fri.verify(&mut proof_stream).is_ok());
+
+        // The database count must match the tree count exactly
+        let mut too_few = open_databases(&fri);
+        too_few.pop();
+        assert_eq!(
+            Err(FriProverError::WrongTreeDatabaseCount {
+                expected: fri.tree_database_count(),
+                actual: fri.tree_database_count() - 1,
+            }),
+            fri.prove_disk_backed(&subgroup, &mut ProofStream::default(), too_few)
+        );
+
+        // The STIR schedule runs disk-backed, too
+        fri.folding_schedule = FoldingSchedule::Stir;
+        let mut stir_in_memory_proof_stream: ProofStream = ProofStream::default();
+        fri.prove(&subgroup, &mut stir_in_memory_proof_stream)
+            .unwrap();
+        let mut stir_proof_stream: ProofStream = ProofStream::default();
+        fri.prove_disk_backed(&subgroup, &mut stir_proof_stream, open_databases(&fri))
+            .unwrap();
+        assert_eq!(
+            stir_in_memory_proof_stream.serialize(),
+            stir_proof_stream.serialize()
+        );
+        assert!(fri.verify(&mut stir_proof_stream).is_ok());
+    }
+
+    #[test]
+    fn fri_deep_test() {
+        type Hasher = blake3::Hasher;
+
+        let fri: Fri<Hasher> = get_x_field_fri_test_object(1024, 4, 6);
+        let subgroup: Vec<XFieldElement> = fri.domain.omega.lift().get_cyclic_group_elements(None);
+
+        let mut proof_stream: ProofStream = ProofStream::default();
+        let (prover_z, prover_y, _) = fri.prove_deep(&subgroup, &mut proof_stream).unwrap();
+        let (z, y, quotient_evaluations) = fri.verify_deep(&mut proof_stream).unwrap();
+        assert_eq!(prover_z, z);
+        assert_eq!(prover_y, y);
+
+        // The returned evaluations open the quotient codeword, so each one
+        // links back to the original codeword through the DEEP relation
+        let omega = fri.domain.omega.lift();
+        for (index, quotient_value) in quotient_evaluations {
+            let expected = (subgroup[index] - y) / (omega.mod_pow_u32(index as u32) - z);
+            assert_eq!(expected, quotient_value);
+        }
+
+        // The out-of-domain point is bound to whatever the caller enqueued
+        // before proving
+        let mut prefixed_stream = ProofStream::new_with_prefix(b"commitment");
+        let (prefixed_z, _, _) = fri.prove_deep(&subgroup, &mut prefixed_stream).unwrap();
+        assert_ne!(z, prefixed_z);
+
+        // A lie about the out-of-domain evaluation leaves a pole in the
+        // quotient, and the low-degree test catches it
+        let mut cheat_stream: ProofStream = ProofStream::default();
+        let cheat_z: XFieldElement = XFieldElement::sample(
+            &cheat_stream.prover_fiat_shamir_tagged(&Fri::<Hasher>::protocol_tag(b"deep")),
+        );
+        let wrong_y = prover_y + XFieldElement::one();
+        cheat_stream.enqueue_length_prepended(&wrong_y).unwrap();
+        let bad_quotient =
+            Fri::<Hasher>::quotient_codeword(&subgroup, fri.domain.omega, cheat_z, wrong_y);
+        fri.prove_in_field(&bad_quotient, &mut cheat_stream)
+            .unwrap();
+        assert!(fri.verify_deep(&mut cheat_stream).is_err());
+    }
+
+    #[test]
+    fn fri_prover_observer_test() {
+        type Hasher = blake3::Hasher;
+
+        #[derive(Default)]
+        struct RecordingObserver {
+            commit_rounds: Vec<(usize, usize)>,
+            query_phases: Vec<usize>,
+        }
+
+        impl FriProverObserver for RecordingObserver {
+            fn on_commit_round(
+                &mut self,
+                round: usize,
+                codeword_length: usize,
+                _elapsed: Duration,
+            ) {
+                self.commit_rounds.push((round, codeword_length));
+            }
+
+            fn on_query_phase(&mut self, num_indices: usize, _elapsed: Duration) {
+                self.query_phases.push(num_indices);
+            }
+        }
+
+        let fri: Fri<Hasher> = get_x_field_fri_test_object(1024, 4, 6);
+        let subgroup: Vec<XFieldElement> = fri.domain.omega.lift().get_cyclic_group_elements(None);
+
+        // The observer only watches: the transcript is byte-identical to
+        // the plain prover's
+        let mut plain_proof_stream: ProofStream = ProofStream::default();
+        fri.prove(&subgroup, &mut plain_proof_stream).unwrap();
+        let mut observer = RecordingObserver::default();
+        let mut proof_stream: ProofStream = ProofStream::default();
+        fri.prove_with_observer(&subgroup, &mut proof_stream, &mut observer)
+            .unwrap();
+        assert_eq!(plain_proof_stream.serialize(), proof_stream.serialize());
+
+        // One commit event per committed codeword, with the lengths the
+        // proof shape predicts, and one query-phase event
+        let expected_commit_rounds: Vec<(usize, usize)> = fri
+            .proof_shape()
+            .round_domain_lengths
+            .into_iter()
+            .enumerate()
+            .collect();
+        assert_eq!(expected_commit_rounds, observer.commit_rounds);
+        assert_eq!(vec![fri.colinearity_checks_count], observer.query_phases);
+    }
+
+    #[test]
+    fn fri_domain_round_domain_test() {
+        type Hasher = blake3::Hasher;
+
+        let fri: Fri<Hasher> = get_x_field_fri_test_object(1024, 4, 6);
+        let domain = fri.domain.clone();
+
+        // Halving squares the coset and halves the length
+        let halved = domain.halve();
+        assert_eq!(domain.omega * domain.omega, halved.omega);
+        assert_eq!(domain.offset * domain.offset, halved.offset);
+        assert_eq!(domain.length / 2, halved.length);
+
+        // Round zero is the domain itself
+        let round_zero = domain.round_domain(0, 2);
+        assert_eq!(domain.omega, round_zero.omega);
+        assert_eq!(domain.offset, round_zero.offset);
+        assert_eq!(domain.length, round_zero.length);
+
+        // One round at folding factor four equals two halvings
+        let round_one = domain.round_domain(1, 4);
+        let halved_twice = domain.halve().halve();
+        assert_eq!(halved_twice.omega, round_one.omega);
+        assert_eq!(halved_twice.offset, round_one.offset);
+        assert_eq!(halved_twice.length, round_one.length);
+
+        // And matches direct exponentiation for a deeper round
+        let round_three = domain.round_domain(3, 2);
+        assert_eq!(domain.omega.mod_pow(8), round_three.omega);
+        assert_eq!(domain.offset.mod_pow(8), round_three.offset);
+        assert_eq!(domain.length / 8, round_three.length);
+    }
+
+    #[test]
+    fn fri_domain_precompute_test() {
+        type Hasher = blake3::Hasher;
+
+        let fri: Fri<Hasher> = get_x_field_fri_test_object(1024, 4, 6);
+        let domain = fri.domain.clone();
+        let polynomial = Polynomial::new(random_elements(100));
+
+        // Evaluation results and domain values are cache-independent
+        let values_before = domain.b_domain_values();
+        let evaluation_before = domain.b_evaluate(&polynomial);
+        domain.precompute();
+        assert_eq!(values_before, domain.b_domain_values());
+        assert_eq!(evaluation_before, domain.b_evaluate(&polynomial));
+
+        // The cached values match the naive per-index computation
+        for (i, value) in domain.domain_values().iter().enumerate() {
+            assert_eq!(domain.omega.mod_pow(i as u64) * domain.offset, *value);
+            assert_eq!(domain.b_domain_value(i as u64), *value);
+        }
+    }
+
+    #[test]
+    fn fri_domain_evaluate_many_test() {
+        type Hasher = blake3::Hasher;
+
+        let fri: Fri<Hasher> = get_x_field_fri_test_object(1024, 4, 6);
+        let domain = fri.domain.clone();
+
+        let b_polynomials: Vec<Polynomial<BFieldElement>> = (0..7)
+            .map(|i| Polynomial::new(random_elements(20 * i + 1)))
+            .collect();
+        let b_evaluations = domain.b_evaluate_many(&b_polynomials);
+        assert_eq!(b_polynomials.len(), b_evaluations.len());
+        for (polynomial, evaluation) in b_polynomials.iter().zip(b_evaluations.iter()) {
+            assert_eq!(domain.b_evaluate(polynomial), *evaluation);
+        }
+
+        let x_polynomials: Vec<Polynomial<XFieldElement>> = (0..7)
+            .map(|i| Polynomial::new(random_elements(20 * i + 1)))
+            .collect();
+        let x_evaluations = domain.x_evaluate_many(&x_polynomials);
+        assert_eq!(x_polynomials.len(), x_evaluations.len());
+        for (polynomial, evaluation) in x_polynomials.iter().zip(x_evaluations.iter()) {
+            assert_eq!(domain.x_evaluate(polynomial), *evaluation);
+        }
+    }
+
+    #[test]
+    fn fri_domain_iter_values_test() {
+        type Hasher = blake3::Hasher;
+
+        let fri: Fri<Hasher> = get_x_field_fri_test_object(1024, 4, 6);
+        let domain = fri.domain.clone();
+
+        let streamed: Vec<BFieldElement> = domain.iter_values().collect();
+        assert_eq!(domain.b_domain_values(), streamed);
+
+        // The iterator is bounded by the domain length even when chained
+        assert_eq!(domain.length, domain.iter_values().count());
+    }
+
+    #[test]
+    fn fri_domain_of_length_test() {
+        let domain = FriDomain::of_length(1024, None).unwrap();
+        assert_eq!(BFieldElement::generator(), domain.offset);
+        assert_eq!(
+            BFieldElement::primitive_root_of_unity(1024).unwrap(),
+            domain.omega
+        );
+        assert_eq!(1024, domain.length);
+
+        let offset = BFieldElement::new(7);
+        let shifted = FriDomain::of_length(64, Some(offset)).unwrap();
+        assert_eq!(offset, shifted.offset);
+
+        // Unsupported lengths are rejected
+        assert_eq!(
+            FriProverError::DomainLengthNotPowerOfTwo,
+            FriDomain::of_length(1000, None).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn fri_parameters_serde_test() {
+        type Hasher = blake3::Hasher;
+
+        let mut fri: Fri<Hasher> = get_x_field_fri_test_object(1024, 4, 6);
+        fri.grinding_bits = 4;
+        fri.zero_knowledge = true;
+        fri.max_last_round_degree = Some(7);
+        fri.folding_schedule = FoldingSchedule::Stir;
+        fri.memory_profile = ProverMemoryProfile::Lean;
+
+        // Roundtrip through bytes and back into a Fri instance
+        let parameters = fri.parameters();
+        let bytes = parameters.to_bytes().unwrap();
+        let recovered = FriParameters::from_bytes(&bytes).unwrap();
+        assert_eq!(parameters, recovered);
+        let rehydrated: Fri<Hasher> = recovered.to_fri().unwrap();
+        assert_eq!(parameters, rehydrated.parameters());
+
+        // A deserialized domain starts with empty caches but behaves the same
+        let domain_bytes = bincode::serialize(&fri.domain).unwrap();
+        let domain: FriDomain = bincode::deserialize(&domain_bytes).unwrap();
+        assert_eq!(fri.domain.b_domain_values(), domain.b_domain_values());
+
+        // Tampered parameter sets are rejected on deserialization
+        let mut bad_parameters = parameters.clone();
+        bad_parameters.folding_factor = 3;
+        let bad_bytes = bad_parameters.to_bytes().unwrap();
+        assert!(FriParameters::from_bytes(&bad_bytes).is_err());
+    }
+
+    #[test]
+    fn fri_domain_change_offset_test() {
+        type Hasher = blake3::Hasher;
+
+        let fri: Fri<Hasher> = get_x_field_fri_test_object(1024, 4, 6);
+        let domain = fri.domain.clone();
+        let new_offset = BFieldElement::new(7);
+
+        // `with_offset` only moves the coset
+        let shifted = domain.with_offset(new_offset);
+        assert_eq!(new_offset, shifted.offset);
+        assert_eq!(domain.omega, shifted.omega);
+        assert_eq!(domain.length, shifted.length);
+
+        // Changing the offset of a codeword matches evaluating the
+        // underlying polynomial over the shifted domain directly
+        let b_polynomial: Polynomial<BFieldElement> = Polynomial::new(random_elements(100));
+        let b_codeword = domain.b_evaluate(&b_polynomial);
+        assert_eq!(
+            shifted.b_evaluate(&b_polynomial),
+            domain.b_change_offset(&b_codeword, new_offset)
+        );
+
+        let x_polynomial: Polynomial<XFieldElement> = Polynomial::new(random_elements(100));
+        let x_codeword = domain.x_evaluate(&x_polynomial);
+        assert_eq!(
+            shifted.x_evaluate(&x_polynomial),
+            domain.x_change_offset(&x_codeword, new_offset)
+        );
+
+        // And shifting back is the identity
+        let there_and_back = shifted.b_change_offset(
+            &domain.b_change_offset(&b_codeword, new_offset),
+            domain.offset,
+        );
+        assert_eq!(b_codeword, there_and_back);
+    }
+
+    #[test]
+    fn fri_compressed_proof_test() {
+        type Hasher = blake3::Hasher;
+
+        let fri: Fri<Hasher> = get_x_field_fri_test_object(1024, 4, 6);
+        let subgroup: Vec<XFieldElement> = fri.domain.omega.lift().get_cyclic_group_elements(None);
+
+        let mut proof_stream: ProofStream = ProofStream::default();
+        fri.prove(&subgroup, &mut proof_stream).unwrap();
 
-            // compute "C" indices and values for next round from "A" and "B`"" of current round
-            current_domain_len /= 2;
-            let c_indices = a_indices.iter().map(|x| x % current_domain_len).collect();
-            let c_values = (0..self.colinearity_checks_count)
-                .into_par_iter()
-                .map(|i| {
-                    Polynomial::<XFieldElement>::get_colinear_y(
-                        (
-                            self.get_evaluation_argument(a_indices[i], r).lift(),
-                            a_values[i],
-                        ),
-                        (
-                            self.get_evaluation_argument(b_indices[i], r).lift(),
-                            b_values[i],
-                        ),
-                        alphas[r],
-                    )
-                })
-                .collect();
+        // Compression is transparent to the verifier
+        let compressed = proof_stream.to_compressed_bytes();
+        assert!(compressed.len() < proof_stream.len());
+        let mut decompressed = ProofStream::from_compressed_bytes(&compressed).unwrap();
+        assert!(fri.verify(&mut decompressed).is_ok());
+    }
 
-            // Return top-level values to caller
-            if r == 0 {
-                for s in 0..self.colinearity_checks_count {
-                    codeword_evaluations.push((a_indices[s], a_values[s]));
-                    codeword_evaluations.push((b_indices[s], b_values[s]));
-                }
-            }
+    #[test]
+    fn fri_incremental_transcript_test() {
+        type Hasher = blake3::Hasher;
 
-            // Notice that next rounds "A"s correspond to current rounds "C":
-            a_indices = c_indices;
-            a_values = c_values;
+        let fri: Fri<Hasher> = get_x_field_fri_test_object(1024, 4, 6);
+        let subgroup: Vec<XFieldElement> = fri.domain.omega.lift().get_cyclic_group_elements(None);
 
-            // Update subgroup generator and offset
-            omega = omega * omega;
-            offset = offset * offset;
-        }
+        // The whole protocol runs on an incremental transcript, as long as
+        // both parties use it
+        let mut proof_stream = ProofStream::new_incremental();
+        fri.prove(&subgroup, &mut proof_stream).unwrap();
+        let mut verifier_stream = ProofStream::incremental_from(proof_stream.serialize());
+        assert!(fri.verify(&mut verifier_stream).is_ok());
 
-        Ok(codeword_evaluations)
+        // An incremental proof is not a rehashing proof
+        let mut mismatched_stream: ProofStream = proof_stream.serialize().into();
+        assert!(fri.verify(&mut mismatched_stream).is_err());
     }
 
-    fn get_evaluation_argument(&self, idx: usize, round: usize) -> BFieldElement {
-        (self.domain.offset * self.domain.omega.mod_pow_u32(idx as u32))
-            .mod_pow_u32(2u32.pow(round as u32))
-    }
+    #[test]
+    fn fri_determinism_modes_test() {
+        type Hasher = blake3::Hasher;
+        use crate::parallel::{set_determinism, Determinism};
 
-    pub fn get_evaluation_domain(&self) -> Vec<BFieldElement> {
-        let omega_domain = self.domain.omega.get_cyclic_group_elements(None);
-        omega_domain
-            .into_iter()
-            .map(|x| x * self.domain.offset)
-            .collect()
-    }
+        let fri: Fri<Hasher> = get_x_field_fri_test_object(1024, 4, 6);
+        let subgroup: Vec<XFieldElement> = fri.domain.omega.lift().get_cyclic_group_elements(None);
 
-    fn num_rounds(&self) -> (u8, u32) {
-        let max_degree = (self.domain.length / self.expansion_factor) - 1;
-        let mut rounds_count = log_2_ceil(max_degree as u128 + 1) as u8;
-        let mut max_degree_of_last_round = 0u32;
-        if self.expansion_factor < self.colinearity_checks_count {
-            let num_missed_rounds = log_2_ceil(
-                (self.colinearity_checks_count as f64 / self.expansion_factor as f64).ceil()
-                    as u128,
-            ) as u8;
-            rounds_count -= num_missed_rounds;
-            max_degree_of_last_round = 2u32.pow(num_missed_rounds as u32) - 1;
+        // The determinism mode only changes scheduling, never the output:
+        // every mode produces the same transcript as the default
+        let mut default_proof_stream: ProofStream = ProofStream::default();
+        fri.prove(&subgroup, &mut default_proof_stream).unwrap();
+
+        for mode in [Determinism::Sequential, Determinism::FixedChunks] {
+            set_determinism(mode);
+            let mut proof_stream: ProofStream = ProofStream::default();
+            fri.prove(&subgroup, &mut proof_stream).unwrap();
+            assert_eq!(
+                default_proof_stream.serialize(),
+                proof_stream.serialize(),
+                "Transcript must not depend on the determinism mode"
+            );
         }
+        set_determinism(Determinism::None);
+    }
 
-        (rounds_count, max_degree_of_last_round)
+    #[test]
+    fn fri_prove_with_digests_test() {
+        type Hasher = blake3::Hasher;
+
+        let mut fri: Fri<Hasher> = get_x_field_fri_test_object(1024, 4, 6);
+        let subgroup: Vec<XFieldElement> = fri.domain.omega.lift().get_cyclic_group_elements(None);
+        let leaf_digests: Vec<Digest> = subgroup
+            .iter()
+            .map(|x| Fri::<Hasher>::leaf_digest(fri.leaf_encoding, x))
+            .collect();
+
+        // Byte-identical to the plain prover, and the proof verifies
+        let mut plain_proof_stream: ProofStream = ProofStream::default();
+        fri.prove(&subgroup, &mut plain_proof_stream).unwrap();
+        let mut proof_stream: ProofStream = ProofStream::default();
+        fri.prove_with_digests(&subgroup, &leaf_digests, &mut proof_stream)
+            .unwrap();
+        assert_eq!(plain_proof_stream.serialize(), proof_stream.serialize());
+        assert!(fri.verify(&mut proof_stream).is_ok());
+
+        // The digest count must match the codeword length
+        assert_eq!(
+            Err(FriProverError::LeafDigestCountMismatch {
+                expected: 1024,
+                actual: 1023,
+            }),
+            fri.prove_with_digests(
+                &subgroup,
+                &leaf_digests[..1023],
+                &mut ProofStream::default()
+            )
+        );
+
+        // Blinding rewrites the codeword, invalidating the digests
+        fri.zero_knowledge = true;
+        assert_eq!(
+            Err(FriProverError::PrecomputedDigestsWithBlinding),
+            fri.prove_with_digests(&subgroup, &leaf_digests, &mut ProofStream::default())
+        );
     }
-}
 
-#[cfg(test)]
-mod fri_domain_tests {
-    use num_traits::One;
+    #[test]
+    fn fri_proof_shape_test() {
+        type Hasher = blake3::Hasher;
 
-    use super::*;
-    use crate::shared_math::b_field_element::BFieldElement;
-    use crate::shared_math::traits::PrimitiveRootOfUnity;
-    use crate::shared_math::x_field_element::XFieldElement;
+        let mut fri: Fri<Hasher> = get_x_field_fri_test_object(1024, 4, 6);
+        let shape = fri.proof_shape();
+        assert_eq!(7, shape.num_rounds);
+        assert_eq!(1, shape.last_round_max_degree);
+        assert_eq!(
+            vec![1024, 512, 256, 128, 64, 32, 16, 8],
+            shape.round_domain_lengths
+        );
+        assert_eq!(8, shape.last_codeword_length);
+        // One batch of 6 paths for the queried positions, plus one batch of
+        // siblings per round at folding factor 2
+        assert_eq!(6 * (1 + 7), shape.num_auth_paths);
+        assert!(!shape.has_grinding_nonce);
+        assert_eq!(0, shape.num_out_of_domain_samples);
+
+        // Grinding and the STIR schedule show up in the shape
+        fri.grinding_bits = 8;
+        fri.folding_schedule = FoldingSchedule::Stir;
+        let stir_shape = fri.proof_shape();
+        assert!(stir_shape.has_grinding_nonce);
+        assert_eq!(7, stir_shape.num_out_of_domain_samples);
+
+        // A higher folding factor means fewer, wider rounds
+        let quartic_fri: Fri<Hasher> =
+            get_x_field_fri_test_object_with_folding_factor(1024, 4, 6, 4);
+        let quartic_shape = quartic_fri.proof_shape();
+        assert_eq!(
+            quartic_shape.num_rounds as usize + 1,
+            quartic_shape.round_domain_lengths.len()
+        );
+        assert_eq!(
+            6 * (1 + quartic_shape.num_rounds as usize * 3),
+            quartic_shape.num_auth_paths
+        );
+    }
 
     #[test]
-    fn x_values_test() {
-        // pol = x^3
-        let x_squared_coefficients = vec![
-            BFieldElement::zero(),
-            BFieldElement::zero(),
-            BFieldElement::zero(),
-            BFieldElement::one(),
+    fn fri_leaf_encoding_test() {
+        type Hasher = blake3::Hasher;
+
+        // The EVM layout is locked down: each coefficient is its own
+        // 32-byte big-endian word, and the words are hashed with SHA-256
+        let value = XFieldElement::new([
+            BFieldElement::new(1),
+            BFieldElement::new(2),
+            BFieldElement::new(3),
+        ]);
+        let expected_sha256: [u8; 32] = [
+            0x97, 0x01, 0xf3, 0x4c, 0x80, 0xe1, 0xef, 0x7f, 0x81, 0x25, 0xe5, 0xd4, 0xd2, 0xd7,
+            0xe1, 0x9b, 0x50, 0x9e, 0x25, 0xd2, 0x6e, 0x46, 0x2d, 0x53, 0x08, 0xb5, 0xab, 0xb9,
+            0x5b, 0x64, 0x78, 0x3e,
         ];
+        let expected_digest = Digest::new([
+            BFieldElement::from_ne_bytes(&expected_sha256[0..8]),
+            BFieldElement::from_ne_bytes(&expected_sha256[8..16]),
+            BFieldElement::from_ne_bytes(&expected_sha256[16..24]),
+            BFieldElement::from_ne_bytes(&expected_sha256[24..32]),
+            BFieldElement::zero(),
+        ]);
+        assert_eq!(
+            expected_digest,
+            Fri::<Hasher>::leaf_digest(LeafEncoding::BaseFieldWords, &value)
+        );
 
-        for order in [4, 8, 32] {
-            let omega = BFieldElement::primitive_root_of_unity(order).unwrap();
-            let domain = FriDomain {
-                offset: BFieldElement::generator(),
-                omega,
-                length: order as usize,
-            };
-            let expected_x_values: Vec<BFieldElement> = (0..order)
-                .map(|i| BFieldElement::generator() * omega.mod_pow(i as u64))
-                .collect();
-            let x_values = domain.b_domain_values();
-            assert_eq!(expected_x_values, x_values);
+        // Prove/verify round trip under the encoding; the transcript
+        // differs from the algebraic one
+        let mut fri: Fri<Hasher> = get_x_field_fri_test_object(1024, 4, 6);
+        let subgroup: Vec<XFieldElement> = fri.domain.omega.lift().get_cyclic_group_elements(None);
+        let mut algebraic_proof_stream: ProofStream = ProofStream::default();
+        fri.prove(&subgroup, &mut algebraic_proof_stream).unwrap();
 
-            // Verify that `x_value` also returns expected values
-            for i in 0..order {
-                assert_eq!(
-                    expected_x_values[i as usize],
-                    domain.b_domain_value(i as u32)
-                );
-            }
+        fri.leaf_encoding = LeafEncoding::BaseFieldWords;
+        let mut proof_stream: ProofStream = ProofStream::default();
+        fri.prove(&subgroup, &mut proof_stream).unwrap();
+        assert_ne!(algebraic_proof_stream.serialize(), proof_stream.serialize());
+        assert!(fri.verify(&mut proof_stream).is_ok());
 
-            let pol = Polynomial::<BFieldElement>::new(x_squared_coefficients.clone());
-            let values = domain.b_evaluate(&pol);
-            assert_ne!(values, x_squared_coefficients);
-            let interpolant = domain.b_interpolate(&values);
-            assert_eq!(pol, interpolant);
+        // A verifier expecting the algebraic encoding must not accept it
+        let mut algebraic_fri = fri.clone();
+        algebraic_fri.leaf_encoding = LeafEncoding::Algebraic;
+        proof_stream.set_index(0);
+        assert!(algebraic_fri.verify(&mut proof_stream).is_err());
 
-            // Verify that batch-evaluated values match a manual evaluation
-            for i in 0..order {
-                assert_eq!(
-                    pol.evaluate(&domain.b_domain_value(i as u32)),
-                    values[i as usize]
-                );
-            }
+        // The lean prover emits the identical transcript under the encoding
+        let mut lean_fri = fri.clone();
+        lean_fri.memory_profile = ProverMemoryProfile::Lean;
+        let mut lean_proof_stream: ProofStream = ProofStream::default();
+        lean_fri.prove(&subgroup, &mut lean_proof_stream).unwrap();
+        assert_eq!(proof_stream.serialize(), lean_proof_stream.serialize());
+    }
 
-            let x_squared_coefficients_lifted: Vec<XFieldElement> = x_squared_coefficients
-                .clone()
-                .into_iter()
-                .map(|x| x.lift())
-                .collect();
-            let xpol = Polynomial::new(x_squared_coefficients_lifted.clone());
-            let x_field_x_values = domain.x_evaluate(&xpol);
-            assert_ne!(x_field_x_values, x_squared_coefficients_lifted);
-            let x_interpolant = domain.x_interpolate(&x_field_x_values);
-            assert_eq!(xpol, x_interpolant);
-        }
+    #[test]
+    fn fri_grinding_test() {
+        type Hasher = blake3::Hasher;
+
+        let subgroup_order = 1024;
+        let expansion_factor = 4;
+        let colinearity_check_count = 6;
+        let mut fri: Fri<Hasher> =
+            get_x_field_fri_test_object(subgroup_order, expansion_factor, colinearity_check_count);
+        fri.grinding_bits = 8;
+        let subgroup = fri.domain.omega.lift().get_cyclic_group_elements(None);
+
+        let mut proof_stream: ProofStream = ProofStream::default();
+        fri.prove(&subgroup, &mut proof_stream).unwrap();
+        assert!(fri.verify(&mut proof_stream).is_ok());
+
+        // Negative: a proof produced without grinding must not pass a
+        // verifier that demands it
+        let ungrinded_fri: Fri<Hasher> =
+            get_x_field_fri_test_object(subgroup_order, expansion_factor, colinearity_check_count);
+        let mut ungrinded_proof_stream: ProofStream = ProofStream::default();
+        ungrinded_fri
+            .prove(&subgroup, &mut ungrinded_proof_stream)
+            .unwrap();
+        assert!(fri.verify(&mut ungrinded_proof_stream).is_err());
+
+        // An unreachable difficulty fails fast instead of spinning the
+        // grinding loop forever
+        fri.grinding_bits = 64;
+        assert_eq!(
+            Err(FriProverError::ExcessiveGrindingBits(64)),
+            fri.prove(&subgroup, &mut ProofStream::default())
+        );
+        let mut bad_parameters = fri.parameters();
+        assert_eq!(
+            Err(FriProverError::ExcessiveGrindingBits(64)),
+            bad_parameters.validate()
+        );
+        bad_parameters.grinding_bits = MAX_GRINDING_BITS;
+        assert!(bad_parameters.validate().is_ok());
+        assert_eq!(
+            Err(FriProverError::ExcessiveGrindingBits(33)),
+            Fri::<Hasher>::builder()
+                .domain_length(1024)
+                .target_security_bits(32)
+                .grinding_bits(33)
+                .build()
+                .map(|_| ())
+        );
     }
-}
 
-#[cfg(test)]
-mod fri_tests {
-    use super::*;
-    use crate::shared_math::b_field_element::BFieldElement;
-    use crate::shared_math::rescue_prime_regular::RescuePrimeRegular;
-    use crate::shared_math::traits::PrimitiveRootOfUnity;
-    use crate::shared_math::traits::{CyclicGroupGenerator, ModPowU32};
-    use crate::shared_math::x_field_element::XFieldElement;
-    use itertools::Itertools;
+    #[test]
+    fn fri_grinding_incremental_transcript_test() {
+        type Hasher = blake3::Hasher;
+
+        // Grinding mines its nonce through the stream's own challenge
+        // derivation, so it must also line up with the verifier when the
+        // transcript runs in incremental mode
+        let mut fri: Fri<Hasher> = get_x_field_fri_test_object(1024, 4, 6);
+        fri.grinding_bits = 4;
+        let subgroup: Vec<XFieldElement> = fri.domain.omega.lift().get_cyclic_group_elements(None);
+
+        let mut proof_stream = ProofStream::new_incremental();
+        fri.prove(&subgroup, &mut proof_stream).unwrap();
+        let mut verifier_stream = ProofStream::incremental_from(proof_stream.serialize());
+        assert!(fri.verify(&mut verifier_stream).is_ok());
+
+        // both modes keep working from the same `Fri` object
+        let mut rehash_proof_stream: ProofStream = ProofStream::default();
+        fri.prove(&subgroup, &mut rehash_proof_stream).unwrap();
+        assert!(fri.verify(&mut rehash_proof_stream).is_ok());
+    }
 
     #[test]
-    fn get_rounds_count_test() {
-        type H = blake3::Hasher;
+    fn fri_batch_prove_and_verify_test() {
+        type Hasher = blake3::Hasher;
 
-        let subgroup_order = 512;
+        let subgroup_order = 1024;
         let expansion_factor = 4;
-        let mut fri: Fri<H> = get_x_field_fri_test_object::<H>(subgroup_order, expansion_factor, 2);
+        let colinearity_check_count = 6;
+        let fri: Fri<Hasher> =
+            get_x_field_fri_test_object(subgroup_order, expansion_factor, colinearity_check_count);
+        let subgroup = fri.domain.omega.get_cyclic_group_elements(None);
 
-        assert_eq!((7, 0), fri.num_rounds());
-        fri.colinearity_checks_count = 8;
-        assert_eq!((6, 1), fri.num_rounds());
-        fri.colinearity_checks_count = 10;
-        assert_eq!((5, 3), fri.num_rounds());
-        fri.colinearity_checks_count = 16;
-        assert_eq!((5, 3), fri.num_rounds());
-        fri.colinearity_checks_count = 17;
-        assert_eq!((4, 7), fri.num_rounds());
-        fri.colinearity_checks_count = 18;
-        assert_eq!((4, 7), fri.num_rounds());
-        fri.colinearity_checks_count = 31;
-        assert_eq!((4, 7), fri.num_rounds());
-        fri.colinearity_checks_count = 32;
-        assert_eq!((4, 7), fri.num_rounds());
-        fri.colinearity_checks_count = 33;
-        assert_eq!((3, 15), fri.num_rounds());
+        // Codewords of varying (low) degrees
+        let codewords: Vec<Vec<XFieldElement>> = [1u32, 7, 63, 255]
+            .iter()
+            .map(|n| subgroup.iter().map(|p| p.mod_pow_u32(*n).lift()).collect())
+            .collect();
 
-        fri.domain.length = 256;
-        assert_eq!((2, 15), fri.num_rounds());
-        fri.colinearity_checks_count = 32;
-        assert_eq!((3, 7), fri.num_rounds());
+        let mut proof_stream: ProofStream = ProofStream::default();
+        let (prover_weights, ret) = fri.prove_batch(&codewords, &mut proof_stream).unwrap();
+        assert_eq!(colinearity_check_count, ret.len());
+        assert_eq!(codewords.len(), prover_weights.len());
 
-        fri.colinearity_checks_count = 32;
-        fri.domain.length = 1048576;
-        fri.expansion_factor = 8;
-        assert_eq!((15, 3), fri.num_rounds());
+        let (verifier_weights, _evaluations) = fri
+            .verify_batch(codewords.len(), &mut proof_stream)
+            .unwrap();
+        assert_eq!(
+            prover_weights, verifier_weights,
+            "Prover and verifier must derive the same batching weights"
+        );
 
-        fri.colinearity_checks_count = 33;
-        fri.domain.length = 1048576;
-        fri.expansion_factor = 8;
-        assert_eq!((14, 7), fri.num_rounds());
+        // Negative: batch containing a too-high-degree codeword must not verify
+        let too_high = subgroup_order as u32 / expansion_factor as u32;
+        let mut bad_codewords = codewords;
+        bad_codewords.push(
+            subgroup
+                .iter()
+                .map(|p| p.mod_pow_u32(too_high).lift())
+                .collect(),
+        );
+        let mut bad_proof_stream: ProofStream = ProofStream::default();
+        fri.prove_batch(&bad_codewords, &mut bad_proof_stream)
+            .unwrap();
+        assert!(fri
+            .verify_batch(bad_codewords.len(), &mut bad_proof_stream)
+            .is_err());
+    }
 
-        fri.colinearity_checks_count = 63;
-        fri.domain.length = 1048576;
-        fri.expansion_factor = 8;
-        assert_eq!((14, 7), fri.num_rounds());
+    #[test]
+    fn fri_with_high_folding_factor_test() {
+        type Hasher = blake3::Hasher;
 
-        fri.colinearity_checks_count = 64;
-        fri.domain.length = 1048576;
-        fri.expansion_factor = 8;
-        assert_eq!((14, 7), fri.num_rounds());
+        let subgroup_order = 1024;
+        let expansion_factor = 4;
+        let colinearity_check_count = 6;
 
-        fri.colinearity_checks_count = 65;
-        fri.domain.length = 1048576;
-        fri.expansion_factor = 8;
-        assert_eq!((13, 15), fri.num_rounds());
+        for folding_factor in [4, 8] {
+            let fri: Fri<Hasher> = get_x_field_fri_test_object_with_folding_factor(
+                subgroup_order,
+                expansion_factor,
+                colinearity_check_count,
+                folding_factor,
+            );
+            let subgroup = fri.domain.omega.get_cyclic_group_elements(None);
 
-        fri.domain.length = 256;
-        fri.expansion_factor = 4;
-        fri.colinearity_checks_count = 17;
-        assert_eq!((3, 7), fri.num_rounds());
+            for n in &[1, 10, 50, 100] {
+                let points: Vec<XFieldElement> =
+                    subgroup.iter().map(|p| p.mod_pow_u32(*n).lift()).collect();
+                let mut proof_stream: ProofStream = ProofStream::default();
+                let ret = fri.prove(&points, &mut proof_stream).unwrap();
+                assert_eq!(colinearity_check_count, ret.len());
+                let verify_result = fri.verify(&mut proof_stream);
+                assert!(
+                    verify_result.is_ok(),
+                    "FRI with folding factor {} must accept a low-degree codeword: {:?}",
+                    folding_factor,
+                    verify_result
+                );
+            }
+
+            // Negative test
+            let too_high = subgroup_order as u32 / expansion_factor as u32;
+            let points: Vec<XFieldElement> = subgroup
+                .iter()
+                .map(|p| p.mod_pow_u32(too_high).lift())
+                .collect();
+            let mut proof_stream: ProofStream = ProofStream::default();
+            fri.prove(&points, &mut proof_stream).unwrap();
+            let verify_result = fri.verify(&mut proof_stream);
+            assert!(verify_result.is_err());
+        }
     }
 
     #[test]
-    fn fri_on_x_field_test() {
-        type Hasher = RescuePrimeRegular;
+    fn fri_proof_serialization_roundtrip_test() {
+        type Hasher = blake3::Hasher;
 
         let subgroup_order = 1024;
         let expansion_factor = 4;
         let colinearity_check_count = 6;
         let fri: Fri<Hasher> =
             get_x_field_fri_test_object(subgroup_order, expansion_factor, colinearity_check_count);
-        let mut proof_stream: ProofStream = ProofStream::default();
         let subgroup = fri.domain.omega.lift().get_cyclic_group_elements(None);
 
-        let ret = fri.prove(&subgroup, &mut proof_stream).unwrap();
-        assert_eq!(fri.colinearity_checks_count, ret.len());
-        assert_eq!(colinearity_check_count, ret.len());
-        let verify_result = fri.verify(&mut proof_stream);
-        assert!(verify_result.is_ok());
+        let mut proof_stream: ProofStream = ProofStream::default();
+        fri.prove(&subgroup, &mut proof_stream).unwrap();
+
+        // Extract the structured proof and check its shape
+        proof_stream.set_index(0);
+        let fri_proof: FriProof = fri.extract_proof(&mut proof_stream).unwrap();
+        let num_rounds = fri.num_rounds().0 as usize;
+        assert_eq!(num_rounds + 1, fri_proof.merkle_roots.len());
+        assert_eq!(num_rounds + 1, fri_proof.query_rounds.len());
+        for query_round in fri_proof.query_rounds.iter() {
+            assert_eq!(colinearity_check_count, query_round.len());
+        }
+
+        // Roundtrip through bytes
+        let proof_bytes = fri_proof.to_bytes().unwrap();
+        let fri_proof_again = FriProof::from_bytes(&proof_bytes).unwrap();
+        assert_eq!(fri_proof, fri_proof_again);
+
+        // The reconstructed proof stream must be byte-identical and verify
+        let mut reconstructed_proof_stream = fri_proof_again.to_proof_stream().unwrap();
+        assert_eq!(
+            proof_stream.serialize(),
+            reconstructed_proof_stream.serialize()
+        );
+        assert!(fri.verify(&mut reconstructed_proof_stream).is_ok());
     }
 
     #[test]
@@ -712,6 +4710,23 @@ mod fri_tests {
         expansion_factor: usize,
         colinearity_checks: usize,
     ) -> Fri<H>
+    where
+        H: AlgebraicHasher + Sized,
+    {
+        get_x_field_fri_test_object_with_folding_factor(
+            subgroup_order,
+            expansion_factor,
+            colinearity_checks,
+            2,
+        )
+    }
+
+    fn get_x_field_fri_test_object_with_folding_factor<H>(
+        subgroup_order: u64,
+        expansion_factor: usize,
+        colinearity_checks: usize,
+        folding_factor: usize,
+    ) -> Fri<H>
     where
         H: AlgebraicHasher + Sized,
     {
@@ -720,15 +4735,17 @@ mod fri_tests {
         // The following offset was picked arbitrarily by copying the one found in
         // `get_b_field_fri_test_object`. It does not generate the full Z_p\{0}, but
         // we're not sure it needs to, Alan?
-        let offset: Option<BFieldElement> = Some(BFieldElement::new(7));
+        let offset = BFieldElement::new(7);
 
         let fri: Fri<H> = Fri::new(
-            offset.unwrap(),
+            offset,
             maybe_omega.unwrap(),
             subgroup_order as usize,
             expansion_factor,
             colinearity_checks,
-        );
+            folding_factor,
+        )
+        .unwrap();
         fri
     }
 }
@@ -1,13 +1,12 @@
 use super::traits::FiniteField;
+use crate::parallel::prelude::*;
 use crate::shared_math::polynomial::Polynomial;
 use crate::timing_reporter::TimingReporter;
 use crate::util_types::tree_m_ary::Node;
 use itertools::{izip, Itertools};
 use num_traits::{One, Zero};
-use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
 use std::cell::RefCell;
 use std::collections::HashMap;
-use std::convert::TryFrom;
 use std::error::Error;
 use std::fmt::{Debug, Display};
 use std::hash::Hash;
@@ -108,8 +107,7 @@ impl<T: Sized> Node<T> {
 
                         if polynomium_products.contains_key(&remaining_exponents) {
                             // TODO: Consider fast multiplication here
-                            intermediate_mul = intermediate_mul
-                                * polynomium_products[&remaining_exponents].clone();
+                            intermediate_mul *= polynomium_products[&remaining_exponents].clone();
                             break;
                         }
 
@@ -151,7 +149,7 @@ impl<T: Sized> Node<T> {
                         };
 
                         // TODO: Consider fast multiplication here
-                        intermediate_mul = intermediate_mul * mod_pow;
+                        intermediate_mul *= mod_pow;
                         intermediate_exponents[i] = *diff_exponent;
 
                         if polynomium_products.contains_key(&intermediate_exponents) {
@@ -475,8 +473,9 @@ impl<FF: FiniteField> MPolynomial<FF> {
                 // If just one of the `exponents_list[i][k]`s is bigger than the `exponents_list[index][k]` it cannot be made
                 // through multiplication but would have to be constructed through division. This would never be worthwhile
                 // so we simply discard that possibility.
-                '_inner: for k in 0..variable_count {
-                    if exponents_list[i][k] < exponents_list[index][k] {
+                '_inner: for (ei, ej) in exponents_list[i].iter().zip(exponents_list[index].iter())
+                {
+                    if ei < ej {
                         continue 'middle;
                     }
                 }
@@ -512,8 +511,7 @@ impl<FF: FiniteField> MPolynomial<FF> {
         // data: (diff, abs)
         let nodes: Vec<Rc<RefCell<Node<PolynomialEvaluationDataNode>>>> = exponents_list
             .into_iter()
-            .enumerate()
-            .map(|(_i, exponents)| {
+            .map(|exponents| {
                 // .map(|exponents| {
                 Rc::new(RefCell::new(Node {
                     children: vec![],
@@ -536,7 +534,7 @@ impl<FF: FiniteField> MPolynomial<FF> {
                 .borrow_mut()
                 .children
                 .push(nodes[end].clone());
-            let mut diff_exponents: Vec<u8> = nodes[end as usize]
+            let mut diff_exponents: Vec<u8> = nodes[end]
                 .borrow()
                 .data
                 .abs_exponents
@@ -550,12 +548,12 @@ impl<FF: FiniteField> MPolynomial<FF> {
                 diff_exponents[*x_point_index] = 0;
             }
             if diff_exponents.iter().sum::<u8>() == 1u8 {
-                nodes[end as usize].borrow_mut().data.single_point =
+                nodes[end].borrow_mut().data.single_point =
                     Some(diff_exponents.iter().position(|&x| x == 1).unwrap());
             }
-            nodes[end as usize].borrow_mut().data.diff_exponents = diff_exponents;
-            nodes[end as usize].borrow_mut().data.diff_sum = weight;
-            nodes[end as usize].borrow_mut().data.x_powers = x_power as usize;
+            nodes[end].borrow_mut().data.diff_exponents = diff_exponents;
+            nodes[end].borrow_mut().data.diff_sum = weight;
+            nodes[end].borrow_mut().data.x_powers = x_power as usize;
         }
         timer.elapsed("built nodes");
 
@@ -712,11 +710,18 @@ impl<FF: FiniteField> MPolynomial<FF> {
             "Dimensionality of multivariate polynomial and point must agree in evaluate"
         );
         let zero: FF = FF::zero();
+        #[cfg(feature = "parallel")]
         let acc = self
             .coefficients
             .par_iter()
             .map(|(k, v)| intermediate_results[k] * *v)
             .reduce(|| zero, |a, b| a + b);
+        #[cfg(not(feature = "parallel"))]
+        let acc = self
+            .coefficients
+            .iter()
+            .map(|(k, v)| intermediate_results[k] * *v)
+            .fold(zero, |a, b| a + b);
         acc
     }
 
@@ -733,11 +738,18 @@ impl<FF: FiniteField> MPolynomial<FF> {
             point.len(),
             "Dimensionality of multivariate polynomial and point must agree in evaluate_symbolic"
         );
+        #[cfg(feature = "parallel")]
         let acc = self
             .coefficients
             .par_iter()
             .map(|(k, v)| exponents_memoization[k].scalar_mul(*v))
             .reduce(Polynomial::zero, |a, b| a + b);
+        #[cfg(not(feature = "parallel"))]
+        let acc = self
+            .coefficients
+            .iter()
+            .map(|(k, v)| exponents_memoization[k].scalar_mul(*v))
+            .fold(Polynomial::zero(), |a, b| a + b);
 
         acc
     }
@@ -1114,11 +1126,7 @@ impl<FF: FiniteField> MPolynomial<FF> {
                 continue;
             }
 
-            let signed_exponents = exponents.iter().map(|e| {
-                let res = i64::try_from(*e);
-                assert!(res.is_ok());
-                res.unwrap()
-            });
+            let signed_exponents = exponents.iter().map(|e| i64::from(*e));
 
             let term_degree_bound = max_degrees
                 .iter()
@@ -1627,7 +1635,7 @@ mod test_mpolynomials {
         let mut precalculated_intermediate_results: HashMap<Vec<u8>, Polynomial<BFieldElement>> =
             HashMap::new();
         let precalculation_result = MPolynomial::precalculate_symbolic_exponents(
-            &[xyz_m.clone()],
+            std::slice::from_ref(&xyz_m),
             &[x.clone(), x.clone(), x.clone()],
             &mut precalculated_intermediate_results,
         );
@@ -1928,7 +1936,7 @@ mod test_mpolynomials {
             BFieldElement::new(6),
             BFieldElement::new(648),
         ];
-        for (i, k) in vec![vec![0, 0], vec![0, 1], vec![0, 2], vec![1, 1], vec![3, 4]]
+        for (i, k) in [vec![0, 0], vec![0, 1], vec![0, 2], vec![1, 1], vec![3, 4]]
             .iter()
             .enumerate()
         {
@@ -1968,18 +1976,8 @@ mod test_mpolynomials {
         let mut exponents: Vec<Vec<u8>> =
             MPolynomial::extract_exponents_list(&[a.clone(), b.clone()]).unwrap();
         exponents.sort();
-        let exponents_a: Vec<Vec<u8>> = a
-            .coefficients
-            .keys()
-            .into_iter()
-            .map(|x| x.to_owned())
-            .collect();
-        let exponents_b: Vec<Vec<u8>> = b
-            .coefficients
-            .keys()
-            .into_iter()
-            .map(|x| x.to_owned())
-            .collect();
+        let exponents_a: Vec<Vec<u8>> = a.coefficients.keys().map(|x| x.to_owned()).collect();
+        let exponents_b: Vec<Vec<u8>> = b.coefficients.keys().map(|x| x.to_owned()).collect();
         let mut expected_exponents_set: HashSet<Vec<u8>> = [exponents_a, exponents_b]
             .iter()
             .flat_map(|mpol| mpol.iter().map(|x| x.to_owned()))
@@ -2011,9 +2009,9 @@ mod test_mpolynomials {
     fn precalculate_exponents_memoization_test() {
         for _ in 0..30 {
             let variable_count = 6;
-            let a = gen_mpolynomial(variable_count, 12, 7, BFieldElement::MAX as u64);
-            let b = gen_mpolynomial(variable_count, 12, 7, BFieldElement::MAX as u64);
-            let c = gen_mpolynomial(variable_count, 12, 7, BFieldElement::MAX as u64);
+            let a = gen_mpolynomial(variable_count, 12, 7, BFieldElement::MAX);
+            let b = gen_mpolynomial(variable_count, 12, 7, BFieldElement::MAX);
+            let c = gen_mpolynomial(variable_count, 12, 7, BFieldElement::MAX);
             let mpolynomials = vec![a, b, c];
             let mut point = gen_upolynomials(variable_count - 1, 5, BFieldElement::MAX);
 
@@ -2042,7 +2040,7 @@ mod test_mpolynomials {
             for (k, v) in precalculated_intermediate_results.iter() {
                 let mut expected_result = Polynomial::from_constant(BFieldElement::one());
                 for (i, &exponent) in k.iter().enumerate() {
-                    expected_result = expected_result * point[i].mod_pow(exponent.into())
+                    expected_result *= point[i].mod_pow(exponent.into())
                 }
                 // println!("k = {:?}", k);
                 assert_eq!(&expected_result, v);
@@ -43,7 +43,6 @@ use super::{
 /// * `omega`^`k` ≠ 1 for all integers 1 ≤ k < n (making it a primitive `n`th root of unity)
 ///
 /// This transform is performed in-place.
-#[allow(clippy::many_single_char_names)]
 pub fn ntt<FF: FiniteField + MulAssign<BFieldElement>>(
     x: &mut [FF],
     omega: BFieldElement,
@@ -63,6 +62,34 @@ pub fn ntt<FF: FiniteField + MulAssign<BFieldElement>>(
     );
     debug_assert!(!omega.mod_pow_u32(n / 2).is_one());
 
+    ntt_with_twiddles(x, &ntt_twiddles(omega, log_2_of_n))
+}
+
+/// The per-stage twiddle bases used by [`ntt`] for a transform of length
+/// `n = 2^log_2_of_n`: entry `s` is `omega^(n / 2^(s+1))`, the generator of
+/// the butterfly twiddles of stage `s`. Precomputing them once lets repeated
+/// transforms over the same domain skip the `mod_pow` per stage; see
+/// [`ntt_with_twiddles`].
+pub fn ntt_twiddles(omega: BFieldElement, log_2_of_n: u32) -> Vec<BFieldElement> {
+    let n = 1u32 << log_2_of_n;
+    (0..log_2_of_n)
+        .map(|s| omega.mod_pow_u32(n >> (s + 1)))
+        .collect()
+}
+
+/// Like [`ntt`], but with the per-stage twiddle bases supplied by the
+/// caller, as computed by [`ntt_twiddles`].
+#[allow(clippy::many_single_char_names)]
+pub fn ntt_with_twiddles<FF: FiniteField + MulAssign<BFieldElement>>(
+    x: &mut [FF],
+    twiddles: &[BFieldElement],
+) {
+    let n = x.len() as u32;
+    let log_2_of_n = twiddles.len() as u32;
+
+    // `n` must be a power of 2
+    debug_assert_eq!(n, 1 << log_2_of_n, "2^log2(n) == n");
+
     for k in 0..n {
         let rk = bitreverse(k, log_2_of_n);
         if k < rk {
@@ -71,8 +98,7 @@ pub fn ntt<FF: FiniteField + MulAssign<BFieldElement>>(
     }
 
     let mut m = 1;
-    for _ in 0..log_2_of_n {
-        let w_m = omega.mod_pow_u32(n / (2 * m));
+    for w_m in twiddles {
         let mut k = 0;
         while k < n {
             let mut w = BFieldElement::one();
@@ -83,7 +109,7 @@ pub fn ntt<FF: FiniteField + MulAssign<BFieldElement>>(
                 tmp -= t;
                 x[(k + j + m) as usize] = tmp;
                 x[(k + j) as usize] += t;
-                w *= w_m;
+                w *= *w_m;
             }
 
             k += 2 * m;
@@ -164,6 +190,22 @@ mod fast_ntt_attempt_tests {
         }
     }
 
+    #[test]
+    fn ntt_with_twiddles_matches_ntt_test() {
+        for log_2_n in 1..10 {
+            let n = 1 << log_2_n;
+            let omega = BFieldElement::primitive_root_of_unity(n).unwrap();
+            let original: Vec<BFieldElement> = random_elements(n as usize);
+
+            let mut transformed = original.clone();
+            ntt::<BFieldElement>(&mut transformed, omega, log_2_n);
+
+            let mut with_twiddles = original.clone();
+            ntt_with_twiddles::<BFieldElement>(&mut with_twiddles, &ntt_twiddles(omega, log_2_n));
+            assert_eq!(transformed, with_twiddles);
+        }
+    }
+
     #[test]
     fn chu_ntt_x_field_prop_test() {
         for log_2_n in 1..10 {
@@ -1,3 +1,4 @@
+use crate::parallel::prelude::*;
 use crate::shared_math::ntt::{intt, ntt};
 use crate::shared_math::other::{log_2_floor, roundup_npo2};
 use crate::shared_math::traits::{FiniteField, ModPowU32};
@@ -9,12 +10,28 @@ use num_traits::{One, Zero};
 use std::convert::From;
 use std::fmt::{Debug, Display, Formatter};
 use std::hash::Hash;
-use std::ops::{Add, AddAssign, Div, Mul, MulAssign, Rem, Sub};
+use std::ops::{Add, AddAssign, Div, Mul, MulAssign, Rem, Sub, SubAssign};
 
 use super::b_field_element::BFieldElement;
 use super::other::{self, log_2_ceil};
 use super::traits::{Inverse, PrimitiveRootOfUnity};
 
+/// Below this domain size or polynomial degree, [`Polynomial::batch_evaluate`]
+/// evaluates every point directly instead of building a subproduct tree.
+const BATCH_EVALUATE_CUTOFF: usize = 32;
+
+/// Below this degree of the smaller operand, multiplication via `*` uses the
+/// schoolbook algorithm; between this and [`MULTIPLY_NTT_CUTOFF`], Karatsuba.
+pub const MULTIPLY_KARATSUBA_CUTOFF: usize = 32;
+
+/// From this degree of the smaller operand on, multiplication via `*` uses
+/// the NTT.
+pub const MULTIPLY_NTT_CUTOFF: usize = 256;
+
+/// Below this degree, [`Polynomial::fast_gcd`] and [`Polynomial::fast_xgcd`]
+/// run the plain Euclidean algorithm instead of recursing via half-GCD.
+const GCD_EUCLID_CUTOFF: isize = 64;
+
 fn degree_raw<T: Add + Div + Mul + Sub + Display + Zero>(coefficients: &[T]) -> isize {
     let mut deg = coefficients.len() as isize - 1;
     while deg >= 0 && coefficients[deg as usize].is_zero() {
@@ -308,7 +325,7 @@ where
 
         let mut hadamard_product: Vec<FF> = rhs_coefficients
             .into_iter()
-            .zip(lhs_coefficients.into_iter())
+            .zip(lhs_coefficients)
             .map(|(r, l)| r * l)
             .collect();
 
@@ -397,6 +414,513 @@ where
         left
     }
 
+    /// Evaluate the polynomial on an arbitrary domain of points.
+    ///
+    /// Large inputs build the subproduct tree of the domain's monic linear
+    /// factors once and reduce the polynomial down the tree, for
+    /// `O(n log^2 n)` field operations -- unlike [`fast_evaluate`], no
+    /// primitive root needs to be supplied and the recursion's zerofiers
+    /// are not recomputed. Small domains and low degrees fall back to
+    /// evaluating every point directly, in parallel.
+    ///
+    /// [`fast_evaluate`]: Self::fast_evaluate
+    pub fn batch_evaluate(&self, domain: &[FF]) -> Vec<FF> {
+        if domain.len() < BATCH_EVALUATE_CUTOFF || self.degree() < BATCH_EVALUATE_CUTOFF as isize {
+            return domain
+                .par_iter()
+                .map(|point| self.evaluate(point))
+                .collect();
+        }
+
+        let tree = Self::subproduct_tree(domain);
+        self.evaluate_with_subproduct_tree(&tree)
+    }
+
+    /// A root order large enough for the largest product over a domain of
+    /// the given size, together with a matching primitive root of unity.
+    fn subproduct_root(domain_length: usize) -> (BFieldElement, usize) {
+        let root_order = (2 * domain_length).next_power_of_two();
+        let primitive_root = BFieldElement::primitive_root_of_unity(root_order as u64).unwrap();
+        (primitive_root, root_order)
+    }
+
+    /// Build the subproduct tree of a domain bottom-up: the leaves are the
+    /// monic linear factors of the domain points, every inner node is the
+    /// product of its two children, an odd node is carried up unchanged,
+    /// and the root is the zerofier of the whole domain.
+    fn subproduct_tree(domain: &[FF]) -> Vec<Vec<Self>> {
+        let (primitive_root, root_order) = Self::subproduct_root(domain.len());
+
+        let leaves: Vec<Self> = domain
+            .iter()
+            .map(|&point| Self {
+                coefficients: vec![-point, FF::one()],
+            })
+            .collect();
+        let mut tree: Vec<Vec<Self>> = vec![leaves];
+        while tree.last().unwrap().len() > 1 {
+            let children = tree.last().unwrap();
+            let parents: Vec<Self> = (0..children.len().div_ceil(2))
+                .into_par_iter()
+                .map(|i| match children.get(2 * i + 1) {
+                    Some(right) => {
+                        Self::fast_multiply(&children[2 * i], right, &primitive_root, root_order)
+                    }
+                    None => children[2 * i].clone(),
+                })
+                .collect();
+            tree.push(parents);
+        }
+        tree
+    }
+
+    /// Reduce the polynomial down a subproduct tree: the remainder modulo a
+    /// node determines all evaluations below it, and the remainder modulo a
+    /// leaf `x - point` is the evaluation at that point.
+    fn evaluate_with_subproduct_tree(&self, tree: &[Vec<Self>]) -> Vec<FF> {
+        let mut remainders = vec![self.clone() % tree.last().unwrap()[0].clone()];
+        for level in tree.iter().rev().skip(1) {
+            let reduced: Vec<Self> = level
+                .par_iter()
+                .enumerate()
+                .map(|(i, node)| remainders[i / 2].clone() % node.clone())
+                .collect();
+            remainders = reduced;
+        }
+
+        remainders
+            .into_iter()
+            .map(|remainder| {
+                remainder
+                    .coefficients
+                    .first()
+                    .copied()
+                    .unwrap_or_else(FF::zero)
+            })
+            .collect()
+    }
+
+    /// The vanishing polynomial of an arbitrary set of points, built with a
+    /// product tree instead of the `O(n^2)` coefficient updates of
+    /// [`zerofier`]. Unlike [`fast_zerofier`], no primitive root needs to
+    /// be supplied. Small domains fall back to the plain construction.
+    ///
+    /// [`zerofier`]: Self::zerofier
+    /// [`fast_zerofier`]: Self::fast_zerofier
+    pub fn batch_zerofier(domain: &[FF]) -> Self {
+        if domain.len() < BATCH_EVALUATE_CUTOFF {
+            return Self::zerofier(domain);
+        }
+        Self::subproduct_tree(domain).pop().unwrap().pop().unwrap()
+    }
+
+    /// Interpolate through the given points, sharing a single subproduct
+    /// tree between the zerofier, the barycentric weights, and the
+    /// combination step, for `O(n log^2 n)` field operations in total.
+    /// The domain points must be distinct; small inputs fall back to
+    /// Lagrange interpolation.
+    pub fn batch_interpolate(domain: &[FF], values: &[FF]) -> Self {
+        assert_eq!(
+            domain.len(),
+            values.len(),
+            "The domain and values lists have to be of equal length."
+        );
+        if domain.len() < BATCH_EVALUATE_CUTOFF {
+            return Self::lagrange_interpolate(domain, values);
+        }
+
+        let tree = Self::subproduct_tree(domain);
+        let zerofier = &tree.last().unwrap()[0];
+
+        // The derivative of the zerofier, evaluated at a domain point, is
+        // the product of that point's differences to all other points: the
+        // inverse barycentric weight.
+        let mut derivative_coefficients = Vec::with_capacity(zerofier.coefficients.len() - 1);
+        let mut exponent = FF::zero();
+        for &coefficient in zerofier.coefficients.iter().skip(1) {
+            exponent += FF::one();
+            derivative_coefficients.push(coefficient * exponent);
+        }
+        let derivative = Self {
+            coefficients: derivative_coefficients,
+        };
+        let denominators = derivative.evaluate_with_subproduct_tree(&tree);
+
+        // Combine up the tree: every leaf holds its point's weighted value
+        // as a constant, and every parent cross-multiplies its children
+        // with the sibling zerofiers from the tree.
+        let (primitive_root, root_order) = Self::subproduct_root(domain.len());
+        let mut layer: Vec<Self> = values
+            .iter()
+            .zip(denominators)
+            .map(|(&value, denominator)| Self::from_constant(value / denominator))
+            .collect();
+        for nodes in tree.iter().take(tree.len() - 1) {
+            let parents: Vec<Self> = (0..nodes.len().div_ceil(2))
+                .into_par_iter()
+                .map(|i| match nodes.get(2 * i + 1) {
+                    Some(right_node) => {
+                        Self::fast_multiply(&layer[2 * i], right_node, &primitive_root, root_order)
+                            + Self::fast_multiply(
+                                &layer[2 * i + 1],
+                                &nodes[2 * i],
+                                &primitive_root,
+                                root_order,
+                            )
+                    }
+                    None => layer[2 * i].clone(),
+                })
+                .collect();
+            layer = parents;
+        }
+        layer.pop().unwrap()
+    }
+
+    /// Multiply, picking the algorithm by the degree of the smaller
+    /// operand: schoolbook below [`MULTIPLY_KARATSUBA_CUTOFF`], Karatsuba
+    /// up to [`MULTIPLY_NTT_CUTOFF`], NTT-based beyond that -- callers
+    /// neither choose a strategy nor supply a root of unity. The `*`
+    /// operator routes through this function.
+    pub fn adaptive_multiply(&self, other: &Self) -> Self {
+        let smaller_degree = self.degree().min(other.degree());
+        if smaller_degree < MULTIPLY_KARATSUBA_CUTOFF as isize {
+            return self.clone().multiply(other.clone());
+        }
+        if smaller_degree < MULTIPLY_NTT_CUTOFF as isize {
+            return Self {
+                coefficients: Self::karatsuba_multiply(
+                    &self.coefficients[..=self.degree() as usize],
+                    &other.coefficients[..=other.degree() as usize],
+                ),
+            };
+        }
+        Self::multiply_via_ntt(self, other)
+    }
+
+    /// Karatsuba multiplication of coefficient slices: split both operands
+    /// in the middle and trade one of the four half-size products for a few
+    /// additions, recursing until the schoolbook base case wins.
+    fn karatsuba_multiply(lhs: &[FF], rhs: &[FF]) -> Vec<FF> {
+        if lhs.len().min(rhs.len()) <= MULTIPLY_KARATSUBA_CUTOFF {
+            let mut product = vec![FF::zero(); lhs.len() + rhs.len() - 1];
+            for (i, &lhs_coefficient) in lhs.iter().enumerate() {
+                for (j, &rhs_coefficient) in rhs.iter().enumerate() {
+                    product[i + j] += lhs_coefficient * rhs_coefficient;
+                }
+            }
+            return product;
+        }
+
+        let half = lhs.len().max(rhs.len()).div_ceil(2);
+        let (lhs_low, lhs_high) = lhs.split_at(half.min(lhs.len()));
+        let (rhs_low, rhs_high) = rhs.split_at(half.min(rhs.len()));
+
+        let low = Self::karatsuba_multiply(lhs_low, rhs_low);
+        let high = match lhs_high.is_empty() || rhs_high.is_empty() {
+            true => vec![],
+            false => Self::karatsuba_multiply(lhs_high, rhs_high),
+        };
+
+        // (lhs_low + lhs_high)(rhs_low + rhs_high) - low - high
+        let mut middle = Self::karatsuba_multiply(
+            &Self::halves_sum(lhs_low, lhs_high),
+            &Self::halves_sum(rhs_low, rhs_high),
+        );
+        for (coefficient, &low_coefficient) in middle.iter_mut().zip(low.iter()) {
+            *coefficient -= low_coefficient;
+        }
+        for (coefficient, &high_coefficient) in middle.iter_mut().zip(high.iter()) {
+            *coefficient -= high_coefficient;
+        }
+
+        // The parts' buffers may carry zero tails past the product degree
+        // when the operand lengths are uneven; the zips drop them.
+        let mut product = vec![FF::zero(); lhs.len() + rhs.len() - 1];
+        for (coefficient, &low_coefficient) in product.iter_mut().zip(low.iter()) {
+            *coefficient += low_coefficient;
+        }
+        for (coefficient, &middle_coefficient) in product[half..].iter_mut().zip(middle.iter()) {
+            *coefficient += middle_coefficient;
+        }
+        for (coefficient, &high_coefficient) in product[2 * half..].iter_mut().zip(high.iter()) {
+            *coefficient += high_coefficient;
+        }
+        product
+    }
+
+    /// The coefficient-wise sum of an operand's low and high half.
+    fn halves_sum(low: &[FF], high: &[FF]) -> Vec<FF> {
+        let mut sum = low.to_vec();
+        for (coefficient, &high_coefficient) in sum.iter_mut().zip(high.iter()) {
+            *coefficient += high_coefficient;
+        }
+        sum
+    }
+
+    /// Multiply via NTT, deriving a primitive root of unity of sufficient
+    /// order from the operands' degrees.
+    fn multiply_via_ntt(lhs: &Self, rhs: &Self) -> Self {
+        if lhs.is_zero() || rhs.is_zero() {
+            return Self::zero();
+        }
+        let degree = (lhs.degree() + rhs.degree()) as usize;
+        let root_order = (degree + 1).next_power_of_two();
+        let primitive_root = BFieldElement::primitive_root_of_unity(root_order as u64).unwrap();
+        Self::fast_multiply(lhs, rhs, &primitive_root, root_order)
+    }
+
+    /// The polynomial modulo `x^length`: its first `length` coefficients.
+    fn truncated(&self, length: usize) -> Self {
+        Self {
+            coefficients: self.coefficients.iter().take(length).copied().collect(),
+        }
+    }
+
+    /// The product modulo `x^n`. The operands are truncated before
+    /// multiplying, so only the coefficients that can reach the result are
+    /// ever touched -- the building block for power-series arithmetic,
+    /// where everything beyond the working precision is discarded anyway.
+    pub fn mul_mod_xn(&self, other: &Self, n: usize) -> Self {
+        self.truncated(n)
+            .adaptive_multiply(&other.truncated(n))
+            .truncated(n)
+    }
+
+    /// The power-series inverse modulo `x^n`, by Newton iteration: every
+    /// step `h -> h(2 - fh)` doubles the number of correct coefficients,
+    /// starting from the inverted constant term -- which must be nonzero.
+    pub fn inverse_mod_xn(&self, n: usize) -> Self {
+        let constant_term = self.coefficients.first().copied().unwrap_or_else(FF::zero);
+        assert!(
+            !constant_term.is_zero(),
+            "Power series inverse requires an invertible constant term"
+        );
+        if n == 0 {
+            return Self::zero();
+        }
+
+        let two = Self::from_constant(FF::one() + FF::one());
+        let mut reciprocal = Self::from_constant(FF::one() / constant_term);
+        let mut precision = 1;
+        while precision < n {
+            precision *= 2;
+            let correction = two.clone() - self.mul_mod_xn(&reciprocal, precision);
+            reciprocal = reciprocal.mul_mod_xn(&correction, precision);
+        }
+        reciprocal.truncated(n)
+    }
+
+    /// The power modulo `x^n`, by square-and-multiply with truncation at
+    /// every step, so the cost depends on `n` rather than on the full
+    /// degree of the power.
+    pub fn pow_mod_xn(&self, exponent: u64, n: usize) -> Self {
+        if n == 0 {
+            return Self::zero();
+        }
+        let mut acc = Self::from_constant(FF::one());
+        for bit_index in (0..u64::BITS - exponent.leading_zeros()).rev() {
+            acc = acc.mul_mod_xn(&acc, n);
+            if exponent & (1 << bit_index) != 0 {
+                acc = acc.mul_mod_xn(self, n);
+            }
+        }
+        acc
+    }
+
+    /// The Taylor shift `p(x + offset)`, computed in `O(n log n)` field
+    /// operations as a single convolution: the factorial-weighted
+    /// coefficient sequence, reversed, is multiplied with the exponential
+    /// series of the offset, and the result is unweighted again. The
+    /// factorials never exceed the polynomial degree and hence stay
+    /// invertible, with one batch inversion covering all of them. This
+    /// replaces the `O(n^2)` re-expansion route for additive shifts;
+    /// multiplicative coset shifts remain the domain of [`scale`].
+    ///
+    /// [`scale`]: Self::scale
+    pub fn taylor_shift(&self, offset: FF) -> Self {
+        let degree = self.degree();
+        if degree < 1 || offset.is_zero() {
+            return self.clone();
+        }
+        let length = degree as usize + 1;
+
+        // factorials 0!, 1!, ..., degree! and their inverses
+        let mut factorials = Vec::with_capacity(length);
+        let mut factorial = FF::one();
+        let mut index = FF::zero();
+        factorials.push(factorial);
+        for _ in 1..length {
+            index += FF::one();
+            factorial *= index;
+            factorials.push(factorial);
+        }
+        let inverse_factorials = FF::batch_inversion(factorials.clone());
+
+        // the convolution of the reversed weighted coefficients with the
+        // exponential series of the offset realigns every binomial term
+        let weighted = Self {
+            coefficients: (0..length)
+                .rev()
+                .map(|j| self.coefficients[j] * factorials[j])
+                .collect(),
+        };
+        let mut offset_power = FF::one();
+        let exponential = Self {
+            coefficients: inverse_factorials
+                .iter()
+                .map(|&inverse_factorial| {
+                    let term = offset_power * inverse_factorial;
+                    offset_power *= offset;
+                    term
+                })
+                .collect(),
+        };
+        let mut convolution = weighted.mul_mod_xn(&exponential, length).coefficients;
+        convolution.resize(length, FF::zero());
+
+        Self {
+            coefficients: (0..length)
+                .map(|k| convolution[degree as usize - k] * inverse_factorials[k])
+                .collect(),
+        }
+    }
+
+    /// The polynomial raised to the power, by square-and-multiply over the
+    /// adaptive multiplication dispatch. As for [`mod_pow`], `0^0 = 1`.
+    ///
+    /// [`mod_pow`]: Self::mod_pow
+    pub fn pow(&self, exponent: u64) -> Self {
+        if exponent == 0 {
+            return Self::from_constant(FF::one());
+        }
+        if self.is_zero() {
+            return Self::zero();
+        }
+        let mut acc = Self::from_constant(FF::one());
+        for bit_index in (0..u64::BITS - exponent.leading_zeros()).rev() {
+            acc = acc.adaptive_multiply(&acc);
+            if exponent & (1 << bit_index) != 0 {
+                acc = acc.adaptive_multiply(self);
+            }
+        }
+        acc
+    }
+
+    /// The composition `self(other)`, by divide-and-conquer on the outer
+    /// coefficients: the outer polynomial splits at a power of two, both
+    /// halves compose recursively, and the halves recombine with a
+    /// repeatedly squared power of the inner polynomial. With the NTT
+    /// multiplication backend the cost is softly linear in the size of the
+    /// result, where the naive Horner rewrite is quadratic.
+    pub fn compose(&self, other: &Self) -> Self {
+        let length = self.coefficients.len();
+        if length <= 2 {
+            return Self::compose_directly(&self.coefficients, other);
+        }
+
+        // the powers `other^1, other^2, other^4, ...` up to the top split
+        let mut powers = vec![other.clone()];
+        while (1 << powers.len()) < length {
+            let last = powers.last().unwrap();
+            powers.push(last.adaptive_multiply(last));
+        }
+
+        Self::compose_split(&self.coefficients, &powers)
+    }
+
+    /// Recursive work horse of [`compose`]: the coefficient slice splits at
+    /// the largest power of two below its length, so the required power of
+    /// the inner polynomial is always among the precomputed squarings.
+    ///
+    /// [`compose`]: Self::compose
+    fn compose_split(coefficients: &[FF], powers: &[Self]) -> Self {
+        if coefficients.len() <= 2 {
+            return Self::compose_directly(coefficients, &powers[0]);
+        }
+
+        let split = 1 << (coefficients.len() - 1).ilog2();
+        let low = Self::compose_split(&coefficients[..split], powers);
+        let high = Self::compose_split(&coefficients[split..], powers);
+        let splitting_power = &powers[split.trailing_zeros() as usize];
+        low + high.adaptive_multiply(splitting_power)
+    }
+
+    /// Composition base case for constant and linear outer polynomials.
+    fn compose_directly(coefficients: &[FF], other: &Self) -> Self {
+        let mut composition = match coefficients.first() {
+            Some(&constant_term) => Self::from_constant(constant_term),
+            None => Self::zero(),
+        };
+        if let Some(&linear_term) = coefficients.get(1) {
+            composition += other.scalar_mul(linear_term);
+        }
+        composition
+    }
+
+    /// Divide with remainder via Newton iteration, in `O(n log n)` field
+    /// operations: the reciprocal power series of the reversed divisor is
+    /// computed to quotient precision by a quadratically convergent
+    /// iteration, and a single multiplication recovers the quotient.
+    /// Schoolbook [`divide`] is the bottleneck for constraint quotients of
+    /// degree around `2^20`; small inputs still take that path. The `Div`
+    /// and `Rem` operators route through this function.
+    ///
+    /// [`divide`]: Self::divide
+    pub fn fast_divide(&self, divisor: &Self) -> (Self, Self) {
+        let dividend_degree = self.degree();
+        let divisor_degree = divisor.degree();
+        if divisor_degree < 0 {
+            panic!(
+                "Cannot divide polynomial by zero. Got: ({:?})/({:?})",
+                self, divisor
+            );
+        }
+        if dividend_degree < divisor_degree {
+            return (Self::zero(), self.clone());
+        }
+        let quotient_length = (dividend_degree - divisor_degree + 1) as usize;
+        if divisor_degree < BATCH_EVALUATE_CUTOFF as isize
+            || quotient_length < BATCH_EVALUATE_CUTOFF
+        {
+            return self.divide(divisor.clone());
+        }
+
+        // The reciprocal power series of the reversed divisor, to quotient
+        // precision; the reversal makes its leading coefficient the
+        // constant term the Newton iteration starts from.
+        let reversed_divisor = Self {
+            coefficients: divisor.coefficients[..=divisor_degree as usize]
+                .iter()
+                .rev()
+                .copied()
+                .collect(),
+        };
+        let reciprocal = reversed_divisor.inverse_mod_xn(quotient_length);
+
+        // The quotient is the reversal of `rev(f) * h mod x^quotient_length`;
+        // trailing zeros of the product are significant under reversal.
+        let reversed_dividend = Self {
+            coefficients: self.coefficients[..=dividend_degree as usize]
+                .iter()
+                .rev()
+                .copied()
+                .collect(),
+        };
+        let mut quotient_coefficients =
+            Self::multiply_via_ntt(&reversed_dividend, &reciprocal.truncated(quotient_length))
+                .truncated(quotient_length)
+                .coefficients;
+        quotient_coefficients.resize(quotient_length, FF::zero());
+        quotient_coefficients.reverse();
+        let quotient = Self {
+            coefficients: quotient_coefficients,
+        };
+
+        let mut remainder = self.clone() - Self::multiply_via_ntt(&quotient, divisor);
+        remainder.normalize();
+        (quotient, remainder)
+    }
+
     pub fn fast_interpolate(
         domain: &[FF],
         values: &[FF],
@@ -640,13 +1164,11 @@ impl<FF: FiniteField> Polynomial<FF> {
         // to zero in all domain points, also prod_i (X - d_i).
         let mut zerofier_array = vec![zero; domain.len() + 1];
         zerofier_array[0] = one;
-        let mut num_coeffs = 1;
-        for &d in domain.iter() {
-            for k in (1..num_coeffs + 1).rev() {
+        for (num_coeffs, &d) in domain.iter().enumerate() {
+            for k in (1..num_coeffs + 2).rev() {
                 zerofier_array[k] = zerofier_array[k - 1] - d * zerofier_array[k];
             }
             zerofier_array[0] = -d * zerofier_array[0];
-            num_coeffs += 1;
         }
 
         // in each iteration of this loop, we accumulate into the sum
@@ -706,6 +1228,53 @@ impl<FF: FiniteField> Polynomial<FF> {
         p2_y_times_dx / dx
     }
 
+    /// Evaluate in `x` the unique interpolant through the given points,
+    /// without materializing the interpolant: the barycentric formula needs
+    /// only the weights and the distances of `x` to the nodes, all inverted
+    /// in one batch. This is the higher-arity generalization of
+    /// [`get_colinear_y`], *e.g.* for 4- or 8-way FRI folding checks.
+    ///
+    /// [`get_colinear_y`]: Self::get_colinear_y
+    pub fn evaluate_from_points(points: &[(FF, FF)], x: FF) -> FF {
+        assert!(!points.is_empty(), "Cannot evaluate through zero points");
+        debug_assert!(
+            has_unique_elements(points.iter().map(|point| point.0)),
+            "Nodes must be unique"
+        );
+
+        // at a node, the interpolant takes the tabulated value
+        if let Some(&(_, y)) = points.iter().find(|&&(node, _)| node == x) {
+            return y;
+        }
+
+        // barycentric weights and node distances, inverted in one batch
+        let mut to_invert: Vec<FF> = points
+            .iter()
+            .map(|&(node, _)| {
+                points
+                    .iter()
+                    .filter(|&&(other_node, _)| other_node != node)
+                    .fold(FF::one(), |product, &(other_node, _)| {
+                        product * (node - other_node)
+                    })
+            })
+            .collect();
+        to_invert.extend(points.iter().map(|&(node, _)| x - node));
+        let inverted = FF::batch_inversion(to_invert);
+        let (weights, distance_inverses) = inverted.split_at(points.len());
+
+        let mut numerator = FF::zero();
+        let mut denominator = FF::zero();
+        for ((&(_, y), &weight), &distance_inverse) in
+            points.iter().zip(weights).zip(distance_inverses)
+        {
+            let term = weight * distance_inverse;
+            numerator += term * y;
+            denominator += term;
+        }
+        numerator / denominator
+    }
+
     pub fn zerofier(domain: &[FF]) -> Self {
         if domain.is_empty() {
             return Self {
@@ -714,13 +1283,11 @@ impl<FF: FiniteField> Polynomial<FF> {
         }
         let mut zerofier_array = vec![FF::zero(); domain.len() + 1];
         zerofier_array[0] = FF::one();
-        let mut num_coeffs = 1;
-        for &d in domain.iter() {
-            for k in (1..num_coeffs + 1).rev() {
+        for (num_coeffs, &d) in domain.iter().enumerate() {
+            for k in (1..num_coeffs + 2).rev() {
                 zerofier_array[k] = zerofier_array[k - 1] - d * zerofier_array[k];
             }
             zerofier_array[0] = -d * zerofier_array[0];
-            num_coeffs += 1;
         }
         Self {
             coefficients: zerofier_array,
@@ -846,6 +1413,30 @@ impl<FF: FiniteField> Polynomial<FF> {
         }
     }
 
+    /// Multiply into a caller-supplied output polynomial, reusing its
+    /// coefficient buffer instead of allocating a fresh one. Inner prover
+    /// loops that multiply in place should hold one scratch polynomial and
+    /// pass it here repeatedly; see also the operator-assign
+    /// implementations, which do exactly that under the hood.
+    pub fn mul_into(&self, other: &Self, out: &mut Self) {
+        let degree_lhs = self.degree();
+        let degree_rhs = other.degree();
+
+        out.coefficients.clear();
+        if degree_lhs < 0 || degree_rhs < 0 {
+            return;
+        }
+
+        out.coefficients
+            .resize(degree_lhs as usize + degree_rhs as usize + 1, FF::zero());
+        for i in 0..=degree_lhs as usize {
+            for j in 0..=degree_rhs as usize {
+                let mul: FF = self.coefficients[i] * other.coefficients[j];
+                out.coefficients[i + j] += mul;
+            }
+        }
+    }
+
     // Multiply a polynomial with itself `pow` times
     #[must_use]
     pub fn mod_pow(&self, pow: BigInt) -> Self {
@@ -867,7 +1458,7 @@ impl<FF: FiniteField> Polynomial<FF> {
             let set: bool =
                 !(pow.clone() & Into::<BigInt>::into(1u128 << (bit_length - 1 - i))).is_zero();
             if set {
-                acc = acc * self.clone();
+                acc *= self.clone();
             }
         }
 
@@ -973,7 +1564,7 @@ impl<FF: FiniteField> Div for Polynomial<FF> {
     type Output = Self;
 
     fn div(self, other: Self) -> Self {
-        let (quotient, _): (Self, Self) = self.divide(other);
+        let (quotient, _): (Self, Self) = self.fast_divide(&other);
         quotient
     }
 }
@@ -982,7 +1573,7 @@ impl<FF: FiniteField> Rem for Polynomial<FF> {
     type Output = Self;
 
     fn rem(self, other: Self) -> Self {
-        let (_, remainder): (Self, Self) = self.divide(other);
+        let (_, remainder): (Self, Self) = self.fast_divide(&other);
         remainder
     }
 }
@@ -1009,7 +1600,7 @@ impl<FF: FiniteField> Add for Polynomial<FF> {
         let summed: Vec<FF> = self
             .coefficients
             .into_iter()
-            .zip_longest(other.coefficients.into_iter())
+            .zip_longest(other.coefficients)
             .map(|a: itertools::EitherOrBoth<FF, FF>| match a {
                 Both(l, r) => l.to_owned() + r.to_owned(),
                 Left(l) => l.to_owned(),
@@ -1045,7 +1636,7 @@ impl<FF: FiniteField> Sub for Polynomial<FF> {
         let summed: Vec<FF> = self
             .coefficients
             .into_iter()
-            .zip_longest(other.coefficients.into_iter())
+            .zip_longest(other.coefficients)
             .map(|a: itertools::EitherOrBoth<FF, FF>| match a {
                 Both(l, r) => l - r,
                 Left(l) => l,
@@ -1081,6 +1672,166 @@ impl<FF: FiniteField> Polynomial<FF> {
             b_factor.scalar_mul(scale),
         )
     }
+
+    /// The greatest common divisor as a monic polynomial, in subquadratic
+    /// time via the half-GCD recursion. The zero polynomial is returned when
+    /// both inputs are zero.
+    pub fn fast_gcd(x: Polynomial<FF>, y: Polynomial<FF>) -> Polynomial<FF> {
+        let (mut a, mut b) = (x, y);
+        while !b.is_zero() {
+            if a.degree() < GCD_EUCLID_CUTOFF {
+                let remainder = a % b.clone();
+                (a, b) = (b, remainder);
+                continue;
+            }
+
+            let reduction = Self::half_gcd(&a, &b);
+            (a, b) = Self::matrix_apply(&reduction, &a, &b);
+            if !b.is_zero() {
+                // the half-GCD only reduces to half the degree of `a`; one
+                // genuine division step lets the next round bite again
+                let (_, remainder) = a.fast_divide(&b);
+                (a, b) = (b, remainder);
+            }
+        }
+
+        match a.leading_coefficient() {
+            Some(lc) => a.scalar_mul(lc.inverse()),
+            None => Self::zero(),
+        }
+    }
+
+    /// Extended Euclidean algorithm in subquadratic time, via the same
+    /// half-GCD recursion as [`fast_gcd`] but with the unimodular matrix of
+    /// Euclid steps accumulated so the Bézout coefficients fall out of its
+    /// first row. Agrees with [`xgcd`] on all inputs where the latter is
+    /// defined.
+    ///
+    /// [`fast_gcd`]: Self::fast_gcd
+    /// [`xgcd`]: Self::xgcd
+    pub fn fast_xgcd(
+        x: Polynomial<FF>,
+        y: Polynomial<FF>,
+    ) -> (Polynomial<FF>, Polynomial<FF>, Polynomial<FF>) {
+        let mut matrix = Self::matrix_identity();
+        let (mut a, mut b) = (x, y);
+        while !b.is_zero() {
+            if a.degree() < GCD_EUCLID_CUTOFF {
+                let (quotient, remainder) = a.fast_divide(&b);
+                Self::matrix_push_quotient(&mut matrix, &quotient);
+                (a, b) = (b, remainder);
+                continue;
+            }
+
+            let reduction = Self::half_gcd(&a, &b);
+            (a, b) = Self::matrix_apply(&reduction, &a, &b);
+            matrix = Self::matrix_multiply(&reduction, &matrix);
+            if !b.is_zero() {
+                let (quotient, remainder) = a.fast_divide(&b);
+                Self::matrix_push_quotient(&mut matrix, &quotient);
+                (a, b) = (b, remainder);
+            }
+        }
+
+        // Same normalization as in `xgcd`: the gcd is monic, and the first
+        // matrix row holds the Bézout coefficients of the unnormalized gcd.
+        let lc = a.leading_coefficient().unwrap();
+        let scale = lc.inverse();
+        let [[a_factor, b_factor], _] = matrix;
+        (
+            a.scalar_mul(scale),
+            a_factor.scalar_mul(scale),
+            b_factor.scalar_mul(scale),
+        )
+    }
+
+    /// A unimodular matrix of Euclid steps on `(a, b)` that reduces the
+    /// degree of `b` below half the degree of `a`.
+    ///
+    /// The recursion computes the leading quotients from the top halves of
+    /// the coefficient vectors only, which is what makes the overall gcd
+    /// subquadratic. Since every factor is a genuine quotient matrix the
+    /// result preserves gcds unconditionally; the trailing Euclid loop merely
+    /// enforces the degree bound in the rare cases where the truncated
+    /// quotients overshoot.
+    fn half_gcd(a: &Self, b: &Self) -> [[Self; 2]; 2] {
+        let half_degree = (a.degree().max(0) as usize).div_ceil(2);
+        let mut matrix = Self::matrix_identity();
+        if b.degree() < half_degree as isize {
+            return matrix;
+        }
+
+        let (mut a1, mut b1) = (a.clone(), b.clone());
+        if a.degree() >= GCD_EUCLID_CUTOFF {
+            // reduce the top halves, then replay the steps on the full pair
+            let reduction =
+                Self::half_gcd(&a.shifted_down(half_degree), &b.shifted_down(half_degree));
+            (a1, b1) = Self::matrix_apply(&reduction, &a1, &b1);
+            matrix = reduction;
+
+            if b1.degree() >= half_degree as isize {
+                let (quotient, remainder) = a1.fast_divide(&b1);
+                Self::matrix_push_quotient(&mut matrix, &quotient);
+                (a1, b1) = (b1, remainder);
+            }
+
+            if b1.degree() >= half_degree as isize {
+                // the remaining pair is half-sized; recurse on its top part
+                let shift = (2 * half_degree).saturating_sub(a1.degree() as usize);
+                let tail_reduction =
+                    Self::half_gcd(&a1.shifted_down(shift), &b1.shifted_down(shift));
+                (a1, b1) = Self::matrix_apply(&tail_reduction, &a1, &b1);
+                matrix = Self::matrix_multiply(&tail_reduction, &matrix);
+            }
+        }
+
+        while b1.degree() >= half_degree as isize {
+            let (quotient, remainder) = a1.fast_divide(&b1);
+            Self::matrix_push_quotient(&mut matrix, &quotient);
+            (a1, b1) = (b1, remainder);
+        }
+
+        matrix
+    }
+
+    /// The polynomial divided by `x^power`, discarding the remainder.
+    fn shifted_down(&self, power: usize) -> Self {
+        Self {
+            coefficients: self.coefficients.get(power..).unwrap_or(&[]).to_vec(),
+        }
+    }
+
+    fn matrix_identity() -> [[Self; 2]; 2] {
+        [[Self::one(), Self::zero()], [Self::zero(), Self::one()]]
+    }
+
+    /// Prepend one Euclid step with the given quotient to the matrix: the
+    /// rows swap, and the quotient times the old bottom row is subtracted
+    /// from the old top row.
+    fn matrix_push_quotient(matrix: &mut [[Self; 2]; 2], quotient: &Self) {
+        let new_bottom = [
+            matrix[0][0].clone() - quotient.clone() * matrix[1][0].clone(),
+            matrix[0][1].clone() - quotient.clone() * matrix[1][1].clone(),
+        ];
+        matrix.swap(0, 1);
+        matrix[1] = new_bottom;
+    }
+
+    fn matrix_multiply(lhs: &[[Self; 2]; 2], rhs: &[[Self; 2]; 2]) -> [[Self; 2]; 2] {
+        [0, 1].map(|row| {
+            [0, 1].map(|column| {
+                lhs[row][0].clone() * rhs[0][column].clone()
+                    + lhs[row][1].clone() * rhs[1][column].clone()
+            })
+        })
+    }
+
+    fn matrix_apply(matrix: &[[Self; 2]; 2], a: &Self, b: &Self) -> (Self, Self) {
+        (
+            matrix[0][0].clone() * a.clone() + matrix[0][1].clone() * b.clone(),
+            matrix[1][0].clone() * a.clone() + matrix[1][1].clone() * b.clone(),
+        )
+    }
 }
 
 impl<FF: FiniteField> Polynomial<FF> {
@@ -1100,13 +1851,87 @@ impl<FF: FiniteField> Polynomial<FF> {
 
         Self { coefficients }
     }
+
+    /// The formal antiderivative with constant term zero; the left inverse
+    /// of [`formal_derivative`]. The divisors `1, ..., n` are inverted in
+    /// one batch.
+    ///
+    /// [`formal_derivative`]: Self::formal_derivative
+    pub fn formal_antiderivative(&self) -> Self {
+        if self.is_zero() {
+            return Self::zero();
+        }
+
+        let mut divisors = Vec::with_capacity(self.coefficients.len());
+        let mut divisor = FF::zero();
+        for _ in 0..self.coefficients.len() {
+            divisor += FF::one();
+            divisors.push(divisor);
+        }
+        let divisor_inverses = FF::batch_inversion(divisors);
+
+        let mut coefficients = vec![FF::zero()];
+        coefficients.extend(
+            self.coefficients
+                .iter()
+                .zip(divisor_inverses)
+                .map(|(&coefficient, divisor_inverse)| coefficient * divisor_inverse),
+        );
+        Self { coefficients }
+    }
 }
 
 impl<FF: FiniteField> Mul for Polynomial<FF> {
     type Output = Self;
 
     fn mul(self, other: Self) -> Self {
-        Self::multiply(self, other)
+        self.adaptive_multiply(&other)
+    }
+}
+
+impl<FF: FiniteField> AddAssign<&Polynomial<FF>> for Polynomial<FF> {
+    fn add_assign(&mut self, rhs: &Self) {
+        if self.coefficients.len() < rhs.coefficients.len() {
+            self.coefficients.resize(rhs.coefficients.len(), FF::zero());
+        }
+        for (coefficient, &rhs_coefficient) in
+            self.coefficients.iter_mut().zip(rhs.coefficients.iter())
+        {
+            *coefficient += rhs_coefficient;
+        }
+    }
+}
+
+impl<FF: FiniteField> SubAssign<&Polynomial<FF>> for Polynomial<FF> {
+    fn sub_assign(&mut self, rhs: &Self) {
+        if self.coefficients.len() < rhs.coefficients.len() {
+            self.coefficients.resize(rhs.coefficients.len(), FF::zero());
+        }
+        for (coefficient, &rhs_coefficient) in
+            self.coefficients.iter_mut().zip(rhs.coefficients.iter())
+        {
+            *coefficient -= rhs_coefficient;
+        }
+    }
+}
+
+impl<FF: FiniteField> SubAssign for Polynomial<FF> {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self -= &rhs;
+    }
+}
+
+impl<FF: FiniteField> MulAssign<&Polynomial<FF>> for Polynomial<FF> {
+    fn mul_assign(&mut self, rhs: &Self) {
+        let mut product = Self::zero();
+        self.mul_into(rhs, &mut product);
+        *self = product;
+    }
+}
+
+impl<FF: FiniteField> MulAssign for Polynomial<FF> {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self *= &rhs;
     }
 }
 
@@ -2241,6 +3066,347 @@ mod test_polynomials {
         }
     }
 
+    #[test]
+    fn batch_evaluate_pb_test() {
+        let mut rng = rand::thread_rng();
+        for _trial_index in 0..20 {
+            // both sides of the Horner fallback cutoff, with domain sizes
+            // that are not powers of two
+            let num_points: usize = rng.gen_range(1..=200);
+            let domain: Vec<BFieldElement> = random_elements(num_points);
+
+            let degree: usize = rng.gen_range(0..300);
+            let coefficients: Vec<BFieldElement> = random_elements(degree);
+            let poly = Polynomial::<BFieldElement> { coefficients };
+
+            let horner_eval = domain.iter().map(|d| poly.evaluate(d)).collect_vec();
+            assert_eq!(horner_eval, poly.batch_evaluate(&domain));
+        }
+
+        // the extension field, the empty domain, and the zero polynomial
+        let domain: Vec<XFieldElement> = random_elements(50);
+        let poly = Polynomial::<XFieldElement> {
+            coefficients: random_elements(100),
+        };
+        let horner_eval = domain.iter().map(|d| poly.evaluate(d)).collect_vec();
+        assert_eq!(horner_eval, poly.batch_evaluate(&domain));
+        assert!(poly.batch_evaluate(&[]).is_empty());
+        assert_eq!(
+            vec![XFieldElement::zero(); 50],
+            Polynomial::<XFieldElement>::zero().batch_evaluate(&domain)
+        );
+    }
+
+    #[test]
+    fn power_series_utilities_test() {
+        let mut rng = rand::thread_rng();
+        let precision = 100;
+
+        for _trial_index in 0..10 {
+            let lhs = Polynomial::<BFieldElement> {
+                coefficients: random_elements(rng.gen_range(1..200)),
+            };
+            let rhs = Polynomial::<BFieldElement> {
+                coefficients: random_elements(rng.gen_range(1..200)),
+            };
+
+            // truncated multiplication agrees with the full product
+            let full_product = lhs.clone() * rhs.clone();
+            let truncated_product = lhs.mul_mod_xn(&rhs, precision);
+            assert!(truncated_product.degree() < precision as isize);
+            for (i, coefficient) in truncated_product.coefficients.iter().enumerate() {
+                assert_eq!(full_product.coefficients[i], *coefficient);
+            }
+
+            // the power-series inverse multiplies back to one mod x^n
+            if lhs.coefficients[0].is_zero() {
+                continue;
+            }
+            let inverse = lhs.inverse_mod_xn(precision);
+            let mut should_be_one = lhs.mul_mod_xn(&inverse, precision);
+            should_be_one.normalize();
+            assert!(should_be_one.is_one());
+        }
+
+        // truncated powers agree with repeated truncated multiplication
+        let base = Polynomial::<XFieldElement> {
+            coefficients: random_elements(10),
+        };
+        let mut expected = Polynomial::from_constant(XFieldElement::one());
+        for exponent in 0..8 {
+            assert_eq!(expected, base.pow_mod_xn(exponent, precision));
+            expected = expected.mul_mod_xn(&base, precision);
+        }
+        assert!(base.pow_mod_xn(5, 0).is_zero());
+    }
+
+    #[test]
+    fn taylor_shift_pb_test() {
+        let mut rng = rand::thread_rng();
+        for _trial_index in 0..10 {
+            // degrees on both sides of the multiplication cutoffs
+            let polynomial = Polynomial::<BFieldElement> {
+                coefficients: random_elements(rng.gen_range(1..300)),
+            };
+            let offset: BFieldElement = rng.gen();
+            let shifted = polynomial.taylor_shift(offset);
+
+            // `p(x + a)` evaluated at `x` is `p` evaluated at `x + a`
+            assert_eq!(polynomial.degree(), shifted.degree());
+            for point in random_elements::<BFieldElement>(5) {
+                assert_eq!(
+                    polynomial.evaluate(&(point + offset)),
+                    shifted.evaluate(&point)
+                );
+            }
+
+            // shifting by zero and round-tripping are the identity
+            assert_eq!(polynomial, polynomial.taylor_shift(BFieldElement::zero()));
+            assert_eq!(polynomial, shifted.taylor_shift(-offset));
+        }
+
+        // also over the extension field
+        let polynomial = Polynomial::<XFieldElement> {
+            coefficients: random_elements(50),
+        };
+        let offset: XFieldElement = rng.gen();
+        let shifted = polynomial.taylor_shift(offset);
+        let point: XFieldElement = rng.gen();
+        assert_eq!(
+            polynomial.evaluate(&(point + offset)),
+            shifted.evaluate(&point)
+        );
+    }
+
+    #[test]
+    fn evaluate_from_points_test() {
+        let mut rng = rand::thread_rng();
+        for arity in [2, 4, 8] {
+            // points on a polynomial of matching degree determine it, so the
+            // barycentric evaluation must agree with direct evaluation
+            let polynomial = Polynomial::<XFieldElement> {
+                coefficients: random_elements(arity),
+            };
+            let nodes: Vec<XFieldElement> = random_elements_distinct(arity);
+            let points: Vec<(XFieldElement, XFieldElement)> = nodes
+                .iter()
+                .map(|&node| (node, polynomial.evaluate(&node)))
+                .collect();
+
+            let x: XFieldElement = rng.gen();
+            assert_eq!(
+                polynomial.evaluate(&x),
+                Polynomial::evaluate_from_points(&points, x)
+            );
+
+            // hitting a node returns the tabulated value
+            assert_eq!(
+                points[arity / 2].1,
+                Polynomial::evaluate_from_points(&points, points[arity / 2].0)
+            );
+        }
+
+        // the two-point case agrees with the colinearity helper
+        let [p0, p1]: [(BFieldElement, BFieldElement); 2] = [rng.gen(), rng.gen()];
+        let x: BFieldElement = rng.gen();
+        assert_eq!(
+            Polynomial::get_colinear_y(p0, p1, x),
+            Polynomial::evaluate_from_points(&[p0, p1], x)
+        );
+    }
+
+    #[test]
+    fn compose_and_pow_pb_test() {
+        let mut rng = rand::thread_rng();
+        for _trial_index in 0..10 {
+            // outer lengths on both sides of the divide-and-conquer base case
+            let outer = Polynomial::<BFieldElement> {
+                coefficients: random_elements(rng.gen_range(1..40)),
+            };
+            let inner = Polynomial {
+                coefficients: random_elements(rng.gen_range(1..10)),
+            };
+            let composition = outer.compose(&inner);
+
+            // composition commutes with evaluation
+            for point in random_elements::<BFieldElement>(5) {
+                assert_eq!(
+                    outer.evaluate(&inner.evaluate(&point)),
+                    composition.evaluate(&point)
+                );
+            }
+
+            // composing with `x` is the identity, in both positions
+            let x = Polynomial::from_constant(BFieldElement::one()).shift_coefficients(1);
+            assert_eq!(outer, outer.compose(&x));
+            assert_eq!(outer, x.compose(&outer));
+
+            // powers agree with the schoolbook definition
+            let exponent: u64 = rng.gen_range(0..8);
+            assert_eq!(outer.mod_pow((exponent as u32).into()), outer.pow(exponent));
+        }
+
+        // degenerate cases
+        let zero = Polynomial::<BFieldElement>::zero();
+        assert!(zero.compose(&zero).is_zero());
+        assert!(zero.pow(5).is_zero());
+        assert!(zero.pow(0).is_one());
+    }
+
+    #[test]
+    fn adaptive_multiply_test() {
+        let mut rng = rand::thread_rng();
+
+        // operand sizes hitting the schoolbook, Karatsuba and NTT
+        // strategies, also in mixed combinations
+        let sizes = [
+            0,
+            1,
+            MULTIPLY_KARATSUBA_CUTOFF - 1,
+            MULTIPLY_KARATSUBA_CUTOFF + 5,
+            2 * MULTIPLY_KARATSUBA_CUTOFF + 1,
+            MULTIPLY_NTT_CUTOFF + 9,
+        ];
+        for lhs_size in sizes {
+            for rhs_size in sizes {
+                let lhs = Polynomial::<BFieldElement> {
+                    coefficients: random_elements(lhs_size),
+                };
+                let rhs = Polynomial::<BFieldElement> {
+                    coefficients: random_elements(rhs_size),
+                };
+                assert_eq!(
+                    lhs.clone().multiply(rhs.clone()),
+                    lhs.clone() * rhs.clone(),
+                    "sizes {lhs_size} x {rhs_size}"
+                );
+            }
+        }
+
+        // the extension field, with degrees around the Karatsuba split
+        for _trial_index in 0..5 {
+            let lhs = Polynomial::<XFieldElement> {
+                coefficients: random_elements(rng.gen_range(33..300)),
+            };
+            let rhs = Polynomial::<XFieldElement> {
+                coefficients: random_elements(rng.gen_range(33..300)),
+            };
+            assert_eq!(lhs.clone().multiply(rhs.clone()), lhs * rhs);
+        }
+    }
+
+    #[test]
+    fn operator_assign_test() {
+        let mut rng = rand::thread_rng();
+        for _trial_index in 0..10 {
+            let lhs = Polynomial::<BFieldElement> {
+                coefficients: random_elements(rng.gen_range(0..20)),
+            };
+            let rhs = Polynomial::<BFieldElement> {
+                coefficients: random_elements(rng.gen_range(0..20)),
+            };
+
+            // the assigning operators match their binary counterparts
+            let mut sum = lhs.clone();
+            sum += &rhs;
+            assert_eq!(lhs.clone() + rhs.clone(), sum);
+
+            let mut difference = lhs.clone();
+            difference -= rhs.clone();
+            assert_eq!(lhs.clone() - rhs.clone(), difference);
+
+            let mut product = lhs.clone();
+            product *= &rhs;
+            assert_eq!(lhs.clone() * rhs.clone(), product);
+
+            // mul_into overwrites whatever the output buffer held
+            let mut out = Polynomial::<BFieldElement> {
+                coefficients: random_elements(25),
+            };
+            lhs.mul_into(&rhs, &mut out);
+            assert_eq!(lhs.clone() * rhs, out);
+        }
+    }
+
+    #[test]
+    fn fast_divide_pb_test() {
+        let mut rng = rand::thread_rng();
+        for _trial_index in 0..20 {
+            // degrees on both sides of the Newton cutoff, including
+            // dividends smaller than their divisors
+            let dividend_degree: usize = rng.gen_range(0..300);
+            let divisor_degree: usize = rng.gen_range(1..300);
+            let dividend = Polynomial::<BFieldElement> {
+                coefficients: random_elements(dividend_degree + 1),
+            };
+            let divisor = Polynomial::<BFieldElement> {
+                coefficients: random_elements(divisor_degree + 1),
+            };
+
+            let (quotient, remainder) = dividend.fast_divide(&divisor);
+            assert_eq!(
+                dividend.divide(divisor.clone()),
+                (quotient.clone(), remainder.clone())
+            );
+            assert!(remainder.degree() < divisor.degree());
+            assert_eq!(dividend, quotient * divisor + remainder);
+        }
+
+        // exact division leaves no remainder, also over the extension field
+        let left = Polynomial::<XFieldElement> {
+            coefficients: random_elements(80),
+        };
+        let right = Polynomial::<XFieldElement> {
+            coefficients: random_elements(90),
+        };
+        let product = left.clone() * right.clone();
+        let (quotient, remainder) = product.fast_divide(&right);
+        assert_eq!(left, quotient);
+        assert!(remainder.is_zero());
+
+        // the operators route through the fast path
+        assert_eq!(left, product.clone() / right.clone());
+        assert!((product % right).is_zero());
+    }
+
+    #[test]
+    fn batch_zerofier_test() {
+        let mut rng = rand::thread_rng();
+        for _trial_index in 0..10 {
+            // both sides of the product-tree cutoff
+            let num_points: usize = rng.gen_range(0..=150);
+            let domain = random_elements_distinct(num_points);
+
+            let zerofier = Polynomial::<BFieldElement>::batch_zerofier(&domain);
+            assert_eq!(Polynomial::zerofier(&domain), zerofier);
+            assert!(domain
+                .iter()
+                .all(|point| zerofier.evaluate(point).is_zero()));
+        }
+    }
+
+    #[test]
+    fn batch_interpolate_test() {
+        let mut rng = rand::thread_rng();
+        for _trial_index in 0..10 {
+            let num_points: usize = rng.gen_range(1..=150);
+            let domain: Vec<BFieldElement> = random_elements_distinct(num_points);
+            let values: Vec<BFieldElement> = random_elements(num_points);
+
+            let interpolant = Polynomial::batch_interpolate(&domain, &values);
+            assert!(interpolant.degree() < num_points as isize);
+            for (point, value) in domain.iter().zip(values.iter()) {
+                assert_eq!(*value, interpolant.evaluate(point));
+            }
+        }
+
+        // the extension field takes the same path
+        let domain: Vec<XFieldElement> = random_elements_distinct(64);
+        let values: Vec<XFieldElement> = random_elements(64);
+        let interpolant = Polynomial::batch_interpolate(&domain, &values);
+        assert_eq!(values, interpolant.batch_evaluate(&domain));
+    }
+
     #[test]
     fn fast_interpolate_test() {
         let _0_17 = BFieldElement::from(0u64);
@@ -2479,6 +3645,53 @@ mod test_polynomials {
         }
     }
 
+    #[test]
+    fn fast_gcd_pb_test() {
+        let mut rng = rand::thread_rng();
+        for _trial_index in 0..10 {
+            // plant a common factor so the gcd is not trivially one
+            let common = Polynomial::<BFieldElement> {
+                coefficients: random_elements(rng.gen_range(1..100)),
+            };
+            let left = Polynomial {
+                coefficients: random_elements(rng.gen_range(1..200)),
+            } * common.clone();
+            let right = Polynomial {
+                coefficients: random_elements(rng.gen_range(1..200)),
+            } * common.clone();
+
+            // the gcd is monic, divides both inputs, and is divisible by the
+            // planted factor
+            let gcd = Polynomial::fast_gcd(left.clone(), right.clone());
+            assert!(gcd.leading_coefficient().unwrap().is_one());
+            assert!((left.clone() % gcd.clone()).is_zero());
+            assert!((right.clone() % gcd.clone()).is_zero());
+            assert!((gcd.clone() % common.clone()).is_zero());
+
+            // the extended version agrees and satisfies the Bézout relation
+            let (xgcd, a_factor, b_factor) = Polynomial::fast_xgcd(left.clone(), right.clone());
+            assert_eq!(gcd, xgcd);
+            assert_eq!(gcd, a_factor * left + b_factor * right);
+        }
+
+        // agreement with the slow algorithm, also over the extension field
+        let x: Polynomial<XFieldElement> = gen_polynomial_non_zero();
+        let y: Polynomial<XFieldElement> = gen_polynomial_non_zero();
+        assert_eq!(
+            Polynomial::xgcd(x.clone(), y.clone()),
+            Polynomial::fast_xgcd(x.clone(), y.clone())
+        );
+
+        // degenerate inputs
+        assert!(
+            Polynomial::<BFieldElement>::fast_gcd(Polynomial::zero(), Polynomial::zero()).is_zero()
+        );
+        assert_eq!(
+            Polynomial::from_constant(BFieldElement::one()),
+            Polynomial::fast_gcd(Polynomial::zero(), Polynomial::from_constant(7u64.into()))
+        );
+    }
+
     #[test]
     fn add_assign_test() {
         for _ in 0..10 {
@@ -2623,7 +3836,7 @@ mod test_polynomials {
     }
 
     #[test]
-    #[should_panic(expected = "assertion failed")]
+    #[should_panic(expected = "Line must not be parallel to y-axis")]
     fn get_point_on_invalid_line_test() {
         let one = BFieldElement::one();
         let two = one + one;
@@ -2872,6 +4085,24 @@ mod test_polynomials {
         }
     }
 
+    #[test]
+    fn antiderivative_test() {
+        // differentiating the antiderivative gives back the polynomial, and
+        // the integration constant is fixed to zero
+        for _ in 0..10 {
+            let p: Polynomial<BFieldElement> = gen_polynomial();
+            let antiderivative = p.formal_antiderivative();
+            assert_eq!(p, antiderivative.formal_derivative());
+            if !antiderivative.is_zero() {
+                assert!(antiderivative.coefficients[0].is_zero());
+            }
+        }
+
+        assert!(Polynomial::<BFieldElement>::zero()
+            .formal_antiderivative()
+            .is_zero());
+    }
+
     #[test]
     fn equality() {
         let mut rng = rand::thread_rng();
@@ -0,0 +1,600 @@
+use itertools::Itertools;
+use num_traits::{One, Zero};
+use serde::{Deserialize, Serialize};
+
+use crate::shared_math::b_field_element::BFieldElement;
+use crate::util_types::algebraic_hasher::{AlgebraicHasher, SpongeHasher};
+
+use super::rescue_prime_digest::Digest;
+
+pub const DIGEST_LENGTH: usize = 5;
+pub const STATE_SIZE: usize = 16;
+pub const CAPACITY: usize = 6;
+pub const RATE: usize = 10;
+pub const NUM_FULL_ROUNDS: usize = 8;
+pub const NUM_PARTIAL_ROUNDS: usize = 22;
+
+pub const ALPHA: u64 = 7;
+
+/// The round constants for the full (external) rounds, [`STATE_SIZE`] per
+/// round. Generated with `poseidon2/generate_constants.py`: SHAKE256 over
+/// the parameter seed string, reduced modulo the field order, mirroring the
+/// procedure behind Rescue-Prime's [`ROUND_CONSTANTS`].
+///
+/// [`ROUND_CONSTANTS`]: crate::shared_math::rescue_prime_regular::ROUND_CONSTANTS
+pub const EXTERNAL_ROUND_CONSTANTS: [u64; NUM_FULL_ROUNDS * STATE_SIZE] = [
+    15411778145591923141,
+    5984128708230859729,
+    1763385538366283792,
+    17113129171533898059,
+    12778548171011192636,
+    1757472560458711001,
+    17647988781227734182,
+    8072456820545875674,
+    10101849941592606981,
+    8345283939181388634,
+    8474472492126967265,
+    2542355153332596758,
+    6129300628928228268,
+    2080325706500486707,
+    7646076212262538563,
+    4043636151911087435,
+    10098795803233332626,
+    12194869944776021033,
+    17623878788782378663,
+    9812590200445735695,
+    1495089695156255619,
+    15077199976376498814,
+    7744783483674486230,
+    2399235500926833133,
+    7380860638030485437,
+    10725129998154170911,
+    686879606871443809,
+    4074106346265646167,
+    16341761160662569930,
+    12915635197578655685,
+    10026113834056698742,
+    3871425940561023979,
+    16067369282905429612,
+    6228076979706255539,
+    12210941039868437295,
+    14715076024268596765,
+    2439967024817273077,
+    15961836859499432244,
+    12196918823669071906,
+    11396071399355161467,
+    1492999865642630543,
+    10672633784207674236,
+    16109928472973075962,
+    4344891679055478435,
+    9796038304881668251,
+    14522920967775427320,
+    359012608194200393,
+    5241460748621427289,
+    6715382173885777311,
+    10144959496445036040,
+    13318263338209681084,
+    8584623312485263476,
+    8198558983161426636,
+    15459424545423091295,
+    17448992026303274952,
+    3318183792838704726,
+    10941648769822590285,
+    4443617045536973222,
+    7966021627791120860,
+    10093739539005479855,
+    12176850477171797419,
+    8498139214903938143,
+    7304954386758438082,
+    12529368363266747138,
+    3932362379688807586,
+    70437481092989466,
+    14330646414330513125,
+    12109417901882846164,
+    14996815158988917889,
+    6493797803951827765,
+    7387393029090064045,
+    11136102015746446705,
+    14467843241880500976,
+    6779859420182067344,
+    17585409465096523291,
+    16205146201130556824,
+    1429486961844638015,
+    11739112939262763901,
+    15830039967949849317,
+    5756328877936894210,
+    17207910947237868542,
+    2137385257463587438,
+    8977489302036888045,
+    8419180014526020072,
+    15667934439037283172,
+    5995875046160137605,
+    11477880187756141079,
+    1329263720118276172,
+    14470509879116899528,
+    16247045576705235783,
+    4241511198037025335,
+    10477512341872304441,
+    3118750530942496792,
+    16801522164023269036,
+    16873596837816624927,
+    1655125022422208483,
+    8313511371125598729,
+    17565945219586334454,
+    18045571963558218370,
+    1461421390699889823,
+    4239176062538774947,
+    13757362491561531719,
+    14589576265919691255,
+    2463845866956453107,
+    15716932924451747742,
+    2119723493049743729,
+    14281421240090693051,
+    1991166637523787245,
+    7132281398712693448,
+    6483360084756489155,
+    2090502935583180365,
+    7442452009610791588,
+    15565485035426463397,
+    11665554735494647914,
+    1768671173456540716,
+    5706681911207353069,
+    10815444413865720858,
+    11253665343209337843,
+    16121799140714385479,
+    2395282925330407442,
+    12310367074850831901,
+    7429145418002265811,
+    6696693850626782520,
+    6981060051190529974,
+    15650512562215511560,
+    18350806438744733986,
+    10932685770562100293,
+    15625266342834828126,
+];
+
+/// The round constants for the partial (internal) rounds, one per round;
+/// only the first state element receives a constant. Generated with
+/// `poseidon2/generate_constants.py`.
+pub const INTERNAL_ROUND_CONSTANTS: [u64; NUM_PARTIAL_ROUNDS] = [
+    9666834832824858776,
+    5955381109114092777,
+    10526754843381051201,
+    10299952162751207510,
+    4669681205711437103,
+    18259383902412223361,
+    1332062019861294719,
+    4216195397687347673,
+    16307155840233871395,
+    14660912478786559952,
+    17593454028031409518,
+    10742980473654694091,
+    6312362361560426327,
+    18145367216169233996,
+    12443168914521677678,
+    7617295807982634247,
+    8756037295674102247,
+    4024306277400504067,
+    8562507835960658485,
+    1085617271090874376,
+    15020497961420466180,
+    15914413326443519126,
+];
+
+/// The diagonal of the internal rounds' linear layer. The matrix is the
+/// all-ones matrix plus this diagonal, so a multiplication is one state sum
+/// and one multiplication per element. Generated with
+/// `poseidon2/generate_constants.py`.
+pub const INTERNAL_MATRIX_DIAGONAL: [u64; STATE_SIZE] = [
+    7617505431918413012,
+    17983612601865331699,
+    12111407383567007144,
+    10385227454699520637,
+    14149108830637907891,
+    5158801503572439073,
+    2799363903741826573,
+    1620348216898410069,
+    5711425390042278250,
+    12352649889971884898,
+    15517654328001844856,
+    7600657762670178313,
+    13218102242528149464,
+    700659348556566900,
+    2117447939401260570,
+    9415005425849445935,
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct Poseidon2State {
+    pub state: [BFieldElement; STATE_SIZE],
+}
+
+impl Poseidon2State {
+    fn new() -> Poseidon2State {
+        Poseidon2State {
+            state: [BFieldElement::zero(); STATE_SIZE],
+        }
+    }
+}
+
+/// The Poseidon2 permutation over the B-field, instantiated with the same
+/// sponge geometry as [`RescuePrimeRegular`]: state size 16, rate 10,
+/// capacity 6, digests of 5 elements. Its S-box is the power map x^7, so
+/// its arithmetization is considerably cheaper than Rescue-Prime's, which
+/// needs the inverse power map in every round. The two hash functions are
+/// interchangeable wherever an [`AlgebraicHasher`] is expected, for example
+/// in [`Fri`] and [`MerkleTree`], but of course commit to different roots.
+///
+/// [`RescuePrimeRegular`]: crate::shared_math::rescue_prime_regular::RescuePrimeRegular
+/// [`Fri`]: crate::shared_math::fri::Fri
+/// [`MerkleTree`]: crate::util_types::merkle_tree::MerkleTree
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct Poseidon2 {}
+
+impl Poseidon2 {
+    /// The S-box x^7, applied to one element.
+    #[inline]
+    fn sbox(element: BFieldElement) -> BFieldElement {
+        let square = element * element;
+        let fourth = square * square;
+        fourth * square * element
+    }
+
+    /// Multiply a 4-element block by the matrix
+    /// `[[5, 7, 1, 3], [4, 6, 1, 1], [1, 3, 5, 7], [1, 1, 4, 6]]`,
+    /// using the addition chain from the Poseidon2 paper.
+    #[inline]
+    fn block_matrix(block: &mut [BFieldElement]) {
+        let two = BFieldElement::new(2);
+        let four = BFieldElement::new(4);
+
+        let t0 = block[0] + block[1];
+        let t1 = block[2] + block[3];
+        let t2 = two * block[1] + t1;
+        let t3 = two * block[3] + t0;
+        let t4 = four * t1 + t3;
+        let t5 = four * t0 + t2;
+        let t6 = t3 + t5;
+        let t7 = t2 + t4;
+
+        block[0] = t6;
+        block[1] = t5;
+        block[2] = t7;
+        block[3] = t4;
+    }
+
+    /// The external linear layer: the circulant matrix
+    /// `circ(2 * M4, M4, M4, M4)`, where M4 is the 4x4 block matrix of
+    /// [`block_matrix`]. It is applied as M4 on every block, followed by
+    /// adding the column sums of all blocks to every block.
+    ///
+    /// [`block_matrix`]: Poseidon2::block_matrix
+    fn external_matrix(state: &mut [BFieldElement; STATE_SIZE]) {
+        for block in state.chunks_exact_mut(4) {
+            Self::block_matrix(block);
+        }
+
+        let mut column_sums = [BFieldElement::zero(); 4];
+        for (i, element) in state.iter().enumerate() {
+            column_sums[i % 4] += *element;
+        }
+        for (i, element) in state.iter_mut().enumerate() {
+            *element += column_sums[i % 4];
+        }
+    }
+
+    /// The internal linear layer: the all-ones matrix plus
+    /// [`INTERNAL_MATRIX_DIAGONAL`], applied as one state sum and one
+    /// multiplication per element.
+    fn internal_matrix(state: &mut [BFieldElement; STATE_SIZE]) {
+        let sum: BFieldElement = state.iter().copied().sum();
+        for (element, diagonal_entry) in state.iter_mut().zip(INTERNAL_MATRIX_DIAGONAL) {
+            *element = *element * BFieldElement::from(diagonal_entry) + sum;
+        }
+    }
+
+    /// One full round: round constants on the whole state, the S-box on the
+    /// whole state, then the external linear layer.
+    fn full_round(sponge: &mut Poseidon2State, round_index: usize) {
+        debug_assert!(
+            round_index < NUM_FULL_ROUNDS,
+            "Cannot apply {}th full round; only have {} in total.",
+            round_index,
+            NUM_FULL_ROUNDS
+        );
+
+        for (i, element) in sponge.state.iter_mut().enumerate() {
+            *element += BFieldElement::from(EXTERNAL_ROUND_CONSTANTS[round_index * STATE_SIZE + i]);
+            *element = Self::sbox(*element);
+        }
+        Self::external_matrix(&mut sponge.state);
+    }
+
+    /// One partial round: a round constant and the S-box on the first state
+    /// element only, then the internal linear layer.
+    fn partial_round(sponge: &mut Poseidon2State, round_index: usize) {
+        debug_assert!(
+            round_index < NUM_PARTIAL_ROUNDS,
+            "Cannot apply {}th partial round; only have {} in total.",
+            round_index,
+            NUM_PARTIAL_ROUNDS
+        );
+
+        sponge.state[0] += BFieldElement::from(INTERNAL_ROUND_CONSTANTS[round_index]);
+        sponge.state[0] = Self::sbox(sponge.state[0]);
+        Self::internal_matrix(&mut sponge.state);
+    }
+
+    /// Apply the Poseidon2 permutation to the state of a sponge: an initial
+    /// external linear layer, half the full rounds, all partial rounds, and
+    /// the remaining full rounds.
+    fn permutation(sponge: &mut Poseidon2State) {
+        Self::external_matrix(&mut sponge.state);
+        for round_index in 0..NUM_FULL_ROUNDS / 2 {
+            Self::full_round(sponge, round_index);
+        }
+        for round_index in 0..NUM_PARTIAL_ROUNDS {
+            Self::partial_round(sponge, round_index);
+        }
+        for round_index in NUM_FULL_ROUNDS / 2..NUM_FULL_ROUNDS {
+            Self::full_round(sponge, round_index);
+        }
+    }
+
+    /// hash_10
+    /// Hash 10 elements, or two digests. There is no padding because
+    /// the input length is fixed.
+    pub fn hash_10(input: &[BFieldElement; 10]) -> [BFieldElement; 5] {
+        let mut sponge = Poseidon2State::new();
+
+        // absorb once
+        sponge.state[..10].copy_from_slice(input);
+
+        // apply domain separation for fixed-length input
+        sponge.state[10] = BFieldElement::one();
+
+        // apply the permutation
+        Self::permutation(&mut sponge);
+
+        // squeeze once
+        sponge.state[..5].try_into().unwrap()
+    }
+
+    /// hash_varlen hashes an arbitrary number of field elements.
+    ///
+    /// Takes care of padding by applying the padding rule: append a single 1 ∈ Fp
+    /// and as many 0 ∈ Fp elements as required to make the number of input elements
+    /// a multiple of `RATE`.
+    pub fn hash_varlen(input: &[BFieldElement]) -> [BFieldElement; 5] {
+        let mut sponge = Poseidon2State::new();
+
+        // pad input
+        let mut padded_input = input.to_vec();
+        padded_input.push(BFieldElement::one());
+        while !padded_input.len().is_multiple_of(RATE) {
+            padded_input.push(BFieldElement::zero());
+        }
+
+        // absorb
+        while !padded_input.is_empty() {
+            for (sponge_state_element, input_element) in sponge
+                .state
+                .iter_mut()
+                .take(RATE)
+                .zip_eq(padded_input.iter().take(RATE))
+            {
+                *sponge_state_element += input_element.to_owned();
+            }
+            padded_input.drain(..RATE);
+            Self::permutation(&mut sponge);
+        }
+
+        // squeeze once
+        sponge.state[..5].try_into().unwrap()
+    }
+}
+
+impl SpongeHasher for Poseidon2 {
+    const RATE: usize = RATE;
+    const STATE_SIZE: usize = STATE_SIZE;
+
+    fn permute(state: &mut [BFieldElement]) {
+        let mut sponge = Poseidon2State {
+            state: state.try_into().unwrap(),
+        };
+        Self::permutation(&mut sponge);
+        state.copy_from_slice(&sponge.state);
+    }
+}
+
+impl AlgebraicHasher for Poseidon2 {
+    fn hash_slice(elements: &[BFieldElement]) -> Digest {
+        Digest::new(Poseidon2::hash_varlen(elements))
+    }
+
+    fn hash_pair(left: &Digest, right: &Digest) -> Digest {
+        let mut input = [BFieldElement::zero(); 10];
+        input[..DIGEST_LENGTH].copy_from_slice(&left.values());
+        input[DIGEST_LENGTH..].copy_from_slice(&right.values());
+        Digest::new(Poseidon2::hash_10(&input))
+    }
+}
+
+#[cfg(test)]
+mod poseidon2_tests {
+    use crate::shared_math::other::{random_elements, random_elements_array};
+    use crate::util_types::merkle_tree::MerkleTree;
+
+    use super::*;
+
+    #[test]
+    fn external_matrix_matches_naive_multiplication_test() {
+        // The block-and-column-sum shortcut must agree with a naive
+        // multiplication by circ(2 * M4, M4, M4, M4).
+        let m4: [[u64; 4]; 4] = [[5, 7, 1, 3], [4, 6, 1, 1], [1, 3, 5, 7], [1, 1, 4, 6]];
+        let mut matrix = [[BFieldElement::zero(); STATE_SIZE]; STATE_SIZE];
+        for row_block in 0..STATE_SIZE / 4 {
+            for col_block in 0..STATE_SIZE / 4 {
+                let factor = if row_block == col_block { 2 } else { 1 };
+                for i in 0..4 {
+                    for j in 0..4 {
+                        matrix[row_block * 4 + i][col_block * 4 + j] =
+                            BFieldElement::new(factor * m4[i][j]);
+                    }
+                }
+            }
+        }
+
+        let input: [BFieldElement; STATE_SIZE] = random_elements_array();
+        let mut expected = [BFieldElement::zero(); STATE_SIZE];
+        for i in 0..STATE_SIZE {
+            for j in 0..STATE_SIZE {
+                expected[i] += matrix[i][j] * input[j];
+            }
+        }
+
+        let mut actual = input;
+        Poseidon2::external_matrix(&mut actual);
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn poseidon2_test_vectors_test() {
+        // hash 10
+        let targets_first_batch: [[u64; 5]; 6] = [
+            [
+                9615809386734890322,
+                5313945079954459774,
+                16045822965887158631,
+                14085606516676594759,
+                10774975907387148489,
+            ],
+            [
+                9201818420210722695,
+                11770806069912870793,
+                16451271207907265549,
+                5733963727876260541,
+                3107273996128364116,
+            ],
+            [
+                6087332737088576728,
+                17172356057755158040,
+                13088250538049416236,
+                231403438775098271,
+                6144073837350066600,
+            ],
+            [
+                12133384031257051520,
+                15261021311606367295,
+                14754195701179217715,
+                1814398749938396155,
+                3528672293158822639,
+            ],
+            [
+                16815057259175195710,
+                4034045194346839902,
+                15612927926918606761,
+                5466650710352982051,
+                11494243023625934504,
+            ],
+            [
+                2185179687359217289,
+                13624085656445427111,
+                9363557499989371313,
+                16894712199377097162,
+                12757219334335608532,
+            ],
+        ];
+        for (i, target) in targets_first_batch.into_iter().enumerate() {
+            let expected = target.map(BFieldElement::new);
+            let mut input = [BFieldElement::zero(); 10];
+            input[input.len() - 1] = BFieldElement::from(i as u64);
+            let actual = Poseidon2::hash_10(&input);
+            assert_eq!(expected, actual);
+        }
+
+        // hash varlen
+        let targets_second_batch: [[u64; 5]; 6] = [
+            [
+                18163645374130196223,
+                12008077374950403197,
+                14522748085038643135,
+                1716768436338622166,
+                3028944418110883234,
+            ],
+            [
+                15805215483941264653,
+                9953612274089230759,
+                6943672773993029965,
+                4020319026955842276,
+                16613298063839398824,
+            ],
+            [
+                17031202625526570852,
+                9579889035079550872,
+                18110501543856873566,
+                9341541783278158615,
+                3928142043424260505,
+            ],
+            [
+                10344001347197412355,
+                1307931199664821114,
+                1838188652109677291,
+                1098861296547557131,
+                10654567113841152585,
+            ],
+            [
+                4261371328804881216,
+                831696723609409870,
+                1003094761142070287,
+                10682745638946868310,
+                17135057561973557273,
+            ],
+            [
+                6935903815179986186,
+                13813357097609437697,
+                12645584278061354255,
+                15824481719209771378,
+                2669796964657536575,
+            ],
+        ];
+        for (i, target) in targets_second_batch.into_iter().enumerate() {
+            let expected = target.map(BFieldElement::new);
+            let input = (0..i as u64).map(BFieldElement::new).collect_vec();
+            let actual = Poseidon2::hash_varlen(&input);
+            assert_eq!(expected, actual);
+        }
+    }
+
+    #[test]
+    fn poseidon2_sponge_matches_hash_varlen_test() {
+        for input_length in [0, 3, 10, 17, 20] {
+            let input: Vec<BFieldElement> = random_elements(input_length);
+
+            let mut sponge = Poseidon2::init();
+            Poseidon2::absorb(&mut sponge, &input);
+            let squeezed = Poseidon2::squeeze(&mut sponge, DIGEST_LENGTH);
+            assert_eq!(Poseidon2::hash_varlen(&input).to_vec(), squeezed);
+        }
+    }
+
+    #[test]
+    fn poseidon2_merkle_tree_test() {
+        // Poseidon2 plugs in wherever an AlgebraicHasher is expected.
+        let leaves: Vec<Digest> = (0..8u64)
+            .map(|i| Poseidon2::hash_slice(&[BFieldElement::new(i)]))
+            .collect();
+        let tree: MerkleTree<Poseidon2> = MerkleTree::from_digests(&leaves);
+
+        let leaf_index = 5;
+        let auth_path = tree.get_authentication_path(leaf_index);
+        assert!(
+            MerkleTree::<Poseidon2>::verify_authentication_path_from_leaf_hash(
+                tree.get_root(),
+                leaf_index as u32,
+                leaves[leaf_index],
+                auth_path,
+            )
+        );
+    }
+}
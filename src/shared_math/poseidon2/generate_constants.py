@@ -0,0 +1,42 @@
+# generate_constants.py
+# Generates the round constants and the internal-matrix diagonal for the
+# Poseidon2 instance over the B-field, mirroring the procedure used for
+# Rescue-Prime's round constants (SHAKE256 over a parameter seed string;
+# see rescue_prime_regular/rescue_prime_regular.sage).
+
+from hashlib import shake_256
+from math import ceil
+
+p = 2**64 - 2**32 + 1
+m = 16              # state size
+capacity = 6
+security_level = 160
+num_full_rounds = 8
+num_partial_rounds = 22
+
+# external round constants, internal round constants, internal diagonal
+num_constants = num_full_rounds * m + num_partial_rounds + m
+
+bytes_per_int = ceil(len(bin(p)[2:]) / 8) + 1
+seed_string = "Poseidon2(%i,%i,%i,%i)" % (p, m, capacity, security_level)
+byte_string = shake_256(bytes(seed_string, "ascii")).digest(bytes_per_int * num_constants)
+
+constants = []
+for i in range(num_constants):
+    chunk = byte_string[bytes_per_int * i : bytes_per_int * (i + 1)]
+    integer = sum(256**j * chunk[j] for j in range(len(chunk)))
+    constants.append(integer % p)
+
+external = constants[: num_full_rounds * m]
+internal = constants[num_full_rounds * m : num_full_rounds * m + num_partial_rounds]
+diagonal = constants[num_full_rounds * m + num_partial_rounds :]
+
+print("EXTERNAL_ROUND_CONSTANTS:")
+for c in external:
+    print("    %i," % c)
+print("INTERNAL_ROUND_CONSTANTS:")
+for c in internal:
+    print("    %i," % c)
+print("INTERNAL_MATRIX_DIAGONAL:")
+for c in diagonal:
+    print("    %i," % c)
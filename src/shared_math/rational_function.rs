@@ -0,0 +1,327 @@
+use std::fmt::{Display, Formatter};
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use num_traits::{One, Zero};
+
+use crate::shared_math::polynomial::Polynomial;
+use crate::shared_math::traits::FiniteField;
+
+/// A quotient of two polynomials over a finite field.
+///
+/// Arithmetic cross-multiplies and never cancels, so intermediate results may
+/// carry a common factor; call [`reduce`] to put a function in lowest terms
+/// with a monic denominator. Constraint quotients can thus be accumulated as
+/// rational functions and divided out once, at the end.
+///
+/// The denominator is always nonzero.
+///
+/// [`reduce`]: Self::reduce
+#[derive(Debug, Clone)]
+pub struct RationalFunction<FF: FiniteField> {
+    numerator: Polynomial<FF>,
+    denominator: Polynomial<FF>,
+}
+
+impl<FF: FiniteField> RationalFunction<FF> {
+    pub fn new(numerator: Polynomial<FF>, denominator: Polynomial<FF>) -> Self {
+        assert!(
+            !denominator.is_zero(),
+            "Rational function must have a nonzero denominator"
+        );
+        Self {
+            numerator,
+            denominator,
+        }
+    }
+
+    pub fn from_polynomial(polynomial: Polynomial<FF>) -> Self {
+        Self {
+            numerator: polynomial,
+            denominator: Polynomial::one(),
+        }
+    }
+
+    pub fn numerator(&self) -> &Polynomial<FF> {
+        &self.numerator
+    }
+
+    pub fn denominator(&self) -> &Polynomial<FF> {
+        &self.denominator
+    }
+
+    /// The degree of the function, *i.e.* the degree of the numerator minus
+    /// the degree of the denominator. Reduces first, so common factors do not
+    /// inflate the answer. Minus infinity, for the zero function, clamps to
+    /// `isize::MIN`.
+    pub fn degree(&self) -> isize {
+        let reduced = self.clone().reduce();
+        if reduced.numerator.is_zero() {
+            return isize::MIN;
+        }
+        reduced.numerator.degree() - reduced.denominator.degree()
+    }
+
+    /// Cancel all common factors and normalize the denominator to be monic.
+    /// The representation is canonical afterwards: equal functions reduce to
+    /// identical numerator-denominator pairs.
+    #[must_use]
+    pub fn reduce(self) -> Self {
+        let gcd = Polynomial::fast_gcd(self.numerator.clone(), self.denominator.clone());
+        let (mut numerator, mut denominator) = (self.numerator, self.denominator);
+        if !gcd.is_one() {
+            (numerator, _) = numerator.fast_divide(&gcd);
+            (denominator, _) = denominator.fast_divide(&gcd);
+        }
+
+        let leading_coefficient_inverse = denominator.leading_coefficient().unwrap().inverse();
+        Self {
+            numerator: numerator.scalar_mul(leading_coefficient_inverse),
+            denominator: denominator.scalar_mul(leading_coefficient_inverse),
+        }
+    }
+
+    /// The function as a polynomial, if the (reduced) denominator is a
+    /// constant; `None` if a nontrivial denominator remains.
+    pub fn to_polynomial(&self) -> Option<Polynomial<FF>> {
+        let reduced = self.clone().reduce();
+        if reduced.denominator.is_one() {
+            Some(reduced.numerator)
+        } else {
+            None
+        }
+    }
+
+    /// The multiplicative inverse. Panics for the zero function.
+    #[must_use]
+    pub fn inverse(&self) -> Self {
+        Self::new(self.denominator.clone(), self.numerator.clone())
+    }
+
+    /// Evaluate the function in one point. Panics on poles, *i.e.* when the
+    /// denominator vanishes in the point but the numerator does not; a
+    /// removable singularity is evaluated after reduction.
+    pub fn evaluate(&self, point: &FF) -> FF {
+        let denominator_value = self.denominator.evaluate(point);
+        if denominator_value.is_zero() {
+            let reduced = self.clone().reduce();
+            return reduced.numerator.evaluate(point)
+                * reduced.denominator.evaluate(point).inverse();
+        }
+        self.numerator.evaluate(point) * denominator_value.inverse()
+    }
+
+    /// Evaluate the function on a whole domain, with a single batch inversion
+    /// covering every denominator value. Panics if the denominator vanishes
+    /// anywhere on the domain.
+    pub fn batch_evaluate(&self, domain: &[FF]) -> Vec<FF> {
+        let numerator_values = self.numerator.batch_evaluate(domain);
+        let denominator_values = FF::batch_inversion(self.denominator.batch_evaluate(domain));
+        numerator_values
+            .into_iter()
+            .zip(denominator_values)
+            .map(|(numerator_value, denominator_inverse)| numerator_value * denominator_inverse)
+            .collect()
+    }
+}
+
+impl<FF: FiniteField> Display for RationalFunction<FF> {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "({})/({})", self.numerator, self.denominator)
+    }
+}
+
+// Equality by cross-multiplication, so unreduced representations of the same
+// function compare equal.
+impl<FF: FiniteField> PartialEq for RationalFunction<FF> {
+    fn eq(&self, other: &Self) -> bool {
+        self.numerator.clone() * other.denominator.clone()
+            == other.numerator.clone() * self.denominator.clone()
+    }
+}
+
+impl<FF: FiniteField> Eq for RationalFunction<FF> {}
+
+impl<FF: FiniteField> Zero for RationalFunction<FF> {
+    fn zero() -> Self {
+        Self::from_polynomial(Polynomial::zero())
+    }
+
+    fn is_zero(&self) -> bool {
+        self.numerator.is_zero()
+    }
+}
+
+impl<FF: FiniteField> One for RationalFunction<FF> {
+    fn one() -> Self {
+        Self::from_polynomial(Polynomial::one())
+    }
+
+    fn is_one(&self) -> bool {
+        self.numerator == self.denominator
+    }
+}
+
+impl<FF: FiniteField> Add for RationalFunction<FF> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            numerator: self.numerator * other.denominator.clone()
+                + other.numerator * self.denominator.clone(),
+            denominator: self.denominator * other.denominator,
+        }
+    }
+}
+
+impl<FF: FiniteField> Sub for RationalFunction<FF> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self + (-other)
+    }
+}
+
+impl<FF: FiniteField> Neg for RationalFunction<FF> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self {
+            numerator: self.numerator.scalar_mul(-FF::one()),
+            denominator: self.denominator,
+        }
+    }
+}
+
+impl<FF: FiniteField> Mul for RationalFunction<FF> {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Self {
+            numerator: self.numerator * other.numerator,
+            denominator: self.denominator * other.denominator,
+        }
+    }
+}
+
+impl<FF: FiniteField> Div for RationalFunction<FF> {
+    type Output = Self;
+
+    // division is multiplication with the flipped right-hand side
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, other: Self) -> Self {
+        self * other.inverse()
+    }
+}
+
+#[cfg(test)]
+mod rational_function_tests {
+    use rand::Rng;
+
+    use super::*;
+    use crate::shared_math::b_field_element::BFieldElement;
+    use crate::shared_math::other::{random_elements, random_elements_distinct};
+    use crate::shared_math::x_field_element::XFieldElement;
+
+    fn random_rational_function(rng: &mut impl Rng) -> RationalFunction<BFieldElement> {
+        RationalFunction::new(
+            Polynomial {
+                coefficients: random_elements(rng.gen_range(1..50)),
+            },
+            Polynomial {
+                coefficients: random_elements(rng.gen_range(1..50)),
+            },
+        )
+    }
+
+    #[test]
+    fn rational_function_arithmetic_test() {
+        let mut rng = rand::thread_rng();
+        for _trial_index in 0..10 {
+            let f = random_rational_function(&mut rng);
+            let g = random_rational_function(&mut rng);
+            let h = random_rational_function(&mut rng);
+
+            // field axioms hold up to cross-multiplication equality
+            assert_eq!(f.clone() + g.clone(), g.clone() + f.clone());
+            assert_eq!(
+                (f.clone() + g.clone()) * h.clone(),
+                f.clone() * h.clone() + g.clone() * h.clone()
+            );
+            assert_eq!(f.clone() - f.clone(), RationalFunction::zero());
+            if !f.is_zero() {
+                assert!((f.clone() / f.clone()).is_one());
+                assert!((f.clone() * f.inverse()).is_one());
+            }
+
+            // arithmetic commutes with evaluation
+            let point: BFieldElement = rng.gen();
+            if !f.denominator().evaluate(&point).is_zero()
+                && !g.denominator().evaluate(&point).is_zero()
+            {
+                assert_eq!(
+                    f.evaluate(&point) + g.evaluate(&point),
+                    (f.clone() + g.clone()).evaluate(&point)
+                );
+                assert_eq!(
+                    f.evaluate(&point) * g.evaluate(&point),
+                    (f.clone() * g.clone()).evaluate(&point)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn rational_function_reduction_test() {
+        let mut rng = rand::thread_rng();
+        let common = Polynomial::<XFieldElement> {
+            coefficients: random_elements(20),
+        };
+        let numerator = Polynomial {
+            coefficients: random_elements(30),
+        };
+        let denominator = Polynomial {
+            coefficients: random_elements(40),
+        };
+
+        // reduction cancels the planted factor and yields a monic denominator
+        let inflated = RationalFunction::new(
+            numerator.clone() * common.clone(),
+            denominator.clone() * common.clone(),
+        );
+        let reduced = inflated.clone().reduce();
+        assert_eq!(inflated, reduced);
+        assert!(reduced
+            .denominator()
+            .leading_coefficient()
+            .unwrap()
+            .is_one());
+        assert!(reduced.denominator().degree() <= denominator.degree());
+        assert_eq!(inflated.degree(), numerator.degree() - denominator.degree());
+
+        // canonical representatives: the zero function reduces to 0/1
+        let zero = RationalFunction::new(Polynomial::zero(), denominator.scalar_mul(rng.gen()));
+        let reduced_zero = zero.reduce();
+        assert!(reduced_zero.numerator().is_zero());
+        assert!(reduced_zero.denominator().is_one());
+
+        // a polynomial in disguise round-trips
+        let product = RationalFunction::new(numerator.clone() * denominator.clone(), denominator);
+        assert_eq!(Some(numerator.clone()), product.to_polynomial());
+        assert_eq!(
+            None,
+            RationalFunction::new(Polynomial::one(), numerator).to_polynomial()
+        );
+    }
+
+    #[test]
+    fn rational_function_batch_evaluate_test() {
+        let mut rng = rand::thread_rng();
+        let function = random_rational_function(&mut rng);
+        let domain: Vec<BFieldElement> = random_elements_distinct(64);
+
+        let values = function.batch_evaluate(&domain);
+        for (point, value) in domain.iter().zip(&values) {
+            assert_eq!(function.evaluate(point), *value);
+        }
+    }
+}
@@ -137,6 +137,21 @@ impl From<[u8; Digest::BYTES]> for Digest {
     }
 }
 
+/// Pack a 32-byte message digest into the first four elements of a
+/// [`Digest`], leaving the fifth zero -- the layout the blake3 and
+/// Keccak-256 wrappers use for their outputs.
+impl From<[u8; MSG_DIGEST_SIZE_IN_BYTES]> for Digest {
+    fn from(item: [u8; MSG_DIGEST_SIZE_IN_BYTES]) -> Self {
+        Self([
+            BFieldElement::from_ne_bytes(&item[0..8]),
+            BFieldElement::from_ne_bytes(&item[8..16]),
+            BFieldElement::from_ne_bytes(&item[16..24]),
+            BFieldElement::from_ne_bytes(&item[24..32]),
+            BFieldElement::zero(),
+        ])
+    }
+}
+
 // The implementations for dev net byte arrays are not to be used on main net
 impl From<Digest> for [u8; MSG_DIGEST_SIZE_IN_BYTES] {
     fn from(input: Digest) -> Self {
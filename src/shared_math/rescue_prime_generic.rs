@@ -0,0 +1,411 @@
+use std::marker::PhantomData;
+use std::sync::OnceLock;
+
+use num_traits::{One, Zero};
+use serde::{Deserialize, Serialize};
+
+use crate::shared_math::b_field_element::BFieldElement;
+use crate::shared_math::other::xgcd;
+use crate::shared_math::rescue_prime_digest::Digest;
+use crate::shared_math::rescue_prime_regular::DIGEST_LENGTH;
+use crate::shared_math::traits::Inverse;
+use crate::util_types::algebraic_hasher::AlgebraicHasher;
+use crate::util_types::keccak256::shake256;
+
+/// The prime factorization of `p - 1`, where `p` is the B-field modulus:
+/// `p - 1 = 2^32 * 3 * 5 * 17 * 257 * 65537`. Used to test candidate field
+/// elements for primitivity.
+const MODULUS_MINUS_ONE_PRIME_FACTORS: [u64; 6] = [2, 3, 5, 17, 257, 65537];
+
+/// One byte more than the modulus occupies, so that reducing a sampled
+/// chunk modulo `p` introduces negligible bias. Matches `bytes_per_int` in
+/// the reference `rescue_prime_regular.sage` script.
+const SAMPLE_BYTES_PER_CONSTANT: usize = 9;
+
+/// A complete Rescue-Prime parameter set over the B-field: round constants
+/// and MDS matrix for a chosen state size and capacity, generated with the
+/// same procedure as the reference `rescue_prime_regular.sage` script that
+/// produced the baked tables of
+/// [`rescue_prime_regular`](crate::shared_math::rescue_prime_regular).
+/// Instantiating `RescuePrimeParameters::new(16, 6, 160, 8)` reproduces
+/// those tables bit for bit; other state sizes yield Rescue-Prime variants
+/// with wider or narrower rates, e.g. a rate large enough to absorb a full
+/// execution-trace row in a single permutation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RescuePrimeParameters {
+    pub state_size: usize,
+    pub capacity: usize,
+    pub rate: usize,
+    pub security_level: usize,
+    pub num_rounds: usize,
+    pub alpha: u64,
+    pub alpha_inv: u64,
+    /// The MDS matrix in row-major order, `state_size * state_size` entries.
+    pub mds: Vec<BFieldElement>,
+    /// All round constants, `2 * state_size * num_rounds` entries: for every
+    /// round, first the constants following the forward S-box, then those
+    /// following the inverse S-box.
+    pub round_constants: Vec<BFieldElement>,
+}
+
+impl RescuePrimeParameters {
+    /// Generate the parameter set for the given state size, capacity,
+    /// security level and round count. The round count must come from the
+    /// security analysis of the Rescue-Prime paper; the reference script
+    /// fixes eight rounds for the 160-bit level at state size 16, and larger
+    /// states only add degrees of freedom to the attacked systems, so eight
+    /// rounds remain the choice for wider variants at the same level.
+    pub fn new(
+        state_size: usize,
+        capacity: usize,
+        security_level: usize,
+        num_rounds: usize,
+    ) -> Self {
+        assert!(capacity < state_size, "Capacity must leave a nonzero rate");
+        let rate = state_size - capacity;
+        assert!(
+            rate >= 2 * DIGEST_LENGTH,
+            "Rate must be large enough to absorb two digests in one permutation"
+        );
+
+        let (alpha, alpha_inv) = Self::get_alphas();
+        let round_constants =
+            Self::get_round_constants(state_size, capacity, security_level, num_rounds);
+        let mds = Self::get_mds_matrix(state_size);
+
+        Self {
+            state_size,
+            capacity,
+            rate,
+            security_level,
+            num_rounds,
+            alpha,
+            alpha_inv,
+            mds,
+            round_constants,
+        }
+    }
+
+    /// The smallest exponent `alpha >= 3` that is invertible modulo `p - 1`,
+    /// together with its inverse.
+    fn get_alphas() -> (u64, u64) {
+        let modulus_minus_one = BFieldElement::MAX as i128;
+        let mut alpha = 3i128;
+        loop {
+            let (gcd, bezout_alpha, _) = xgcd(alpha, modulus_minus_one);
+            if gcd.is_one() {
+                let alpha_inv = bezout_alpha.rem_euclid(modulus_minus_one);
+                return (alpha as u64, alpha_inv as u64);
+            }
+            alpha += 1;
+        }
+    }
+
+    /// The round constants: SHAKE256 output on the seed string
+    /// `Rescue-XLIX(p,m,capacity,security_level)`, read in nine-byte
+    /// little-endian chunks and reduced modulo `p`.
+    fn get_round_constants(
+        state_size: usize,
+        capacity: usize,
+        security_level: usize,
+        num_rounds: usize,
+    ) -> Vec<BFieldElement> {
+        let num_constants = 2 * state_size * num_rounds;
+        let seed_string = format!(
+            "Rescue-XLIX({},{},{},{})",
+            BFieldElement::QUOTIENT,
+            state_size,
+            capacity,
+            security_level
+        );
+        let byte_string = shake256(
+            seed_string.as_bytes(),
+            SAMPLE_BYTES_PER_CONSTANT * num_constants,
+        );
+
+        byte_string
+            .chunks_exact(SAMPLE_BYTES_PER_CONSTANT)
+            .map(|chunk| {
+                let mut integer = 0u128;
+                for (i, byte) in chunk.iter().enumerate() {
+                    integer += (*byte as u128) << (8 * i);
+                }
+                BFieldElement::new((integer % BFieldElement::QUOTIENT as u128) as u64)
+            })
+            .collect()
+    }
+
+    /// The MDS matrix: the transpose of the right half of the reduced row
+    /// echelon form of the `m x 2m` Vandermonde matrix over the powers of
+    /// the smallest primitive element of the field. This makes the matrix a
+    /// systematic generator matrix of a Reed-Solomon code, hence MDS.
+    fn get_mds_matrix(state_size: usize) -> Vec<BFieldElement> {
+        let mut generator = BFieldElement::new(2);
+        while !Self::is_primitive(generator) {
+            generator += BFieldElement::one();
+        }
+
+        // Vandermonde matrix over the powers of the generator
+        let mut matrix: Vec<Vec<BFieldElement>> = (0..state_size)
+            .map(|i| {
+                (0..2 * state_size)
+                    .map(|j| generator.mod_pow((i * j) as u64))
+                    .collect()
+            })
+            .collect();
+
+        // reduced row echelon form; the left half reduces to the identity
+        for column in 0..state_size {
+            let pivot_row = (column..state_size)
+                .find(|&row| !matrix[row][column].is_zero())
+                .expect("Vandermonde matrix must have full rank");
+            matrix.swap(column, pivot_row);
+
+            let pivot_inverse = matrix[column][column].inverse();
+            for entry in matrix[column].iter_mut() {
+                *entry *= pivot_inverse;
+            }
+
+            let scaled_pivot_row = matrix[column].clone();
+            for (row, matrix_row) in matrix.iter_mut().enumerate() {
+                if row == column {
+                    continue;
+                }
+                let factor = matrix_row[column];
+                for (entry, pivot_entry) in matrix_row.iter_mut().zip(scaled_pivot_row.iter()) {
+                    *entry -= factor * *pivot_entry;
+                }
+            }
+        }
+
+        // the MDS matrix is the transpose of the right half
+        let mut mds = Vec::with_capacity(state_size * state_size);
+        for i in 0..state_size {
+            for row in matrix.iter() {
+                mds.push(row[state_size + i]);
+            }
+        }
+        mds
+    }
+
+    /// Whether the element generates the full multiplicative group, i.e.
+    /// whether no proper divisor of `p - 1` is an order of the element.
+    fn is_primitive(element: BFieldElement) -> bool {
+        MODULUS_MINUS_ONE_PRIME_FACTORS
+            .into_iter()
+            .all(|factor| !element.mod_pow(BFieldElement::MAX / factor).is_one())
+    }
+
+    /// The Rescue-XLIX permutation under this parameter set. The state slice
+    /// must hold exactly `state_size` elements.
+    pub fn permutation(&self, state: &mut [BFieldElement]) {
+        assert_eq!(self.state_size, state.len());
+        for round in 0..self.num_rounds {
+            // forward S-box, MDS, constants
+            for element in state.iter_mut() {
+                *element = element.mod_pow(self.alpha);
+            }
+            self.apply_mds(state);
+            let constants_a = &self.round_constants[2 * round * self.state_size..];
+            for (element, constant) in state.iter_mut().zip(constants_a) {
+                *element += *constant;
+            }
+
+            // inverse S-box, MDS, constants
+            for element in state.iter_mut() {
+                *element = element.mod_pow(self.alpha_inv);
+            }
+            self.apply_mds(state);
+            let constants_b = &self.round_constants[(2 * round + 1) * self.state_size..];
+            for (element, constant) in state.iter_mut().zip(constants_b) {
+                *element += *constant;
+            }
+        }
+    }
+
+    fn apply_mds(&self, state: &mut [BFieldElement]) {
+        let mut new_state = vec![BFieldElement::zero(); self.state_size];
+        for (i, new_element) in new_state.iter_mut().enumerate() {
+            for (j, element) in state.iter().enumerate() {
+                *new_element += self.mds[i * self.state_size + j] * *element;
+            }
+        }
+        state.copy_from_slice(&new_state);
+    }
+
+    /// The variable-length sponge hash under this parameter set: pad with
+    /// one followed by zeros to a multiple of the rate, absorb, squeeze
+    /// once.
+    pub fn hash_varlen(&self, input: &[BFieldElement]) -> [BFieldElement; DIGEST_LENGTH] {
+        let mut state = vec![BFieldElement::zero(); self.state_size];
+
+        // pad input
+        let mut padded_input = input.to_vec();
+        padded_input.push(BFieldElement::one());
+        while !padded_input.len().is_multiple_of(self.rate) {
+            padded_input.push(BFieldElement::zero());
+        }
+
+        // absorb
+        for block in padded_input.chunks_exact(self.rate) {
+            for (state_element, input_element) in state.iter_mut().zip(block) {
+                *state_element += *input_element;
+            }
+            self.permutation(&mut state);
+        }
+
+        // squeeze once
+        state[..DIGEST_LENGTH].try_into().unwrap()
+    }
+
+    /// The fixed-length hash of two digests: both digests fill the front of
+    /// the state, the first capacity element is set to one for domain
+    /// separation, and a single permutation is applied.
+    pub fn hash_pair(&self, left: &Digest, right: &Digest) -> [BFieldElement; DIGEST_LENGTH] {
+        let mut state = vec![BFieldElement::zero(); self.state_size];
+        state[..DIGEST_LENGTH].copy_from_slice(&left.values());
+        state[DIGEST_LENGTH..2 * DIGEST_LENGTH].copy_from_slice(&right.values());
+
+        // domain separation for fixed length
+        state[self.rate] = BFieldElement::one();
+
+        self.permutation(&mut state);
+        state[..DIGEST_LENGTH].try_into().unwrap()
+    }
+}
+
+/// Selects a [`RescuePrimeParameters`] instance at the type level, so code
+/// generic over an [`AlgebraicHasher`] can pick a Rescue-Prime variant
+/// without threading parameter values around. Implementors generate the
+/// parameters once and cache them for the lifetime of the process.
+pub trait RescuePrimeParameterSet: Clone + Send + Sync {
+    fn parameters() -> &'static RescuePrimeParameters;
+}
+
+/// The parameter set of
+/// [`RescuePrimeRegular`](crate::shared_math::rescue_prime_regular::RescuePrimeRegular):
+/// state size 16, capacity 6, 160-bit security level, eight rounds.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct RescuePrimeStandardParameters {}
+
+impl RescuePrimeParameterSet for RescuePrimeStandardParameters {
+    fn parameters() -> &'static RescuePrimeParameters {
+        static PARAMETERS: OnceLock<RescuePrimeParameters> = OnceLock::new();
+        PARAMETERS.get_or_init(|| RescuePrimeParameters::new(16, 6, 160, 8))
+    }
+}
+
+/// A wider variant with state size 24 and rate 18, so that an entire
+/// 16-element execution-trace row fits into a single permutation.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct RescuePrimeWideParameters {}
+
+impl RescuePrimeParameterSet for RescuePrimeWideParameters {
+    fn parameters() -> &'static RescuePrimeParameters {
+        static PARAMETERS: OnceLock<RescuePrimeParameters> = OnceLock::new();
+        PARAMETERS.get_or_init(|| RescuePrimeParameters::new(24, 6, 160, 8))
+    }
+}
+
+/// Rescue-Prime over a type-level parameter set. With
+/// [`RescuePrimeStandardParameters`] this hashes identically to
+/// [`RescuePrimeRegular`](crate::shared_math::rescue_prime_regular::RescuePrimeRegular),
+/// only slower, since the S-boxes use generic modular exponentiation
+/// instead of the hand-tuned addition chains; prefer the concrete hasher
+/// for the standard parameters and the generic one for everything else.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RescuePrimeGeneric<P: RescuePrimeParameterSet> {
+    _parameters: PhantomData<P>,
+}
+
+impl<P: RescuePrimeParameterSet> AlgebraicHasher for RescuePrimeGeneric<P> {
+    fn hash_slice(elements: &[BFieldElement]) -> Digest {
+        Digest::new(P::parameters().hash_varlen(elements))
+    }
+
+    fn hash_pair(left: &Digest, right: &Digest) -> Digest {
+        Digest::new(P::parameters().hash_pair(left, right))
+    }
+}
+
+#[cfg(test)]
+mod rescue_prime_generic_tests {
+    use crate::shared_math::other::{random_elements, random_elements_array};
+    use crate::shared_math::rescue_prime_regular::{
+        RescuePrimeRegular, ALPHA, ALPHA_INV, MDS, ROUND_CONSTANTS,
+    };
+    use crate::util_types::merkle_tree::MerkleTree;
+
+    use super::*;
+
+    #[test]
+    fn standard_parameters_match_baked_tables_test() {
+        let parameters = RescuePrimeStandardParameters::parameters();
+
+        assert_eq!(ALPHA, parameters.alpha);
+        assert_eq!(ALPHA_INV, parameters.alpha_inv);
+        assert_eq!(
+            ROUND_CONSTANTS.map(BFieldElement::new).to_vec(),
+            parameters.round_constants
+        );
+        assert_eq!(MDS.map(BFieldElement::new).to_vec(), parameters.mds);
+    }
+
+    #[test]
+    fn generic_hasher_matches_rescue_prime_regular_test() {
+        type Generic = RescuePrimeGeneric<RescuePrimeStandardParameters>;
+
+        for input_length in [0, 1, 9, 10, 11, 20, 31] {
+            let input: Vec<BFieldElement> = random_elements(input_length);
+            assert_eq!(
+                RescuePrimeRegular::hash_slice(&input),
+                Generic::hash_slice(&input)
+            );
+        }
+
+        let left = Digest::new(random_elements_array());
+        let right = Digest::new(random_elements_array());
+        assert_eq!(
+            RescuePrimeRegular::hash_pair(&left, &right),
+            Generic::hash_pair(&left, &right)
+        );
+    }
+
+    #[test]
+    fn wide_parameters_test() {
+        type Wide = RescuePrimeGeneric<RescuePrimeWideParameters>;
+
+        let parameters = RescuePrimeWideParameters::parameters();
+        assert_eq!(24, parameters.state_size);
+        assert_eq!(18, parameters.rate);
+        assert_eq!(
+            2 * parameters.state_size * parameters.num_rounds,
+            parameters.round_constants.len()
+        );
+
+        // A full 16-element trace row fits into the rate, so hashing it
+        // costs exactly one permutation; the padded input is one block.
+        let trace_row: Vec<BFieldElement> = random_elements(16);
+        assert!(trace_row.len() < parameters.rate);
+        assert_eq!(Wide::hash_slice(&trace_row), Wide::hash_slice(&trace_row));
+
+        // The wide variant is a different hash function than the standard
+        // one, and it slots in wherever an AlgebraicHasher is expected.
+        assert_ne!(
+            RescuePrimeRegular::hash_slice(&trace_row),
+            Wide::hash_slice(&trace_row)
+        );
+
+        let leaves: Vec<Digest> = random_elements(8);
+        let tree: MerkleTree<Wide> = MerkleTree::from_digests(&leaves);
+        let auth_path = tree.get_authentication_path(3);
+        assert!(
+            MerkleTree::<Wide>::verify_authentication_path_from_leaf_hash(
+                tree.get_root(),
+                3,
+                leaves[3],
+                auth_path,
+            )
+        );
+    }
+}
@@ -4,7 +4,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::shared_math::b_field_element::BFieldElement;
 use crate::shared_math::traits::FiniteField;
-use crate::util_types::algebraic_hasher::AlgebraicHasher;
+use crate::util_types::algebraic_hasher::{AlgebraicHasher, SpongeHasher};
 
 use super::rescue_prime_digest::Digest;
 
@@ -980,6 +980,195 @@ impl RescuePrimeRegular {
         }
     }
 
+    #[inline]
+    fn multi_square<const N: usize>(arrays: &mut [[BFieldElement; STATE_SIZE]; N]) {
+        for array in arrays.iter_mut() {
+            Self::batch_square(array);
+        }
+    }
+
+    #[inline]
+    fn multi_square_n<const N: usize, const M: usize>(
+        arrays: &mut [[BFieldElement; STATE_SIZE]; N],
+    ) {
+        for _ in 0..M {
+            Self::multi_square(arrays);
+        }
+    }
+
+    #[inline]
+    fn multi_mul_into<const N: usize>(
+        arrays: &mut [[BFieldElement; STATE_SIZE]; N],
+        operands: [[BFieldElement; STATE_SIZE]; N],
+    ) {
+        for (array, operand) in arrays.iter_mut().zip_eq(operands.iter()) {
+            Self::batch_mul_into(array, *operand);
+        }
+    }
+
+    /// [`batch_mod_pow_alpha_inv`] across `N` states at once. The same
+    /// 72-multiplication addition chain, but every step runs over `16 * N`
+    /// independent lanes, which is what lets the autovectorizer fill wide
+    /// vector registers.
+    ///
+    /// [`batch_mod_pow_alpha_inv`]: RescuePrimeRegular::batch_mod_pow_alpha_inv
+    #[inline]
+    fn multi_mod_pow_alpha_inv<const N: usize>(
+        arrays: [[BFieldElement; STATE_SIZE]; N],
+    ) -> [[BFieldElement; STATE_SIZE]; N] {
+        let mut p1 = arrays;
+        Self::multi_square(&mut p1);
+
+        let mut p2 = p1;
+        Self::multi_square(&mut p2);
+
+        let mut p3 = p2;
+        Self::multi_square_n::<N, 3>(&mut p3);
+        Self::multi_mul_into(&mut p3, p2);
+
+        let mut p4 = p3;
+        Self::multi_square_n::<N, 6>(&mut p4);
+        Self::multi_mul_into(&mut p4, p3);
+
+        let mut p5 = p4;
+        Self::multi_square_n::<N, 12>(&mut p5);
+        Self::multi_mul_into(&mut p5, p4);
+
+        let mut p6 = p5;
+        Self::multi_square_n::<N, 6>(&mut p6);
+        Self::multi_mul_into(&mut p6, p3);
+
+        let mut p7 = p6;
+        Self::multi_square_n::<N, 31>(&mut p7);
+        Self::multi_mul_into(&mut p7, p6);
+
+        let mut result = p7;
+        Self::multi_square(&mut result);
+        Self::multi_mul_into(&mut result, p6);
+        Self::multi_square_n::<N, 2>(&mut result);
+        Self::multi_mul_into(&mut result, p2);
+        Self::multi_mul_into(&mut result, p1);
+        Self::multi_mul_into(&mut result, arrays);
+        result
+    }
+
+    #[inline]
+    fn multi_mod_pow_alpha<const N: usize>(
+        arrays: [[BFieldElement; STATE_SIZE]; N],
+    ) -> [[BFieldElement; STATE_SIZE]; N] {
+        let mut result = arrays;
+        Self::multi_square(&mut result);
+        Self::multi_mul_into(&mut result, arrays);
+        Self::multi_square(&mut result);
+        Self::multi_mul_into(&mut result, arrays);
+        result
+    }
+
+    /// One round of XLIX across `N` states at once; see [`xlix_round`].
+    ///
+    /// [`xlix_round`]: RescuePrimeRegular::xlix_round
+    fn xlix_round_batch<const N: usize>(
+        states: &mut [[BFieldElement; STATE_SIZE]; N],
+        round_index: usize,
+    ) {
+        // S-box
+        *states = Self::multi_mod_pow_alpha(*states);
+
+        // MDS matrix
+        for state in states.iter_mut() {
+            let mut v: [BFieldElement; STATE_SIZE] = [BFieldElement::zero(); STATE_SIZE];
+            for i in 0..STATE_SIZE {
+                for j in 0..STATE_SIZE {
+                    v[i] += BFieldElement::from(MDS[i * STATE_SIZE + j]) * state[j];
+                }
+            }
+            *state = v;
+        }
+
+        // round constants A
+        for state in states.iter_mut() {
+            for (i, element) in state.iter_mut().enumerate() {
+                *element += BFieldElement::from(ROUND_CONSTANTS[round_index * STATE_SIZE * 2 + i]);
+            }
+        }
+
+        // Inverse S-box
+        *states = Self::multi_mod_pow_alpha_inv(*states);
+
+        // MDS matrix
+        for state in states.iter_mut() {
+            let mut v: [BFieldElement; STATE_SIZE] = [BFieldElement::zero(); STATE_SIZE];
+            for i in 0..STATE_SIZE {
+                for j in 0..STATE_SIZE {
+                    v[i] += BFieldElement::from(MDS[i * STATE_SIZE + j]) * state[j];
+                }
+            }
+            *state = v;
+        }
+
+        // round constants B
+        for state in states.iter_mut() {
+            for (i, element) in state.iter_mut().enumerate() {
+                *element += BFieldElement::from(
+                    ROUND_CONSTANTS[round_index * STATE_SIZE * 2 + STATE_SIZE + i],
+                );
+            }
+        }
+    }
+
+    /// Apply XLIX to `N` states at once. The S-box power maps run over all
+    /// `16 * N` lanes in lockstep, so independent multiplication chains are
+    /// adjacent and the compiler can schedule them into wide vector
+    /// registers or at least overlap their latencies.
+    pub fn xlix_batch<const N: usize>(states: &mut [[BFieldElement; STATE_SIZE]; N]) {
+        for round_index in 0..NUM_ROUNDS {
+            Self::xlix_round_batch(states, round_index);
+        }
+    }
+
+    /// The number of states a batched permutation call should process at
+    /// once on the running CPU: 8 with AVX-512, 4 with AVX2, and 2
+    /// otherwise, so that even the plain scalar kernel overlaps two
+    /// independent multiplication chains.
+    pub fn permutation_batch_size() -> usize {
+        #[cfg(target_arch = "x86_64")]
+        {
+            if is_x86_feature_detected!("avx512f") {
+                8
+            } else if is_x86_feature_detected!("avx2") {
+                4
+            } else {
+                2
+            }
+        }
+        #[cfg(not(target_arch = "x86_64"))]
+        {
+            2
+        }
+    }
+
+    /// [`hash_pair`] over `N` sibling pairs at once, fed from a slice of
+    /// `2 * N` digests; the workhorse of [`hash_pairwise`].
+    ///
+    /// [`hash_pair`]: AlgebraicHasher::hash_pair
+    /// [`hash_pairwise`]: AlgebraicHasher::hash_pairwise
+    fn hash_pair_batch<const N: usize>(children: &[Digest]) -> [Digest; N] {
+        debug_assert_eq!(2 * N, children.len());
+
+        let mut states = [[BFieldElement::zero(); STATE_SIZE]; N];
+        for (state, pair) in states.iter_mut().zip_eq(children.chunks_exact(2)) {
+            state[..DIGEST_LENGTH].copy_from_slice(&pair[0].values());
+            state[DIGEST_LENGTH..2 * DIGEST_LENGTH].copy_from_slice(&pair[1].values());
+
+            // domain separation for fixed-length input
+            state[RATE] = BFieldElement::one();
+        }
+
+        Self::xlix_batch(&mut states);
+
+        states.map(|state| Digest::new(state[..DIGEST_LENGTH].try_into().unwrap()))
+    }
+
     /// hash_10
     /// Hash 10 elements, or two digests. There is no padding because
     /// the input length is fixed.
@@ -1010,7 +1199,7 @@ impl RescuePrimeRegular {
         // pad input
         let mut padded_input = input.to_vec();
         padded_input.push(BFieldElement::one());
-        while padded_input.len() % RATE != 0 {
+        while !padded_input.len().is_multiple_of(RATE) {
             padded_input.push(BFieldElement::zero());
         }
 
@@ -1076,6 +1265,19 @@ impl RescuePrimeRegular {
     }
 }
 
+impl SpongeHasher for RescuePrimeRegular {
+    const RATE: usize = RATE;
+    const STATE_SIZE: usize = STATE_SIZE;
+
+    fn permute(state: &mut [BFieldElement]) {
+        let mut sponge = RescuePrimeRegularState {
+            state: state.try_into().unwrap(),
+        };
+        Self::xlix(&mut sponge);
+        state.copy_from_slice(&sponge.state);
+    }
+}
+
 impl AlgebraicHasher for RescuePrimeRegular {
     fn hash_slice(elements: &[BFieldElement]) -> Digest {
         Digest::new(RescuePrimeRegular::hash_varlen(elements))
@@ -1087,13 +1289,37 @@ impl AlgebraicHasher for RescuePrimeRegular {
         input[DIGEST_LENGTH..].copy_from_slice(&right.values());
         Digest::new(RescuePrimeRegular::hash_10(&input))
     }
+
+    fn hash_pairwise(children: &[Digest]) -> Vec<Digest> {
+        assert!(
+            children.len().is_multiple_of(2),
+            "Pairwise hashing needs an even number of digests"
+        );
+
+        let batch_size = Self::permutation_batch_size();
+        let mut parents = Vec::with_capacity(children.len() / 2);
+        let mut remainder = children;
+        while remainder.len() >= 2 * batch_size {
+            let (batch, rest) = remainder.split_at(2 * batch_size);
+            match batch_size {
+                8 => parents.extend(Self::hash_pair_batch::<8>(batch)),
+                4 => parents.extend(Self::hash_pair_batch::<4>(batch)),
+                _ => parents.extend(Self::hash_pair_batch::<2>(batch)),
+            }
+            remainder = rest;
+        }
+        for pair in remainder.chunks_exact(2) {
+            parents.push(Self::hash_pair(&pair[0], &pair[1]));
+        }
+        parents
+    }
 }
 
 #[cfg(test)]
 mod rescue_prime_regular_tests {
     use itertools::Itertools;
 
-    use crate::shared_math::other::random_elements_array;
+    use crate::shared_math::other::{random_elements, random_elements_array};
 
     use super::*;
 
@@ -1413,6 +1639,48 @@ mod rescue_prime_regular_tests {
         }
     }
 
+    #[test]
+    fn sponge_matches_hash_varlen_test() {
+        for input_length in [0, 3, 10, 17, 20] {
+            let input: Vec<BFieldElement> = random_elements(input_length);
+
+            let mut sponge = RescuePrimeRegular::init();
+            RescuePrimeRegular::absorb(&mut sponge, &input);
+            let squeezed = RescuePrimeRegular::squeeze(&mut sponge, DIGEST_LENGTH);
+            assert_eq!(RescuePrimeRegular::hash_varlen(&input).to_vec(), squeezed);
+        }
+    }
+
+    #[test]
+    fn hash_pairwise_matches_hash_pair_test() {
+        // Exercise every batch width plus the scalar remainder path.
+        for num_pairs in [1, 2, 3, 4, 7, 8, 9, 20] {
+            let children: Vec<Digest> = random_elements(2 * num_pairs);
+            let expected: Vec<Digest> = children
+                .chunks_exact(2)
+                .map(|pair| RescuePrimeRegular::hash_pair(&pair[0], &pair[1]))
+                .collect();
+            assert_eq!(expected, RescuePrimeRegular::hash_pairwise(&children));
+        }
+    }
+
+    #[test]
+    fn xlix_batch_matches_xlix_test() {
+        let mut states = [[BFieldElement::zero(); STATE_SIZE]; 4];
+        for state in states.iter_mut() {
+            *state = random_elements_array();
+        }
+
+        let mut batched = states;
+        RescuePrimeRegular::xlix_batch(&mut batched);
+
+        for (state, batched_state) in states.into_iter().zip(batched) {
+            let mut sponge = RescuePrimeRegularState { state };
+            RescuePrimeRegular::xlix(&mut sponge);
+            assert_eq!(sponge.state, batched_state);
+        }
+    }
+
     #[test]
     fn trace_consistent_test() {
         for _ in 0..10 {
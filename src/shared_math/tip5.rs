@@ -0,0 +1,474 @@
+use itertools::Itertools;
+use num_traits::{One, Zero};
+use serde::{Deserialize, Serialize};
+
+use crate::shared_math::b_field_element::BFieldElement;
+use crate::util_types::algebraic_hasher::{AlgebraicHasher, SpongeHasher};
+
+use super::rescue_prime_digest::Digest;
+
+pub const DIGEST_LENGTH: usize = 5;
+pub const STATE_SIZE: usize = 16;
+pub const CAPACITY: usize = 6;
+pub const RATE: usize = 10;
+pub const NUM_ROUNDS: usize = 5;
+
+/// The number of state elements the split-and-lookup S-box is applied to;
+/// the remaining elements go through the power map x^7.
+pub const NUM_SPLIT_AND_LOOKUP: usize = 4;
+
+/// The split-and-lookup S-box's byte permutation: the offset Fermat cube
+/// map over GF(257), `i -> (i + 1)^3 mod 257 - 1`. The cube map permutes
+/// the 256 nonzero residues, and the offsets shift its domain and image to
+/// the byte range. Generated with `tip5/generate_constants.py`.
+pub const LOOKUP_TABLE: [u8; 256] = [
+    0, 7, 26, 63, 124, 215, 85, 254, 214, 228, 45, 185, 140, 173, 33, 240, 29, 177, 176, 32, 8,
+    110, 87, 202, 204, 99, 150, 106, 230, 14, 235, 128, 213, 239, 212, 138, 23, 130, 208, 6, 44,
+    71, 93, 116, 146, 189, 251, 81, 199, 97, 38, 28, 73, 179, 95, 84, 152, 48, 35, 119, 49, 88,
+    242, 3, 148, 169, 72, 120, 62, 161, 166, 83, 175, 191, 137, 19, 100, 129, 112, 55, 221, 102,
+    218, 61, 151, 237, 68, 164, 17, 147, 46, 234, 203, 216, 22, 141, 65, 57, 123, 12, 244, 54, 219,
+    231, 96, 77, 180, 154, 5, 253, 133, 165, 98, 195, 205, 134, 245, 30, 9, 188, 59, 142, 186, 197,
+    181, 144, 92, 31, 224, 163, 111, 74, 58, 69, 113, 196, 67, 246, 225, 10, 121, 50, 60, 157, 90,
+    122, 2, 250, 101, 75, 178, 159, 24, 36, 201, 11, 243, 132, 198, 190, 114, 233, 39, 52, 21, 209,
+    108, 238, 91, 187, 18, 104, 194, 37, 153, 34, 200, 143, 126, 155, 236, 118, 64, 80, 172, 89,
+    94, 193, 135, 183, 86, 107, 252, 13, 167, 206, 136, 220, 207, 103, 171, 160, 76, 182, 227, 217,
+    158, 56, 174, 4, 66, 109, 139, 162, 184, 211, 249, 47, 125, 232, 117, 43, 16, 42, 127, 20, 241,
+    25, 149, 105, 156, 51, 53, 168, 145, 247, 223, 79, 78, 226, 15, 222, 82, 115, 70, 210, 27, 41,
+    1, 170, 40, 131, 192, 229, 248, 255,
+];
+
+/// The round constants, [`STATE_SIZE`] per round. Generated with
+/// `tip5/generate_constants.py`: SHAKE256 over the parameter seed string,
+/// reduced modulo the field order, mirroring the procedure behind
+/// Rescue-Prime's [`ROUND_CONSTANTS`].
+///
+/// [`ROUND_CONSTANTS`]: crate::shared_math::rescue_prime_regular::ROUND_CONSTANTS
+pub const ROUND_CONSTANTS: [u64; NUM_ROUNDS * STATE_SIZE] = [
+    1265951620770914839,
+    379797653234848911,
+    11604918400926622664,
+    2898473442009459680,
+    7603253369303696383,
+    17566946922456926857,
+    13895983755905496097,
+    6278389836250660431,
+    15530062745330836063,
+    18388417613217885467,
+    1553071378083280062,
+    17834995666838566210,
+    15823118343970786642,
+    13506543190169399980,
+    4603342705666594469,
+    3690672490619592552,
+    5715161254939216609,
+    8738717308589771254,
+    8872269584984814816,
+    15218962883865968615,
+    4718855101649920617,
+    8849259689906110788,
+    1075209062402568913,
+    17357847473979036890,
+    15027510344144519191,
+    15974311532045367037,
+    3525884298346770099,
+    7700554662241297118,
+    3699479868668664779,
+    2372314339485956131,
+    12300396462007791039,
+    7370438686194350480,
+    8768769768346267297,
+    187712305800350045,
+    13736186582471278723,
+    11931789183759763661,
+    14178103009822364780,
+    11581383740776820806,
+    17202609834360746609,
+    5251120259922041756,
+    14983673478249848905,
+    7268317089538540705,
+    16106652741681887026,
+    12390865906644219444,
+    8326398777153506426,
+    12258899699662257730,
+    8662056771156483533,
+    9679154615976090615,
+    2618859045945731317,
+    5269820010681329270,
+    14214068594980239406,
+    16069242405484806764,
+    979987700226569738,
+    183680100075189821,
+    12579319148715375671,
+    10984568002572535673,
+    359847652280207210,
+    17474577554964679117,
+    2956380597633940334,
+    9636395911336380952,
+    4773554915614819256,
+    10998246043218769545,
+    5012602569572437312,
+    7589158149818506061,
+    1371750278482484416,
+    1049832716794992165,
+    10887508515184629097,
+    8567342422771002004,
+    14983136404319248182,
+    18350031913056425545,
+    17844593187698498546,
+    17487875073643778775,
+    13453957776238459885,
+    7591165081263441652,
+    13488122437548965292,
+    6917660518074533236,
+    3221328007639964783,
+    3031869661459267298,
+    6051485632578669430,
+    403779087224861052,
+];
+
+/// The first column of the circulant MDS matrix. Row `i` of the matrix is
+/// this column rotated downwards by `i`. The entries are small, so a row
+/// product fits comfortably in the field without intermediate reductions.
+pub const MDS_MATRIX_FIRST_COLUMN: [u64; STATE_SIZE] = [
+    61402, 1108, 28750, 33823, 7454, 43244, 53865, 12034, 56951, 27521, 41351, 40901, 12021, 59689,
+    26798, 17845,
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct Tip5State {
+    pub state: [BFieldElement; STATE_SIZE],
+}
+
+impl Tip5State {
+    fn new() -> Tip5State {
+        Tip5State {
+            state: [BFieldElement::zero(); STATE_SIZE],
+        }
+    }
+}
+
+/// The Tip5 hash function over the B-field, instantiated with the same
+/// sponge geometry as [`RescuePrimeRegular`]: state size 16, rate 10,
+/// capacity 6, digests of 5 elements. Four state elements go through a
+/// byte-wise lookup table and the rest through the cheap power map x^7, so
+/// prover-side leaf hashing is considerably faster than with Rescue-Prime,
+/// whose rounds need the expensive inverse power map. The lookup table is
+/// what makes the round count of 5 sufficient; an arithmetization
+/// represents it with a lookup argument. Interchangeable with the crate's
+/// other [`AlgebraicHasher`]s, for example in [`Fri`] and [`MerkleTree`],
+/// but of course commits to different roots.
+///
+/// [`RescuePrimeRegular`]: crate::shared_math::rescue_prime_regular::RescuePrimeRegular
+/// [`Fri`]: crate::shared_math::fri::Fri
+/// [`MerkleTree`]: crate::util_types::merkle_tree::MerkleTree
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct Tip5 {}
+
+impl Tip5 {
+    /// The split-and-lookup S-box: decompose the canonical representation
+    /// into bytes, send each byte through [`LOOKUP_TABLE`], and reassemble.
+    #[inline]
+    fn split_and_lookup(element: BFieldElement) -> BFieldElement {
+        let mut bytes = element.value().to_le_bytes();
+        for byte in bytes.iter_mut() {
+            *byte = LOOKUP_TABLE[*byte as usize];
+        }
+        BFieldElement::new(u64::from_le_bytes(bytes))
+    }
+
+    /// The power map x^7, applied to one element.
+    #[inline]
+    fn sbox(element: BFieldElement) -> BFieldElement {
+        let square = element * element;
+        let fourth = square * square;
+        fourth * square * element
+    }
+
+    /// Multiply the state by the circulant MDS matrix whose first column is
+    /// [`MDS_MATRIX_FIRST_COLUMN`].
+    fn mds_matrix(state: &mut [BFieldElement; STATE_SIZE]) {
+        let mut result = [BFieldElement::zero(); STATE_SIZE];
+        for (i, result_element) in result.iter_mut().enumerate() {
+            for (j, state_element) in state.iter().enumerate() {
+                let matrix_entry = MDS_MATRIX_FIRST_COLUMN[(STATE_SIZE + i - j) % STATE_SIZE];
+                *result_element += BFieldElement::from(matrix_entry) * *state_element;
+            }
+        }
+        *state = result;
+    }
+
+    /// Apply one round: the S-box layer (split-and-lookup on the first
+    /// [`NUM_SPLIT_AND_LOOKUP`] elements, the power map on the rest), the
+    /// MDS matrix, and the round constants.
+    fn round(sponge: &mut Tip5State, round_index: usize) {
+        debug_assert!(
+            round_index < NUM_ROUNDS,
+            "Cannot apply {}th round; only have {} in total.",
+            round_index,
+            NUM_ROUNDS
+        );
+
+        for element in sponge.state.iter_mut().take(NUM_SPLIT_AND_LOOKUP) {
+            *element = Self::split_and_lookup(*element);
+        }
+        for element in sponge.state.iter_mut().skip(NUM_SPLIT_AND_LOOKUP) {
+            *element = Self::sbox(*element);
+        }
+
+        Self::mds_matrix(&mut sponge.state);
+
+        for (i, element) in sponge.state.iter_mut().enumerate() {
+            *element += BFieldElement::from(ROUND_CONSTANTS[round_index * STATE_SIZE + i]);
+        }
+    }
+
+    /// Apply the Tip5 permutation to the state of a sponge.
+    fn permutation(sponge: &mut Tip5State) {
+        for round_index in 0..NUM_ROUNDS {
+            Self::round(sponge, round_index);
+        }
+    }
+
+    /// hash_10
+    /// Hash 10 elements, or two digests. There is no padding because
+    /// the input length is fixed.
+    pub fn hash_10(input: &[BFieldElement; 10]) -> [BFieldElement; 5] {
+        let mut sponge = Tip5State::new();
+
+        // absorb once
+        sponge.state[..10].copy_from_slice(input);
+
+        // apply domain separation for fixed-length input
+        sponge.state[10] = BFieldElement::one();
+
+        // apply the permutation
+        Self::permutation(&mut sponge);
+
+        // squeeze once
+        sponge.state[..5].try_into().unwrap()
+    }
+
+    /// hash_varlen hashes an arbitrary number of field elements.
+    ///
+    /// Takes care of padding by applying the padding rule: append a single 1 ∈ Fp
+    /// and as many 0 ∈ Fp elements as required to make the number of input elements
+    /// a multiple of `RATE`.
+    pub fn hash_varlen(input: &[BFieldElement]) -> [BFieldElement; 5] {
+        let mut sponge = Tip5State::new();
+
+        // pad input
+        let mut padded_input = input.to_vec();
+        padded_input.push(BFieldElement::one());
+        while !padded_input.len().is_multiple_of(RATE) {
+            padded_input.push(BFieldElement::zero());
+        }
+
+        // absorb
+        while !padded_input.is_empty() {
+            for (sponge_state_element, input_element) in sponge
+                .state
+                .iter_mut()
+                .take(RATE)
+                .zip_eq(padded_input.iter().take(RATE))
+            {
+                *sponge_state_element += input_element.to_owned();
+            }
+            padded_input.drain(..RATE);
+            Self::permutation(&mut sponge);
+        }
+
+        // squeeze once
+        sponge.state[..5].try_into().unwrap()
+    }
+}
+
+impl SpongeHasher for Tip5 {
+    const RATE: usize = RATE;
+    const STATE_SIZE: usize = STATE_SIZE;
+
+    fn permute(state: &mut [BFieldElement]) {
+        let mut sponge = Tip5State {
+            state: state.try_into().unwrap(),
+        };
+        Self::permutation(&mut sponge);
+        state.copy_from_slice(&sponge.state);
+    }
+}
+
+impl AlgebraicHasher for Tip5 {
+    fn hash_slice(elements: &[BFieldElement]) -> Digest {
+        Digest::new(Tip5::hash_varlen(elements))
+    }
+
+    fn hash_pair(left: &Digest, right: &Digest) -> Digest {
+        let mut input = [BFieldElement::zero(); 10];
+        input[..DIGEST_LENGTH].copy_from_slice(&left.values());
+        input[DIGEST_LENGTH..].copy_from_slice(&right.values());
+        Digest::new(Tip5::hash_10(&input))
+    }
+}
+
+#[cfg(test)]
+mod tip5_tests {
+    use crate::shared_math::other::random_elements;
+    use crate::util_types::merkle_tree::MerkleTree;
+
+    use super::*;
+
+    #[test]
+    fn lookup_table_is_a_permutation_test() {
+        let mut seen = [false; 256];
+        for (i, entry) in LOOKUP_TABLE.into_iter().enumerate() {
+            assert_eq!((i as u64 + 1).pow(3) % 257 - 1, entry as u64);
+            seen[entry as usize] = true;
+        }
+        assert!(seen.into_iter().all(|byte_was_hit| byte_was_hit));
+    }
+
+    #[test]
+    fn tip5_test_vectors_test() {
+        // hash 10
+        let targets_first_batch: [[u64; 5]; 6] = [
+            [
+                7905622115741880060,
+                8486629489914690371,
+                16273543125984424512,
+                2943340092741922445,
+                12654311030830210708,
+            ],
+            [
+                9614442086570066736,
+                68531651742574507,
+                3065231143527487346,
+                13794403734268841952,
+                334448351747448454,
+            ],
+            [
+                13699831691432555288,
+                9730226096144386892,
+                4882848883632115652,
+                6021415938038416031,
+                13107289871688935572,
+            ],
+            [
+                11055391875314740582,
+                5868384220756646858,
+                410697759093281332,
+                3990879349850936324,
+                7327272107510375985,
+            ],
+            [
+                15290577959792021060,
+                4625699693436422109,
+                17764185767425615324,
+                4486234906232746616,
+                3152617566396252596,
+            ],
+            [
+                17647875432824866432,
+                8157568915857558602,
+                3565470473677678189,
+                1509839308312839045,
+                6238386032879378174,
+            ],
+        ];
+        for (i, target) in targets_first_batch.into_iter().enumerate() {
+            let expected = target.map(BFieldElement::new);
+            let mut input = [BFieldElement::zero(); 10];
+            input[input.len() - 1] = BFieldElement::from(i as u64);
+            let actual = Tip5::hash_10(&input);
+            assert_eq!(expected, actual);
+        }
+
+        // hash varlen
+        let targets_second_batch: [[u64; 5]; 6] = [
+            [
+                5471872524083522924,
+                8848408325720313513,
+                10696218012636777748,
+                5880803205920407013,
+                13849818273867930209,
+            ],
+            [
+                17402404827189867459,
+                3327676931255532069,
+                7441809912934867304,
+                16664411244296679963,
+                11796211424448264242,
+            ],
+            [
+                17328486821373872667,
+                5838591264587237197,
+                7249795920454271757,
+                15928761897937150803,
+                10783974317051237344,
+            ],
+            [
+                11839017716470674659,
+                332264320096853061,
+                12298703090302082394,
+                9944108071348050267,
+                9549217719516535586,
+            ],
+            [
+                18272840136784803727,
+                1547803580378493686,
+                12324704001372429086,
+                4740997675074265617,
+                16586195098866524879,
+            ],
+            [
+                80523681117032850,
+                5276803182855051587,
+                6616690948510331011,
+                1996825929598896065,
+                14291834872808291391,
+            ],
+        ];
+        for (i, target) in targets_second_batch.into_iter().enumerate() {
+            let expected = target.map(BFieldElement::new);
+            let input = (0..i as u64).map(BFieldElement::new).collect_vec();
+            let actual = Tip5::hash_varlen(&input);
+            assert_eq!(expected, actual);
+        }
+    }
+
+    #[test]
+    fn tip5_sponge_matches_hash_varlen_test() {
+        for input_length in [0, 3, 10, 17, 20] {
+            let input: Vec<BFieldElement> = random_elements(input_length);
+
+            let mut sponge = Tip5::init();
+            Tip5::absorb(&mut sponge, &input);
+            let squeezed = Tip5::squeeze(&mut sponge, DIGEST_LENGTH);
+            assert_eq!(Tip5::hash_varlen(&input).to_vec(), squeezed);
+
+            // Absorbing the same input in pieces must not change anything,
+            // and neither must asking for more output than one rate holds.
+            let mut piecewise = Tip5::init();
+            let (head, tail) = input.split_at(input_length / 2);
+            Tip5::absorb(&mut piecewise, head);
+            Tip5::absorb(&mut piecewise, tail);
+            let many = Tip5::squeeze(&mut piecewise, 3 * RATE);
+            assert_eq!(Tip5::hash_varlen(&input).to_vec(), many[..DIGEST_LENGTH]);
+        }
+    }
+
+    #[test]
+    fn tip5_merkle_tree_test() {
+        // Tip5 plugs in wherever an AlgebraicHasher is expected.
+        let leaves: Vec<Digest> = random_elements(8);
+        let tree: MerkleTree<Tip5> = MerkleTree::from_digests(&leaves);
+
+        let leaf_index = 3;
+        let auth_path = tree.get_authentication_path(leaf_index);
+        assert!(
+            MerkleTree::<Tip5>::verify_authentication_path_from_leaf_hash(
+                tree.get_root(),
+                leaf_index as u32,
+                leaves[leaf_index],
+                auth_path,
+            )
+        );
+    }
+}
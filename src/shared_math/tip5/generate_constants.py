@@ -0,0 +1,39 @@
+# generate_constants.py
+# Generates the round constants and the lookup table for Tip5 over the
+# B-field, mirroring the procedure used for Rescue-Prime's round constants
+# (SHAKE256 over a parameter seed string; see
+# rescue_prime_regular/rescue_prime_regular.sage).
+
+from hashlib import shake_256
+from math import ceil
+
+p = 2**64 - 2**32 + 1
+m = 16              # state size
+capacity = 6
+security_level = 160
+num_rounds = 5
+
+num_constants = num_rounds * m
+
+bytes_per_int = ceil(len(bin(p)[2:]) / 8) + 1
+seed_string = "Tip5(%i,%i,%i,%i)" % (p, m, capacity, security_level)
+byte_string = shake_256(bytes(seed_string, "ascii")).digest(bytes_per_int * num_constants)
+
+constants = []
+for i in range(num_constants):
+    chunk = byte_string[bytes_per_int * i : bytes_per_int * (i + 1)]
+    integer = sum(256**j * chunk[j] for j in range(len(chunk)))
+    constants.append(integer % p)
+
+print("ROUND_CONSTANTS:")
+for c in constants:
+    print("    %i," % c)
+
+# The lookup table is the offset Fermat cube map over GF(257): the cube map
+# permutes the 256 nonzero residues, and the offsets shift its domain and
+# image to the byte range.
+print("LOOKUP_TABLE:")
+table = [pow(i + 1, 3, 257) - 1 for i in range(256)]
+assert sorted(table) == list(range(256))
+for i in range(0, 256, 8):
+    print("    " + " ".join("%i," % t for t in table[i : i + 8]))
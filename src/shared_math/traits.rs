@@ -5,6 +5,8 @@ use std::fmt::{Debug, Display};
 use std::hash::Hash;
 use std::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
 
+use crate::shared_math::b_field_element::BFieldElement;
+
 pub trait CyclicGroupGenerator
 where
     Self: Sized,
@@ -75,6 +77,7 @@ pub trait FiniteField:
     + Neg<Output = Self>
     + AddAssign
     + MulAssign
+    + MulAssign<BFieldElement>
     + SubAssign
     + FromVecu8
     + New
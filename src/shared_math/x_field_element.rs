@@ -184,7 +184,6 @@ impl XFieldElement {
 }
 
 impl Inverse for XFieldElement {
-    #[must_use]
     fn inverse(&self) -> Self {
         let self_as_poly: Polynomial<BFieldElement> = self.to_owned().into();
         let (_, a, _) = Polynomial::<BFieldElement>::xgcd(self_as_poly, Self::shah_polynomial());
@@ -1032,7 +1031,7 @@ mod x_field_element_test {
 
         let expecteds = [1u64, 3, 9, 27, 81, 243].iter().map(|&x| {
             XFieldElement::new([
-                BFieldElement::new(x as u64),
+                BFieldElement::new(x),
                 BFieldElement::zero(),
                 BFieldElement::zero(),
             ])
@@ -74,7 +74,6 @@ impl Display for TimingReport {
             .step_durations
             .iter()
             .map(|(label, duration)| (label.len(), format!("{} ms", duration.as_millis()).len()))
-            .into_iter()
             .unzip();
         let max_label_width = label_widths.into_iter().max().unwrap_or(0);
         let max_duration_width = duration_widths.into_iter().max().unwrap_or(0);
@@ -101,7 +100,7 @@ impl Display for TimingReport {
 
             let durations_count = durations_mean.len();
 
-            if durations_count % 2 == 0 {
+            if durations_count.is_multiple_of(2) {
                 (durations_mean[durations_count / 2 - 1] + durations_mean[durations_count / 2]) / 2
             } else {
                 durations_mean[durations_count / 2]
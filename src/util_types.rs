@@ -1,7 +1,13 @@
 pub mod algebraic_hasher;
 pub mod blake3_wrapper;
+#[cfg(feature = "std")]
 pub mod database_array;
+#[cfg(feature = "std")]
 pub mod database_vector;
+pub mod fiat_shamir_rng;
+pub mod hasher_kind;
+pub mod index_sampler;
+pub mod keccak256;
 pub mod merkle_tree;
 pub mod mmr;
 pub mod proof_stream;
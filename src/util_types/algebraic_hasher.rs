@@ -1,11 +1,21 @@
-use num_traits::Zero;
-use rayon::prelude::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
+use crate::parallel::prelude::*;
+use num_bigint::BigUint;
+use num_traits::{One, ToPrimitive, Zero};
 
 use crate::shared_math::b_field_element::BFieldElement;
+use crate::shared_math::extension_field_element::ExtensionFieldElement;
 use crate::shared_math::other;
 use crate::shared_math::rescue_prime_digest::Digest;
 use crate::shared_math::x_field_element::XFieldElement;
 
+/// Tag prepended to the seed when deriving X-field scalars with
+/// [`AlgebraicHasher::sample_scalars`]: the ASCII string "sclr".
+pub const SCALAR_SAMPLING_DOMAIN_TAG: BFieldElement = BFieldElement::new(0x7363_6c72);
+
+/// Tag prepended to the seed when deriving base-field weights with
+/// [`AlgebraicHasher::sample_weights`]: the ASCII string "wght".
+pub const WEIGHT_SAMPLING_DOMAIN_TAG: BFieldElement = BFieldElement::new(0x7767_6874);
+
 pub trait AlgebraicHasher: Clone + Send + Sync {
     fn hash_slice(elements: &[BFieldElement]) -> Digest;
     fn hash_pair(left: &Digest, right: &Digest) -> Digest;
@@ -13,6 +23,63 @@ pub trait AlgebraicHasher: Clone + Send + Sync {
         Self::hash_slice(&item.to_sequence())
     }
 
+    /// Hash a variable-length sequence of field elements under an explicit
+    /// padding rule: the sequence length is prepended as one field element
+    /// before hashing. Sequences of different lengths therefore hash to
+    /// different digests even when their raw concatenations coincide --
+    /// e.g. the empty sequence versus a single zero. Plain [`hash_slice`]
+    /// offers no such guarantee for hashers that process the bare
+    /// concatenation, like the blake3 and Keccak-256 wrappers.
+    ///
+    /// [`hash_slice`]: AlgebraicHasher::hash_slice
+    fn hash_sequence(elements: &[BFieldElement]) -> Digest {
+        let mut padded = Vec::with_capacity(elements.len() + 1);
+        padded.push(BFieldElement::new(elements.len() as u64));
+        padded.extend_from_slice(elements);
+        Self::hash_slice(&padded)
+    }
+
+    /// Iterate the hash `count` times over the digest, as used by hash
+    /// chains: zero iterations return the digest unchanged. Inverting `n`
+    /// iterations requires `n` preimage computations, so a chain head
+    /// commits to the whole chain.
+    fn hash_n_times(digest: &Digest, count: usize) -> Digest {
+        let mut current = *digest;
+        for _ in 0..count {
+            current = Self::hash_slice(&current.to_sequence());
+        }
+        current
+    }
+
+    /// Combine any number of digests into one by hashing their concatenated
+    /// element sequences. This is the collision-resistant replacement for
+    /// ad-hoc digest arithmetic like XORing digests together, which lets an
+    /// attacker cancel contributions; here, changing any input digest
+    /// changes the combination.
+    fn combine_digests(digests: &[Digest]) -> Digest {
+        let sequence: Vec<BFieldElement> =
+            digests.iter().flat_map(|digest| digest.values()).collect();
+        Self::hash_slice(&sequence)
+    }
+
+    /// Hash consecutive pairs of a slice of digests: element `i` of the
+    /// output is the hash of elements `2i` and `2i + 1` of the input, which
+    /// must have even length. Equivalent to mapping [`hash_pair`] over the
+    /// pairs -- the default does exactly that -- but hashers with a batched
+    /// permutation kernel override it to process several states at once.
+    ///
+    /// [`hash_pair`]: AlgebraicHasher::hash_pair
+    fn hash_pairwise(children: &[Digest]) -> Vec<Digest> {
+        assert!(
+            children.len().is_multiple_of(2),
+            "Pairwise hashing needs an even number of digests"
+        );
+        children
+            .chunks_exact(2)
+            .map(|pair| Self::hash_pair(&pair[0], &pair[1]))
+            .collect()
+    }
+
     /// Given a uniform random `input` digest and a `max` that is a power of two,
     /// produce a uniform random number in the interval `[0; max)`. The input should
     /// be a Fiat-Shamir digest to ensure a high degree of randomness.
@@ -43,11 +110,42 @@ pub trait AlgebraicHasher: Clone + Send + Sync {
         acc
     }
 
-    // FIXME: This is not uniform.
+    // FIXME: This is not uniform. Kept for callers that depend on the
+    // historical index stream; new code should use `sample_uniform_index`.
     fn sample_index_not_power_of_two(seed: &Digest, max: usize) -> usize {
         Self::sample_index(seed, (1 << 16) * other::roundup_npo2(max as u64) as usize) % max
     }
 
+    /// Map a uniform random digest to an index in `[0; upper_bound)` for an
+    /// arbitrary nonzero upper bound, with constant work and no rejection
+    /// loop.
+    ///
+    /// The digest is read as an integer `v` uniform in `[0; p^5)`, where
+    /// `p` is the field order, and the index is the wide-multiplication
+    /// reduction `v * upper_bound / p^5`. Every index therefore has either
+    /// `floor(p^5 / upper_bound)` or `ceil(p^5 / upper_bound)` preimages,
+    /// bounding the statistical distance from uniform by
+    /// `upper_bound / p^5 < 2^-255` -- unlike the modulo reduction of
+    /// [`sample_index_not_power_of_two`], whose bias is detectable for
+    /// large bounds.
+    ///
+    /// [`sample_index_not_power_of_two`]: AlgebraicHasher::sample_index_not_power_of_two
+    fn sample_uniform_index(seed: &Digest, upper_bound: usize) -> usize {
+        assert!(upper_bound > 0, "Upper bound must be nonzero");
+
+        // The first digest element is the most significant limb: wrappers
+        // around byte-oriented hashers zero-pad the tail of the digest, and
+        // a structurally zero leading limb would collapse the reduction.
+        let modulus = BigUint::from(BFieldElement::QUOTIENT);
+        let mut acc = BigUint::zero();
+        for value in seed.values() {
+            acc = acc * &modulus + BigUint::from(value.value());
+        }
+
+        let index = acc * upper_bound / modulus.pow(seed.values().len() as u32);
+        index.to_usize().unwrap()
+    }
+
     /// Given a uniform random `seed` digest, a `max` that is a power of two,
     /// produce `count` uniform random numbers (sample indices) in the interval
     /// `[0; max)`. The seed should be a Fiat-Shamir digest to ensure a high
@@ -72,6 +170,133 @@ pub trait AlgebraicHasher: Clone + Send + Sync {
 
         digests
     }
+
+    /// Like [`get_n_hash_rounds`], but with a domain tag prepended to the
+    /// seed, so that different consumers of the same Fiat-Shamir seed draw
+    /// from disjoint randomness streams.
+    ///
+    /// [`get_n_hash_rounds`]: AlgebraicHasher::get_n_hash_rounds
+    fn get_n_tagged_hash_rounds(
+        domain_tag: BFieldElement,
+        seed: &Digest,
+        count: usize,
+    ) -> Vec<Digest> {
+        let mut digests = Vec::with_capacity(count);
+        (0..count)
+            .into_par_iter()
+            .map(|i: usize| {
+                Self::hash_slice(&[vec![domain_tag], seed.to_sequence(), i.to_sequence()].concat())
+            })
+            .collect_into_vec(&mut digests);
+
+        digests
+    }
+
+    /// Derive `count` X-field elements from a Fiat-Shamir seed, tagged with
+    /// [`SCALAR_SAMPLING_DOMAIN_TAG`] and a per-element counter.
+    fn sample_scalars(seed: &Digest, count: usize) -> Vec<XFieldElement> {
+        Self::get_n_tagged_hash_rounds(SCALAR_SAMPLING_DOMAIN_TAG, seed, count)
+            .iter()
+            .map(XFieldElement::sample)
+            .collect()
+    }
+
+    /// Derive `count` base-field weights from a Fiat-Shamir seed, tagged
+    /// with [`WEIGHT_SAMPLING_DOMAIN_TAG`] and a per-element counter.
+    fn sample_weights(seed: &Digest, count: usize) -> Vec<BFieldElement> {
+        Self::get_n_tagged_hash_rounds(WEIGHT_SAMPLING_DOMAIN_TAG, seed, count)
+            .iter()
+            .map(|digest| digest.values()[0])
+            .collect()
+    }
+}
+
+/// The state of a [`SpongeHasher`]: the permutation's state elements plus
+/// the bookkeeping for incremental absorption and squeezing. Constructed
+/// with [`SpongeHasher::init`].
+#[derive(Debug, Clone)]
+pub struct SpongeState {
+    state: Vec<BFieldElement>,
+    absorb_index: usize,
+    squeeze_index: usize,
+    squeezing: bool,
+}
+
+/// Stateful absorb/squeeze on top of an [`AlgebraicHasher`]'s permutation.
+///
+/// Where [`AlgebraicHasher::hash_slice`] is one-shot -- a growing input
+/// must be re-hashed from scratch -- a sponge absorbs input incrementally
+/// and produces output of any length, which is what an incremental
+/// Fiat-Shamir transcript needs. The sponge applies the same padding rule
+/// as the crate's variable-length hash functions (append a single 1 ∈ Fp,
+/// then 0 ∈ Fp up to the rate), so absorbing a sequence in one or many
+/// calls and squeezing [`DIGEST_LENGTH`][crate::shared_math::rescue_prime_regular::DIGEST_LENGTH]
+/// elements reproduces the corresponding `hash_varlen` digest.
+/// Absorbing after a squeeze resumes the duplex at the start of the rate.
+pub trait SpongeHasher: AlgebraicHasher {
+    /// The number of state elements absorbed or squeezed per permutation.
+    const RATE: usize;
+
+    /// The full width of the permutation's state.
+    const STATE_SIZE: usize;
+
+    /// Apply the hasher's permutation to a state of [`STATE_SIZE`] elements.
+    ///
+    /// [`STATE_SIZE`]: SpongeHasher::STATE_SIZE
+    fn permute(state: &mut [BFieldElement]);
+
+    /// A sponge in the all-zeros initial state.
+    fn init() -> SpongeState {
+        SpongeState {
+            state: vec![BFieldElement::zero(); Self::STATE_SIZE],
+            absorb_index: 0,
+            squeeze_index: 0,
+            squeezing: false,
+        }
+    }
+
+    /// Absorb a sequence of field elements, permuting whenever the rate is
+    /// full.
+    fn absorb(sponge: &mut SpongeState, input: &[BFieldElement]) {
+        if sponge.squeezing {
+            sponge.squeezing = false;
+            sponge.absorb_index = 0;
+        }
+
+        for element in input {
+            sponge.state[sponge.absorb_index] += *element;
+            sponge.absorb_index += 1;
+            if sponge.absorb_index == Self::RATE {
+                Self::permute(&mut sponge.state);
+                sponge.absorb_index = 0;
+            }
+        }
+    }
+
+    /// Squeeze any number of field elements, permuting whenever the rate is
+    /// exhausted. The first squeeze after an absorption pads and permutes.
+    fn squeeze(sponge: &mut SpongeState, num_elements: usize) -> Vec<BFieldElement> {
+        if !sponge.squeezing {
+            // The unfilled part of the rate is all zeros, so adding the
+            // leading 1 completes the padding rule.
+            sponge.state[sponge.absorb_index] += BFieldElement::one();
+            Self::permute(&mut sponge.state);
+            sponge.absorb_index = 0;
+            sponge.squeeze_index = 0;
+            sponge.squeezing = true;
+        }
+
+        let mut output = Vec::with_capacity(num_elements);
+        for _ in 0..num_elements {
+            if sponge.squeeze_index == Self::RATE {
+                Self::permute(&mut sponge.state);
+                sponge.squeeze_index = 0;
+            }
+            output.push(sponge.state[sponge.squeeze_index]);
+            sponge.squeeze_index += 1;
+        }
+        output
+    }
 }
 
 pub trait Hashable {
@@ -103,6 +328,12 @@ impl Hashable for XFieldElement {
     }
 }
 
+impl<const DEGREE: usize> Hashable for ExtensionFieldElement<DEGREE> {
+    fn to_sequence(&self) -> Vec<BFieldElement> {
+        self.coefficients.to_vec()
+    }
+}
+
 // FIXME: Not safe.
 impl Hashable for usize {
     fn to_sequence(&self) -> Vec<BFieldElement> {
@@ -121,3 +352,34 @@ impl Hashable for BFieldElement {
         vec![*self]
     }
 }
+
+/// Length-prefixed, so that nested structures encode canonically: the
+/// sequence of a vector is its length followed by the sequences of its
+/// items, and can be parsed back unambiguously as long as every item type
+/// encodes either to a fixed width or with its own prefix.
+impl<T: Hashable> Hashable for Vec<T> {
+    fn to_sequence(&self) -> Vec<BFieldElement> {
+        let mut sequence = vec![BFieldElement::new(self.len() as u64)];
+        for item in self {
+            sequence.append(&mut item.to_sequence());
+        }
+        sequence
+    }
+}
+
+impl<T: Hashable, U: Hashable> Hashable for (T, U) {
+    fn to_sequence(&self) -> Vec<BFieldElement> {
+        [self.0.to_sequence(), self.1.to_sequence()].concat()
+    }
+}
+
+/// Tagged with a zero or one element, so `None` is distinguishable from
+/// `Some` of any value.
+impl<T: Hashable> Hashable for Option<T> {
+    fn to_sequence(&self) -> Vec<BFieldElement> {
+        match self {
+            None => vec![BFieldElement::zero()],
+            Some(item) => [vec![BFieldElement::one()], item.to_sequence()].concat(),
+        }
+    }
+}
@@ -15,7 +15,7 @@ impl AlgebraicHasher for blake3::Hasher {
     }
 
     fn hash_pair(left: &Digest, right: &Digest) -> Digest {
-        Self::hash_slice(&vec![left.to_sequence(), right.to_sequence()].concat())
+        Self::hash_slice(&[left.to_sequence(), right.to_sequence()].concat())
     }
 }
 
@@ -143,6 +143,24 @@ impl<T: Serialize + DeserializeOwned> DatabaseVector<T> {
         self.set_length(length + 1);
     }
 
+    /// Append many values with a single write batch. Much cheaper than
+    /// repeated `push`es, since the length is only updated once and the
+    /// values hit the database together.
+    pub fn batch_push(&mut self, values: &[T]) {
+        let length = self.len();
+        let mut batch_write = WriteBatch::new();
+        for (offset, value) in values.iter().enumerate() {
+            let index_bytes: Vec<u8> = bincode::serialize(&(length + offset as u128)).unwrap();
+            let value_bytes: Vec<u8> = bincode::serialize(value).unwrap();
+            batch_write.put(&index_bytes, &value_bytes);
+        }
+
+        self.db
+            .write(batch_write, true)
+            .expect("Failed to batch-write to database");
+        self.set_length(length + values.len() as u128);
+    }
+
     /// Dispose of the vector and return the database. You should probably only use this for testing.
     pub fn extract_db(self) -> DB {
         self.db
@@ -203,6 +221,22 @@ mod database_vector_tests {
         assert_eq!(17, db_vector.get(39));
     }
 
+    #[test]
+    fn batch_push_test() {
+        let opt = rusty_leveldb::in_memory();
+        let db = DB::open("mydatabase", opt).unwrap();
+        let mut db_vector: DatabaseVector<u64> = DatabaseVector::new(db);
+        db_vector.push(17);
+
+        // Batch-push and verify length and values
+        db_vector.batch_push(&[4040, 4141, 4444]);
+        assert_eq!(4, db_vector.len());
+        assert_eq!(17, db_vector.get(0));
+        assert_eq!(4040, db_vector.get(1));
+        assert_eq!(4141, db_vector.get(2));
+        assert_eq!(4444, db_vector.get(3));
+    }
+
     #[test]
     fn push_many_test() {
         let opt = rusty_leveldb::in_memory();
@@ -0,0 +1,153 @@
+use std::marker::PhantomData;
+
+use rand_core::{CryptoRng, Error, RngCore};
+
+use crate::shared_math::rescue_prime_digest::Digest;
+use crate::util_types::algebraic_hasher::{AlgebraicHasher, Hashable};
+
+/// A deterministic random number generator seeded from a Fiat-Shamir
+/// transcript digest, behind the standard [`RngCore`] interface.
+///
+/// Output bytes come from the same hash-counter construction as
+/// [`IndexSampler`](crate::util_types::index_sampler::IndexSampler): each
+/// block hashes the seed together with a running counter, and the digest's
+/// field elements are serialized as canonical little-endian bytes. Prover
+/// and verifier constructing the generator from the same seed and drawing
+/// the same amounts obtain identical streams, so all of `rand`'s machinery
+/// -- shuffles, distributions, `Rng::gen` -- stays transcript-derived and
+/// reproducible.
+///
+/// The canonical representation of a field element is bounded by the
+/// modulus, so the top bytes of every eight-byte group are very slightly
+/// biased; the bias is of the order `2^-32` and irrelevant for challenge
+/// generation.
+#[derive(Debug, Clone)]
+pub struct FiatShamirRng<H: AlgebraicHasher> {
+    seed: Digest,
+    counter: u32,
+    buffer: Vec<u8>,
+    _hasher: PhantomData<H>,
+}
+
+impl<H: AlgebraicHasher> FiatShamirRng<H> {
+    pub fn new(seed: &Digest) -> Self {
+        Self {
+            seed: *seed,
+            counter: 0,
+            buffer: vec![],
+            _hasher: PhantomData,
+        }
+    }
+
+    /// Refill the byte buffer with the serialization of the next challenge
+    /// digest: the hash of the seed and the running counter.
+    fn refill_buffer(&mut self) {
+        let mut sequence = self.seed.to_sequence();
+        sequence.append(&mut self.counter.to_sequence());
+        self.counter += 1;
+
+        let digest = H::hash_slice(&sequence);
+        self.buffer = digest
+            .values()
+            .iter()
+            .flat_map(|element| element.value().to_le_bytes())
+            .collect();
+    }
+}
+
+impl<H: AlgebraicHasher> RngCore for FiatShamirRng<H> {
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0u8; 4];
+        self.fill_bytes(&mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0u8; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        let mut written = 0;
+        while written < dest.len() {
+            if self.buffer.is_empty() {
+                self.refill_buffer();
+            }
+            let num_bytes = usize::min(dest.len() - written, self.buffer.len());
+            dest[written..written + num_bytes].copy_from_slice(&self.buffer[..num_bytes]);
+            self.buffer.drain(..num_bytes);
+            written += num_bytes;
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl<H: AlgebraicHasher> CryptoRng for FiatShamirRng<H> {}
+
+#[cfg(test)]
+mod fiat_shamir_rng_tests {
+    use rand::seq::SliceRandom;
+    use rand::Rng;
+
+    use super::*;
+    use crate::shared_math::other::random_elements_array;
+
+    #[test]
+    fn fiat_shamir_rng_is_deterministic_test() {
+        type H = blake3::Hasher;
+
+        let seed = Digest::new(random_elements_array());
+        let mut first_rng = FiatShamirRng::<H>::new(&seed);
+        let mut second_rng = FiatShamirRng::<H>::new(&seed);
+
+        // The same draws from the same seed yield the same stream ...
+        let first_draws: Vec<u64> = (0..100).map(|_| first_rng.next_u64()).collect();
+        let second_draws: Vec<u64> = (0..100).map(|_| second_rng.next_u64()).collect();
+        assert_eq!(first_draws, second_draws);
+
+        // ... across differently sized requests straddling block boundaries.
+        let mut first_bytes = [0u8; 131];
+        first_rng.fill_bytes(&mut first_bytes);
+        let mut second_bytes = [0u8; 131];
+        for chunk in second_bytes.chunks_mut(7) {
+            second_rng.fill_bytes(chunk);
+        }
+        assert_eq!(first_bytes, second_bytes);
+
+        // A different seed yields a different stream.
+        let other_seed = Digest::new(random_elements_array());
+        let mut other_rng = FiatShamirRng::<H>::new(&other_seed);
+        assert_ne!(first_draws[0], other_rng.next_u64());
+    }
+
+    #[test]
+    fn fiat_shamir_rng_drives_rand_apis_test() {
+        type H = blake3::Hasher;
+
+        let seed = Digest::new(random_elements_array());
+        let mut rng = FiatShamirRng::<H>::new(&seed);
+
+        // Standard rand machinery runs on top of the adapter and inherits
+        // its determinism.
+        let mut shuffled: Vec<usize> = (0..64).collect();
+        shuffled.shuffle(&mut rng);
+        let in_range: Vec<u32> = (0..32).map(|_| rng.gen_range(0..1000)).collect();
+
+        let mut replay_rng = FiatShamirRng::<H>::new(&seed);
+        let mut replayed: Vec<usize> = (0..64).collect();
+        replayed.shuffle(&mut replay_rng);
+        assert_eq!(shuffled, replayed);
+        assert_eq!(
+            in_range,
+            (0..32)
+                .map(|_| replay_rng.gen_range(0..1000))
+                .collect::<Vec<u32>>()
+        );
+        assert!(in_range.iter().all(|&value| value < 1000));
+    }
+}
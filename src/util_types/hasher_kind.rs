@@ -0,0 +1,137 @@
+use serde::{Deserialize, Serialize};
+
+use crate::shared_math::b_field_element::BFieldElement;
+use crate::shared_math::poseidon2::Poseidon2;
+use crate::shared_math::rescue_prime_digest::Digest;
+use crate::shared_math::rescue_prime_regular::RescuePrimeRegular;
+use crate::shared_math::tip5::Tip5;
+use crate::util_types::algebraic_hasher::AlgebraicHasher;
+use crate::util_types::keccak256::Keccak256;
+
+/// The crate's hash backends as a runtime value, so serialized proofs can
+/// record which hasher produced them and a verifier can dispatch without
+/// being generic over the hasher type. The dispatching methods forward to
+/// the corresponding [`AlgebraicHasher`] implementation; for anything
+/// beyond hashing -- index sampling, sponge use -- match on the kind and
+/// instantiate the generic code path with the concrete type.
+///
+/// Digests convert between their field-element form and the 32-byte form
+/// byte-oriented consumers expect through the [`Digest`] `From`
+/// conversions; the byte-to-digest direction uses the same packing as the
+/// blake3 and Keccak-256 wrappers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HasherKind {
+    RescuePrime,
+    Blake3,
+    Keccak256,
+    Poseidon2,
+    Tip5,
+}
+
+impl HasherKind {
+    /// [`AlgebraicHasher::hash_slice`] under the selected backend.
+    pub fn hash_slice(&self, elements: &[BFieldElement]) -> Digest {
+        match self {
+            HasherKind::RescuePrime => RescuePrimeRegular::hash_slice(elements),
+            HasherKind::Blake3 => blake3::Hasher::hash_slice(elements),
+            HasherKind::Keccak256 => Keccak256::hash_slice(elements),
+            HasherKind::Poseidon2 => Poseidon2::hash_slice(elements),
+            HasherKind::Tip5 => Tip5::hash_slice(elements),
+        }
+    }
+
+    /// [`AlgebraicHasher::hash_pair`] under the selected backend.
+    pub fn hash_pair(&self, left: &Digest, right: &Digest) -> Digest {
+        match self {
+            HasherKind::RescuePrime => RescuePrimeRegular::hash_pair(left, right),
+            HasherKind::Blake3 => blake3::Hasher::hash_pair(left, right),
+            HasherKind::Keccak256 => Keccak256::hash_pair(left, right),
+            HasherKind::Poseidon2 => Poseidon2::hash_pair(left, right),
+            HasherKind::Tip5 => Tip5::hash_pair(left, right),
+        }
+    }
+
+    /// [`AlgebraicHasher::hash_sequence`] under the selected backend.
+    pub fn hash_sequence(&self, elements: &[BFieldElement]) -> Digest {
+        match self {
+            HasherKind::RescuePrime => RescuePrimeRegular::hash_sequence(elements),
+            HasherKind::Blake3 => blake3::Hasher::hash_sequence(elements),
+            HasherKind::Keccak256 => Keccak256::hash_sequence(elements),
+            HasherKind::Poseidon2 => Poseidon2::hash_sequence(elements),
+            HasherKind::Tip5 => Tip5::hash_sequence(elements),
+        }
+    }
+}
+
+#[cfg(test)]
+mod hasher_kind_tests {
+    use num_traits::Zero;
+
+    use crate::shared_math::other::random_elements;
+    use crate::shared_math::rescue_prime_digest::MSG_DIGEST_SIZE_IN_BYTES;
+
+    use super::*;
+
+    #[test]
+    fn hasher_kind_dispatch_test() {
+        let elements: Vec<BFieldElement> = random_elements(7);
+        let left = Tip5::hash_slice(&elements);
+        let right = RescuePrimeRegular::hash_slice(&elements);
+
+        // Dynamic dispatch must agree with the statically typed hashers ...
+        assert_eq!(
+            RescuePrimeRegular::hash_slice(&elements),
+            HasherKind::RescuePrime.hash_slice(&elements)
+        );
+        assert_eq!(
+            Keccak256::hash_pair(&left, &right),
+            HasherKind::Keccak256.hash_pair(&left, &right)
+        );
+        assert_eq!(
+            Poseidon2::hash_sequence(&elements),
+            HasherKind::Poseidon2.hash_sequence(&elements)
+        );
+
+        // ... and the backends must disagree with each other.
+        let kinds = [
+            HasherKind::RescuePrime,
+            HasherKind::Blake3,
+            HasherKind::Keccak256,
+            HasherKind::Poseidon2,
+            HasherKind::Tip5,
+        ];
+        for (i, first) in kinds.into_iter().enumerate() {
+            for second in kinds.into_iter().skip(i + 1) {
+                assert_ne!(first.hash_slice(&elements), second.hash_slice(&elements));
+            }
+        }
+    }
+
+    #[test]
+    fn hasher_kind_serialization_test() {
+        for kind in [
+            HasherKind::RescuePrime,
+            HasherKind::Blake3,
+            HasherKind::Keccak256,
+            HasherKind::Poseidon2,
+            HasherKind::Tip5,
+        ] {
+            let serialized = bincode::serialize(&kind).unwrap();
+            let deserialized: HasherKind = bincode::deserialize(&serialized).unwrap();
+            assert_eq!(kind, deserialized);
+        }
+    }
+
+    #[test]
+    fn digest_byte_conversion_test() {
+        // A 32-byte message digest packs into four field elements plus a
+        // zero, exactly like the blake3 and Keccak-256 wrappers pack their
+        // outputs, and converts back to the same bytes.
+        let bytes: [u8; MSG_DIGEST_SIZE_IN_BYTES] = core::array::from_fn(|i| i as u8);
+        let digest: Digest = bytes.into();
+        assert!(digest.values()[4].is_zero());
+
+        let round_trip: [u8; MSG_DIGEST_SIZE_IN_BYTES] = digest.into();
+        assert_eq!(bytes, round_trip);
+    }
+}
@@ -0,0 +1,241 @@
+use std::collections::HashMap;
+use std::marker::PhantomData;
+
+use crate::shared_math::rescue_prime_digest::Digest;
+use crate::util_types::algebraic_hasher::{AlgebraicHasher, Hashable};
+
+/// A deterministic stream of pseudorandom indices derived from a Fiat-Shamir
+/// seed.
+///
+/// Each sample hashes the seed together with a running counter, so prover and
+/// verifier obtain the same indices by constructing a sampler from the same
+/// seed and issuing the same sequence of calls. Distinct-index sampling uses
+/// a sparse Fisher-Yates shuffle and runs in time linear in the number of
+/// requested indices, independent of the sampling range.
+#[derive(Debug, Clone)]
+pub struct IndexSampler<H: AlgebraicHasher> {
+    seed: Digest,
+    counter: u32,
+    _hasher: PhantomData<H>,
+}
+
+impl<H: AlgebraicHasher> IndexSampler<H> {
+    pub fn new(seed: &Digest) -> Self {
+        Self {
+            seed: *seed,
+            counter: 0,
+            _hasher: PhantomData,
+        }
+    }
+
+    /// The next challenge digest: the hash of the seed and the running
+    /// counter.
+    fn next_digest(&mut self) -> Digest {
+        let mut sequence = self.seed.to_sequence();
+        sequence.append(&mut self.counter.to_sequence());
+        self.counter += 1;
+        H::hash_slice(&sequence)
+    }
+
+    /// Sample one index in the range `[0, upper_bound)`. The upper bound must
+    /// be a power of two.
+    pub fn sample_index(&mut self, upper_bound: usize) -> usize {
+        H::sample_index(&self.next_digest(), upper_bound)
+    }
+
+    /// Sample one index in the range `[0, upper_bound)` for an arbitrary
+    /// upper bound, via the rejection-free wide-multiplication reduction of
+    /// [`AlgebraicHasher::sample_uniform_index`].
+    pub fn sample_index_not_power_of_two(&mut self, upper_bound: usize) -> usize {
+        H::sample_uniform_index(&self.next_digest(), upper_bound)
+    }
+
+    /// Sample `count` distinct indices in the range `[0, upper_bound)`.
+    ///
+    /// Implemented as a Fisher-Yates shuffle over a sparse representation of
+    /// the index range, so no rejection loop is needed and the running time
+    /// is `O(count)` regardless of `upper_bound`.
+    pub fn sample_distinct_indices(&mut self, count: usize, upper_bound: usize) -> Vec<usize> {
+        assert!(
+            count <= upper_bound,
+            "Cannot sample more distinct indices than the range holds"
+        );
+
+        // `swaps` sparsely records the permutation the shuffle builds up:
+        // absent keys still hold their own value.
+        let mut swaps: HashMap<usize, usize> = HashMap::new();
+        let mut indices: Vec<usize> = Vec::with_capacity(count);
+        for i in 0..count {
+            let pick = i + self.sample_index_not_power_of_two(upper_bound - i);
+            indices.push(*swaps.get(&pick).unwrap_or(&pick));
+            let replacement = *swaps.get(&i).unwrap_or(&i);
+            swaps.insert(pick, replacement);
+        }
+
+        indices
+    }
+}
+
+#[cfg(test)]
+mod index_sampler_tests {
+    use itertools::Itertools;
+
+    use super::*;
+    use crate::shared_math::b_field_element::BFieldElement;
+    use crate::shared_math::other::random_elements_array;
+
+    #[test]
+    fn sample_uniform_index_statistics_test() {
+        type H = blake3::Hasher;
+
+        // A bound the modulo reduction is maximally biased for: just above
+        // a power of two.
+        let upper_bound = 33;
+        let num_samples = 33_000;
+
+        let seed = Digest::new(random_elements_array());
+        let mut sampler = IndexSampler::<H>::new(&seed);
+        let mut histogram = vec![0usize; upper_bound];
+        for _ in 0..num_samples {
+            histogram[sampler.sample_index_not_power_of_two(upper_bound)] += 1;
+        }
+
+        // Every bucket expects 1000 hits with a standard deviation of about
+        // 31; a five-sigma corridor keeps the deterministic stream safe.
+        assert!(histogram.iter().all(|&count| (850..=1150).contains(&count)));
+
+        // Small bounds are exhaustively covered, including the edges.
+        let mut edge_sampler = IndexSampler::<H>::new(&seed);
+        let small: Vec<usize> = (0..100)
+            .map(|_| edge_sampler.sample_index_not_power_of_two(3))
+            .collect();
+        assert!(small.iter().all(|&index| index < 3));
+        assert!((0..3).all(|index| small.contains(&index)));
+    }
+
+    #[test]
+    fn hash_sequence_length_padding_test() {
+        type H = blake3::Hasher;
+
+        // The raw concatenation cannot tell the empty sequence from a run
+        // of zeros; the length prefix can.
+        let zero = BFieldElement::new(0);
+        assert_ne!(H::hash_sequence(&[]), H::hash_sequence(&[zero]));
+        assert_ne!(H::hash_sequence(&[zero]), H::hash_sequence(&[zero, zero]));
+
+        // Deterministic, and distinct from the unpadded hash.
+        let elements = vec![BFieldElement::new(42); 7];
+        assert_eq!(H::hash_sequence(&elements), H::hash_sequence(&elements));
+        assert_ne!(H::hash_sequence(&elements), H::hash_slice(&elements));
+    }
+
+    #[test]
+    fn hash_chain_and_digest_combination_test() {
+        type H = blake3::Hasher;
+
+        let digest = Digest::new(random_elements_array());
+
+        // Zero iterations is the identity, one iteration is one hash, and
+        // chains compose.
+        assert_eq!(digest, H::hash_n_times(&digest, 0));
+        assert_eq!(H::hash(&digest), H::hash_n_times(&digest, 1));
+        assert_eq!(
+            H::hash_n_times(&H::hash_n_times(&digest, 3), 4),
+            H::hash_n_times(&digest, 7)
+        );
+
+        // The combination of digests depends on every input and its order.
+        let other = Digest::new(random_elements_array());
+        let combined = H::combine_digests(&[digest, other]);
+        assert_eq!(combined, H::combine_digests(&[digest, other]));
+        assert_ne!(combined, H::combine_digests(&[other, digest]));
+        assert_ne!(combined, H::combine_digests(&[digest]));
+    }
+
+    #[test]
+    fn nested_hashable_encoding_test() {
+        type H = blake3::Hasher;
+
+        // Vectors are length-prefixed: the empty vector is not a zero.
+        let empty: Vec<BFieldElement> = vec![];
+        assert_ne!(H::hash(&empty), H::hash(&vec![BFieldElement::new(0)]));
+
+        // Nested vectors with the same flattening encode differently.
+        let unbalanced = vec![vec![BFieldElement::new(1), BFieldElement::new(2)], vec![]];
+        let balanced = vec![vec![BFieldElement::new(1)], vec![BFieldElement::new(2)]];
+        assert_ne!(H::hash(&unbalanced), H::hash(&balanced));
+
+        // Options are tagged, so `None` differs from every `Some`.
+        assert_ne!(
+            H::hash(&None::<BFieldElement>),
+            H::hash(&Some(BFieldElement::new(0)))
+        );
+
+        // Tuples concatenate their components' sequences.
+        let pair = (BFieldElement::new(5), Digest::new(random_elements_array()));
+        assert_eq!(
+            H::hash(&pair),
+            H::hash_slice(&[pair.0.to_sequence(), pair.1.to_sequence()].concat())
+        );
+    }
+
+    #[test]
+    fn sample_scalars_and_weights_test() {
+        type H = blake3::Hasher;
+
+        let seed = Digest::new(random_elements_array());
+
+        // Deterministic in the seed, with one counter per element.
+        let scalars = H::sample_scalars(&seed, 30);
+        assert_eq!(scalars, H::sample_scalars(&seed, 30));
+        assert_eq!(30, scalars.iter().unique().count());
+
+        let weights = H::sample_weights(&seed, 30);
+        assert_eq!(weights, H::sample_weights(&seed, 30));
+        assert_eq!(30, weights.iter().unique().count());
+
+        // The two streams are domain separated: the weights are not a
+        // projection of the scalars drawn from the same seed.
+        let scalar_constant_terms: Vec<_> = scalars
+            .iter()
+            .map(|scalar| scalar.coefficients[0])
+            .collect();
+        assert_ne!(scalar_constant_terms, weights);
+
+        // A shorter request is a prefix of a longer one.
+        assert_eq!(scalars[..10], H::sample_scalars(&seed, 10)[..]);
+        assert_eq!(weights[..10], H::sample_weights(&seed, 10)[..]);
+    }
+
+    #[test]
+    fn index_sampler_test() {
+        type H = blake3::Hasher;
+
+        let seed = Digest::new(random_elements_array());
+        let mut sampler = IndexSampler::<H>::new(&seed);
+
+        // Bounds are respected
+        for _ in 0..100 {
+            assert!(sampler.sample_index(64) < 64);
+            assert!(sampler.sample_index_not_power_of_two(100) < 100);
+        }
+
+        // Distinct sampling yields distinct in-range indices ...
+        let indices = sampler.sample_distinct_indices(50, 64);
+        assert_eq!(50, indices.len());
+        assert_eq!(50, indices.iter().unique().count());
+        assert!(indices.iter().all(|&index| index < 64));
+
+        // ... including the degenerate full-range case
+        let all_indices = sampler.sample_distinct_indices(16, 16);
+        assert_eq!(16, all_indices.iter().unique().count());
+
+        // The stream is deterministic in the seed
+        let mut first_sampler = IndexSampler::<H>::new(&seed);
+        let mut second_sampler = IndexSampler::<H>::new(&seed);
+        assert_eq!(
+            first_sampler.sample_distinct_indices(20, 1 << 20),
+            second_sampler.sample_distinct_indices(20, 1 << 20)
+        );
+    }
+}
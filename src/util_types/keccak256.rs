@@ -0,0 +1,267 @@
+use num_traits::Zero;
+use serde::{Deserialize, Serialize};
+
+use crate::shared_math::b_field_element::BFieldElement;
+use crate::shared_math::rescue_prime_digest::Digest;
+use crate::util_types::algebraic_hasher::{AlgebraicHasher, Hashable};
+
+/// The number of bytes absorbed per permutation: 1600 state bits minus the
+/// 512-bit capacity of Keccak-256.
+const RATE_IN_BYTES: usize = 136;
+
+const NUM_ROUNDS: usize = 24;
+
+const ROUND_CONSTANTS: [u64; NUM_ROUNDS] = [
+    0x0000_0000_0000_0001,
+    0x0000_0000_0000_8082,
+    0x8000_0000_0000_808a,
+    0x8000_0000_8000_8000,
+    0x0000_0000_0000_808b,
+    0x0000_0000_8000_0001,
+    0x8000_0000_8000_8081,
+    0x8000_0000_0000_8009,
+    0x0000_0000_0000_008a,
+    0x0000_0000_0000_0088,
+    0x0000_0000_8000_8009,
+    0x0000_0000_8000_000a,
+    0x0000_0000_8000_808b,
+    0x8000_0000_0000_008b,
+    0x8000_0000_0000_8089,
+    0x8000_0000_0000_8003,
+    0x8000_0000_0000_8002,
+    0x8000_0000_0000_0080,
+    0x0000_0000_0000_800a,
+    0x8000_0000_8000_000a,
+    0x8000_0000_8000_8081,
+    0x8000_0000_0000_8080,
+    0x0000_0000_8000_0001,
+    0x8000_0000_8000_8008,
+];
+
+/// The rho-step rotation amounts, in the lane order of [`PI`].
+const RHO: [u32; 24] = [
+    1, 3, 6, 10, 15, 21, 28, 36, 45, 55, 2, 14, 27, 41, 56, 8, 25, 43, 62, 18, 39, 61, 20, 44,
+];
+
+/// The pi-step lane permutation: in every iteration, the current lane moves
+/// to the listed index.
+const PI: [usize; 24] = [
+    10, 7, 11, 17, 18, 3, 5, 16, 8, 21, 24, 4, 15, 23, 19, 13, 12, 2, 20, 14, 22, 9, 6, 1,
+];
+
+/// The Keccak-f[1600] permutation over the 5x5 lane state.
+fn keccak_f(state: &mut [u64; 25]) {
+    for round_constant in ROUND_CONSTANTS {
+        // theta
+        let mut column_parities = [0u64; 5];
+        for (i, parity) in column_parities.iter_mut().enumerate() {
+            *parity = state[i] ^ state[i + 5] ^ state[i + 10] ^ state[i + 15] ^ state[i + 20];
+        }
+        for i in 0..5 {
+            let diffusion =
+                column_parities[(i + 4) % 5] ^ column_parities[(i + 1) % 5].rotate_left(1);
+            for row_start in (0..25).step_by(5) {
+                state[row_start + i] ^= diffusion;
+            }
+        }
+
+        // rho and pi
+        let mut lane = state[1];
+        for (rotation, target) in RHO.into_iter().zip(PI) {
+            let displaced = state[target];
+            state[target] = lane.rotate_left(rotation);
+            lane = displaced;
+        }
+
+        // chi
+        for row_start in (0..25).step_by(5) {
+            let row: [u64; 5] = state[row_start..row_start + 5].try_into().unwrap();
+            for i in 0..5 {
+                state[row_start + i] = row[i] ^ (!row[(i + 1) % 5] & row[(i + 2) % 5]);
+            }
+        }
+
+        // iota
+        state[0] ^= round_constant;
+    }
+}
+
+/// The Keccak-256 hash of a byte string: the original Keccak submission
+/// with the multi-rate padding byte `0x01`, as deployed in the EVM's
+/// `KECCAK256` opcode -- not the `0x06` padding of standardized SHA3-256.
+pub fn keccak256(input: &[u8]) -> [u8; 32] {
+    let mut state = [0u64; 25];
+
+    // pad input
+    let mut padded_input = input.to_vec();
+    padded_input.push(0x01);
+    while !padded_input.len().is_multiple_of(RATE_IN_BYTES) {
+        padded_input.push(0x00);
+    }
+    *padded_input.last_mut().unwrap() |= 0x80;
+
+    // absorb
+    for block in padded_input.chunks_exact(RATE_IN_BYTES) {
+        for (lane, lane_bytes) in state.iter_mut().zip(block.chunks_exact(8)) {
+            *lane ^= u64::from_le_bytes(lane_bytes.try_into().unwrap());
+        }
+        keccak_f(&mut state);
+    }
+
+    // squeeze once
+    let mut digest = [0u8; 32];
+    for (digest_bytes, lane) in digest.chunks_exact_mut(8).zip(state) {
+        digest_bytes.copy_from_slice(&lane.to_le_bytes());
+    }
+    digest
+}
+
+/// The SHAKE256 extendable-output function: the same sponge as
+/// [`keccak256`], but with the standardized `0x1f` domain padding and an
+/// arbitrary output length. This is the function the reference Rescue-Prime
+/// scripts use to derive round constants from a seed string.
+pub fn shake256(input: &[u8], num_output_bytes: usize) -> Vec<u8> {
+    let mut state = [0u64; 25];
+
+    // pad input
+    let mut padded_input = input.to_vec();
+    padded_input.push(0x1f);
+    while !padded_input.len().is_multiple_of(RATE_IN_BYTES) {
+        padded_input.push(0x00);
+    }
+    *padded_input.last_mut().unwrap() |= 0x80;
+
+    // absorb
+    for block in padded_input.chunks_exact(RATE_IN_BYTES) {
+        for (lane, lane_bytes) in state.iter_mut().zip(block.chunks_exact(8)) {
+            *lane ^= u64::from_le_bytes(lane_bytes.try_into().unwrap());
+        }
+        keccak_f(&mut state);
+    }
+
+    // squeeze one rate's worth of bytes per permutation
+    let mut output = Vec::with_capacity(num_output_bytes + RATE_IN_BYTES);
+    while output.len() < num_output_bytes {
+        for lane in state.iter().take(RATE_IN_BYTES / 8) {
+            output.extend_from_slice(&lane.to_le_bytes());
+        }
+        keccak_f(&mut state);
+    }
+    output.truncate(num_output_bytes);
+    output
+}
+
+/// Keccak-256 behind the [`AlgebraicHasher`] interface, for commitments
+/// that an EVM contract can verify with the native `KECCAK256` opcode.
+/// Every field element is encoded as one 32-byte EVM word -- 24 zero bytes
+/// followed by the canonical value, big-endian -- so the preimage of a hash
+/// is exactly what Solidity's `abi.encode` produces for the corresponding
+/// `uint256` values. The 32 digest bytes are packed into the first four
+/// elements of a [`Digest`], like the blake3 wrapper does.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct Keccak256 {}
+
+impl AlgebraicHasher for Keccak256 {
+    fn hash_slice(elements: &[BFieldElement]) -> Digest {
+        let mut words: Vec<u8> = Vec::with_capacity(32 * elements.len());
+        for element in elements {
+            words.extend_from_slice(&[0u8; 24]);
+            words.extend_from_slice(&element.value().to_be_bytes());
+        }
+
+        let bytes = keccak256(&words);
+        Digest::new([
+            BFieldElement::from_ne_bytes(&bytes[0..8]),
+            BFieldElement::from_ne_bytes(&bytes[8..16]),
+            BFieldElement::from_ne_bytes(&bytes[16..24]),
+            BFieldElement::from_ne_bytes(&bytes[24..32]),
+            BFieldElement::zero(),
+        ])
+    }
+
+    fn hash_pair(left: &Digest, right: &Digest) -> Digest {
+        Self::hash_slice(&[left.to_sequence(), right.to_sequence()].concat())
+    }
+}
+
+#[cfg(test)]
+mod keccak256_tests {
+    use crate::shared_math::other::random_elements;
+    use crate::util_types::merkle_tree::MerkleTree;
+
+    use super::*;
+
+    #[test]
+    fn keccak256_known_answers_test() {
+        // The standard Keccak (not SHA3) test vectors, as computed by the
+        // EVM's KECCAK256 opcode.
+        let empty_string_hash: [u8; 32] = [
+            0xc5, 0xd2, 0x46, 0x01, 0x86, 0xf7, 0x23, 0x3c, 0x92, 0x7e, 0x7d, 0xb2, 0xdc, 0xc7,
+            0x03, 0xc0, 0xe5, 0x00, 0xb6, 0x53, 0xca, 0x82, 0x27, 0x3b, 0x7b, 0xfa, 0xd8, 0x04,
+            0x5d, 0x85, 0xa4, 0x70,
+        ];
+        assert_eq!(empty_string_hash, keccak256(b""));
+
+        let abc_hash: [u8; 32] = [
+            0x4e, 0x03, 0x65, 0x7a, 0xea, 0x45, 0xa9, 0x4f, 0xc7, 0xd4, 0x7b, 0xa8, 0x26, 0xc8,
+            0xd6, 0x67, 0xc0, 0xd1, 0xe6, 0xe3, 0x3a, 0x64, 0xa0, 0x36, 0xec, 0x44, 0xf5, 0x8f,
+            0xa1, 0x2d, 0x6c, 0x45,
+        ];
+        assert_eq!(abc_hash, keccak256(b"abc"));
+
+        // Two blocks' worth of input exercises the multi-absorption path.
+        let long_input = vec![0xa5u8; 2 * RATE_IN_BYTES + 17];
+        assert_ne!(keccak256(&long_input), keccak256(&long_input[1..]));
+    }
+
+    #[test]
+    fn shake256_known_answer_test() {
+        // The standard SHAKE256 vector for the empty string.
+        let empty_string_output: [u8; 32] = [
+            0x46, 0xb9, 0xdd, 0x2b, 0x0b, 0xa8, 0x8d, 0x13, 0x23, 0x3b, 0x3f, 0xeb, 0x74, 0x3e,
+            0xeb, 0x24, 0x3f, 0xcd, 0x52, 0xea, 0x62, 0xb8, 0x1b, 0x82, 0xb5, 0x0c, 0x27, 0x64,
+            0x6e, 0xd5, 0x76, 0x2f,
+        ];
+        assert_eq!(empty_string_output.to_vec(), shake256(b"", 32));
+
+        // Longer outputs extend shorter ones, across the one-block boundary.
+        let long_output = shake256(b"abc", 2 * RATE_IN_BYTES + 17);
+        assert_eq!(shake256(b"abc", 32), long_output[..32]);
+    }
+
+    #[test]
+    fn keccak256_word_encoding_test() {
+        // hash_slice must hash the concatenation of 32-byte big-endian EVM
+        // words, so a contract can recompute it from uint256 values.
+        let elements = [BFieldElement::new(1), BFieldElement::new(0xdead_beef)];
+        let mut words = [0u8; 64];
+        words[31] = 1;
+        words[60..64].copy_from_slice(&0xdead_beefu32.to_be_bytes());
+
+        let expected = keccak256(&words);
+        let digest_values = Keccak256::hash_slice(&elements).values();
+        let mut actual = [0u8; 32];
+        for (chunk, value) in actual.chunks_exact_mut(8).zip(digest_values) {
+            chunk.copy_from_slice(&value.value().to_ne_bytes());
+        }
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn keccak256_merkle_tree_test() {
+        // Keccak-256 plugs in wherever an AlgebraicHasher is expected.
+        let leaves: Vec<Digest> = random_elements(8);
+        let tree: MerkleTree<Keccak256> = MerkleTree::from_digests(&leaves);
+
+        let leaf_index = 6;
+        let auth_path = tree.get_authentication_path(leaf_index);
+        assert!(
+            MerkleTree::<Keccak256>::verify_authentication_path_from_leaf_hash(
+                tree.get_root(),
+                leaf_index as u32,
+                leaves[leaf_index],
+                auth_path,
+            )
+        );
+    }
+}
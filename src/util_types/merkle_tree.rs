@@ -1,33 +1,142 @@
+use crate::parallel::prelude::*;
+use crate::parallel::{map_collect, map_collect_range};
 use itertools::izip;
-use rayon::iter::{
-    IndexedParallelIterator, IntoParallelIterator, IntoParallelRefIterator, ParallelIterator,
-};
 use serde::{Deserialize, Serialize};
 use std::cmp::Reverse;
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
 use std::fmt::Debug;
+#[cfg(feature = "std")]
+use std::fs::File;
+#[cfg(feature = "std")]
+use std::io::{Read, Seek, SeekFrom, Write};
 use std::marker::{PhantomData, Send, Sync};
+#[cfg(feature = "std")]
+use std::path::Path;
 
+use crate::shared_math::b_field_element::BFieldElement;
+#[cfg(feature = "std")]
+use crate::shared_math::other::log_2_floor;
 use crate::shared_math::other::{
-    bit_representation, get_height_of_complete_binary_tree, is_power_of_two,
+    bit_representation, get_height_of_complete_binary_tree, is_power_of_two, random_elements,
 };
 use crate::shared_math::rescue_prime_digest::Digest;
+use crate::shared_math::rescue_prime_regular::DIGEST_LENGTH;
+use crate::shared_math::x_field_element::XFieldElement;
+#[cfg(feature = "std")]
+use crate::util_types::database_vector::DatabaseVector;
 use crate::util_types::shared::bag_peaks;
+#[cfg(feature = "std")]
+use rusty_leveldb::DB;
 
-use super::algebraic_hasher::AlgebraicHasher;
+use super::algebraic_hasher::{AlgebraicHasher, Hashable};
 
 // Chosen from a very small number of benchmark runs, optimized for a slow
 // hash function (the original Rescue Prime implementation). It should probably
 // be a higher number than 16 when using a faster hash function.
 const PARALLELLIZATION_THRESHOLD: usize = 16;
 
-#[derive(Debug)]
-pub struct MerkleTree<H: AlgebraicHasher> {
-    pub nodes: Vec<Digest>,
+/// The number of sibling pairs handed to a hasher's pairwise batch method
+/// per work item when a tree level is hashed in parallel.
+const HASH_BATCH_SIZE: usize = 64;
+
+/// The hashing interface [`MerkleTree`] is built on: a digest type and a
+/// two-to-one compression function.
+///
+/// Every [`AlgebraicHasher`] is a `MerkleTreeHasher` over the algebraic
+/// [`Digest`], via the blanket implementation below. Hashers whose native
+/// digest is something else entirely -- 32 raw bytes, say -- implement the
+/// trait directly, so their digests need not be shoehorned into the
+/// five-element field representation.
+pub trait MerkleTreeHasher: Clone + Send + Sync {
+    type Digest: Copy + Debug + PartialEq + Send + Sync;
+
+    fn hash_pair(left: &Self::Digest, right: &Self::Digest) -> Self::Digest;
+
+    /// Hash consecutive pairs of a slice of digests; see
+    /// [`AlgebraicHasher::hash_pairwise`]. Tree construction hands whole
+    /// runs of sibling pairs to this method, so hashers with a batched
+    /// permutation kernel get contiguous work.
+    fn hash_pairwise(children: &[Self::Digest]) -> Vec<Self::Digest> {
+        assert!(
+            children.len().is_multiple_of(2),
+            "Pairwise hashing needs an even number of digests"
+        );
+        children
+            .chunks_exact(2)
+            .map(|pair| Self::hash_pair(&pair[0], &pair[1]))
+            .collect()
+    }
+}
+
+impl<H: AlgebraicHasher> MerkleTreeHasher for H {
+    type Digest = Digest;
+
+    fn hash_pair(left: &Digest, right: &Digest) -> Digest {
+        <H as AlgebraicHasher>::hash_pair(left, right)
+    }
+
+    fn hash_pairwise(children: &[Digest]) -> Vec<Digest> {
+        <H as AlgebraicHasher>::hash_pairwise(children)
+    }
+}
+
+/// The version of the domain-separated Merkle commitment scheme
+/// implemented by [`DomainSeparatedHasher`] and
+/// [`domain_separated_leaf_digest`]. Version 1 prepends
+/// [`MERKLE_LEAF_DOMAIN_TAG`] to every leaf preimage and
+/// [`MERKLE_NODE_DOMAIN_TAG`] to every internal-node preimage.
+pub const MERKLE_COMMITMENT_SCHEME_VERSION: u8 = 1;
+
+/// Domain separation tag for leaf hashing: the ASCII bytes of `"leaf"`.
+pub const MERKLE_LEAF_DOMAIN_TAG: BFieldElement = BFieldElement::new(0x6c65_6166);
+
+/// Domain separation tag for internal-node hashing: the ASCII bytes of
+/// `"node"`.
+pub const MERKLE_NODE_DOMAIN_TAG: BFieldElement = BFieldElement::new(0x6e6f_6465);
+
+/// Hash a leaf preimage under the versioned commitment scheme: the leaf
+/// tag followed by the preimage elements.
+///
+/// Without the tag, a leaf whose preimage happens to be ten field
+/// elements has the same hash-input shape as an internal node -- two
+/// digests compressed by `hash_pair` -- so an attacker could open a
+/// committed leaf as a fake subtree or vice versa. Tagging both domains
+/// makes the preimage sets disjoint.
+pub fn domain_separated_leaf_digest<H: AlgebraicHasher>(elements: &[BFieldElement]) -> Digest {
+    let mut sequence = Vec::with_capacity(1 + elements.len());
+    sequence.push(MERKLE_LEAF_DOMAIN_TAG);
+    sequence.extend_from_slice(elements);
+    H::hash_slice(&sequence)
+}
+
+/// An [`AlgebraicHasher`] wrapped to compress internal nodes under the
+/// versioned commitment scheme: the node tag followed by the two child
+/// digests. Trees built with this hasher commit under scheme version
+/// [`MERKLE_COMMITMENT_SCHEME_VERSION`]; pair it with
+/// [`domain_separated_leaf_digest`] on the leaf side.
+#[derive(Clone, Debug)]
+pub struct DomainSeparatedHasher<H: AlgebraicHasher>(PhantomData<H>);
+
+impl<H: AlgebraicHasher> MerkleTreeHasher for DomainSeparatedHasher<H> {
+    type Digest = Digest;
+
+    fn hash_pair(left: &Digest, right: &Digest) -> Digest {
+        let mut sequence = Vec::with_capacity(1 + 2 * DIGEST_LENGTH);
+        sequence.push(MERKLE_NODE_DOMAIN_TAG);
+        sequence.extend(left.values());
+        sequence.extend(right.values());
+        H::hash_slice(&sequence)
+    }
+}
+
+pub struct MerkleTree<H: MerkleTreeHasher> {
+    pub nodes: Vec<H::Digest>,
     pub _hasher: PhantomData<H>,
 }
 
-impl<H: AlgebraicHasher> Clone for MerkleTree<H> {
+impl<H: MerkleTreeHasher> Clone for MerkleTree<H> {
     fn clone(&self) -> Self {
         Self {
             nodes: self.nodes.clone(),
@@ -36,9 +145,95 @@ impl<H: AlgebraicHasher> Clone for MerkleTree<H> {
     }
 }
 
+impl<H: MerkleTreeHasher> Debug for MerkleTree<H> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("MerkleTree")
+            .field("nodes", &self.nodes)
+            .finish()
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
 pub struct PartialAuthenticationPath<Digest>(pub Vec<Option<Digest>>);
 
+impl PartialAuthenticationPath<Digest> {
+    /// Compact byte encoding of the path: the path length, a presence
+    /// bitmask with one bit per node (least significant bit first), and
+    /// the present digests packed back to back. Serde's per-node `Option`
+    /// tags cost a byte each; the bitmask costs a bit, which adds up over
+    /// the many paths of a high query count.
+    pub fn encode(&self) -> Vec<u8> {
+        let length = self.0.len();
+        let mut bytes = Vec::with_capacity(1 + length.div_ceil(8) + length * Digest::BYTES);
+        bytes.push(length as u8);
+
+        let mut bitmask = vec![0u8; length.div_ceil(8)];
+        for (i, node) in self.0.iter().enumerate() {
+            if node.is_some() {
+                bitmask[i / 8] |= 1 << (i % 8);
+            }
+        }
+        bytes.extend_from_slice(&bitmask);
+
+        for digest in self.0.iter().flatten() {
+            bytes.extend_from_slice(&bincode::serialize(digest).unwrap());
+        }
+
+        bytes
+    }
+
+    /// Decode a path produced by [`encode`], or `None` if the bytes are
+    /// malformed or have trailing garbage.
+    ///
+    /// [`encode`]: PartialAuthenticationPath::encode
+    pub fn decode(bytes: &[u8]) -> Option<Self> {
+        let length = *bytes.first()? as usize;
+        let bitmask = bytes.get(1..1 + length.div_ceil(8))?;
+
+        let mut cursor = 1 + length.div_ceil(8);
+        let mut nodes: Vec<Option<Digest>> = Vec::with_capacity(length);
+        for i in 0..length {
+            if bitmask[i / 8] >> (i % 8) & 1 == 1 {
+                let digest_bytes = bytes.get(cursor..cursor + Digest::BYTES)?;
+                nodes.push(Some(bincode::deserialize(digest_bytes).ok()?));
+                cursor += Digest::BYTES;
+            } else {
+                nodes.push(None);
+            }
+        }
+
+        match cursor == bytes.len() {
+            true => Some(PartialAuthenticationPath(nodes)),
+            false => None,
+        }
+    }
+}
+
+/// The reason an authentication structure failed to verify, identifying the
+/// first offending leaf where one can be singled out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthenticationStructureError {
+    /// The numbers of leaf indices, leaf digests, and paths do not agree.
+    MismatchedInputLengths,
+    /// A leaf index points past the leaf count implied by the path length.
+    LeafIndexOutOfRange { leaf_index: usize },
+    /// A path node was elided but cannot be reconstructed from the other
+    /// openings; `level` counts from the leaf layer.
+    MissingNode { leaf_index: usize, level: usize },
+    /// The reconstructed path does not hash up to the claimed root.
+    WrongDigest { leaf_index: usize },
+    /// Two openings of the same tree imply different digests for a node.
+    ConflictingNode { node_index: u64 },
+}
+
+impl Error for AuthenticationStructureError {}
+
+impl fmt::Display for AuthenticationStructureError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
 /// # Design
 /// The following are implemented as static methods:
 ///
@@ -53,79 +248,73 @@ pub struct PartialAuthenticationPath<Digest>(pub Vec<Option<Digest>>);
 /// the original `MerkleTree` object, but only partial information from it,
 /// in the form of the quadrupples: `(root_hash, index, digest, auth_path)`.
 /// These are exactly the arguments for the `verify_*` family of static methods.
-impl<H: AlgebraicHasher> MerkleTree<H> {
-    /// Calculate a Merkle root from a list of digests that is not necessarily a power of two.
-    pub fn root_from_arbitrary_number_of_digests(digests: &[Digest]) -> Digest {
-        // This function should preferably construct a whole Merkle tree data structure and not just the root,
-        // but I couldn't figure out how to do that as the indexing for this problem seems hard to me. Perhaps, the
-        // data structure would need to be changed, since some of the nodes will be `None`/null.
-
-        // The main reason this function exists is that I wanted to be able to calculate a Merkle
-        // root from an odd (non-2^k) number of digests in parallel. This will be used when calculating the digest
-        // of a block, where one of the components is a list of MS addition/removal records.
+impl<H: MerkleTreeHasher> MerkleTree<H> {
+    /// The Merkle root over `digests`, without building the tree.
+    ///
+    /// [`from_digests`] allocates and retains all `2n` nodes so that
+    /// authentication paths can be extracted later. Callers that only
+    /// need the commitment can fold layer by layer instead: each level
+    /// replaces the previous one, so peak memory beyond the input slice
+    /// is a single half-size level. Levels above the parallelization
+    /// threshold are hashed in parallel, like [`from_digests`].
+    ///
+    /// [`from_digests`]: MerkleTree::from_digests
+    pub fn root_from_digests(digests: &[H::Digest]) -> H::Digest {
+        assert!(
+            is_power_of_two(digests.len()),
+            "Size of input for Merkle tree must be a power of 2"
+        );
 
-        // Note that this function *does* allow the calculation of a MT root from an empty list of digests
-        // since the number of removal records in a block can be zero.
+        if digests.len() == 1 {
+            return digests[0];
+        }
 
-        let heights = bit_representation(digests.len() as u128);
-        let mut trees: Vec<MerkleTree<H>> = vec![];
-        let mut acc_counter = 0;
-        for height in heights {
-            let sub_tree = Self::from_digests(&digests[acc_counter..acc_counter + (1 << height)]);
-            acc_counter += 1 << height;
-            trees.push(sub_tree);
+        let mut level: Vec<H::Digest> = Self::parent_level(digests);
+        while level.len() > 1 {
+            level = Self::parent_level(&level);
         }
 
-        // Calculate the root from a list of Merkle trees
-        let roots: Vec<Digest> = trees.iter().map(|t| t.get_root()).collect();
+        level[0]
+    }
 
-        bag_peaks::<H>(&roots)
+    /// One level of parent digests from a level of child digests. Parallel
+    /// levels are split into runs of [`HASH_BATCH_SIZE`] pairs, each handed
+    /// to [`MerkleTreeHasher::hash_pairwise`] in one piece.
+    fn parent_level(level: &[H::Digest]) -> Vec<H::Digest> {
+        let parent_count = level.len() / 2;
+        if parent_count < PARALLELLIZATION_THRESHOLD {
+            return H::hash_pairwise(level);
+        }
+
+        let num_batches = parent_count.div_ceil(HASH_BATCH_SIZE);
+        let batches: Vec<Vec<H::Digest>> = map_collect_range(num_batches, |batch_index| {
+            let start = 2 * batch_index * HASH_BATCH_SIZE;
+            let end = (start + 2 * HASH_BATCH_SIZE).min(level.len());
+            H::hash_pairwise(&level[start..end])
+        });
+        batches.into_iter().flatten().collect()
     }
 
     /// Takes an array of digests and builds a MerkleTree over them.
     /// The digests are used copied over as the leaves of the tree.
-    pub fn from_digests(digests: &[Digest]) -> Self {
-        let leaves_count = digests.len();
-
-        assert!(
-            is_power_of_two(leaves_count),
-            "Size of input for Merkle tree must be a power of 2"
-        );
-
-        let filler = digests[0];
-
-        // nodes[0] is never used for anything.
-        let mut nodes = vec![filler; 2 * leaves_count];
-        nodes[leaves_count..(leaves_count + leaves_count)]
-            .clone_from_slice(&digests[..leaves_count]);
-
-        // Parallel digest calculations
-        let mut node_count_on_this_level: usize = digests.len() / 2;
-        let mut count_acc: usize = 0;
-        while node_count_on_this_level >= PARALLELLIZATION_THRESHOLD {
-            let mut local_digests: Vec<Digest> = Vec::with_capacity(node_count_on_this_level);
-            (0..node_count_on_this_level)
-                .into_par_iter()
-                .map(|i| {
-                    let j = node_count_on_this_level + i;
-                    let left_child = &nodes[j * 2];
-                    let right_child = &nodes[j * 2 + 1];
-                    H::hash_pair(left_child, right_child)
-                })
-                .collect_into_vec(&mut local_digests);
-            nodes[node_count_on_this_level..(node_count_on_this_level + node_count_on_this_level)]
-                .clone_from_slice(&local_digests[..node_count_on_this_level]);
-            count_acc += node_count_on_this_level;
-            node_count_on_this_level /= 2;
-        }
+    ///
+    /// Uses the default parallelization settings; see [`builder`] for a
+    /// construction with configurable scheduling.
+    ///
+    /// [`builder`]: MerkleTree::builder
+    pub fn from_digests(digests: &[H::Digest]) -> Self {
+        Self::builder().build(digests)
+    }
 
-        // Sequential digest calculations
-        for i in (1..(digests.len() - count_acc)).rev() {
-            nodes[i] = H::hash_pair(&nodes[i * 2], &nodes[i * 2 + 1]);
+    /// A [`MerkleTreeBuilder`] with the crate's default parallelization
+    /// threshold, rayon's own work splitting, and parallel hashing enabled.
+    pub fn builder() -> MerkleTreeBuilder<H> {
+        MerkleTreeBuilder {
+            parallel_threshold: PARALLELLIZATION_THRESHOLD,
+            chunk_size: None,
+            sequential: false,
+            _hasher: PhantomData,
         }
-
-        let _hasher = PhantomData;
-        Self { nodes, _hasher }
     }
 
     // Similar to `get_proof', but instead of returning a `Vec<Node<T>>`, we only
@@ -143,9 +332,9 @@ impl<H: AlgebraicHasher> MerkleTree<H> {
     //   vec![ H(d), H(H(a)+H(b)) ]
     //
     // ... so a criss-cross of siblings upwards.
-    pub fn get_authentication_path(&self, leaf_index: usize) -> Vec<Digest> {
+    pub fn get_authentication_path(&self, leaf_index: usize) -> Vec<H::Digest> {
         let height = self.get_height();
-        let mut auth_path: Vec<Digest> = Vec::with_capacity(height);
+        let mut auth_path: Vec<H::Digest> = Vec::with_capacity(height);
 
         let mut node_index = leaf_index + self.nodes.len() / 2;
         while node_index > 1 {
@@ -162,10 +351,10 @@ impl<H: AlgebraicHasher> MerkleTree<H> {
 
     // Consider renaming this `verify_leaf_with_authentication_path()`.
     pub fn verify_authentication_path_from_leaf_hash(
-        root_hash: Digest,
+        root_hash: H::Digest,
         leaf_index: u32,
-        leaf_hash: Digest,
-        auth_path: Vec<Digest>,
+        leaf_hash: H::Digest,
+        auth_path: Vec<H::Digest>,
     ) -> bool {
         let path_length = auth_path.len() as u32;
 
@@ -175,7 +364,7 @@ impl<H: AlgebraicHasher> MerkleTree<H> {
         for path_hash in auth_path.iter() {
             // Use Merkle tree index parity (odd/even) to determine which
             // order to concatenate the hashes before hashing them.
-            if i % 2 == 0 {
+            if i.is_multiple_of(2) {
                 acc_hash = H::hash_pair(&acc_hash, path_hash);
             } else {
                 acc_hash = H::hash_pair(path_hash, &acc_hash);
@@ -191,10 +380,10 @@ impl<H: AlgebraicHasher> MerkleTree<H> {
     /// contain the leaf node that we are verifying for, otherwise this
     /// function will panic.
     fn verify_authentication_path_from_leaf_hash_with_memoization(
-        root_hash: &Digest,
+        root_hash: &H::Digest,
         leaf_index: u32,
-        auth_path: &[Digest],
-        partial_tree: &HashMap<u64, Digest>,
+        auth_path: &[H::Digest],
+        partial_tree: &HashMap<u64, H::Digest>,
     ) -> bool {
         let path_length = auth_path.len() as u32;
 
@@ -209,7 +398,7 @@ impl<H: AlgebraicHasher> MerkleTree<H> {
         let mut i = node_index >> level_in_tree;
         let mut acc_hash = partial_tree[&(i as u64)];
         while i / 2 >= 1 {
-            if i % 2 == 0 {
+            if i.is_multiple_of(2) {
                 acc_hash = H::hash_pair(&acc_hash, &auth_path[level_in_tree]);
             } else {
                 acc_hash = H::hash_pair(&auth_path[level_in_tree], &acc_hash);
@@ -225,62 +414,8 @@ impl<H: AlgebraicHasher> MerkleTree<H> {
     pub fn get_authentication_structure(
         &self,
         indices: &[usize],
-    ) -> Vec<PartialAuthenticationPath<Digest>> {
-        let mut calculable_indices: HashSet<usize> = HashSet::new();
-        let mut output: Vec<PartialAuthenticationPath<Digest>> = Vec::with_capacity(indices.len());
-        for i in indices.iter() {
-            let new_branch = PartialAuthenticationPath(
-                self.get_authentication_path(*i)
-                    .into_iter()
-                    .map(Some)
-                    .collect(),
-            );
-            let mut index = self.nodes.len() / 2 + i;
-            calculable_indices.insert(index);
-            for _ in 1..new_branch.0.len() {
-                calculable_indices.insert(index ^ 1);
-                index /= 2;
-            }
-            output.push(new_branch);
-        }
-
-        let mut complete = false;
-        while !complete {
-            complete = true;
-            let mut keys: Vec<usize> = calculable_indices.iter().copied().map(|x| x / 2).collect();
-            // reverse sort, from big to small, This should be the fastest way to reverse sort.
-            // cf. https://stackoverflow.com/a/60916195/2574407
-            keys.sort_by_key(|w| Reverse(*w));
-            for key in keys.iter() {
-                if calculable_indices.contains(&(key * 2))
-                    && calculable_indices.contains(&(key * 2 + 1))
-                    && !calculable_indices.contains(key)
-                {
-                    calculable_indices.insert(*key);
-                    complete = false;
-                }
-            }
-        }
-
-        let mut scanned: HashSet<usize> = HashSet::new();
-        for (i, b) in indices.iter().zip(output.iter_mut()) {
-            let mut index: usize = self.nodes.len() / 2 + i;
-            scanned.insert(index);
-            for elem in b.0.iter_mut() {
-                if calculable_indices.contains(&((index ^ 1) * 2))
-                    && calculable_indices.contains(&((index ^ 1) * 2 + 1))
-                    || (index ^ 1) as i64 - self.nodes.len() as i64 / 2 > 0 // TODO: Maybe > 1 here?
-                        && indices.contains(&((index ^ 1) - self.nodes.len() / 2))
-                    || scanned.contains(&(index ^ 1))
-                {
-                    *elem = None;
-                }
-                scanned.insert(index ^ 1);
-                index /= 2;
-            }
-        }
-
-        output
+    ) -> Vec<PartialAuthenticationPath<H::Digest>> {
+        authentication_structure_with_lookup(self.nodes.len(), indices, |i| self.nodes[i])
     }
 
     /// Verifies a list of leaf_indices and corresponding
@@ -294,27 +429,47 @@ impl<H: AlgebraicHasher> MerkleTree<H> {
     /// * `leaf_digests` - List of the leaves' values (i.e. digests) to verify
     /// * `auth_paths` - List of paths corresponding to the leaves.
     pub fn verify_authentication_structure_from_leaves(
-        root_hash: Digest,
+        root_hash: H::Digest,
         leaf_indices: &[usize],
-        leaf_digests: &[Digest],
-        partial_auth_paths: &[PartialAuthenticationPath<Digest>],
+        leaf_digests: &[H::Digest],
+        partial_auth_paths: &[PartialAuthenticationPath<H::Digest>],
     ) -> bool {
+        Self::check_authentication_structure_from_leaves(
+            root_hash,
+            leaf_indices,
+            leaf_digests,
+            partial_auth_paths,
+        )
+        .is_ok()
+    }
+
+    /// Like [`verify_authentication_structure_from_leaves`], but reports
+    /// which leaf failed to authenticate and why instead of collapsing
+    /// every failure mode into `false`.
+    ///
+    /// [`verify_authentication_structure_from_leaves`]: MerkleTree::verify_authentication_structure_from_leaves
+    pub fn check_authentication_structure_from_leaves(
+        root_hash: H::Digest,
+        leaf_indices: &[usize],
+        leaf_digests: &[H::Digest],
+        partial_auth_paths: &[PartialAuthenticationPath<H::Digest>],
+    ) -> Result<(), AuthenticationStructureError> {
         if leaf_indices.len() != partial_auth_paths.len()
             || leaf_indices.len() != leaf_digests.len()
         {
-            return false;
+            return Err(AuthenticationStructureError::MismatchedInputLengths);
         }
 
         if leaf_indices.is_empty() {
-            return true;
+            return Ok(());
         }
         debug_assert_eq!(leaf_indices.len(), leaf_digests.len());
         debug_assert_eq!(leaf_digests.len(), partial_auth_paths.len());
         debug_assert_eq!(partial_auth_paths.len(), leaf_indices.len());
 
-        let mut partial_auth_paths: Vec<PartialAuthenticationPath<Digest>> =
+        let mut partial_auth_paths: Vec<PartialAuthenticationPath<H::Digest>> =
             partial_auth_paths.to_owned();
-        let mut partial_tree: HashMap<u64, Digest> = HashMap::new();
+        let mut partial_tree: HashMap<u64, H::Digest> = HashMap::new();
 
         // FIXME: We find the offset from which leaf nodes occur in the tree by looking at the
         // first partial authentication path. This is a bit hacked, since what if not all
@@ -323,6 +478,12 @@ impl<H: AlgebraicHasher> MerkleTree<H> {
         let auth_path_length = partial_auth_paths[0].0.len();
         let half_tree_size = 2u64.pow(auth_path_length as u32);
 
+        if let Some(leaf_index) = leaf_indices.iter().find(|i| **i as u64 >= half_tree_size) {
+            return Err(AuthenticationStructureError::LeafIndexOutOfRange {
+                leaf_index: *leaf_index,
+            });
+        }
+
         // Bootstrap partial tree
         for (i, leaf_hash, partial_auth_path) in
             izip!(leaf_indices, leaf_digests, partial_auth_paths.clone())
@@ -370,7 +531,7 @@ impl<H: AlgebraicHasher> MerkleTree<H> {
             complete = new_derivable_digests_indices.is_empty();
 
             // Calculate derivable digests in parallel
-            let mut new_digests: Vec<(u64, Digest)> =
+            let mut new_digests: Vec<(u64, H::Digest)> =
                 Vec::with_capacity(new_derivable_digests_indices.len());
             new_derivable_digests_indices
                 .par_iter()
@@ -394,16 +555,19 @@ impl<H: AlgebraicHasher> MerkleTree<H> {
         for (i, partial_auth_path) in leaf_indices.iter().zip(partial_auth_paths.iter_mut()) {
             let mut index = half_tree_size + *i as u64;
 
-            for elem in partial_auth_path.0.iter_mut() {
+            for (level, elem) in partial_auth_path.0.iter_mut().enumerate() {
                 let sibling = index ^ 1;
 
                 if elem.is_none() {
                     // If the Merkle tree/proof is manipulated, the value partial_tree[&(index ^ 1)]
                     // is not guaranteed to exist. So have to  check
-                    // whether it exists and return false if it does not
+                    // whether it exists and report the gap if it does not
 
                     if !partial_tree.contains_key(&sibling) {
-                        return false;
+                        return Err(AuthenticationStructureError::MissingNode {
+                            leaf_index: *i,
+                            level,
+                        });
                     }
 
                     *elem = Some(partial_tree[&sibling]);
@@ -419,10 +583,10 @@ impl<H: AlgebraicHasher> MerkleTree<H> {
             .map(Self::unwrap_partial_authentication_path)
             .collect::<Vec<_>>();
 
-        leaf_indices
+        let path_verifies: Vec<bool> = leaf_indices
             .par_iter()
             .zip(reconstructed_auth_paths.par_iter())
-            .all(|(index, auth_path)| {
+            .map(|(index, auth_path)| {
                 Self::verify_authentication_path_from_leaf_hash_with_memoization(
                     &root_hash,
                     *index as u32,
@@ -430,27 +594,47 @@ impl<H: AlgebraicHasher> MerkleTree<H> {
                     &partial_tree,
                 )
             })
+            .collect();
+
+        match path_verifies.iter().position(|verifies| !verifies) {
+            Some(position) => Err(AuthenticationStructureError::WrongDigest {
+                leaf_index: leaf_indices[position],
+            }),
+            None => Ok(()),
+        }
     }
 
     /// Verifies a list of leaf_indices and corresponding
     /// auth_pairs (auth_path, leaf_digest) against a Merkle root.
     pub fn verify_authentication_structure(
-        root_hash: Digest,
+        root_hash: H::Digest,
         leaf_indices: &[usize],
-        auth_pairs: &[(PartialAuthenticationPath<Digest>, Digest)],
+        auth_pairs: &[(PartialAuthenticationPath<H::Digest>, H::Digest)],
     ) -> bool {
+        Self::check_authentication_structure(root_hash, leaf_indices, auth_pairs).is_ok()
+    }
+
+    /// Like [`verify_authentication_structure`], but reports which leaf
+    /// failed to authenticate and why instead of collapsing every failure
+    /// mode into `false`.
+    ///
+    /// [`verify_authentication_structure`]: MerkleTree::verify_authentication_structure
+    pub fn check_authentication_structure(
+        root_hash: H::Digest,
+        leaf_indices: &[usize],
+        auth_pairs: &[(PartialAuthenticationPath<H::Digest>, H::Digest)],
+    ) -> Result<(), AuthenticationStructureError> {
         if leaf_indices.len() != auth_pairs.len() {
-            return false;
+            return Err(AuthenticationStructureError::MismatchedInputLengths);
         }
 
         if leaf_indices.is_empty() {
-            return true;
+            return Ok(());
         }
-        assert_eq!(leaf_indices.len(), auth_pairs.len());
 
         let (auth_paths, leaves): (Vec<_>, Vec<_>) = auth_pairs.iter().cloned().unzip();
 
-        Self::verify_authentication_structure_from_leaves(
+        Self::check_authentication_structure_from_leaves(
             root_hash,
             leaf_indices,
             &leaves,
@@ -459,8 +643,8 @@ impl<H: AlgebraicHasher> MerkleTree<H> {
     }
 
     fn unwrap_partial_authentication_path(
-        partial_auth_path: &PartialAuthenticationPath<Digest>,
-    ) -> Vec<Digest> {
+        partial_auth_path: &PartialAuthenticationPath<H::Digest>,
+    ) -> Vec<H::Digest> {
         partial_auth_path
             .clone()
             .0
@@ -469,7 +653,184 @@ impl<H: AlgebraicHasher> MerkleTree<H> {
             .collect()
     }
 
-    pub fn get_root(&self) -> Digest {
+    /// The `2^cap_height` roots of the subtrees at level `cap_height` of
+    /// the tree, ordered left to right. Publishing these instead of the
+    /// single root shortens every authentication path by `cap_height`
+    /// digests -- a standard proof-size/verifier-cost tradeoff. A
+    /// `cap_height` of 0 yields the ordinary root.
+    pub fn get_cap_roots(&self, cap_height: usize) -> Vec<H::Digest> {
+        assert!(
+            cap_height <= self.get_height(),
+            "Cap height cannot exceed tree height. Height: {}, cap height: {}",
+            self.get_height(),
+            cap_height
+        );
+        self.nodes[(1 << cap_height)..(1 << (cap_height + 1))].to_vec()
+    }
+
+    /// Like [`get_authentication_path`], but stopping `cap_height` levels
+    /// below the root, for verification against the cap roots from
+    /// [`get_cap_roots`]. The returned path holds `log n - cap_height`
+    /// digests.
+    ///
+    /// [`get_authentication_path`]: MerkleTree::get_authentication_path
+    pub fn get_capped_authentication_path(
+        &self,
+        leaf_index: usize,
+        cap_height: usize,
+    ) -> Vec<H::Digest> {
+        let height = self.get_height();
+        assert!(
+            cap_height <= height,
+            "Cap height cannot exceed tree height. Height: {}, cap height: {}",
+            height,
+            cap_height
+        );
+
+        let mut auth_path: Vec<H::Digest> = Vec::with_capacity(height - cap_height);
+        let mut node_index = leaf_index + self.nodes.len() / 2;
+        while node_index >= 1 << (cap_height + 1) {
+            auth_path.push(self.nodes[node_index ^ 1]);
+            node_index /= 2;
+        }
+
+        auth_path
+    }
+
+    /// Verify a capped authentication path against the published cap
+    /// roots; the counterpart of [`get_capped_authentication_path`]. The
+    /// cap height is implied by the number of cap roots, which must be a
+    /// power of two.
+    ///
+    /// [`get_capped_authentication_path`]: MerkleTree::get_capped_authentication_path
+    pub fn verify_capped_authentication_path(
+        cap_roots: &[H::Digest],
+        leaf_index: u32,
+        leaf_hash: H::Digest,
+        auth_path: Vec<H::Digest>,
+    ) -> bool {
+        if !is_power_of_two(cap_roots.len()) {
+            return false;
+        }
+
+        let path_length = auth_path.len() as u32;
+        let mut acc_hash = leaf_hash;
+        let mut i = leaf_index + cap_roots.len() as u32 * 2u32.pow(path_length);
+        for path_hash in auth_path.iter() {
+            if i.is_multiple_of(2) {
+                acc_hash = H::hash_pair(&acc_hash, path_hash);
+            } else {
+                acc_hash = H::hash_pair(path_hash, &acc_hash);
+            }
+            i /= 2;
+        }
+
+        acc_hash == cap_roots[i as usize - cap_roots.len()]
+    }
+
+    /// Replace the leaf at `leaf_index` with `new_digest`, recomputing
+    /// only the O(log n) internal nodes on the path to the root. Much
+    /// cheaper than rebuilding the tree when only a handful of leaves
+    /// change.
+    pub fn update_leaf(&mut self, leaf_index: usize, new_digest: H::Digest) {
+        let leaf_count = self.get_leaf_count();
+        assert!(
+            leaf_index < leaf_count,
+            "Cannot update leaf outside of tree. Leaf count: {}, leaf index: {}",
+            leaf_count,
+            leaf_index
+        );
+
+        let mut node_index = leaf_count + leaf_index;
+        self.nodes[node_index] = new_digest;
+        while node_index > 1 {
+            node_index /= 2;
+            self.nodes[node_index] =
+                H::hash_pair(&self.nodes[node_index * 2], &self.nodes[node_index * 2 + 1]);
+        }
+    }
+
+    /// A compact proof that the consecutive leaves in `start..end` are
+    /// committed under the root: the sibling digests on the two boundary
+    /// paths, bottom-up, left boundary before right per level. All
+    /// internal nodes spanned by the range itself are recomputed by the
+    /// verifier, so the proof holds at most `2 log n` digests no matter
+    /// how long the range is -- where per-index openings of a long
+    /// segment grow linearly with its length.
+    pub fn get_range_proof(&self, start: usize, end: usize) -> Vec<H::Digest> {
+        let leaf_count = self.get_leaf_count();
+        assert!(
+            start < end && end <= leaf_count,
+            "Range {}..{} must be non-empty and within the {} leaves",
+            start,
+            end,
+            leaf_count
+        );
+
+        let mut proof: Vec<H::Digest> = Vec::with_capacity(2 * self.get_height());
+        let mut lo = leaf_count + start;
+        let mut hi = leaf_count + end - 1;
+        while lo > 1 {
+            if lo % 2 == 1 {
+                proof.push(self.nodes[lo ^ 1]);
+                lo -= 1;
+            }
+            if hi.is_multiple_of(2) {
+                proof.push(self.nodes[hi ^ 1]);
+                hi += 1;
+            }
+            lo /= 2;
+            hi /= 2;
+        }
+
+        proof
+    }
+
+    /// Verify a proof produced by [`get_range_proof`] against a root,
+    /// given the digests of the leaves in `start..start + leaves.len()`.
+    ///
+    /// [`get_range_proof`]: MerkleTree::get_range_proof
+    pub fn verify_range_proof(
+        root_hash: H::Digest,
+        leaf_count: usize,
+        start: usize,
+        leaves: &[H::Digest],
+        proof: &[H::Digest],
+    ) -> bool {
+        if leaves.is_empty() || start + leaves.len() > leaf_count || !is_power_of_two(leaf_count) {
+            return false;
+        }
+
+        let mut level: Vec<H::Digest> = leaves.to_vec();
+        let mut lo = leaf_count + start;
+        let mut hi = lo + leaves.len() - 1;
+        let mut proof_iter = proof.iter();
+        while lo > 1 {
+            if lo % 2 == 1 {
+                let Some(sibling) = proof_iter.next() else {
+                    return false;
+                };
+                level.insert(0, *sibling);
+                lo -= 1;
+            }
+            if hi.is_multiple_of(2) {
+                let Some(sibling) = proof_iter.next() else {
+                    return false;
+                };
+                level.push(*sibling);
+                hi += 1;
+            }
+            level = (0..level.len() / 2)
+                .map(|i| H::hash_pair(&level[2 * i], &level[2 * i + 1]))
+                .collect();
+            lo /= 2;
+            hi /= 2;
+        }
+
+        proof_iter.next().is_none() && level[0] == root_hash
+    }
+
+    pub fn get_root(&self) -> H::Digest {
         self.nodes[1]
     }
 
@@ -483,12 +844,12 @@ impl<H: AlgebraicHasher> MerkleTree<H> {
         get_height_of_complete_binary_tree(self.get_leaf_count())
     }
 
-    pub fn get_all_leaves(&self) -> Vec<Digest> {
+    pub fn get_all_leaves(&self) -> Vec<H::Digest> {
         let first_leaf = self.nodes.len() / 2;
         self.nodes[first_leaf..].to_vec()
     }
 
-    pub fn get_leaf_by_index(&self, index: usize) -> Digest {
+    pub fn get_leaf_by_index(&self, index: usize) -> H::Digest {
         let first_leaf_index = self.nodes.len() / 2;
         let beyond_last_leaf_index = self.nodes.len();
         assert!(
@@ -498,7 +859,7 @@ impl<H: AlgebraicHasher> MerkleTree<H> {
         self.nodes[first_leaf_index + index]
     }
 
-    pub fn get_leaves_by_indices(&self, leaf_indices: &[usize]) -> Vec<Digest> {
+    pub fn get_leaves_by_indices(&self, leaf_indices: &[usize]) -> Vec<H::Digest> {
         let leaf_count = leaf_indices.len();
 
         let mut result = Vec::with_capacity(leaf_count);
@@ -508,303 +869,2225 @@ impl<H: AlgebraicHasher> MerkleTree<H> {
         }
         result
     }
-}
 
-pub type SaltedAuthenticationStructure<Digest> = Vec<(PartialAuthenticationPath<Digest>, Digest)>;
+    /// Iterate over the committed leaf digests, in leaf-index order.
+    /// Unlike [`get_all_leaves`], this does not allocate a new vector.
+    ///
+    /// [`get_all_leaves`]: MerkleTree::get_all_leaves
+    pub fn iter_leaves(&self) -> impl Iterator<Item = H::Digest> + '_ {
+        let first_leaf = self.nodes.len() / 2;
+        self.nodes[first_leaf..].iter().copied()
+    }
 
-#[derive(Clone, Debug)]
-pub struct SaltedMerkleTree<H: AlgebraicHasher> {
-    internal_merkle_tree: MerkleTree<H>,
-    salts: Vec<Digest>,
+    /// The digest of an arbitrary internal node, addressed by layer and
+    /// index within that layer. Layer 0 is the root; layer
+    /// [`get_height`] is the leaves. Index 0 is the leftmost node of a
+    /// layer, and layer `l` holds `2^l` nodes.
+    ///
+    /// [`get_height`]: MerkleTree::get_height
+    pub fn get_node(&self, layer: usize, index: usize) -> H::Digest {
+        assert!(layer <= self.get_height(), "Out of bounds layer requested");
+        assert!(index < 1 << layer, "Out of bounds index requested");
+        self.nodes[(1 << layer) + index]
+    }
 }
 
-impl<H: AlgebraicHasher> SaltedMerkleTree<H>
-where
-    H: Sync + Send, // FIXME: Remove these.
-{
-    pub fn from_digests(leaves: &[Digest], salts: &[Digest]) -> Self {
+/// Configures how [`MerkleTree::from_digests`]' internal-node hashing is
+/// scheduled. The default -- one parallel map per level, handed to rayon in
+/// one piece -- oversubscribes the thread pool for small trees and leaves
+/// work splitting entirely to the pool for large ones. The builder lets
+/// callers raise the level size below which hashing stays on the calling
+/// thread, pin the parallel work to fixed-size chunks, or opt out of
+/// threading altogether. All settings produce the same tree; only
+/// scheduling changes.
+///
+/// Obtained through [`MerkleTree::builder`].
+pub struct MerkleTreeBuilder<H: MerkleTreeHasher> {
+    parallel_threshold: usize,
+    chunk_size: Option<usize>,
+    sequential: bool,
+    _hasher: PhantomData<H>,
+}
+
+impl<H: MerkleTreeHasher> MerkleTreeBuilder<H> {
+    /// The level size below which hashing runs sequentially on the calling
+    /// thread. Levels this size or larger are hashed in parallel.
+    pub fn parallel_threshold(mut self, parallel_threshold: usize) -> Self {
+        self.parallel_threshold = parallel_threshold;
+        self
+    }
+
+    /// Split every parallel level into chunks of this many nodes and hand
+    /// the chunks to the thread pool as indivisible units, instead of
+    /// letting rayon split the level adaptively. Must not be zero.
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = Some(chunk_size);
+        self
+    }
+
+    /// Hash every level sequentially on the calling thread, regardless of
+    /// size. Useful when the surrounding workload already saturates the
+    /// thread pool.
+    pub fn sequential(mut self) -> Self {
+        self.sequential = true;
+        self
+    }
+
+    /// Build a Merkle tree over the digests with the configured scheduling.
+    /// The digests are copied over as the leaves of the tree; their count
+    /// must be a power of two.
+    pub fn build(&self, digests: &[H::Digest]) -> MerkleTree<H> {
+        let leaves_count = digests.len();
+
         assert!(
-            is_power_of_two(leaves.len()),
+            is_power_of_two(leaves_count),
             "Size of input for Merkle tree must be a power of 2"
         );
 
+        let filler = digests[0];
+
         // nodes[0] is never used for anything.
-        let mut nodes: Vec<Digest> = vec![Digest::default(); 2 * leaves.len()];
-
-        for i in 0..leaves.len() {
-            let value = leaves[i];
-            let leaf_digest = H::hash_pair(&value, &salts[i]);
+        let mut nodes = vec![filler; 2 * leaves_count];
+        nodes[leaves_count..(2 * leaves_count)].clone_from_slice(digests);
 
-            nodes[leaves.len() + i] = leaf_digest;
+        // Parallel digest calculations
+        let mut node_count_on_this_level: usize = leaves_count / 2;
+        let mut count_acc: usize = 0;
+        while !self.sequential && node_count_on_this_level >= self.parallel_threshold {
+            let local_digests = self.hash_level(&nodes, node_count_on_this_level);
+            nodes[node_count_on_this_level..(2 * node_count_on_this_level)]
+                .clone_from_slice(&local_digests);
+            count_acc += node_count_on_this_level;
+            node_count_on_this_level /= 2;
         }
 
-        // loop from `len(L) - 1` to 1
-        for i in (1..(nodes.len() / 2)).rev() {
-            let left = nodes[i * 2];
-            let right = nodes[i * 2 + 1];
-            nodes[i] = H::hash_pair(&left, &right);
+        // Sequential digest calculations
+        for i in (1..(leaves_count - count_acc)).rev() {
+            nodes[i] = H::hash_pair(&nodes[i * 2], &nodes[i * 2 + 1]);
         }
 
         let _hasher = PhantomData;
-        let internal_merkle_tree = MerkleTree::<H> { nodes, _hasher };
+        MerkleTree { nodes, _hasher }
+    }
 
-        Self {
-            internal_merkle_tree,
-            salts: salts.to_vec(),
-        }
+    /// One parallel level of parent digests, split into fixed chunks of
+    /// pairs -- the configured chunk size, or [`HASH_BATCH_SIZE`] -- each
+    /// handed to [`MerkleTreeHasher::hash_pairwise`] in one piece.
+    fn hash_level(&self, nodes: &[H::Digest], node_count_on_this_level: usize) -> Vec<H::Digest> {
+        let children = &nodes[2 * node_count_on_this_level..4 * node_count_on_this_level];
+
+        let Some(chunk_size) = self.chunk_size else {
+            let num_batches = node_count_on_this_level.div_ceil(HASH_BATCH_SIZE);
+            let batches: Vec<Vec<H::Digest>> = map_collect_range(num_batches, |batch_index| {
+                let start = 2 * batch_index * HASH_BATCH_SIZE;
+                let end = (start + 2 * HASH_BATCH_SIZE).min(children.len());
+                H::hash_pairwise(&children[start..end])
+            });
+            return batches.into_iter().flatten().collect();
+        };
+
+        let chunk_starts: Vec<usize> = (0..node_count_on_this_level).step_by(chunk_size).collect();
+        let chunks: Vec<Vec<H::Digest>> = chunk_starts
+            .into_par_iter()
+            .map(|start| {
+                let end = (start + chunk_size).min(node_count_on_this_level);
+                H::hash_pairwise(&children[2 * start..2 * end])
+            })
+            .collect();
+        chunks.into_iter().flatten().collect()
     }
+}
 
-    pub fn get_authentication_path_and_salt(&self, index: usize) -> (Vec<Digest>, Digest) {
-        let authentication_path = self.internal_merkle_tree.get_authentication_path(index);
-        let salt = self.salts[index];
+impl<H: AlgebraicHasher> MerkleTree<H> {
+    /// Calculate a Merkle root from a list of digests that is not necessarily a power of two.
+    pub fn root_from_arbitrary_number_of_digests(digests: &[Digest]) -> Digest {
+        // This function should preferably construct a whole Merkle tree data structure and not just the root,
+        // but I couldn't figure out how to do that as the indexing for this problem seems hard to me. Perhaps, the
+        // data structure would need to be changed, since some of the nodes will be `None`/null.
 
-        (authentication_path, salt)
+        // The main reason this function exists is that I wanted to be able to calculate a Merkle
+        // root from an odd (non-2^k) number of digests in parallel. This will be used when calculating the digest
+        // of a block, where one of the components is a list of MS addition/removal records.
+
+        // Note that this function *does* allow the calculation of a MT root from an empty list of digests
+        // since the number of removal records in a block can be zero.
+
+        let heights = bit_representation(digests.len() as u128);
+        let mut trees: Vec<MerkleTree<H>> = vec![];
+        let mut acc_counter = 0;
+        for height in heights {
+            let sub_tree = Self::from_digests(&digests[acc_counter..acc_counter + (1 << height)]);
+            acc_counter += 1 << height;
+            trees.push(sub_tree);
+        }
+
+        // Calculate the root from a list of Merkle trees
+        let roots: Vec<Digest> = trees.iter().map(|t| t.get_root()).collect();
+
+        bag_peaks::<H>(&roots)
     }
 
-    pub fn verify_authentication_path(
-        root_hash: Digest,
-        index: u32,
-        leaf: Digest,
-        auth_path: Vec<Digest>,
-        leaf_salt: Digest,
-    ) -> bool {
-        let leaf_hash = H::hash_pair(&leaf, &leaf_salt);
+    /// Verify openings against several roots -- as the FRI query phase
+    /// produces, one root per round -- in one batched pass. All leaf
+    /// values across all roots are digested in a single parallel map, the
+    /// per-root structures are then checked in parallel, and each root
+    /// still shares reconstructed digests among its own openings. Returns
+    /// one result per root, in input order.
+    pub fn check_authentication_structures_batch<V: Hashable + Send + Sync>(
+        openings: &[RootOpenings<V>],
+    ) -> Vec<Result<(), AuthenticationStructureError>> {
+        let all_values: Vec<&V> = openings
+            .iter()
+            .flat_map(|opening| opening.leaf_values.iter())
+            .collect();
+        let all_digests: Vec<Digest> = map_collect(&all_values, |value| H::hash(*value));
 
-        // Set `leaf_hash` to H(value + salts[0..])
-        MerkleTree::<H>::verify_authentication_path_from_leaf_hash(
-            root_hash, index, leaf_hash, auth_path,
-        )
+        let mut cursor = 0;
+        let digests_per_root: Vec<&[Digest]> = openings
+            .iter()
+            .map(|opening| {
+                let digests = &all_digests[cursor..cursor + opening.leaf_values.len()];
+                cursor += opening.leaf_values.len();
+                digests
+            })
+            .collect();
+
+        map_collect_range(openings.len(), |i| {
+            let opening = &openings[i];
+            Self::check_authentication_structure_from_leaves(
+                opening.root,
+                &opening.leaf_indices,
+                digests_per_root[i],
+                &opening.partial_auth_paths,
+            )
+        })
     }
+}
 
-    pub fn get_authentication_structure_and_salt(
-        &self,
-        indices: &[usize],
-    ) -> SaltedAuthenticationStructure<Digest> {
-        // Get the partial authentication paths without salts
-        let partial_authentication_paths: Vec<PartialAuthenticationPath<Digest>> = self
-            .internal_merkle_tree
-            .get_authentication_structure(indices);
+/// The magic bytes opening a serialized Merkle tree file.
+#[cfg(feature = "std")]
+const MERKLE_TREE_FILE_MAGIC: [u8; 4] = *b"TFMT";
+
+/// The version of the Merkle tree file format emitted by
+/// [`MerkleTree::serialize_to`].
+#[cfg(feature = "std")]
+const MERKLE_TREE_FILE_VERSION: u8 = 1;
+
+#[cfg(feature = "std")]
+#[derive(Debug, PartialEq, Eq)]
+pub enum MerkleTreeFileError {
+    BadMagic,
+    UnsupportedFormatVersion(u8),
+    MalformedHeader,
+    VariableWidthDigest,
+}
 
-        // Get the salts associated with the leafs
-        // salts are random data, so they cannot be compressed
-        let mut ret: SaltedAuthenticationStructure<Digest> = Vec::with_capacity(indices.len());
-        for (i, index) in indices.iter().enumerate() {
-            let salt = self.salts[*index];
-            ret.push((partial_authentication_paths[i].clone(), salt));
+#[cfg(feature = "std")]
+impl Error for MerkleTreeFileError {}
+
+#[cfg(feature = "std")]
+impl fmt::Display for MerkleTreeFileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<H: MerkleTreeHasher> MerkleTree<H>
+where
+    H::Digest: Serialize,
+{
+    /// Persist the fully built tree to `path`, so later prover runs can
+    /// reopen the commitment with [`MappedMerkleTree::open`] instead of
+    /// rebuilding it from the leaves.
+    ///
+    /// The format is a small header -- magic, version, leaf count, node
+    /// width -- followed by the nodes in heap order at a fixed width, so
+    /// a reader can seek to any single node. Digest types whose encoding
+    /// is not fixed-width are rejected.
+    pub fn serialize_to(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let node_width = bincode::serialize(&self.nodes[1])?.len();
+
+        let mut file = File::create(path)?;
+        file.write_all(&MERKLE_TREE_FILE_MAGIC)?;
+        file.write_all(&[MERKLE_TREE_FILE_VERSION])?;
+        file.write_all(&(self.get_leaf_count() as u64).to_le_bytes())?;
+        file.write_all(&(node_width as u32).to_le_bytes())?;
+        for node in self.nodes[1..].iter() {
+            let node_bytes = bincode::serialize(node)?;
+            if node_bytes.len() != node_width {
+                return Err(Box::new(MerkleTreeFileError::VariableWidthDigest));
+            }
+            file.write_all(&node_bytes)?;
         }
 
-        ret
+        Ok(())
     }
+}
 
-    /// To use this function the user must provide the corresponding *UNSALTED* `leaves`.
-    pub fn verify_authentication_structure(
-        root_hash: Digest,
-        indices: &[usize],
-        unsalted_leaves: &[Digest],
-        proof: &SaltedAuthenticationStructure<Digest>,
-    ) -> bool {
-        if indices.len() != proof.len() || indices.len() != unsalted_leaves.len() {
-            debug_assert!(indices.len() == proof.len());
-            debug_assert!(indices.len() == unsalted_leaves.len());
+/// A read-only view of a Merkle tree persisted with
+/// [`MerkleTree::serialize_to`].
+///
+/// Nodes are paged in from the file on demand -- the fixed-width format
+/// makes every node seekable -- so reopening a commitment costs a header
+/// read, and extracting an authentication structure reads only the nodes
+/// it reveals. The `&mut self` on the accessors is an artifact of the
+/// file handle's seek position, as with [`DiskBackedMerkleTree`].
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct MappedMerkleTree<H: MerkleTreeHasher> {
+    file: File,
+    leaf_count: usize,
+    node_width: usize,
+    _hasher: PhantomData<H>,
+}
 
-            return false;
+#[cfg(feature = "std")]
+impl<H: MerkleTreeHasher> MappedMerkleTree<H>
+where
+    H::Digest: serde::de::DeserializeOwned,
+{
+    pub fn open(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let mut file = File::open(path)?;
+
+        let mut magic = [0u8; 4];
+        file.read_exact(&mut magic)?;
+        if magic != MERKLE_TREE_FILE_MAGIC {
+            return Err(Box::new(MerkleTreeFileError::BadMagic));
         }
 
-        if indices.is_empty() {
-            return true;
+        let mut version = [0u8; 1];
+        file.read_exact(&mut version)?;
+        if version[0] != MERKLE_TREE_FILE_VERSION {
+            return Err(Box::new(MerkleTreeFileError::UnsupportedFormatVersion(
+                version[0],
+            )));
         }
 
-        let mut leaf_hashes: Vec<Digest> = Vec::with_capacity(indices.len());
-        for (value, (_, salt)) in izip!(unsalted_leaves, proof) {
-            let leaf_hash = H::hash_pair(value, salt);
-            leaf_hashes.push(leaf_hash);
+        let mut leaf_count_bytes = [0u8; 8];
+        file.read_exact(&mut leaf_count_bytes)?;
+        let leaf_count = u64::from_le_bytes(leaf_count_bytes) as usize;
+
+        let mut node_width_bytes = [0u8; 4];
+        file.read_exact(&mut node_width_bytes)?;
+        let node_width = u32::from_le_bytes(node_width_bytes) as usize;
+
+        let header_size = file.stream_position()? as usize;
+        let expected_size = header_size + (2 * leaf_count - 1) * node_width;
+        if !is_power_of_two(leaf_count) || file.metadata()?.len() as usize != expected_size {
+            return Err(Box::new(MerkleTreeFileError::MalformedHeader));
         }
 
-        let saltless_proof: Vec<PartialAuthenticationPath<Digest>> =
-            proof.iter().map(|x| x.0.clone()).collect();
+        Ok(Self {
+            file,
+            leaf_count,
+            node_width,
+            _hasher: PhantomData,
+        })
+    }
 
-        MerkleTree::<H>::verify_authentication_structure_from_leaves(
-            root_hash,
-            indices,
-            &leaf_hashes,
-            &saltless_proof,
-        )
+    /// Read the node at `heap_index`, using the same 1-indexed numbering
+    /// as [`MerkleTree::nodes`].
+    fn node(&mut self, heap_index: usize) -> H::Digest {
+        debug_assert!(
+            0 < heap_index && heap_index < 2 * self.leaf_count,
+            "Heap index out of range. Leaf count: {}, heap index: {}",
+            self.leaf_count,
+            heap_index
+        );
+        let header_size = (MERKLE_TREE_FILE_MAGIC.len() + 1 + 8 + 4) as u64;
+        let offset = header_size + ((heap_index - 1) * self.node_width) as u64;
+        self.file.seek(SeekFrom::Start(offset)).unwrap();
+        let mut node_bytes = vec![0u8; self.node_width];
+        self.file.read_exact(&mut node_bytes).unwrap();
+        bincode::deserialize(&node_bytes).unwrap()
     }
 
-    pub fn get_root(&self) -> Digest {
-        self.internal_merkle_tree.get_root()
+    pub fn get_root(&mut self) -> H::Digest {
+        self.node(1)
     }
 
     pub fn get_leaf_count(&self) -> usize {
-        self.internal_merkle_tree.get_leaf_count()
+        self.leaf_count
     }
 
-    #[allow(dead_code)]
     pub fn get_height(&self) -> usize {
-        self.internal_merkle_tree.get_height()
+        get_height_of_complete_binary_tree(self.leaf_count)
     }
 
-    pub fn get_all_salted_leaves(&self) -> Vec<Digest> {
-        let first_leaf = self.internal_merkle_tree.nodes.len() / 2;
-        self.internal_merkle_tree.nodes[first_leaf..].to_vec()
+    pub fn get_leaf(&mut self, leaf_index: usize) -> H::Digest {
+        self.node(self.leaf_count + leaf_index)
     }
 
-    pub fn get_salted_leaf_by_index(&self, index: usize) -> Digest {
-        let first_leaf_index = self.internal_merkle_tree.nodes.len() / 2;
-        let beyond_last_leaf_index = self.internal_merkle_tree.nodes.len();
+    pub fn get_authentication_path(&mut self, leaf_index: usize) -> Vec<H::Digest> {
+        let mut auth_path: Vec<H::Digest> = Vec::with_capacity(self.get_height());
+        let mut node_index = leaf_index + self.leaf_count;
+        while node_index > 1 {
+            auth_path.push(self.node(node_index ^ 1));
+            node_index /= 2;
+        }
+
+        auth_path
+    }
+
+    /// Identical output to [`MerkleTree::get_authentication_structure`],
+    /// reading only the revealed nodes from the file.
+    pub fn get_authentication_structure(
+        &mut self,
+        indices: &[usize],
+    ) -> Vec<PartialAuthenticationPath<H::Digest>> {
+        authentication_structure_with_lookup(2 * self.leaf_count, indices, |i| self.node(i))
+    }
+}
+
+/// The openings of one tree in a
+/// [`MerkleTree::check_authentication_structures_batch`] call: the claimed
+/// root, the opened leaf indices, the leaf values -- hashed by the batch,
+/// not by the caller -- and their authentication paths.
+pub struct RootOpenings<V> {
+    pub root: Digest,
+    pub leaf_indices: Vec<usize>,
+    pub leaf_values: Vec<V>,
+    pub partial_auth_paths: Vec<PartialAuthenticationPath<Digest>>,
+}
+
+/// A fixed-capacity bitset over node indices. Queries past the capacity
+/// simply answer `false`, matching the semantics of a set lookup.
+struct NodeBitSet {
+    words: Vec<u64>,
+    capacity: usize,
+}
+
+impl NodeBitSet {
+    fn new(capacity: usize) -> Self {
+        Self {
+            words: vec![0u64; capacity.div_ceil(64)],
+            capacity,
+        }
+    }
+
+    fn set(&mut self, index: usize) {
+        self.words[index / 64] |= 1 << (index % 64);
+    }
+
+    fn get(&self, index: usize) -> bool {
+        index < self.capacity && self.words[index / 64] >> (index % 64) & 1 == 1
+    }
+}
+
+/// Shared core of [`MerkleTree::get_authentication_structure`] and
+/// [`DiskBackedMerkleTree::get_authentication_structure`]. The pruning
+/// logic only manipulates node indices; digests are fetched through `node`,
+/// and only for the positions that remain revealed, which is what lets the
+/// disk-backed variant read a minimal number of nodes.
+///
+/// The calculable set is tracked in a [`NodeBitSet`] and closed under
+/// derivation in a single bottom-up sweep -- a child's index is always
+/// larger than its parent's, so one descending pass sees every child's
+/// final state -- keeping openings of thousands of indices on large trees
+/// linear in the tree size instead of quadratic in the query count.
+fn authentication_structure_with_lookup<D, F>(
+    node_count: usize,
+    indices: &[usize],
+    mut node: F,
+) -> Vec<PartialAuthenticationPath<D>>
+where
+    F: FnMut(usize) -> D,
+{
+    let path_length = get_height_of_complete_binary_tree(node_count / 2);
+
+    // Mark every opened leaf and every sibling on an opened path.
+    let mut calculable = NodeBitSet::new(node_count);
+    let mut opened_leaves = NodeBitSet::new(node_count / 2);
+    for i in indices.iter() {
+        opened_leaves.set(*i);
+        let mut index = node_count / 2 + i;
+        calculable.set(index);
+        for _ in 1..path_length {
+            calculable.set(index ^ 1);
+            index /= 2;
+        }
+    }
+
+    // Close under derivation: a parent is calculable when both its
+    // children are.
+    for parent in (1..node_count / 2).rev() {
+        if calculable.get(2 * parent) && calculable.get(2 * parent + 1) {
+            calculable.set(parent);
+        }
+    }
+
+    let mut scanned = NodeBitSet::new(node_count);
+    let mut output: Vec<PartialAuthenticationPath<D>> = Vec::with_capacity(indices.len());
+    for i in indices.iter() {
+        let mut path: Vec<Option<D>> = Vec::with_capacity(path_length);
+        let mut index: usize = node_count / 2 + i;
+        scanned.set(index);
+        for _ in 0..path_length {
+            let sibling = index ^ 1;
+            let redundant = calculable.get(sibling * 2) && calculable.get(sibling * 2 + 1)
+                || sibling > node_count / 2 && opened_leaves.get(sibling - node_count / 2)
+                || scanned.get(sibling);
+            if redundant {
+                path.push(None);
+            } else {
+                path.push(Some(node(sibling)));
+            }
+            scanned.set(sibling);
+            index /= 2;
+        }
+        output.push(PartialAuthenticationPath(path));
+    }
+
+    output
+}
+
+/// The verifier-side reconstruction of the known part of a Merkle tree.
+///
+/// Verifying each opening of a tree independently re-derives the same
+/// internal nodes over and over, and cannot tell whether two openings are
+/// mutually consistent -- only whether each one reaches the root. This
+/// structure absorbs openings (leaf digests plus their
+/// [`PartialAuthenticationPath`]s) into one pool of reconstructed nodes,
+/// using the same 1-indexed heap numbering as [`MerkleTree::nodes`]:
+/// every absorbed or derived node is checked against what is already
+/// known, so conflicting openings are rejected, and nodes reconstructed
+/// in one round are reused when later rounds open the same tree.
+pub struct PartialMerkleTree<H: MerkleTreeHasher> {
+    nodes: HashMap<u64, H::Digest>,
+    leaf_count: usize,
+    _hasher: PhantomData<H>,
+}
+
+impl<H: MerkleTreeHasher> PartialMerkleTree<H> {
+    pub fn new(leaf_count: usize) -> Self {
         assert!(
-            index < first_leaf_index || beyond_last_leaf_index <= index,
-            "Out of bounds index requested"
+            is_power_of_two(leaf_count),
+            "Size of input for Merkle tree must be a power of 2"
         );
-        self.internal_merkle_tree.nodes[first_leaf_index + index]
+
+        Self {
+            nodes: HashMap::new(),
+            leaf_count,
+            _hasher: PhantomData,
+        }
     }
 
-    pub fn get_salted_leaves_by_indices(&self, leaf_indices: &[usize]) -> Vec<Digest> {
-        let leaf_count = leaf_indices.len();
+    /// Absorb one authentication structure into the pool of known nodes,
+    /// rejecting it if it conflicts with anything absorbed before or
+    /// leaves a gap that the other openings cannot fill.
+    pub fn absorb(
+        &mut self,
+        leaf_indices: &[usize],
+        leaf_digests: &[H::Digest],
+        partial_auth_paths: &[PartialAuthenticationPath<H::Digest>],
+    ) -> Result<(), AuthenticationStructureError> {
+        let height = get_height_of_complete_binary_tree(self.leaf_count);
+        if leaf_indices.len() != partial_auth_paths.len()
+            || leaf_indices.len() != leaf_digests.len()
+            || partial_auth_paths.iter().any(|path| path.0.len() != height)
+        {
+            return Err(AuthenticationStructureError::MismatchedInputLengths);
+        }
+        if let Some(leaf_index) = leaf_indices.iter().find(|i| **i >= self.leaf_count) {
+            return Err(AuthenticationStructureError::LeafIndexOutOfRange {
+                leaf_index: *leaf_index,
+            });
+        }
 
-        let mut result = Vec::with_capacity(leaf_count);
+        for (i, leaf_digest, partial_auth_path) in
+            izip!(leaf_indices, leaf_digests, partial_auth_paths)
+        {
+            let mut index = (self.leaf_count + i) as u64;
+            self.insert_node(index, *leaf_digest)?;
+            for node in partial_auth_path.0.iter() {
+                if let Some(node) = node {
+                    self.insert_node(index ^ 1, *node)?;
+                }
+                index /= 2;
+            }
+        }
 
-        for index in leaf_indices {
-            result.push(self.get_salted_leaf_by_index(*index));
+        self.derive()?;
+
+        // Every sibling on every absorbed path must now be known, or the
+        // opening cannot be connected to the root.
+        for (i, partial_auth_path) in leaf_indices.iter().zip(partial_auth_paths.iter()) {
+            let mut index = (self.leaf_count + i) as u64;
+            for (level, _) in partial_auth_path.0.iter().enumerate() {
+                if !self.nodes.contains_key(&(index ^ 1)) {
+                    return Err(AuthenticationStructureError::MissingNode {
+                        leaf_index: *i,
+                        level,
+                    });
+                }
+                index /= 2;
+            }
         }
-        result
+
+        Ok(())
     }
 
-    pub fn get_salts(&self) -> &[Digest] {
-        &self.salts
+    /// Record a node digest, rejecting a digest that disagrees with one
+    /// already known for the same position.
+    fn insert_node(
+        &mut self,
+        node_index: u64,
+        digest: H::Digest,
+    ) -> Result<(), AuthenticationStructureError> {
+        match self.nodes.get(&node_index) {
+            None => {
+                self.nodes.insert(node_index, digest);
+                Ok(())
+            }
+            Some(known) if *known == digest => Ok(()),
+            Some(_) => Err(AuthenticationStructureError::ConflictingNode { node_index }),
+        }
     }
-}
 
-#[cfg(test)]
-mod merkle_tree_test {
-    use super::*;
-    use crate::shared_math::b_field_element::BFieldElement;
-    use crate::shared_math::other::{
-        random_elements, random_elements_distinct_range, random_elements_range,
-    };
-    use crate::shared_math::rescue_prime_regular::RescuePrimeRegular;
-    use crate::test_shared::corrupt_digest;
-    use crate::util_types::algebraic_hasher::Hashable;
-    use itertools::Itertools;
-    use rand::{Rng, RngCore};
-    use std::iter::zip;
+    /// Derive every parent whose children are both known, to a fixpoint.
+    /// A derived digest that disagrees with an absorbed one is a conflict.
+    fn derive(&mut self) -> Result<(), AuthenticationStructureError> {
+        let mut complete = false;
+        while !complete {
+            complete = true;
+            let mut parent_keys: Vec<u64> = self.nodes.keys().map(|index| index / 2).collect();
+            parent_keys.sort_by_key(|index| Reverse(*index));
+            for parent_key in parent_keys {
+                if parent_key == 0 {
+                    continue;
+                }
+                let (Some(left), Some(right)) = (
+                    self.nodes.get(&(2 * parent_key)),
+                    self.nodes.get(&(2 * parent_key + 1)),
+                ) else {
+                    continue;
+                };
+                let derived = H::hash_pair(left, right);
+                match self.nodes.get(&parent_key) {
+                    None => {
+                        self.nodes.insert(parent_key, derived);
+                        complete = false;
+                    }
+                    Some(known) if *known == derived => (),
+                    Some(_) => {
+                        return Err(AuthenticationStructureError::ConflictingNode {
+                            node_index: parent_key,
+                        })
+                    }
+                }
+            }
+        }
 
-    /// Count the number of hashes present in all partial authentication paths
-    fn count_hashes<Digest: Clone>(proof: &SaltedAuthenticationStructure<Digest>) -> usize {
-        proof
-            .iter()
-            .map(|(partial_auth_path, _)| {
+        Ok(())
+    }
+
+    /// The digest of the node at `node_index`, if reconstructed.
+    pub fn get_node(&self, node_index: u64) -> Option<H::Digest> {
+        self.nodes.get(&node_index).copied()
+    }
+
+    pub fn get_leaf(&self, leaf_index: usize) -> Option<H::Digest> {
+        self.get_node((self.leaf_count + leaf_index) as u64)
+    }
+
+    pub fn get_root(&self) -> Option<H::Digest> {
+        self.get_node(1)
+    }
+
+    /// Whether the absorbed openings connect to the given root.
+    pub fn verify_root(&self, root_hash: H::Digest) -> bool {
+        self.get_root() == Some(root_hash)
+    }
+
+    pub fn get_leaf_count(&self) -> usize {
+        self.leaf_count
+    }
+
+    pub fn get_height(&self) -> usize {
+        get_height_of_complete_binary_tree(self.leaf_count)
+    }
+}
+
+/// A Merkle tree whose nodes live in a [`DatabaseVector`] instead of RAM.
+///
+/// When committing to very large codewords -- FRI domains of 2^26 leaves and
+/// beyond -- the node array of an in-memory [`MerkleTree`] alone runs to
+/// gigabytes per round. This variant writes every level to the backing
+/// database as it is computed, keeping at most one level in memory during
+/// construction, and reads back only the nodes an authentication structure
+/// actually reveals.
+///
+/// The tree is immutable once built; the `&mut self` on the accessors is an
+/// artifact of the database handle requiring mutable access for reads.
+#[cfg(feature = "std")]
+pub struct DiskBackedMerkleTree<H: AlgebraicHasher> {
+    nodes: DatabaseVector<Digest>,
+    leaf_count: usize,
+    _hasher: PhantomData<H>,
+}
+
+#[cfg(feature = "std")]
+impl<H: AlgebraicHasher> DiskBackedMerkleTree<H> {
+    /// Build a tree over `digests`, storing all nodes in `db`. The database
+    /// must be empty.
+    ///
+    /// Levels are written bottom-up, leaves first, so peak memory during
+    /// construction is the input slice plus one half-size level of parent
+    /// digests.
+    pub fn from_digests(digests: &[Digest], db: DB) -> Self {
+        let leaf_count = digests.len();
+
+        assert!(
+            is_power_of_two(leaf_count),
+            "Size of input for Merkle tree must be a power of 2"
+        );
+
+        let mut nodes: DatabaseVector<Digest> = DatabaseVector::new(db);
+        nodes.batch_push(digests);
+
+        let mut level: Vec<Digest> = Self::parent_level(digests);
+        while !level.is_empty() {
+            nodes.batch_push(&level);
+            if level.len() == 1 {
+                break;
+            }
+            level = Self::parent_level(&level);
+        }
+
+        Self {
+            nodes,
+            leaf_count,
+            _hasher: PhantomData,
+        }
+    }
+
+    /// One level of parent digests from a level of child digests.
+    fn parent_level(level: &[Digest]) -> Vec<Digest> {
+        (0..level.len() / 2)
+            .into_par_iter()
+            .map(|i| H::hash_pair(&level[2 * i], &level[2 * i + 1]))
+            .collect()
+    }
+
+    /// Fetch the node at `heap_index`, using the same 1-indexed numbering as
+    /// [`MerkleTree::nodes`]: the root is node 1 and the leaves start at
+    /// `leaf_count`. On disk the levels are stored bottom-up in the order
+    /// they are computed, so the heap index is translated first.
+    fn node(&mut self, heap_index: usize) -> Digest {
+        debug_assert!(
+            0 < heap_index && heap_index < 2 * self.leaf_count,
+            "Heap index out of range. Leaf count: {}, heap index: {}",
+            self.leaf_count,
+            heap_index
+        );
+        let level_size = 1usize << log_2_floor(heap_index as u128);
+        let storage_index = 2 * self.leaf_count - 2 * level_size + (heap_index - level_size);
+        self.nodes.get(storage_index as u128)
+    }
+
+    pub fn get_root(&mut self) -> Digest {
+        self.node(1)
+    }
+
+    pub fn get_leaf_count(&self) -> usize {
+        self.leaf_count
+    }
+
+    pub fn get_height(&self) -> usize {
+        get_height_of_complete_binary_tree(self.leaf_count)
+    }
+
+    /// The disk-backed counterpart of
+    /// [`MerkleTree::get_authentication_structure`]: identical output,
+    /// reading only the revealed nodes from the database.
+    pub fn get_authentication_structure(
+        &mut self,
+        indices: &[usize],
+    ) -> Vec<PartialAuthenticationPath<Digest>> {
+        authentication_structure_with_lookup(2 * self.leaf_count, indices, |i| self.node(i))
+    }
+}
+
+/// A mutable, persistent Merkle tree stored in a [`DatabaseVector`].
+///
+/// Where [`DiskBackedMerkleTree`] is built once over a complete set of
+/// digests and then frozen, this variant commits to a dataset that grows
+/// over time: leaves are appended (and updated) one at a time, and every
+/// operation touches only the `log n` nodes on the affected path, so the
+/// tree never has to fit in RAM. The capacity is fixed at creation and
+/// unoccupied leaves hold a caller-chosen padding digest, making the root
+/// well-defined at every fill level.
+///
+/// Nodes are stored in the same 1-indexed heap layout as
+/// [`MerkleTree::nodes`]: the root at node 1, the leaves starting at
+/// `capacity`, and node 0 unused.
+#[cfg(feature = "std")]
+pub struct ArchivalMerkleTree<H: AlgebraicHasher> {
+    nodes: DatabaseVector<Digest>,
+    leaf_count: usize,
+    capacity: usize,
+    _hasher: PhantomData<H>,
+}
+
+#[cfg(feature = "std")]
+impl<H: AlgebraicHasher> ArchivalMerkleTree<H> {
+    /// Create an empty tree of the given capacity -- a power of two --
+    /// with every leaf set to `padding_leaf`. The database must be empty.
+    ///
+    /// Each level of the padding tree is a single repeated digest, so
+    /// initialization costs `log n` hashes and one write per node.
+    pub fn new(capacity: usize, padding_leaf: Digest, db: DB) -> Self {
+        assert!(
+            is_power_of_two(capacity),
+            "Capacity of archival Merkle tree must be a power of 2"
+        );
+
+        let height = get_height_of_complete_binary_tree(capacity);
+        let mut padding_digests: Vec<Digest> = vec![padding_leaf];
+        for _ in 0..height {
+            let child = padding_digests.last().unwrap();
+            padding_digests.push(H::hash_pair(child, child));
+        }
+
+        // Node 0 is never used for anything; the levels follow top-down.
+        let mut nodes: DatabaseVector<Digest> = DatabaseVector::new(db);
+        nodes.push(padding_leaf);
+        for level in (0..=height).rev() {
+            nodes.batch_push(&vec![padding_digests[level]; capacity >> level]);
+        }
+
+        Self {
+            nodes,
+            leaf_count: 0,
+            capacity,
+            _hasher: PhantomData,
+        }
+    }
+
+    /// Append the next leaf and recompute the nodes on its path to the
+    /// root.
+    pub fn append(&mut self, leaf: Digest) {
+        assert!(
+            self.leaf_count < self.capacity,
+            "Cannot append to an archival Merkle tree at capacity. Capacity: {}",
+            self.capacity
+        );
+        let leaf_index = self.leaf_count;
+        self.leaf_count += 1;
+        self.rewrite_path(leaf_index, leaf);
+    }
+
+    /// Overwrite the leaf at `leaf_index` and recompute the nodes on its
+    /// path to the root.
+    pub fn update_leaf(&mut self, leaf_index: usize, new_digest: Digest) {
+        assert!(
+            leaf_index < self.leaf_count,
+            "Cannot update leaf {} of an archival Merkle tree with {} leaves",
+            leaf_index,
+            self.leaf_count
+        );
+        self.rewrite_path(leaf_index, new_digest);
+    }
+
+    /// Write `leaf` at `leaf_index` and rehash the ancestors above it.
+    fn rewrite_path(&mut self, leaf_index: usize, leaf: Digest) {
+        let mut node_index = self.capacity + leaf_index;
+        self.nodes.set(node_index as u128, leaf);
+        while node_index > 1 {
+            node_index /= 2;
+            let parent = H::hash_pair(
+                &self.nodes.get(2 * node_index as u128),
+                &self.nodes.get(2 * node_index as u128 + 1),
+            );
+            self.nodes.set(node_index as u128, parent);
+        }
+    }
+
+    pub fn get_root(&mut self) -> Digest {
+        self.nodes.get(1)
+    }
+
+    pub fn get_leaf_count(&self) -> usize {
+        self.leaf_count
+    }
+
+    pub fn get_capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn get_height(&self) -> usize {
+        get_height_of_complete_binary_tree(self.capacity)
+    }
+
+    pub fn get_leaf(&mut self, leaf_index: usize) -> Digest {
+        self.nodes.get((self.capacity + leaf_index) as u128)
+    }
+
+    /// The authentication path for one leaf, sibling digests bottom-up,
+    /// reading `log n` nodes from the database. Verifies with
+    /// [`MerkleTree::verify_authentication_path_from_leaf_hash`].
+    pub fn get_authentication_path(&mut self, leaf_index: usize) -> Vec<Digest> {
+        let mut auth_path: Vec<Digest> = Vec::with_capacity(self.get_height());
+        let mut node_index = self.capacity + leaf_index;
+        while node_index > 1 {
+            auth_path.push(self.nodes.get((node_index ^ 1) as u128));
+            node_index /= 2;
+        }
+
+        auth_path
+    }
+}
+
+/// The depth of a [`SparseMerkleTree`]: one level per bit of a
+/// [`Digest`]-sized key.
+pub const SPARSE_MERKLE_TREE_HEIGHT: usize = DIGEST_LENGTH * 64;
+
+/// A Merkle tree over the full [`Digest`]-sized key space, almost all of
+/// whose leaves are empty.
+///
+/// The position of a key-value pair is determined by the bits of its key,
+/// and every empty subtree at a given height hashes to the same
+/// precomputed default digest, so the tree is never materialized: only the
+/// occupied leaves are stored, and roots and authentication paths are
+/// derived from them on demand. Because absent keys resolve to a
+/// well-defined empty leaf, the tree supports proofs of non-inclusion as
+/// well as inclusion -- the natural companion to the accumulative
+/// Merkle/MMR structures for key-value state commitments.
+///
+/// An occupied leaf holds `H(key || value)`, binding the value to its key;
+/// an empty leaf is the all-zero digest. Key bits are consumed least
+/// significant limb and bit first, from the root down.
+#[derive(Clone, Debug)]
+pub struct SparseMerkleTree<H: AlgebraicHasher> {
+    leaves: HashMap<Digest, Digest>,
+    empty_digests: Vec<Digest>,
+    _hasher: PhantomData<H>,
+}
+
+impl<H: AlgebraicHasher> SparseMerkleTree<H> {
+    pub fn new() -> Self {
+        let mut empty_digests: Vec<Digest> = Vec::with_capacity(SPARSE_MERKLE_TREE_HEIGHT + 1);
+        empty_digests.push(Digest::default());
+        for _ in 0..SPARSE_MERKLE_TREE_HEIGHT {
+            let child = empty_digests.last().unwrap();
+            empty_digests.push(H::hash_pair(child, child));
+        }
+
+        Self {
+            leaves: HashMap::new(),
+            empty_digests,
+            _hasher: PhantomData,
+        }
+    }
+
+    /// The bit of `key` selecting the branch at the given depth below the
+    /// root.
+    fn key_bit(key: &Digest, depth: usize) -> u64 {
+        key.values()[depth / 64].value() >> (depth % 64) & 1
+    }
+
+    /// The digest of an occupied leaf.
+    fn leaf_digest(key: &Digest, value: &Digest) -> Digest {
+        H::hash_pair(key, value)
+    }
+
+    /// Insert a key-value pair, overwriting any previous value for the key.
+    pub fn insert(&mut self, key: Digest, value: Digest) {
+        self.leaves.insert(key, value);
+    }
+
+    /// Remove a key, turning its leaf back into the empty default.
+    pub fn remove(&mut self, key: &Digest) -> Option<Digest> {
+        self.leaves.remove(key)
+    }
+
+    pub fn get(&self, key: &Digest) -> Option<Digest> {
+        self.leaves.get(key).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.leaves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.leaves.is_empty()
+    }
+
+    pub fn get_root(&self) -> Digest {
+        let entries: Vec<(&Digest, &Digest)> = self.leaves.iter().collect();
+        self.subtree_root(&entries, 0)
+    }
+
+    /// The root of the subtree at the given depth holding `entries`, all
+    /// of whose keys agree on the first `depth` bits.
+    fn subtree_root(&self, entries: &[(&Digest, &Digest)], depth: usize) -> Digest {
+        if entries.is_empty() {
+            return self.empty_digests[SPARSE_MERKLE_TREE_HEIGHT - depth];
+        }
+        if depth == SPARSE_MERKLE_TREE_HEIGHT {
+            let (key, value) = entries[0];
+            return Self::leaf_digest(key, value);
+        }
+
+        let (zeros, ones): (Vec<_>, Vec<_>) = entries
+            .iter()
+            .partition(|(key, _)| Self::key_bit(key, depth) == 0);
+
+        H::hash_pair(
+            &self.subtree_root(&zeros, depth + 1),
+            &self.subtree_root(&ones, depth + 1),
+        )
+    }
+
+    /// The authentication path for `key`: sibling subtree roots bottom-up,
+    /// regardless of whether the key is present. Verifies with
+    /// [`verify_inclusion_proof`] when the key is in the tree and with
+    /// [`verify_noninclusion_proof`] when it is not.
+    ///
+    /// [`verify_inclusion_proof`]: SparseMerkleTree::verify_inclusion_proof
+    /// [`verify_noninclusion_proof`]: SparseMerkleTree::verify_noninclusion_proof
+    pub fn get_authentication_path(&self, key: &Digest) -> Vec<Digest> {
+        let mut auth_path: Vec<Digest> = Vec::with_capacity(SPARSE_MERKLE_TREE_HEIGHT);
+        let mut entries: Vec<(&Digest, &Digest)> = self.leaves.iter().collect();
+        for depth in 0..SPARSE_MERKLE_TREE_HEIGHT {
+            let own_bit = Self::key_bit(key, depth);
+            let (zeros, ones): (Vec<_>, Vec<_>) = entries
+                .iter()
+                .partition(|(entry_key, _)| Self::key_bit(entry_key, depth) == 0);
+            let (own, sibling) = match own_bit {
+                0 => (zeros, ones),
+                _ => (ones, zeros),
+            };
+            auth_path.push(self.subtree_root(&sibling, depth + 1));
+            entries = own;
+        }
+
+        auth_path.reverse();
+        auth_path
+    }
+
+    /// Hash a leaf up along `key`'s path to the root implied by the
+    /// authentication path.
+    fn fold_authentication_path(key: &Digest, leaf: Digest, auth_path: &[Digest]) -> Digest {
+        let mut acc_hash = leaf;
+        for (i, sibling) in auth_path.iter().enumerate() {
+            let depth = SPARSE_MERKLE_TREE_HEIGHT - 1 - i;
+            acc_hash = match Self::key_bit(key, depth) {
+                0 => H::hash_pair(&acc_hash, sibling),
+                _ => H::hash_pair(sibling, &acc_hash),
+            };
+        }
+
+        acc_hash
+    }
+
+    /// Verify that `key` maps to `value` under the tree with the given
+    /// root.
+    pub fn verify_inclusion_proof(
+        root_hash: Digest,
+        key: &Digest,
+        value: &Digest,
+        auth_path: &[Digest],
+    ) -> bool {
+        auth_path.len() == SPARSE_MERKLE_TREE_HEIGHT
+            && Self::fold_authentication_path(key, Self::leaf_digest(key, value), auth_path)
+                == root_hash
+    }
+
+    /// Verify that `key` is absent from the tree with the given root, i.e.
+    /// that its leaf is the empty default.
+    pub fn verify_noninclusion_proof(
+        root_hash: Digest,
+        key: &Digest,
+        auth_path: &[Digest],
+    ) -> bool {
+        auth_path.len() == SPARSE_MERKLE_TREE_HEIGHT
+            && Self::fold_authentication_path(key, Digest::default(), auth_path) == root_hash
+    }
+}
+
+impl<H: AlgebraicHasher> Default for SparseMerkleTree<H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A Merkle tree of configurable arity.
+///
+/// Hashers with a wide absorption rate, like Rescue, can compress four or
+/// eight digests in as few permutations as two, so a wider tree trades a
+/// slightly larger authentication path -- `arity - 1` siblings per level
+/// over `log_arity n` levels -- for far fewer permutations per path, and
+/// a quarter or an eighth of the tree depth on large FRI domains.
+///
+/// Nodes are stored level by level, leaves first.
+#[derive(Clone, Debug)]
+pub struct MultiArityMerkleTree<H: AlgebraicHasher> {
+    levels: Vec<Vec<Digest>>,
+    arity: usize,
+    _hasher: PhantomData<H>,
+}
+
+impl<H: AlgebraicHasher> MultiArityMerkleTree<H> {
+    /// Build a tree of the given arity over `digests`. The arity must be
+    /// 2, 4, or 8, and the number of leaves a power of the arity.
+    pub fn from_digests(digests: &[Digest], arity: usize) -> Self {
+        assert!(
+            matches!(arity, 2 | 4 | 8),
+            "Merkle tree arity must be one of 2, 4, or 8. Got: {}",
+            arity
+        );
+
+        let mut leaf_count = digests.len();
+        while leaf_count > 1 {
+            assert!(
+                leaf_count.is_multiple_of(arity),
+                "Size of input for arity-{} Merkle tree must be a power of {}",
+                arity,
+                arity
+            );
+            leaf_count /= arity;
+        }
+
+        let mut levels: Vec<Vec<Digest>> = vec![digests.to_vec()];
+        while levels.last().unwrap().len() > 1 {
+            let children = levels.last().unwrap();
+            let parents: Vec<Digest> = map_collect_range(children.len() / arity, |i| {
+                Self::compress(&children[arity * i..arity * (i + 1)])
+            });
+            levels.push(parents);
+        }
+
+        Self {
+            levels,
+            arity,
+            _hasher: PhantomData,
+        }
+    }
+
+    /// The parent digest of `arity` sibling digests.
+    fn compress(siblings: &[Digest]) -> Digest {
+        let sequence: Vec<_> = siblings
+            .iter()
+            .flat_map(|digest| digest.to_sequence())
+            .collect();
+        H::hash_slice(&sequence)
+    }
+
+    pub fn get_root(&self) -> Digest {
+        self.levels.last().unwrap()[0]
+    }
+
+    pub fn get_leaf_count(&self) -> usize {
+        self.levels[0].len()
+    }
+
+    pub fn get_arity(&self) -> usize {
+        self.arity
+    }
+
+    /// The tree height: the number of hashing levels between a leaf and
+    /// the root, i.e. `log_arity n`.
+    pub fn get_height(&self) -> usize {
+        self.levels.len() - 1
+    }
+
+    /// The authentication path for one leaf: per level, the `arity - 1`
+    /// sibling digests in left-to-right order, own position omitted.
+    pub fn get_authentication_path(&self, leaf_index: usize) -> Vec<Vec<Digest>> {
+        let mut auth_path: Vec<Vec<Digest>> = Vec::with_capacity(self.get_height());
+        let mut index = leaf_index;
+        for level in self.levels.iter().take(self.levels.len() - 1) {
+            let position = index % self.arity;
+            let family_start = index - position;
+            let siblings: Vec<Digest> = (family_start..family_start + self.arity)
+                .filter(|i| *i != index)
+                .map(|i| level[i])
+                .collect();
+            auth_path.push(siblings);
+            index /= self.arity;
+        }
+
+        auth_path
+    }
+
+    /// Verify an authentication path produced by
+    /// [`get_authentication_path`] against a root.
+    ///
+    /// [`get_authentication_path`]: MultiArityMerkleTree::get_authentication_path
+    pub fn verify_authentication_path(
+        root_hash: Digest,
+        arity: usize,
+        leaf_index: usize,
+        leaf_digest: Digest,
+        auth_path: &[Vec<Digest>],
+    ) -> bool {
+        let mut acc_hash = leaf_digest;
+        let mut index = leaf_index;
+        for siblings in auth_path.iter() {
+            if siblings.len() != arity - 1 {
+                return false;
+            }
+            let position = index % arity;
+            let mut family: Vec<Digest> = siblings.to_vec();
+            family.insert(position, acc_hash);
+            acc_hash = Self::compress(&family);
+            index /= arity;
+        }
+
+        acc_hash == root_hash
+    }
+}
+
+/// A Merkle commitment to a matrix, one row per leaf.
+///
+/// Each leaf is the hash of an entire row, so a multi-column trace is
+/// committed in a single tree: one root, and one authentication path per
+/// opened row, where a tree per column would cost a path per column.
+/// Openings reveal whole rows, which is what a colinearity check across
+/// columns wants anyway.
+#[derive(Clone, Debug)]
+pub struct RowMerkleTree<H: AlgebraicHasher> {
+    internal_merkle_tree: MerkleTree<H>,
+    rows: Vec<Vec<XFieldElement>>,
+}
+
+impl<H: AlgebraicHasher> RowMerkleTree<H> {
+    /// Build a tree over the rows of a matrix. The number of rows must be
+    /// a power of two and all rows must have the same width.
+    pub fn from_rows(rows: &[Vec<XFieldElement>]) -> Self {
+        assert!(
+            is_power_of_two(rows.len()),
+            "Size of input for Merkle tree must be a power of 2"
+        );
+        assert!(
+            rows.iter().all(|row| row.len() == rows[0].len()),
+            "All rows must have the same width"
+        );
+
+        let digests: Vec<Digest> = map_collect(rows, |row| Self::row_digest(row));
+        let internal_merkle_tree = MerkleTree::from_digests(&digests);
+
+        Self {
+            internal_merkle_tree,
+            rows: rows.to_vec(),
+        }
+    }
+
+    /// The leaf digest of one row: the hash of the row's elements laid
+    /// out as a flat sequence of base field elements.
+    fn row_digest(row: &[XFieldElement]) -> Digest {
+        let sequence: Vec<_> = row
+            .iter()
+            .flat_map(|element| element.to_sequence())
+            .collect();
+        H::hash_slice(&sequence)
+    }
+
+    pub fn get_root(&self) -> Digest {
+        self.internal_merkle_tree.get_root()
+    }
+
+    pub fn get_leaf_count(&self) -> usize {
+        self.internal_merkle_tree.get_leaf_count()
+    }
+
+    pub fn get_height(&self) -> usize {
+        self.internal_merkle_tree.get_height()
+    }
+
+    pub fn get_row_by_index(&self, index: usize) -> Vec<XFieldElement> {
+        self.rows[index].clone()
+    }
+
+    /// A compact authentication structure for the given row indices,
+    /// paired with the opened rows themselves.
+    pub fn get_authentication_structure_and_rows(
+        &self,
+        indices: &[usize],
+    ) -> Vec<(PartialAuthenticationPath<Digest>, Vec<XFieldElement>)> {
+        let partial_auth_paths = self
+            .internal_merkle_tree
+            .get_authentication_structure(indices);
+
+        partial_auth_paths
+            .into_iter()
+            .zip(indices.iter())
+            .map(|(path, index)| (path, self.rows[*index].clone()))
+            .collect()
+    }
+
+    /// Verify opened rows against a row-matrix Merkle root.
+    pub fn verify_authentication_structure(
+        root_hash: Digest,
+        indices: &[usize],
+        proof: &[(PartialAuthenticationPath<Digest>, Vec<XFieldElement>)],
+    ) -> bool {
+        if indices.len() != proof.len() {
+            return false;
+        }
+
+        let leaf_digests: Vec<Digest> =
+            proof.iter().map(|(_, row)| Self::row_digest(row)).collect();
+        let auth_paths: Vec<PartialAuthenticationPath<Digest>> =
+            proof.iter().map(|(path, _)| path.clone()).collect();
+
+        MerkleTree::<H>::verify_authentication_structure_from_leaves(
+            root_hash,
+            indices,
+            &leaf_digests,
+            &auth_paths,
+        )
+    }
+}
+
+/// A streaming Merkle root computation with O(log n) working memory.
+///
+/// [`MerkleTree::from_digests`] needs every leaf in memory and stores all
+/// internal nodes -- fine for producing authentication paths, wasteful
+/// when only the root is wanted. This builder consumes leaves one at a
+/// time and keeps a single pending subtree root per tree level: when two
+/// subtrees of the same height exist, they are immediately merged into
+/// their parent. For layer persistence, see [`DiskBackedMerkleTree`].
+pub struct StreamingMerkleTreeBuilder<H: AlgebraicHasher> {
+    // `pending[h]` is the root of a complete subtree over 2^h leaves
+    // awaiting a right-hand sibling of the same height.
+    pending: Vec<Option<Digest>>,
+    leaf_count: usize,
+    _hasher: PhantomData<H>,
+}
+
+impl<H: AlgebraicHasher> Default for StreamingMerkleTreeBuilder<H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<H: AlgebraicHasher> StreamingMerkleTreeBuilder<H> {
+    pub fn new() -> Self {
+        Self {
+            pending: vec![],
+            leaf_count: 0,
+            _hasher: PhantomData,
+        }
+    }
+
+    /// Append the next leaf digest, merging completed subtrees as far up
+    /// as they go.
+    pub fn append(&mut self, leaf: Digest) {
+        self.leaf_count += 1;
+
+        let mut carry = leaf;
+        let mut height = 0;
+        loop {
+            if height == self.pending.len() {
+                self.pending.push(None);
+            }
+            match self.pending[height].take() {
+                None => {
+                    self.pending[height] = Some(carry);
+                    return;
+                }
+                Some(left_sibling) => {
+                    carry = H::hash_pair(&left_sibling, &carry);
+                    height += 1;
+                }
+            }
+        }
+    }
+
+    /// How many leaves have been appended so far.
+    pub fn get_leaf_count(&self) -> usize {
+        self.leaf_count
+    }
+
+    /// Consume the builder and produce the root. The number of appended
+    /// leaves must be a power of two, matching the shape
+    /// [`MerkleTree::from_digests`] accepts.
+    pub fn finish(mut self) -> Digest {
+        assert!(
+            is_power_of_two(self.leaf_count),
+            "Size of input for Merkle tree must be a power of 2"
+        );
+        self.pending
+            .pop()
+            .expect("A power-of-two leaf count is nonzero")
+            .expect("The topmost pending subtree spans all leaves")
+    }
+
+    /// The root of the Merkle tree over the leaves of `leaves`, without
+    /// materializing more than O(log n) digests at a time.
+    pub fn root_from_leaf_iterator<I: IntoIterator<Item = Digest>>(leaves: I) -> Digest {
+        let mut builder = Self::new();
+        for leaf in leaves {
+            builder.append(leaf);
+        }
+        builder.finish()
+    }
+}
+
+pub type SaltedAuthenticationStructure<Digest> = Vec<(PartialAuthenticationPath<Digest>, Digest)>;
+
+#[derive(Clone, Debug)]
+pub struct SaltedMerkleTree<H: AlgebraicHasher> {
+    internal_merkle_tree: MerkleTree<H>,
+    salts: Vec<Digest>,
+}
+
+impl<H: AlgebraicHasher> SaltedMerkleTree<H>
+where
+    H: Sync + Send, // FIXME: Remove these.
+{
+    pub fn from_digests(leaves: &[Digest], salts: &[Digest]) -> Self {
+        assert!(
+            is_power_of_two(leaves.len()),
+            "Size of input for Merkle tree must be a power of 2"
+        );
+
+        // nodes[0] is never used for anything.
+        let mut nodes: Vec<Digest> = vec![Digest::default(); 2 * leaves.len()];
+
+        for i in 0..leaves.len() {
+            let value = leaves[i];
+            let leaf_digest = H::hash_pair(&value, &salts[i]);
+
+            nodes[leaves.len() + i] = leaf_digest;
+        }
+
+        // loop from `len(L) - 1` to 1
+        for i in (1..(nodes.len() / 2)).rev() {
+            let left = nodes[i * 2];
+            let right = nodes[i * 2 + 1];
+            nodes[i] = H::hash_pair(&left, &right);
+        }
+
+        let _hasher = PhantomData;
+        let internal_merkle_tree = MerkleTree::<H> { nodes, _hasher };
+
+        Self {
+            internal_merkle_tree,
+            salts: salts.to_vec(),
+        }
+    }
+
+    /// Build a salted tree over `leaves`, drawing one fresh random salt
+    /// per leaf. This is the constructor to reach for when the salts have
+    /// no life outside the tree: revealing a salt only for opened leaves
+    /// is what keeps the unopened leaf values hidden.
+    pub fn with_random_salts(leaves: &[Digest]) -> Self {
+        let salts: Vec<Digest> = random_elements(leaves.len());
+        Self::from_digests(leaves, &salts)
+    }
+
+    pub fn get_authentication_path_and_salt(&self, index: usize) -> (Vec<Digest>, Digest) {
+        let authentication_path = self.internal_merkle_tree.get_authentication_path(index);
+        let salt = self.salts[index];
+
+        (authentication_path, salt)
+    }
+
+    pub fn verify_authentication_path(
+        root_hash: Digest,
+        index: u32,
+        leaf: Digest,
+        auth_path: Vec<Digest>,
+        leaf_salt: Digest,
+    ) -> bool {
+        let leaf_hash = H::hash_pair(&leaf, &leaf_salt);
+
+        // Set `leaf_hash` to H(value + salts[0..])
+        MerkleTree::<H>::verify_authentication_path_from_leaf_hash(
+            root_hash, index, leaf_hash, auth_path,
+        )
+    }
+
+    pub fn get_authentication_structure_and_salt(
+        &self,
+        indices: &[usize],
+    ) -> SaltedAuthenticationStructure<Digest> {
+        // Get the partial authentication paths without salts
+        let partial_authentication_paths: Vec<PartialAuthenticationPath<Digest>> = self
+            .internal_merkle_tree
+            .get_authentication_structure(indices);
+
+        // Get the salts associated with the leafs
+        // salts are random data, so they cannot be compressed
+        let mut ret: SaltedAuthenticationStructure<Digest> = Vec::with_capacity(indices.len());
+        for (i, index) in indices.iter().enumerate() {
+            let salt = self.salts[*index];
+            ret.push((partial_authentication_paths[i].clone(), salt));
+        }
+
+        ret
+    }
+
+    /// To use this function the user must provide the corresponding *UNSALTED* `leaves`.
+    pub fn verify_authentication_structure(
+        root_hash: Digest,
+        indices: &[usize],
+        unsalted_leaves: &[Digest],
+        proof: &SaltedAuthenticationStructure<Digest>,
+    ) -> bool {
+        if indices.len() != proof.len() || indices.len() != unsalted_leaves.len() {
+            debug_assert!(indices.len() == proof.len());
+            debug_assert!(indices.len() == unsalted_leaves.len());
+
+            return false;
+        }
+
+        if indices.is_empty() {
+            return true;
+        }
+
+        let mut leaf_hashes: Vec<Digest> = Vec::with_capacity(indices.len());
+        for (value, (_, salt)) in izip!(unsalted_leaves, proof) {
+            let leaf_hash = H::hash_pair(value, salt);
+            leaf_hashes.push(leaf_hash);
+        }
+
+        let saltless_proof: Vec<PartialAuthenticationPath<Digest>> =
+            proof.iter().map(|x| x.0.clone()).collect();
+
+        MerkleTree::<H>::verify_authentication_structure_from_leaves(
+            root_hash,
+            indices,
+            &leaf_hashes,
+            &saltless_proof,
+        )
+    }
+
+    pub fn get_root(&self) -> Digest {
+        self.internal_merkle_tree.get_root()
+    }
+
+    pub fn get_leaf_count(&self) -> usize {
+        self.internal_merkle_tree.get_leaf_count()
+    }
+
+    #[allow(dead_code)]
+    pub fn get_height(&self) -> usize {
+        self.internal_merkle_tree.get_height()
+    }
+
+    pub fn get_all_salted_leaves(&self) -> Vec<Digest> {
+        let first_leaf = self.internal_merkle_tree.nodes.len() / 2;
+        self.internal_merkle_tree.nodes[first_leaf..].to_vec()
+    }
+
+    pub fn get_salted_leaf_by_index(&self, index: usize) -> Digest {
+        let first_leaf_index = self.internal_merkle_tree.nodes.len() / 2;
+        let beyond_last_leaf_index = self.internal_merkle_tree.nodes.len();
+        assert!(
+            index < first_leaf_index || beyond_last_leaf_index <= index,
+            "Out of bounds index requested"
+        );
+        self.internal_merkle_tree.nodes[first_leaf_index + index]
+    }
+
+    pub fn get_salted_leaves_by_indices(&self, leaf_indices: &[usize]) -> Vec<Digest> {
+        let leaf_count = leaf_indices.len();
+
+        let mut result = Vec::with_capacity(leaf_count);
+
+        for index in leaf_indices {
+            result.push(self.get_salted_leaf_by_index(*index));
+        }
+        result
+    }
+
+    pub fn get_salts(&self) -> &[Digest] {
+        &self.salts
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod merkle_tree_test {
+    use super::*;
+    use crate::shared_math::b_field_element::BFieldElement;
+    use crate::shared_math::other::{
+        random_elements, random_elements_distinct_range, random_elements_range,
+    };
+    use crate::shared_math::rescue_prime_regular::RescuePrimeRegular;
+    use crate::test_shared::corrupt_digest;
+    use crate::util_types::algebraic_hasher::Hashable;
+    use itertools::Itertools;
+    use rand::{Rng, RngCore};
+    use std::iter::zip;
+
+    /// Count the number of hashes present in all partial authentication paths
+    fn count_hashes<Digest: Clone>(proof: &SaltedAuthenticationStructure<Digest>) -> usize {
+        proof
+            .iter()
+            .map(|(partial_auth_path, _)| {
                 let optional_digests = partial_auth_path.0.clone();
                 optional_digests.iter().flatten().count()
             })
-            .sum()
-    }
+            .sum()
+    }
+
+    impl<H: AlgebraicHasher> MerkleTree<H> {
+        /// For writing negative tests only.
+        fn set_root(&mut self, new_root: Digest) {
+            self.nodes[1] = new_root
+        }
+    }
+
+    impl<H: AlgebraicHasher> SaltedMerkleTree<H> {
+        /// For writing negative tests only.
+        #[allow(dead_code)]
+        fn set_root(&mut self, new_root: Digest) {
+            self.internal_merkle_tree.set_root(new_root)
+        }
+    }
+
+    #[test]
+    fn merkle_tree_test_32() {
+        type H = blake3::Hasher;
+
+        let num_leaves = 32;
+        let leaves: Vec<Digest> = random_elements(num_leaves);
+        let tree: MerkleTree<H> = MerkleTree::from_digests(&leaves);
+
+        for test_size in 0..20 {
+            // Create a vector of distinct, uniform random indices `random_indices`
+            // Separate one of these distinct indices `random_index` for negative testing.
+            let num_indices = test_size + 10;
+            let (bad_index, random_indices): (usize, Vec<usize>) = {
+                let mut tmp = random_elements_distinct_range(num_indices, 0..num_leaves);
+                (tmp.remove(0), tmp)
+            };
+
+            // Get a vector of digests for each of those indices
+            let selected_leaves: Vec<Digest> = tree.get_leaves_by_indices(&random_indices);
+
+            // Get the partial authentication paths for those indices
+            let partial_auth_paths = tree.get_authentication_structure(&random_indices);
+
+            // Get a membership proof for those indices
+            let proof: Vec<(PartialAuthenticationPath<Digest>, Digest)> =
+                zip(partial_auth_paths, selected_leaves.clone()).collect();
+
+            // Assert membership of randomly chosen leaves
+            let random_leaves_are_members = MerkleTree::<H>::verify_authentication_structure(
+                tree.get_root(),
+                &random_indices,
+                &proof,
+            );
+            assert!(random_leaves_are_members);
+
+            // Assert completeness of proof
+            let all_randomly_chosen_leaves_occur_in_proof = proof
+                .iter()
+                .enumerate()
+                .all(|(i, (_auth_path, digest))| *digest == leaves[random_indices[i]]);
+            assert!(all_randomly_chosen_leaves_occur_in_proof);
+
+            // Negative: Verify bad Merkle root
+            let bad_root_digest = corrupt_digest(&tree.get_root());
+            let bad_root_verifies = MerkleTree::<H>::verify_authentication_structure(
+                bad_root_digest,
+                &random_indices,
+                &proof,
+            );
+            assert!(!bad_root_verifies);
+
+            // Negative: Make random indices not match proof length (too long)
+            let bad_random_indices_1 = {
+                let mut tmp = random_indices.clone();
+                tmp.push(tmp[0]);
+                tmp
+            };
+            let too_many_indices_verifies = MerkleTree::<H>::verify_authentication_structure(
+                tree.get_root(),
+                &bad_random_indices_1,
+                &proof,
+            );
+            assert!(!too_many_indices_verifies);
+
+            // Negative: Make random indices not match proof length (too short)
+            let bad_random_indices_2 = {
+                let mut tmp = random_indices.clone();
+                tmp.remove(0);
+                tmp
+            };
+            let too_few_indices_verifies = MerkleTree::<H>::verify_authentication_structure(
+                tree.get_root(),
+                &bad_random_indices_2,
+                &proof,
+            );
+            assert!(!too_few_indices_verifies);
+
+            // Negative: Request non-existent index
+            let bad_random_indices_3 = {
+                let mut tmp = random_indices.clone();
+                tmp[0] = bad_index;
+                tmp
+            };
+            let non_existent_index_verifies = MerkleTree::<H>::verify_authentication_structure(
+                tree.get_root(),
+                &bad_random_indices_3,
+                &proof,
+            );
+            assert!(!non_existent_index_verifies);
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn disk_backed_merkle_tree_equivalence_test() {
+        type H = blake3::Hasher;
+
+        for num_leaves in [1usize, 2, 64] {
+            let leaves: Vec<Digest> = random_elements(num_leaves);
+            let in_memory_tree: MerkleTree<H> = MerkleTree::from_digests(&leaves);
+            let db = rusty_leveldb::DB::open("mydatabase", rusty_leveldb::in_memory()).unwrap();
+            let mut disk_backed_tree: DiskBackedMerkleTree<H> =
+                DiskBackedMerkleTree::from_digests(&leaves, db);
+
+            assert_eq!(in_memory_tree.get_root(), disk_backed_tree.get_root());
+            assert_eq!(
+                in_memory_tree.get_leaf_count(),
+                disk_backed_tree.get_leaf_count()
+            );
+            assert_eq!(in_memory_tree.get_height(), disk_backed_tree.get_height());
+
+            let num_indices = num_leaves.min(10);
+            let indices: Vec<usize> = random_elements_range(num_indices, 0..num_leaves);
+            assert_eq!(
+                in_memory_tree.get_authentication_structure(&indices),
+                disk_backed_tree.get_authentication_structure(&indices)
+            );
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn archival_merkle_tree_test() {
+        type H = blake3::Hasher;
+
+        let capacity = 16;
+        let padding_leaf = Digest::default();
+        let db = rusty_leveldb::DB::open("mydatabase", rusty_leveldb::in_memory()).unwrap();
+        let mut archival_tree: ArchivalMerkleTree<H> =
+            ArchivalMerkleTree::new(capacity, padding_leaf, db);
+
+        // The empty tree is the tree over all padding leaves.
+        let all_padding_tree: MerkleTree<H> =
+            MerkleTree::from_digests(&vec![padding_leaf; capacity]);
+        assert_eq!(all_padding_tree.get_root(), archival_tree.get_root());
+        assert_eq!(0, archival_tree.get_leaf_count());
+        assert_eq!(capacity, archival_tree.get_capacity());
+        assert_eq!(all_padding_tree.get_height(), archival_tree.get_height());
+
+        // After every append, the root matches the in-memory tree over the
+        // appended leaves plus padding.
+        let leaves: Vec<Digest> = random_elements(capacity);
+        for (i, leaf) in leaves.iter().enumerate() {
+            archival_tree.append(*leaf);
+
+            let mut padded_leaves = leaves[..=i].to_vec();
+            padded_leaves.resize(capacity, padding_leaf);
+            let in_memory_tree: MerkleTree<H> = MerkleTree::from_digests(&padded_leaves);
+            assert_eq!(in_memory_tree.get_root(), archival_tree.get_root());
+            assert_eq!(i + 1, archival_tree.get_leaf_count());
+        }
+
+        // Authentication paths verify and match the in-memory tree's.
+        let in_memory_tree: MerkleTree<H> = MerkleTree::from_digests(&leaves);
+        for (leaf_index, leaf) in leaves.iter().enumerate() {
+            let auth_path = archival_tree.get_authentication_path(leaf_index);
+            assert_eq!(
+                in_memory_tree.get_authentication_path(leaf_index),
+                auth_path
+            );
+            assert!(MerkleTree::<H>::verify_authentication_path_from_leaf_hash(
+                archival_tree.get_root(),
+                leaf_index as u32,
+                *leaf,
+                auth_path,
+            ));
+        }
+
+        // Updating a leaf matches a rebuilt in-memory tree.
+        let new_leaf: Digest = random_elements(1)[0];
+        archival_tree.update_leaf(7, new_leaf);
+        let mut updated_leaves = leaves.clone();
+        updated_leaves[7] = new_leaf;
+        let updated_in_memory_tree: MerkleTree<H> = MerkleTree::from_digests(&updated_leaves);
+        assert_eq!(updated_in_memory_tree.get_root(), archival_tree.get_root());
+        assert_eq!(new_leaf, archival_tree.get_leaf(7));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    #[should_panic(expected = "Cannot append to an archival Merkle tree at capacity")]
+    fn archival_merkle_tree_append_at_capacity_test() {
+        type H = blake3::Hasher;
+
+        let db = rusty_leveldb::DB::open("mydatabase", rusty_leveldb::in_memory()).unwrap();
+        let mut archival_tree: ArchivalMerkleTree<H> =
+            ArchivalMerkleTree::new(2, Digest::default(), db);
+        for leaf in random_elements::<Digest>(3) {
+            archival_tree.append(leaf);
+        }
+    }
+
+    #[test]
+    fn sparse_merkle_tree_test() {
+        type H = blake3::Hasher;
+
+        let mut tree: SparseMerkleTree<H> = SparseMerkleTree::new();
+        let empty_root = tree.get_root();
+        assert!(tree.is_empty());
+
+        let keys: Vec<Digest> = random_elements(4);
+        let values: Vec<Digest> = random_elements(4);
+        for (key, value) in keys.iter().zip(values.iter()) {
+            tree.insert(*key, *value);
+        }
+        assert_eq!(4, tree.len());
+        let root = tree.get_root();
+        assert_ne!(empty_root, root);
+
+        // Inclusion proofs verify for present keys and reject a wrong value.
+        for (key, value) in keys.iter().zip(values.iter()) {
+            assert_eq!(Some(*value), tree.get(key));
+            let auth_path = tree.get_authentication_path(key);
+            assert!(SparseMerkleTree::<H>::verify_inclusion_proof(
+                root, key, value, &auth_path
+            ));
+            let wrong_value = corrupt_digest(value);
+            assert!(!SparseMerkleTree::<H>::verify_inclusion_proof(
+                root,
+                key,
+                &wrong_value,
+                &auth_path
+            ));
+            assert!(!SparseMerkleTree::<H>::verify_noninclusion_proof(
+                root, key, &auth_path
+            ));
+        }
+
+        // Non-inclusion proofs verify for absent keys, and cannot double as
+        // inclusion proofs.
+        let absent_key: Digest = random_elements(1)[0];
+        let absent_auth_path = tree.get_authentication_path(&absent_key);
+        assert!(SparseMerkleTree::<H>::verify_noninclusion_proof(
+            root,
+            &absent_key,
+            &absent_auth_path
+        ));
+        assert!(!SparseMerkleTree::<H>::verify_inclusion_proof(
+            root,
+            &absent_key,
+            &values[0],
+            &absent_auth_path
+        ));
+
+        // Overwriting and removing change the root; removal restores the
+        // previous commitment.
+        tree.insert(keys[2], values[3]);
+        assert_ne!(root, tree.get_root());
+        tree.insert(keys[2], values[2]);
+        assert_eq!(root, tree.get_root());
+        for key in keys.iter() {
+            tree.remove(key);
+        }
+        assert_eq!(empty_root, tree.get_root());
+    }
+
+    /// A hasher over raw 32-byte Blake3 digests, bypassing the algebraic
+    /// digest representation entirely.
+    #[derive(Clone, Debug)]
+    struct RawBlake3;
+
+    impl MerkleTreeHasher for RawBlake3 {
+        type Digest = [u8; 32];
+
+        fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+            let mut hasher = blake3::Hasher::new();
+            hasher.update(left);
+            hasher.update(right);
+            *hasher.finalize().as_bytes()
+        }
+    }
+
+    #[test]
+    fn merkle_tree_generic_digest_test() {
+        let num_leaves = 16;
+        let leaves: Vec<[u8; 32]> = (0..num_leaves as u8)
+            .map(|i| *blake3::hash(&[i]).as_bytes())
+            .collect();
+        let tree: MerkleTree<RawBlake3> = MerkleTree::from_digests(&leaves);
+
+        for (leaf_index, leaf) in leaves.iter().enumerate() {
+            let auth_path = tree.get_authentication_path(leaf_index);
+            assert!(
+                MerkleTree::<RawBlake3>::verify_authentication_path_from_leaf_hash(
+                    tree.get_root(),
+                    leaf_index as u32,
+                    *leaf,
+                    auth_path,
+                )
+            );
+        }
+
+        let indices = vec![0, 5, 11];
+        let proof: Vec<(PartialAuthenticationPath<[u8; 32]>, [u8; 32])> = tree
+            .get_authentication_structure(&indices)
+            .into_iter()
+            .zip(indices.iter().map(|i| leaves[*i]))
+            .collect();
+        assert!(MerkleTree::<RawBlake3>::verify_authentication_structure(
+            tree.get_root(),
+            &indices,
+            &proof
+        ));
+
+        let mut bad_proof = proof;
+        bad_proof[1].1 = leaves[6];
+        assert!(!MerkleTree::<RawBlake3>::verify_authentication_structure(
+            tree.get_root(),
+            &indices,
+            &bad_proof
+        ));
+    }
+
+    #[test]
+    fn merkle_tree_root_from_digests_test() {
+        type H = blake3::Hasher;
+
+        // Sizes both below and above the parallelization threshold.
+        for num_leaves in [1usize, 2, 8, 64] {
+            let leaves: Vec<Digest> = random_elements(num_leaves);
+            let tree: MerkleTree<H> = MerkleTree::from_digests(&leaves);
+            assert_eq!(tree.get_root(), MerkleTree::<H>::root_from_digests(&leaves));
+        }
+    }
+
+    #[test]
+    fn merkle_tree_builder_test() {
+        type H = blake3::Hasher;
+
+        // Sizes both below and above the parallelization threshold.
+        for num_leaves in [2usize, 8, 64, 256] {
+            let leaves: Vec<Digest> = random_elements(num_leaves);
+            let reference: MerkleTree<H> = MerkleTree::from_digests(&leaves);
+
+            // Every scheduling configuration must produce the same tree.
+            let sequential = MerkleTree::<H>::builder().sequential().build(&leaves);
+            let chunked = MerkleTree::<H>::builder().chunk_size(3).build(&leaves);
+            let eager = MerkleTree::<H>::builder()
+                .parallel_threshold(2)
+                .chunk_size(7)
+                .build(&leaves);
+
+            assert_eq!(reference.nodes, sequential.nodes);
+            assert_eq!(reference.nodes, chunked.nodes);
+            assert_eq!(reference.nodes, eager.nodes);
+        }
+    }
+
+    #[test]
+    fn merkle_tree_accessors_test() {
+        type H = blake3::Hasher;
+
+        let num_leaves = 16;
+        let leaves: Vec<Digest> = random_elements(num_leaves);
+        let tree: MerkleTree<H> = MerkleTree::from_digests(&leaves);
+
+        assert_eq!(num_leaves, tree.get_leaf_count());
+        assert_eq!(4, tree.get_height());
+        assert_eq!(leaves, tree.iter_leaves().collect::<Vec<_>>());
+
+        // The root is the sole node of layer 0, the leaves make up the
+        // bottom layer, and every inner node hashes its two children.
+        assert_eq!(tree.get_root(), tree.get_node(0, 0));
+        for (i, leaf) in leaves.iter().enumerate() {
+            assert_eq!(*leaf, tree.get_node(tree.get_height(), i));
+        }
+        for layer in 0..tree.get_height() {
+            for index in 0..1 << layer {
+                let expected = <H as MerkleTreeHasher>::hash_pair(
+                    &tree.get_node(layer + 1, 2 * index),
+                    &tree.get_node(layer + 1, 2 * index + 1),
+                );
+                assert_eq!(expected, tree.get_node(layer, index));
+            }
+        }
+    }
+
+    #[test]
+    fn authentication_structure_many_indices_test() {
+        type H = blake3::Hasher;
+
+        let num_leaves = 1 << 10;
+        let leaves: Vec<Digest> = random_elements(num_leaves);
+        let tree: MerkleTree<H> = MerkleTree::from_digests(&leaves);
+
+        let indices = random_elements_distinct_range(300, 0..num_leaves);
+        let partial_auth_paths = tree.get_authentication_structure(&indices);
+        let leaf_digests = tree.get_leaves_by_indices(&indices);
+        assert_eq!(
+            Ok(()),
+            MerkleTree::<H>::check_authentication_structure_from_leaves(
+                tree.get_root(),
+                &indices,
+                &leaf_digests,
+                &partial_auth_paths,
+            )
+        );
+
+        // The dedup must actually bite: far fewer digests are revealed
+        // than per-index paths would carry.
+        let revealed: usize = partial_auth_paths
+            .iter()
+            .map(|path| path.0.iter().flatten().count())
+            .sum();
+        assert!(revealed < indices.len() * tree.get_height() / 2);
+    }
+
+    #[test]
+    fn domain_separated_hashing_test() {
+        type H = blake3::Hasher;
+
+        // Tagged leaves differ from untagged ones, and a ten-element leaf
+        // preimage no longer shares its hash-input shape with a node.
+        let elements: Vec<BFieldElement> = random_elements(2 * DIGEST_LENGTH);
+        let tagged = domain_separated_leaf_digest::<H>(&elements);
+        assert_ne!(H::hash_slice(&elements), tagged);
+
+        // The domain-separated tree commits under a different root than
+        // the plain tree, but behaves identically otherwise.
+        let leaves: Vec<Digest> = random_elements(8);
+        let plain_tree: MerkleTree<H> = MerkleTree::from_digests(&leaves);
+        let tagged_tree: MerkleTree<DomainSeparatedHasher<H>> = MerkleTree::from_digests(&leaves);
+        assert_ne!(plain_tree.get_root(), tagged_tree.get_root());
+
+        let auth_path = tagged_tree.get_authentication_path(3);
+        assert!(
+            MerkleTree::<DomainSeparatedHasher<H>>::verify_authentication_path_from_leaf_hash(
+                tagged_tree.get_root(),
+                3,
+                leaves[3],
+                auth_path,
+            )
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn mapped_merkle_tree_test() {
+        type H = blake3::Hasher;
+
+        let num_leaves = 32;
+        let leaves: Vec<Digest> = random_elements(num_leaves);
+        let tree: MerkleTree<H> = MerkleTree::from_digests(&leaves);
+
+        let path = std::env::temp_dir().join("mapped_merkle_tree_test.tfmt");
+        tree.serialize_to(&path).unwrap();
+        let mut mapped_tree: MappedMerkleTree<H> = MappedMerkleTree::open(&path).unwrap();
+
+        assert_eq!(tree.get_root(), mapped_tree.get_root());
+        assert_eq!(tree.get_leaf_count(), mapped_tree.get_leaf_count());
+        assert_eq!(tree.get_height(), mapped_tree.get_height());
+        assert_eq!(leaves[17], mapped_tree.get_leaf(17));
+        assert_eq!(
+            tree.get_authentication_path(6),
+            mapped_tree.get_authentication_path(6)
+        );
+
+        let indices = vec![0, 5, 17, 31];
+        assert_eq!(
+            tree.get_authentication_structure(&indices),
+            mapped_tree.get_authentication_structure(&indices)
+        );
+
+        // A file that is not a serialized Merkle tree is rejected.
+        let garbage_path = std::env::temp_dir().join("mapped_merkle_tree_garbage_test.tfmt");
+        std::fs::write(&garbage_path, b"not a merkle tree").unwrap();
+        let garbage_error = MappedMerkleTree::<H>::open(&garbage_path).unwrap_err();
+        assert_eq!(
+            Some(&MerkleTreeFileError::BadMagic),
+            garbage_error.downcast_ref::<MerkleTreeFileError>()
+        );
+
+        std::fs::remove_file(&path).unwrap();
+        std::fs::remove_file(&garbage_path).unwrap();
+    }
+
+    #[test]
+    fn check_authentication_structures_batch_test() {
+        type H = blake3::Hasher;
+
+        // One tree per FRI round, each committing to hashed leaf values.
+        let num_leaves = 16;
+        let values_per_tree: Vec<Vec<BFieldElement>> =
+            (0..3).map(|_| random_elements(num_leaves)).collect();
+        let trees: Vec<MerkleTree<H>> = values_per_tree
+            .iter()
+            .map(|values| MerkleTree::from_digests(&map_collect(values, H::hash)))
+            .collect();
+        let indices_per_tree = [vec![0, 3, 7], vec![9], vec![5, 11, 14, 15]];
+
+        let mut openings: Vec<RootOpenings<BFieldElement>> = trees
+            .iter()
+            .zip(values_per_tree.iter())
+            .zip(indices_per_tree.iter())
+            .map(|((tree, values), indices)| RootOpenings {
+                root: tree.get_root(),
+                leaf_indices: indices.clone(),
+                leaf_values: indices.iter().map(|i| values[*i]).collect(),
+                partial_auth_paths: tree.get_authentication_structure(indices),
+            })
+            .collect();
 
-    impl<H: AlgebraicHasher> MerkleTree<H> {
-        /// For writing negative tests only.
-        fn set_root(&mut self, new_root: Digest) {
-            self.nodes[1] = new_root
-        }
-    }
+        let results = MerkleTree::<H>::check_authentication_structures_batch(&openings);
+        assert_eq!(vec![Ok(()), Ok(()), Ok(())], results);
 
-    impl<H: AlgebraicHasher> SaltedMerkleTree<H> {
-        /// For writing negative tests only.
-        #[allow(dead_code)]
-        fn set_root(&mut self, new_root: Digest) {
-            self.internal_merkle_tree.set_root(new_root)
-        }
+        // A corrupted value fails only its own root; the single opened
+        // index of the middle root keeps the attribution unambiguous.
+        openings[1].leaf_values[0].increment();
+        let tampered_results = MerkleTree::<H>::check_authentication_structures_batch(&openings);
+        assert_eq!(Ok(()), tampered_results[0]);
+        assert_eq!(
+            Err(AuthenticationStructureError::WrongDigest { leaf_index: 9 }),
+            tampered_results[1]
+        );
+        assert_eq!(Ok(()), tampered_results[2]);
     }
 
     #[test]
-    fn merkle_tree_test_32() {
+    fn partial_merkle_tree_test() {
         type H = blake3::Hasher;
 
-        let num_leaves = 32;
+        let num_leaves = 16;
         let leaves: Vec<Digest> = random_elements(num_leaves);
         let tree: MerkleTree<H> = MerkleTree::from_digests(&leaves);
 
-        for test_size in 0..20 {
-            // Create a vector of distinct, uniform random indices `random_indices`
-            // Separate one of these distinct indices `random_index` for negative testing.
-            let num_indices = test_size + 10;
-            let (bad_index, random_indices): (usize, Vec<usize>) = {
-                let mut tmp = random_elements_distinct_range(num_indices, 0..num_leaves);
-                (tmp.remove(0), tmp)
-            };
-
-            // Get a vector of digests for each of those indices
-            let selected_leaves: Vec<Digest> = tree.get_leaves_by_indices(&random_indices);
-
-            // Get the partial authentication paths for those indices
-            let partial_auth_paths = tree.get_authentication_structure(&random_indices);
+        let first_indices = vec![2, 7];
+        let first_paths = tree.get_authentication_structure(&first_indices);
+        let first_leaves = tree.get_leaves_by_indices(&first_indices);
 
-            // Get a membership proof for those indices
-            let proof: Vec<(PartialAuthenticationPath<Digest>, Digest)> =
-                zip(partial_auth_paths, selected_leaves.clone()).collect();
+        let mut partial_tree: PartialMerkleTree<H> = PartialMerkleTree::new(num_leaves);
+        partial_tree
+            .absorb(&first_indices, &first_leaves, &first_paths)
+            .unwrap();
+        assert!(partial_tree.verify_root(tree.get_root()));
+        assert_eq!(Some(leaves[7]), partial_tree.get_leaf(7));
 
-            // Assert membership of randomly chosen leaves
-            let random_leaves_are_members = MerkleTree::<H>::verify_authentication_structure(
-                tree.get_root(),
-                &random_indices,
-                &proof,
-            );
-            assert!(random_leaves_are_members);
+        // Reconstructed internal nodes match the prover's tree.
+        assert_eq!(Some(tree.nodes[3]), partial_tree.get_node(3));
+        assert_eq!(
+            Some(tree.nodes[num_leaves / 2 + 1]),
+            partial_tree.get_node((num_leaves / 2 + 1) as u64)
+        );
 
-            // Assert completeness of proof
-            let all_randomly_chosen_leaves_occur_in_proof = proof
-                .iter()
-                .enumerate()
-                .all(|(i, (_auth_path, digest))| *digest == leaves[random_indices[i]]);
-            assert!(all_randomly_chosen_leaves_occur_in_proof);
+        // A second, consistent opening of the same tree is absorbed into
+        // the same pool of nodes.
+        let second_indices = vec![7, 12];
+        let second_paths = tree.get_authentication_structure(&second_indices);
+        let second_leaves = tree.get_leaves_by_indices(&second_indices);
+        partial_tree
+            .absorb(&second_indices, &second_leaves, &second_paths)
+            .unwrap();
+        assert!(partial_tree.verify_root(tree.get_root()));
+        assert_eq!(Some(leaves[12]), partial_tree.get_leaf(12));
+
+        // An opening of a different tree at the same indices conflicts.
+        let mut other_leaves = leaves.clone();
+        other_leaves[12] = corrupt_digest(&other_leaves[12]);
+        let other_tree: MerkleTree<H> = MerkleTree::from_digests(&other_leaves);
+        let conflict = partial_tree.absorb(
+            &second_indices,
+            &other_tree.get_leaves_by_indices(&second_indices),
+            &other_tree.get_authentication_structure(&second_indices),
+        );
+        assert!(matches!(
+            conflict,
+            Err(AuthenticationStructureError::ConflictingNode { .. })
+        ));
 
-            // Negative: Verify bad Merkle root
-            let bad_root_digest = corrupt_digest(&tree.get_root());
-            let bad_root_verifies = MerkleTree::<H>::verify_authentication_structure(
-                bad_root_digest,
-                &random_indices,
-                &proof,
-            );
-            assert!(!bad_root_verifies);
+        // An opening whose elided nodes cannot be reconstructed is
+        // rejected as incomplete.
+        let third_indices = vec![0, 1];
+        let mut third_paths = tree.get_authentication_structure(&third_indices);
+        third_paths[0].0[2] = None;
+        let incomplete = PartialMerkleTree::<H>::new(num_leaves).absorb(
+            &third_indices,
+            &tree.get_leaves_by_indices(&third_indices),
+            &third_paths,
+        );
+        assert_eq!(
+            Err(AuthenticationStructureError::MissingNode {
+                leaf_index: 0,
+                level: 2
+            }),
+            incomplete
+        );
+    }
 
-            // Negative: Make random indices not match proof length (too long)
-            let bad_random_indices_1 = {
-                let mut tmp = random_indices.clone();
-                tmp.push(tmp[0]);
-                tmp
-            };
-            let too_many_indices_verifies = MerkleTree::<H>::verify_authentication_structure(
-                tree.get_root(),
-                &bad_random_indices_1,
-                &proof,
-            );
-            assert!(!too_many_indices_verifies);
+    #[test]
+    fn merkle_tree_range_proof_test() {
+        type H = blake3::Hasher;
 
-            // Negative: Make random indices not match proof length (too short)
-            let bad_random_indices_2 = {
-                let mut tmp = random_indices.clone();
-                tmp.remove(0);
-                tmp
-            };
-            let too_few_indices_verifies = MerkleTree::<H>::verify_authentication_structure(
-                tree.get_root(),
-                &bad_random_indices_2,
-                &proof,
-            );
-            assert!(!too_few_indices_verifies);
+        let num_leaves = 32;
+        let leaves: Vec<Digest> = random_elements(num_leaves);
+        let tree: MerkleTree<H> = MerkleTree::from_digests(&leaves);
+        let root = tree.get_root();
+
+        for (start, end) in [(0, 1), (0, num_leaves), (3, 17), (7, 8), (16, 32), (1, 31)] {
+            let proof = tree.get_range_proof(start, end);
+            assert!(proof.len() <= 2 * tree.get_height());
+            assert!(MerkleTree::<H>::verify_range_proof(
+                root,
+                num_leaves,
+                start,
+                &leaves[start..end],
+                &proof
+            ));
 
-            // Negative: Request non-existent index
-            let bad_random_indices_3 = {
-                let mut tmp = random_indices.clone();
-                tmp[0] = bad_index;
-                tmp
-            };
-            let non_existent_index_verifies = MerkleTree::<H>::verify_authentication_structure(
-                tree.get_root(),
-                &bad_random_indices_3,
-                &proof,
-            );
-            assert!(!non_existent_index_verifies);
+            // Negative: shifted range, corrupted sibling, truncated proof,
+            // and corrupted leaf must all be rejected.
+            assert!(!MerkleTree::<H>::verify_range_proof(
+                root,
+                num_leaves,
+                (start + 1) % num_leaves,
+                &leaves[start..end],
+                &proof
+            ));
+            if !proof.is_empty() {
+                let mut bad_proof = proof.clone();
+                bad_proof[0] = corrupt_digest(&bad_proof[0]);
+                assert!(!MerkleTree::<H>::verify_range_proof(
+                    root,
+                    num_leaves,
+                    start,
+                    &leaves[start..end],
+                    &bad_proof
+                ));
+                assert!(!MerkleTree::<H>::verify_range_proof(
+                    root,
+                    num_leaves,
+                    start,
+                    &leaves[start..end],
+                    &proof[..proof.len() - 1]
+                ));
+            }
+            let mut bad_leaves = leaves[start..end].to_vec();
+            bad_leaves[0] = corrupt_digest(&bad_leaves[0]);
+            assert!(!MerkleTree::<H>::verify_range_proof(
+                root,
+                num_leaves,
+                start,
+                &bad_leaves,
+                &proof
+            ));
         }
+
+        // The whole tree needs no proof digests at all.
+        assert!(tree.get_range_proof(0, num_leaves).is_empty());
     }
 
     #[test]
@@ -942,6 +3225,312 @@ mod merkle_tree_test {
         }
     }
 
+    #[test]
+    fn merkle_tree_check_authentication_structure_test() {
+        type H = blake3::Hasher;
+
+        let num_leaves = 16;
+        let leaves: Vec<Digest> = random_elements(num_leaves);
+        let tree: MerkleTree<H> = MerkleTree::from_digests(&leaves);
+
+        // A single opened index keeps the failure attribution unambiguous:
+        // with several indices, a corrupted leaf also corrupts the path
+        // nodes reconstructed from it, so another index may fail first.
+        let indices = vec![5];
+        let proof: Vec<(PartialAuthenticationPath<Digest>, Digest)> = tree
+            .get_authentication_structure(&indices)
+            .into_iter()
+            .zip(indices.iter().map(|i| leaves[*i]))
+            .collect();
+
+        assert_eq!(
+            Ok(()),
+            MerkleTree::<H>::check_authentication_structure(tree.get_root(), &indices, &proof)
+        );
+
+        let mut wrong_digest_proof = proof.clone();
+        wrong_digest_proof[0].1 = corrupt_digest(&wrong_digest_proof[0].1);
+        assert_eq!(
+            Err(AuthenticationStructureError::WrongDigest { leaf_index: 5 }),
+            MerkleTree::<H>::check_authentication_structure(
+                tree.get_root(),
+                &indices,
+                &wrong_digest_proof
+            )
+        );
+
+        let mut missing_node_proof = proof.clone();
+        missing_node_proof[0].0 .0[2] = None;
+        assert_eq!(
+            Err(AuthenticationStructureError::MissingNode {
+                leaf_index: 5,
+                level: 2
+            }),
+            MerkleTree::<H>::check_authentication_structure(
+                tree.get_root(),
+                &indices,
+                &missing_node_proof
+            )
+        );
+
+        assert_eq!(
+            Err(AuthenticationStructureError::LeafIndexOutOfRange {
+                leaf_index: num_leaves
+            }),
+            MerkleTree::<H>::check_authentication_structure(tree.get_root(), &[num_leaves], &proof)
+        );
+
+        assert_eq!(
+            Err(AuthenticationStructureError::MismatchedInputLengths),
+            MerkleTree::<H>::check_authentication_structure(tree.get_root(), &[5, 6], &proof)
+        );
+    }
+
+    #[test]
+    fn partial_authentication_path_encoding_test() {
+        type H = blake3::Hasher;
+
+        let num_leaves = 64;
+        let leaves: Vec<Digest> = random_elements(num_leaves);
+        let tree = MerkleTree::<H>::from_digests(&leaves);
+
+        let indices = [0, 5, 11, 12, 13, 63];
+        for path in tree.get_authentication_structure(&indices) {
+            let encoded = path.encode();
+            assert_eq!(
+                Some(path.clone()),
+                PartialAuthenticationPath::decode(&encoded)
+            );
+
+            // The compact encoding beats the serde one
+            assert!(encoded.len() < bincode::serialize(&path).unwrap().len());
+
+            // Truncated and padded inputs are rejected
+            assert_eq!(
+                None,
+                PartialAuthenticationPath::decode(&encoded[..encoded.len() - 1])
+            );
+            let padded = [encoded, vec![0u8]].concat();
+            assert_eq!(None, PartialAuthenticationPath::decode(&padded));
+        }
+
+        assert_eq!(
+            None,
+            PartialAuthenticationPath::decode(&[]),
+            "The empty byte string holds no path"
+        );
+    }
+
+    #[test]
+    fn multi_arity_merkle_tree_test() {
+        type H = blake3::Hasher;
+
+        let num_leaves = 64;
+        let leaves: Vec<Digest> = random_elements(num_leaves);
+
+        for (arity, expected_height) in [(2, 6), (4, 3), (8, 2)] {
+            let tree = MultiArityMerkleTree::<H>::from_digests(&leaves, arity);
+            assert_eq!(num_leaves, tree.get_leaf_count());
+            assert_eq!(arity, tree.get_arity());
+            assert_eq!(expected_height, tree.get_height());
+
+            for leaf_index in [0, 17, num_leaves - 1] {
+                let auth_path = tree.get_authentication_path(leaf_index);
+                assert_eq!(expected_height, auth_path.len());
+                assert!(MultiArityMerkleTree::<H>::verify_authentication_path(
+                    tree.get_root(),
+                    arity,
+                    leaf_index,
+                    leaves[leaf_index],
+                    &auth_path,
+                ));
+
+                // Negative: wrong leaf digest
+                assert!(!MultiArityMerkleTree::<H>::verify_authentication_path(
+                    tree.get_root(),
+                    arity,
+                    leaf_index,
+                    corrupt_digest(&leaves[leaf_index]),
+                    &auth_path,
+                ));
+
+                // Negative: wrong leaf position
+                assert!(!MultiArityMerkleTree::<H>::verify_authentication_path(
+                    tree.get_root(),
+                    arity,
+                    leaf_index + 1,
+                    leaves[leaf_index],
+                    &auth_path,
+                ));
+            }
+        }
+    }
+
+    #[should_panic = "Size of input for arity-8 Merkle tree must be a power of 8"]
+    #[test]
+    fn multi_arity_merkle_tree_wrong_leaf_count_test() {
+        type H = blake3::Hasher;
+
+        let leaves: Vec<Digest> = random_elements(16);
+        let _tree = MultiArityMerkleTree::<H>::from_digests(&leaves, 8);
+    }
+
+    #[test]
+    fn row_merkle_tree_test() {
+        type H = blake3::Hasher;
+
+        let num_rows = 16;
+        let row_width = 4;
+        let rows: Vec<Vec<XFieldElement>> =
+            (0..num_rows).map(|_| random_elements(row_width)).collect();
+        let tree = RowMerkleTree::<H>::from_rows(&rows);
+        assert_eq!(num_rows, tree.get_leaf_count());
+
+        let indices = [0, 5, 5, 11, 15];
+        let proof = tree.get_authentication_structure_and_rows(&indices);
+        for (index, (_, row)) in indices.iter().zip(proof.iter()) {
+            assert_eq!(rows[*index], *row);
+            assert_eq!(rows[*index], tree.get_row_by_index(*index));
+        }
+        assert!(RowMerkleTree::<H>::verify_authentication_structure(
+            tree.get_root(),
+            &indices,
+            &proof,
+        ));
+
+        // Negative: a single tampered row element fails verification.
+        // (Tamper a non-duplicated index; a duplicated one is also served
+        // by its untampered twin when the partial tree is reconstructed.)
+        let mut bad_proof = proof.clone();
+        bad_proof[3].1[2].increment(0);
+        assert!(!RowMerkleTree::<H>::verify_authentication_structure(
+            tree.get_root(),
+            &indices,
+            &bad_proof,
+        ));
+
+        // Negative: the wrong root fails verification
+        assert!(!RowMerkleTree::<H>::verify_authentication_structure(
+            corrupt_digest(&tree.get_root()),
+            &indices,
+            &proof,
+        ));
+    }
+
+    #[test]
+    fn streaming_merkle_tree_builder_test() {
+        type H = blake3::Hasher;
+
+        for num_leaves in [1usize, 2, 16, 64] {
+            let leaves: Vec<Digest> = random_elements(num_leaves);
+            let in_memory_tree = MerkleTree::<H>::from_digests(&leaves);
+
+            let mut builder = StreamingMerkleTreeBuilder::<H>::new();
+            for leaf in leaves.iter() {
+                builder.append(*leaf);
+            }
+            assert_eq!(num_leaves, builder.get_leaf_count());
+            assert_eq!(in_memory_tree.get_root(), builder.finish());
+
+            assert_eq!(
+                in_memory_tree.get_root(),
+                StreamingMerkleTreeBuilder::<H>::root_from_leaf_iterator(leaves),
+            );
+        }
+    }
+
+    #[should_panic = "Size of input for Merkle tree must be a power of 2"]
+    #[test]
+    fn streaming_merkle_tree_builder_non_power_of_two_test() {
+        type H = blake3::Hasher;
+
+        let leaves: Vec<Digest> = random_elements(3);
+        let _root = StreamingMerkleTreeBuilder::<H>::root_from_leaf_iterator(leaves);
+    }
+
+    #[test]
+    fn merkle_tree_cap_test() {
+        type H = blake3::Hasher;
+
+        let num_leaves = 32;
+        let height = 5;
+        let leaves: Vec<Digest> = random_elements(num_leaves);
+        let tree = MerkleTree::<H>::from_digests(&leaves);
+
+        // Cap height 0 is the ordinary root
+        assert_eq!(vec![tree.get_root()], tree.get_cap_roots(0));
+        assert_eq!(
+            tree.get_authentication_path(11),
+            tree.get_capped_authentication_path(11, 0)
+        );
+
+        for cap_height in 0..=height {
+            let cap_roots = tree.get_cap_roots(cap_height);
+            assert_eq!(1 << cap_height, cap_roots.len());
+
+            for leaf_index in [0, 11, num_leaves - 1] {
+                let auth_path = tree.get_capped_authentication_path(leaf_index, cap_height);
+                assert_eq!(height - cap_height, auth_path.len());
+                assert!(MerkleTree::<H>::verify_capped_authentication_path(
+                    &cap_roots,
+                    leaf_index as u32,
+                    leaves[leaf_index],
+                    auth_path.clone(),
+                ));
+
+                // Negative: a different leaf does not verify
+                assert!(!MerkleTree::<H>::verify_capped_authentication_path(
+                    &cap_roots,
+                    leaf_index as u32,
+                    corrupt_digest(&leaves[leaf_index]),
+                    auth_path,
+                ));
+            }
+        }
+    }
+
+    #[test]
+    fn merkle_tree_update_leaf_test() {
+        type H = blake3::Hasher;
+
+        let num_leaves = 16;
+        let mut leaves: Vec<Digest> = random_elements(num_leaves);
+        let mut tree = MerkleTree::<H>::from_digests(&leaves);
+
+        // Updating a handful of leaves matches a full rebuild
+        for leaf_index in [0, 7, 15] {
+            let new_leaf: Digest = random_elements(1)[0];
+            leaves[leaf_index] = new_leaf;
+            tree.update_leaf(leaf_index, new_leaf);
+
+            let rebuilt_tree = MerkleTree::<H>::from_digests(&leaves);
+            assert_eq!(rebuilt_tree.get_root(), tree.get_root());
+            // nodes[0] is unused and holds whatever filler construction
+            // left there, so it is excluded from the comparison
+            assert_eq!(rebuilt_tree.nodes[1..], tree.nodes[1..]);
+        }
+
+        // Authentication paths from the updated tree verify
+        let leaf_index = 7;
+        let auth_path = tree.get_authentication_path(leaf_index);
+        assert!(MerkleTree::<H>::verify_authentication_path_from_leaf_hash(
+            tree.get_root(),
+            leaf_index as u32,
+            leaves[leaf_index],
+            auth_path,
+        ));
+    }
+
+    #[should_panic = "Cannot update leaf outside of tree. Leaf count: 4, leaf index: 4"]
+    #[test]
+    fn merkle_tree_update_leaf_out_of_bounds_test() {
+        type H = blake3::Hasher;
+
+        let leaves: Vec<Digest> = random_elements(4);
+        let mut tree = MerkleTree::<H>::from_digests(&leaves);
+        tree.update_leaf(4, leaves[0]);
+    }
+
     #[test]
     fn merkle_tree_get_authentication_path_test() {
         type H = blake3::Hasher;
@@ -1214,6 +3803,35 @@ mod merkle_tree_test {
         assert!(!bad_salts_verify);
     }
 
+    #[test]
+    fn salted_merkle_tree_with_random_salts_test() {
+        type H = blake3::Hasher;
+
+        let num_leaves = 8;
+        let leaves: Vec<Digest> = random_elements(num_leaves);
+        let tree = SaltedMerkleTree::<H>::with_random_salts(&leaves);
+        assert_eq!(num_leaves, tree.get_salts().len());
+        assert_eq!(
+            num_leaves,
+            tree.get_salts().iter().unique().count(),
+            "Salts are most probably unique"
+        );
+
+        let leaf_indices = [0, 3, 5];
+        let selected_leaves: Vec<Digest> = leaf_indices.iter().map(|i| leaves[*i]).collect();
+        let proof = tree.get_authentication_structure_and_salt(&leaf_indices);
+        assert!(SaltedMerkleTree::<H>::verify_authentication_structure(
+            tree.get_root(),
+            &leaf_indices,
+            &selected_leaves,
+            &proof,
+        ));
+
+        // Two trees over the same leaves commit to different roots
+        let other_tree = SaltedMerkleTree::<H>::with_random_salts(&leaves);
+        assert_ne!(tree.get_root(), other_tree.get_root());
+    }
+
     fn make_salted_merkle_tree_test<H: AlgebraicHasher>(
         leaf_indices: &[usize],
         leaves_c: &[Digest],
@@ -1349,7 +3967,7 @@ mod merkle_tree_test {
         // `451282252958277131` and `3796554602848593414` as this is where the error was
         // first caught.
 
-        let values_reg0 = vec![
+        let values_reg0 = [
             BFieldElement::new(451282252958277131),
             BFieldElement::new(3796554602848593414),
         ];
@@ -1542,7 +4160,7 @@ mod merkle_tree_test {
 
     #[test]
     fn verify_some_payload() {
-        /// This tests that we do not confuse indices and payloads in the test `verify_all_leaves_individually`.
+        // This tests that we do not confuse indices and payloads in the test `verify_all_leaves_individually`.
 
         type H = RescuePrimeRegular;
         type MT = MerkleTree<H>;
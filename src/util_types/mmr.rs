@@ -1,3 +1,4 @@
+#[cfg(feature = "std")]
 pub mod archival_mmr;
 pub mod mmr_accumulator;
 pub mod mmr_membership_proof;
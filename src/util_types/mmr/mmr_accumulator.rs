@@ -2,6 +2,7 @@ use serde::{Deserialize, Serialize};
 use std::marker::PhantomData;
 use std::{collections::HashMap, fmt::Debug};
 
+#[cfg(feature = "std")]
 use super::archival_mmr::ArchivalMmr;
 use super::mmr_membership_proof::MmrMembershipProof;
 use super::mmr_trait::Mmr;
@@ -15,6 +16,7 @@ use crate::util_types::algebraic_hasher::AlgebraicHasher;
 use crate::util_types::shared::bag_peaks;
 use crate::utils::has_unique_elements;
 
+#[cfg(feature = "std")]
 impl<H: AlgebraicHasher> From<ArchivalMmr<H>> for MmrAccumulator<H> {
     fn from(mut ammr: ArchivalMmr<H>) -> Self {
         MmrAccumulator {
@@ -32,6 +34,7 @@ pub struct MmrAccumulator<H: AlgebraicHasher> {
     _hasher: PhantomData<H>,
 }
 
+#[cfg(feature = "std")]
 impl<H: AlgebraicHasher> From<&mut ArchivalMmr<H>> for MmrAccumulator<H> {
     fn from(archive: &mut ArchivalMmr<H>) -> Self {
         Self {
@@ -286,7 +289,7 @@ impl<H: AlgebraicHasher> Mmr<H> for MmrAccumulator<H> {
             for (digest, authentication_path_indices) in membership_proof
                 .authentication_path
                 .iter_mut()
-                .zip(ap_indices.into_iter())
+                .zip(ap_indices)
             {
                 // Any number of hashes can be updated in the authentication path, since
                 // we're modifying multiple leaves in the MMR
@@ -311,7 +314,7 @@ impl<H: AlgebraicHasher> Mmr<H> for MmrAccumulator<H> {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod accumulator_mmr_tests {
     use std::cmp;
 
@@ -410,7 +413,7 @@ mod accumulator_mmr_tests {
         let leaf_hashes_start: Vec<Digest> = random_elements(3);
         let appended_leafs: Vec<Digest> = random_elements(2);
         let leaf_hashes_end: Vec<Digest> =
-            vec![leaf_hashes_start.clone(), appended_leafs.clone()].concat();
+            [leaf_hashes_start.clone(), appended_leafs.clone()].concat();
         let mut accumulator_mmr_start: MmrAccumulator<H> = MmrAccumulator::new(leaf_hashes_start);
         let mut accumulator_mmr_end: MmrAccumulator<H> = MmrAccumulator::new(leaf_hashes_end);
 
@@ -500,10 +503,8 @@ mod accumulator_mmr_tests {
             let mutated_leaf_mps = mutated_leaf_indices
                 .iter()
                 .map(|i| ammr.prove_membership(*i).0);
-            let mutation_data: Vec<(MmrMembershipProof<H>, Digest)> = mutated_leaf_mps
-                .into_iter()
-                .zip(new_leafs.into_iter())
-                .collect();
+            let mutation_data: Vec<(MmrMembershipProof<H>, Digest)> =
+                mutated_leaf_mps.into_iter().zip(new_leafs).collect();
 
             assert_eq!(mutated_leaf_count as usize, mutation_data.len());
 
@@ -572,7 +573,7 @@ mod accumulator_mmr_tests {
 
             for append_size in 0..18 {
                 let appends: Vec<Digest> = (2000..2000 + append_size)
-                    .map(|x| H::hash(&(x as u128)))
+                    .map(|x| H::hash(&{ x }))
                     .collect();
                 let mutate_count = cmp::min(12, start_size);
                 for mutate_size in 0..mutate_count {
@@ -619,7 +620,6 @@ mod accumulator_mmr_tests {
                         .clone()
                         .into_iter()
                         .zip(membership_proofs)
-                        .map(|(v, mp)| (v, mp))
                         .collect();
                     assert!(accumulator_mmr.verify_batch_update(
                         &expected_new_peaks_from_accumulator,
@@ -381,10 +381,7 @@ impl<H: AlgebraicHasher> MmrMembershipProof<H> {
         // `own_node_indices` and check if the element is contained `deducible_hashes`.
         // If it is, then the appropriate element in `self.authentication_path` needs to
         // be replaced with an element from `deducible_hashes`.
-        for (digest, own_node_index) in self
-            .authentication_path
-            .iter_mut()
-            .zip(own_node_ap_indices.into_iter())
+        for (digest, own_node_index) in self.authentication_path.iter_mut().zip(own_node_ap_indices)
         {
             if !deducible_hashes.contains_key(&own_node_index) {
                 continue;
@@ -448,7 +445,7 @@ impl<H: AlgebraicHasher> MmrMembershipProof<H> {
             for (digest, authentication_path_indices) in membership_proof
                 .authentication_path
                 .iter_mut()
-                .zip(ap_indices.into_iter())
+                .zip(ap_indices)
             {
                 // Maximum 1 digest can be updated in each authentication path
                 // so if that is encountered, we might as well break and go to
@@ -479,6 +476,7 @@ impl<H: AlgebraicHasher> MmrMembershipProof<H> {
     ///  - membership_proofs -- own membership proofs, to be updated
     ///  - authentication_paths_and_leafs -- membership proofs of the mutated
     ///    leafs, and the new leaf values
+    ///
     /// Returns those indices into the slice of membership proofs that were updated.
     pub fn batch_update_from_batch_leaf_mutation(
         membership_proofs: &mut [&mut Self],
@@ -549,7 +547,7 @@ impl<H: AlgebraicHasher> MmrMembershipProof<H> {
             for (digest, authentication_path_indices) in membership_proof
                 .authentication_path
                 .iter_mut()
-                .zip(ap_indices.into_iter())
+                .zip(ap_indices)
             {
                 // Any number of hashes can be updated in the authentication path, since
                 // we're modifying multiple leaves in the MMR
@@ -570,7 +568,7 @@ impl<H: AlgebraicHasher> MmrMembershipProof<H> {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod mmr_membership_proof_test {
     use rand::Rng;
 
@@ -653,7 +651,7 @@ mod mmr_membership_proof_test {
         let mut expected_peak_indices_and_heights: Vec<(u128, u32)> =
             vec![(7, 2), (7, 2), (7, 2), (7, 2), (10, 1), (10, 1), (11, 0)];
         for (data_index, expected_peak_index) in
-            (0..mmr_size as u128).zip(expected_peak_indices_and_heights.into_iter())
+            (0..mmr_size as u128).zip(expected_peak_indices_and_heights)
         {
             let (membership_proof, _peaks): (MmrMembershipProof<H>, Vec<Digest>) =
                 archival_mmr.prove_membership(data_index);
@@ -667,9 +665,9 @@ mod mmr_membership_proof_test {
         mmr_size = 8;
         let leaf_hash: Digest = H::hash(&1337u128);
         archival_mmr.append(leaf_hash);
-        expected_peak_indices_and_heights = vec![(15, 3); mmr_size as usize];
+        expected_peak_indices_and_heights = vec![(15, 3); mmr_size];
         for (data_index, expected_peak_index) in
-            (0..mmr_size as u128).zip(expected_peak_indices_and_heights.into_iter())
+            (0..mmr_size as u128).zip(expected_peak_indices_and_heights)
         {
             let (membership_proof, _peaks): (MmrMembershipProof<H>, Vec<Digest>) =
                 archival_mmr.prove_membership(data_index);
@@ -695,7 +693,7 @@ mod mmr_membership_proof_test {
             (16, 0),
         ];
         for (data_index, expected_peak_index) in
-            (0..mmr_size as u128).zip(expected_peak_indices_and_heights.into_iter())
+            (0..mmr_size as u128).zip(expected_peak_indices_and_heights)
         {
             let (membership_proof, _peaks): (MmrMembershipProof<H>, Vec<Digest>) =
                 archival_mmr.prove_membership(data_index);
@@ -735,11 +733,7 @@ mod mmr_membership_proof_test {
         let mut updated_leaf_hashes = leaf_hashes;
         updated_leaf_hashes[2] = new_leaf2;
         updated_leaf_hashes[3] = new_leaf3;
-        for (_i, (mp, leaf_hash)) in membership_proofs
-            .iter()
-            .zip(updated_leaf_hashes.iter())
-            .enumerate()
-        {
+        for (mp, leaf_hash) in membership_proofs.iter().zip(updated_leaf_hashes.iter()) {
             mp.verify(
                 &archival_mmr.get_peaks(),
                 leaf_hash,
@@ -765,7 +759,7 @@ mod mmr_membership_proof_test {
             membership_proofs
                 .clone()
                 .into_iter()
-                .zip(leaf_hashes_final.clone().into_iter())
+                .zip(leaf_hashes_final.clone())
                 .collect();
         let changed_values = MmrMembershipProof::batch_update_from_batch_leaf_mutation(
             &mut membership_proofs.iter_mut().collect::<Vec<_>>(),
@@ -826,7 +820,7 @@ mod mmr_membership_proof_test {
             let mutation_argument: Vec<(MmrMembershipProof<H>, Digest)> = authentication_paths
                 .clone()
                 .into_iter()
-                .zip(new_leafs.clone().into_iter())
+                .zip(new_leafs.clone())
                 .collect();
             let updated_mp_indices_0 = MmrMembershipProof::batch_update_from_batch_leaf_mutation(
                 &mut own_membership_proofs.iter_mut().collect::<Vec<_>>(),
@@ -834,7 +828,7 @@ mod mmr_membership_proof_test {
             );
 
             // update MMR
-            for i in 0..(modified_leaf_count as usize) {
+            for i in 0..modified_leaf_count {
                 let auth_path = authentication_paths[i].clone();
                 leaf_hashes[auth_path.data_index as usize] = new_leafs[i];
                 archival_mmr.mutate_leaf_raw(auth_path.data_index, new_leafs[i]);
@@ -853,8 +847,10 @@ mod mmr_membership_proof_test {
             assert_eq!(updated_mp_indices_0, updated_mp_indices_1);
 
             // test that all updated membership proofs are valid under the updated MMR
-            for i in 0..own_membership_proof_count {
-                let membership_proof = own_membership_proofs[i as usize].clone();
+            for membership_proof in own_membership_proofs
+                .iter()
+                .take(own_membership_proof_count)
+            {
                 assert!(
                     membership_proof
                         .verify(
@@ -880,7 +876,7 @@ mod mmr_membership_proof_test {
         let (leaf_hashes, mut membership_proofs) =
             make_populated_archival_mmr::<H>(total_leaf_count);
 
-        for i in 0..total_leaf_count as usize {
+        for i in 0..total_leaf_count {
             let leaf_mutation_membership_proof = membership_proofs[i].clone();
             let new_leaf = leaf_hashes[i];
             let ret = MmrMembershipProof::batch_update_from_leaf_mutation(
@@ -897,7 +893,7 @@ mod mmr_membership_proof_test {
             membership_proofs
                 .clone()
                 .into_iter()
-                .zip(leaf_hashes.clone().into_iter())
+                .zip(leaf_hashes.clone())
                 .collect();
         let ret = MmrMembershipProof::batch_update_from_batch_leaf_mutation(
             &mut membership_proofs.iter_mut().collect::<Vec<_>>(),
@@ -954,9 +950,8 @@ mod mmr_membership_proof_test {
             );
         }
 
-        for i in 0..modified_leaf_count {
-            let data_index = i as u128;
-            archival_mmr.mutate_leaf_raw(data_index, new_leafs[i as usize]);
+        for (i, new_leaf) in new_leafs.iter().enumerate().take(modified_leaf_count) {
+            archival_mmr.mutate_leaf_raw(i as u128, *new_leaf);
         }
 
         for (i, mp) in membership_proofs.iter().enumerate() {
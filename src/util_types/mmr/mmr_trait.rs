@@ -4,9 +4,9 @@ use crate::util_types::algebraic_hasher::AlgebraicHasher;
 use super::{mmr_accumulator::MmrAccumulator, mmr_membership_proof::MmrMembershipProof};
 
 pub trait Mmr<H: AlgebraicHasher> {
-    /// Create a new MMR instanc from a list of hash digests. The supplied digests
-    /// are the leaves of the MMR.
-
+    // Create a new MMR instanc from a list of hash digests. The supplied digests
+    // are the leaves of the MMR.
+    //
     // constructors cannot be part of the interface sicne the archival version requires a
     // database which we want the caller to create, and the accumulator does not need a
     // constructor.
@@ -293,10 +293,7 @@ pub fn calculate_new_peaks_from_append<H: AlgebraicHasher>(
     while new_node_is_right_child {
         let new_hash = peaks.pop().unwrap();
         let previous_peak_res = peaks.pop();
-        let previous_peak = match previous_peak_res {
-            None => return None,
-            Some(peak) => peak,
-        };
+        let previous_peak = previous_peak_res?;
         membership_proof.authentication_path.push(previous_peak);
         peaks.push(H::hash_pair(&previous_peak, &new_hash));
         new_node_index += 1;
@@ -330,10 +327,7 @@ pub fn calculate_new_peaks_from_leaf_mutation<H: AlgebraicHasher>(
 
     // Find the correct peak to update
     let peak_index_res = leaf_index_to_peak_index(membership_proof.data_index, leaf_count);
-    let peak_index = match peak_index_res {
-        None => return None,
-        Some(pi) => pi,
-    };
+    let peak_index = peak_index_res?;
 
     let mut calculated_peaks: Vec<Digest> = old_peaks.to_vec();
     calculated_peaks[peak_index as usize] = acc_hash;
@@ -341,7 +335,7 @@ pub fn calculate_new_peaks_from_leaf_mutation<H: AlgebraicHasher>(
     Some(calculated_peaks)
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod mmr_test {
     use rand::RngCore;
 
@@ -1,21 +1,149 @@
-use serde::{de::DeserializeOwned, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
 use std::{error::Error, fmt, result::Result};
 
+use crate::shared_math::b_field_element::BFieldElement;
 use crate::shared_math::rescue_prime_digest::Digest;
+use crate::shared_math::x_field_element::XFieldElement;
 
+use super::algebraic_hasher::AlgebraicHasher;
 use super::blake3_wrapper::from_blake3_digest;
+use super::merkle_tree::PartialAuthenticationPath;
 
-#[derive(Debug, Default, PartialEq, Eq)]
+/// How a [`ProofStream`] derives its Fiat-Shamir challenges.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TranscriptMode {
+    /// Re-hash the entire accumulated byte stream on every challenge.
+    /// Quadratic in proof size, but byte-compatible with all previously
+    /// produced proofs; remains the default for that reason.
+    #[default]
+    Rehash,
+    /// Incremental sponge transcript: absorb each enqueued (prover side)
+    /// or dequeued (verifier side) byte exactly once into a running
+    /// hasher, and squeeze challenges on demand by finalizing a clone of
+    /// it -- with the domain-separation tag absorbed last. Linear in proof
+    /// size, but derives different challenges than [`Rehash`], so both
+    /// parties must agree on the mode.
+    ///
+    /// [`Rehash`]: TranscriptMode::Rehash
+    Incremental,
+}
+
+/// A single event recorded while a [`ProofStream`] is in recording mode;
+/// see [`ProofStream::set_recording`]. Prover and verifier each produce a
+/// log, and [`TranscriptEvent::first_divergence`] pinpoints the first
+/// place the two disagree -- replacing println archaeology when a verify
+/// unexpectedly fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TranscriptEvent {
+    Enqueue { offset: usize, bytes: Vec<u8> },
+    Dequeue { offset: usize, bytes: Vec<u8> },
+    Challenge { tag: Vec<u8>, digest: Digest },
+}
+
+impl TranscriptEvent {
+    /// Whether a prover-side event and a verifier-side event describe the
+    /// same transcript step. An `Enqueue` corresponds to the `Dequeue` of
+    /// the same bytes at the same offset; challenges must agree on both
+    /// tag and digest.
+    fn corresponds_to(&self, verifier_event: &TranscriptEvent) -> bool {
+        match (self, verifier_event) {
+            (
+                TranscriptEvent::Enqueue { offset, bytes },
+                TranscriptEvent::Dequeue {
+                    offset: read_offset,
+                    bytes: read_bytes,
+                },
+            ) => offset == read_offset && bytes == read_bytes,
+            (lhs, rhs) => lhs == rhs,
+        }
+    }
+
+    /// The index of the first event where the verifier's log diverges
+    /// from the prover's, or `None` if the logs correspond step for step.
+    /// A log that is a strict prefix of the other diverges at its end.
+    pub fn first_divergence(
+        prover_events: &[TranscriptEvent],
+        verifier_events: &[TranscriptEvent],
+    ) -> Option<usize> {
+        let shared_length = std::cmp::min(prover_events.len(), verifier_events.len());
+        for i in 0..shared_length {
+            if !prover_events[i].corresponds_to(&verifier_events[i]) {
+                return Some(i);
+            }
+        }
+
+        match prover_events.len() == verifier_events.len() {
+            true => None,
+            false => Some(shared_length),
+        }
+    }
+}
+
+#[derive(Debug)]
 pub struct ProofStream {
     read_index: usize,
     transcript: Vec<u8>,
+    mode: TranscriptMode,
+    limits: ProofStreamLimits,
+    items_read: usize,
+    stats: ProofStreamStats,
+    // Monotone counters of challenges derived on either side; see
+    // `seal_challenge_count`. Atomic only so that derivation can stay
+    // `&self`; there is no cross-thread protocol here.
+    prover_challenge_count: AtomicUsize,
+    verifier_challenge_count: AtomicUsize,
+    // Debug recording of transcript events; see `set_recording`. Behind a
+    // mutex only so that challenge derivation can stay `&self`.
+    recording: bool,
+    events: Mutex<Vec<TranscriptEvent>>,
+    // Running sponges for `TranscriptMode::Incremental`; unused in
+    // `Rehash` mode. The absorber tracks everything enqueued, the read
+    // absorber everything dequeued.
+    absorber: blake3::Hasher,
+    read_absorber: blake3::Hasher,
 }
 
+impl Default for ProofStream {
+    fn default() -> Self {
+        Self {
+            read_index: 0,
+            transcript: vec![],
+            mode: TranscriptMode::default(),
+            limits: ProofStreamLimits::default(),
+            items_read: 0,
+            stats: ProofStreamStats::default(),
+            prover_challenge_count: AtomicUsize::new(0),
+            verifier_challenge_count: AtomicUsize::new(0),
+            recording: false,
+            events: Mutex::new(vec![]),
+            absorber: blake3::Hasher::new(),
+            read_absorber: blake3::Hasher::new(),
+        }
+    }
+}
+
+// The sponges are a function of the other fields, so they don't take part
+// in equality.
+impl PartialEq for ProofStream {
+    fn eq(&self, other: &Self) -> bool {
+        self.read_index == other.read_index
+            && self.transcript == other.transcript
+            && self.mode == other.mode
+    }
+}
+
+impl Eq for ProofStream {}
+
 impl From<Vec<u8>> for ProofStream {
     fn from(item: Vec<u8>) -> Self {
         ProofStream {
             read_index: 0,
             transcript: item,
+            ..ProofStream::default()
         }
     }
 }
@@ -23,8 +151,105 @@ impl From<Vec<u8>> for ProofStream {
 #[derive(Debug, PartialEq, Eq)]
 pub enum ProofStreamError {
     TranscriptLengthExceeded,
+    UnexpectedItem {
+        expected: &'static str,
+        actual: &'static str,
+    },
+    MalformedHeader,
+    BadMagic,
+    UnsupportedFormatVersion(u8),
+    HasherMismatch {
+        expected: String,
+        actual: String,
+    },
+    MalformedCompressedData,
+    MalformedFrame,
+    BoundaryMismatch,
+    ChallengeCountMismatch {
+        expected: usize,
+        actual: usize,
+    },
+    LabelMismatch {
+        expected: String,
+        actual: String,
+    },
+    ItemSizeLimitExceeded {
+        limit: usize,
+        actual: usize,
+    },
+    TotalSizeLimitExceeded {
+        limit: usize,
+        actual: usize,
+    },
+    ItemCountLimitExceeded {
+        limit: usize,
+    },
+}
+
+/// Verifier-side bounds on what a [`ProofStream`] will read. A malicious
+/// proof can claim arbitrary item lengths; with limits in place, a dequeue
+/// that exceeds them errors out before any oversized allocation happens.
+/// All limits default to unbounded, preserving existing behavior; a
+/// verifier of untrusted proofs should set them to the sizes its protocol
+/// can actually produce.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProofStreamLimits {
+    pub max_item_size: usize,
+    pub max_total_size: usize,
+    pub max_item_count: usize,
+}
+
+impl Default for ProofStreamLimits {
+    fn default() -> Self {
+        Self {
+            max_item_size: usize::MAX,
+            max_total_size: usize::MAX,
+            max_item_count: usize::MAX,
+        }
+    }
 }
 
+/// A per-item-type byte breakdown of a [`ProofStream`], as returned by
+/// [`ProofStream::stats`]. Bytes written through the typed
+/// [`enqueue_item`] path are attributed to their item kind (including the
+/// length prefix); everything else -- raw enqueues, labels, masked values,
+/// grinding nonces -- lands in `misc_bytes`. Useful for seeing what
+/// dominates a proof's size when tuning parameters.
+///
+/// [`enqueue_item`]: ProofStream::enqueue_item
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ProofStreamStats {
+    pub merkle_root_bytes: usize,
+    pub authentication_structure_bytes: usize,
+    pub codeword_bytes: usize,
+    pub misc_bytes: usize,
+    pub total_bytes: usize,
+}
+
+/// The shortest backreference the compressed proof encoding emits. Matches
+/// below this length cost more to encode than the literal bytes they
+/// replace.
+const COMPRESSION_MIN_MATCH: usize = 8;
+
+/// How far beyond the compressed input size the decompressor is willing to
+/// preallocate for the declared transcript length. Honest proofs compress by
+/// far less than this factor; a stream that really does expand further only
+/// pays for the amortized growth instead of handing an attacker a 4 GiB
+/// allocation for a 4-byte header.
+const COMPRESSION_MAX_INITIAL_EXPANSION: usize = 32;
+
+/// Magic bytes opening every versioned proof serialization; see
+/// [`ProofStream::to_versioned_bytes`].
+const PROOF_FORMAT_MAGIC: &[u8; 4] = b"TFPS";
+
+/// The current version of the versioned proof serialization format.
+const PROOF_FORMAT_VERSION: u8 = 1;
+
+/// The largest chunk [`ProofStream::write_to`] emits in a single frame.
+/// Bounding the frame size means [`ProofStream::read_from`] never has to
+/// trust a length field larger than this, no matter what the peer sends.
+const STREAM_FRAME_SIZE: usize = 1 << 16;
+
 impl Error for ProofStreamError {}
 
 impl fmt::Display for ProofStreamError {
@@ -33,18 +258,350 @@ impl fmt::Display for ProofStreamError {
     }
 }
 
+/// A typed item on a [`ProofStream`]. Where the raw `enqueue`/`dequeue`
+/// methods require the reader to know the exact byte layout of every item,
+/// the typed [`enqueue_item`]/[`dequeue_item`] pair carries the item kind
+/// in the transcript itself, so a mismatched read surfaces as an explicit
+/// [`ProofStreamError::UnexpectedItem`] instead of silently
+/// misinterpreting bytes.
+///
+/// [`enqueue_item`]: ProofStream::enqueue_item
+/// [`dequeue_item`]: ProofStream::dequeue_item
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProofItem {
+    MerkleRoot(Digest),
+    AuthenticationStructure(Vec<PartialAuthenticationPath<Digest>>),
+    Codeword(Vec<XFieldElement>),
+    MaskedValues(Vec<XFieldElement>),
+    GrindingNonce(u64),
+}
+
+impl ProofItem {
+    fn label(&self) -> &'static str {
+        match self {
+            ProofItem::MerkleRoot(_) => "MerkleRoot",
+            ProofItem::AuthenticationStructure(_) => "AuthenticationStructure",
+            ProofItem::Codeword(_) => "Codeword",
+            ProofItem::MaskedValues(_) => "MaskedValues",
+            ProofItem::GrindingNonce(_) => "GrindingNonce",
+        }
+    }
+
+    fn unexpected(&self, expected: &'static str) -> ProofStreamError {
+        ProofStreamError::UnexpectedItem {
+            expected,
+            actual: self.label(),
+        }
+    }
+
+    pub fn as_merkle_root(&self) -> Result<Digest, ProofStreamError> {
+        match self {
+            ProofItem::MerkleRoot(root) => Ok(*root),
+            other => Err(other.unexpected("MerkleRoot")),
+        }
+    }
+
+    pub fn as_authentication_structure(
+        &self,
+    ) -> Result<Vec<PartialAuthenticationPath<Digest>>, ProofStreamError> {
+        match self {
+            ProofItem::AuthenticationStructure(paths) => Ok(paths.clone()),
+            other => Err(other.unexpected("AuthenticationStructure")),
+        }
+    }
+
+    pub fn as_codeword(&self) -> Result<Vec<XFieldElement>, ProofStreamError> {
+        match self {
+            ProofItem::Codeword(codeword) => Ok(codeword.clone()),
+            other => Err(other.unexpected("Codeword")),
+        }
+    }
+
+    pub fn as_masked_values(&self) -> Result<Vec<XFieldElement>, ProofStreamError> {
+        match self {
+            ProofItem::MaskedValues(values) => Ok(values.clone()),
+            other => Err(other.unexpected("MaskedValues")),
+        }
+    }
+
+    pub fn as_grinding_nonce(&self) -> Result<u64, ProofStreamError> {
+        match self {
+            ProofItem::GrindingNonce(nonce) => Ok(*nonce),
+            other => Err(other.unexpected("GrindingNonce")),
+        }
+    }
+}
+
 impl ProofStream {
     pub fn new_with_prefix(prefix: &[u8]) -> Self {
+        let mut proof_stream = Self::default();
+        proof_stream.extend_transcript(prefix);
+        proof_stream
+    }
+
+    /// An empty proof stream in [`TranscriptMode::Incremental`].
+    pub fn new_incremental() -> Self {
         Self {
-            read_index: 0,
-            transcript: prefix.to_vec(),
+            mode: TranscriptMode::Incremental,
+            ..Self::default()
+        }
+    }
+
+    /// A proof stream over existing proof bytes in
+    /// [`TranscriptMode::Incremental`], e.g. for verifying a proof produced
+    /// by an incremental prover. The enqueue-side sponge absorbs all given
+    /// bytes; the dequeue-side sponge fills as the bytes are read.
+    pub fn incremental_from(bytes: Vec<u8>) -> Self {
+        let mut proof_stream = Self::new_incremental();
+        proof_stream.extend_transcript(&bytes);
+        proof_stream
+    }
+
+    pub fn transcript_mode(&self) -> TranscriptMode {
+        self.mode
+    }
+
+    /// Bound what subsequent dequeues will read; see [`ProofStreamLimits`].
+    pub fn set_limits(&mut self, limits: ProofStreamLimits) {
+        self.limits = limits;
+    }
+
+    pub fn limits(&self) -> ProofStreamLimits {
+        self.limits
+    }
+
+    /// A byte breakdown of the transcript by item type. Only bytes written
+    /// through [`enqueue_item`] are attributed to a specific kind; on a
+    /// stream deserialized from raw bytes, everything is `misc_bytes`.
+    ///
+    /// [`enqueue_item`]: ProofStream::enqueue_item
+    pub fn stats(&self) -> ProofStreamStats {
+        let mut stats = self.stats;
+        stats.total_bytes = self.transcript.len();
+        stats.misc_bytes = stats.total_bytes
+            - stats.merkle_root_bytes
+            - stats.authentication_structure_bytes
+            - stats.codeword_bytes;
+        stats
+    }
+
+    /// Charge one item of the given size against the configured limits.
+    /// Every dequeue goes through here before touching the item's bytes.
+    fn charge_item(&mut self, item_size: usize) -> Result<(), ProofStreamError> {
+        if self.transcript.len() > self.limits.max_total_size {
+            return Err(ProofStreamError::TotalSizeLimitExceeded {
+                limit: self.limits.max_total_size,
+                actual: self.transcript.len(),
+            });
+        }
+        if item_size > self.limits.max_item_size {
+            return Err(ProofStreamError::ItemSizeLimitExceeded {
+                limit: self.limits.max_item_size,
+                actual: item_size,
+            });
+        }
+        if self.items_read >= self.limits.max_item_count {
+            return Err(ProofStreamError::ItemCountLimitExceeded {
+                limit: self.limits.max_item_count,
+            });
+        }
+        self.items_read += 1;
+
+        Ok(())
+    }
+
+    /// Append bytes to the transcript, absorbing them into the running
+    /// sponge when in incremental mode. All writes go through here.
+    fn extend_transcript(&mut self, bytes: &[u8]) {
+        if self.recording {
+            self.events.lock().unwrap().push(TranscriptEvent::Enqueue {
+                offset: self.transcript.len(),
+                bytes: bytes.to_vec(),
+            });
+        }
+        if self.mode == TranscriptMode::Incremental {
+            self.absorber.update(bytes);
+        }
+        self.transcript.extend_from_slice(bytes);
+    }
+
+    /// Advance the read index, absorbing the bytes read into the running
+    /// dequeue-side sponge when in incremental mode. All reads go through
+    /// here.
+    fn advance_read_index(&mut self, new_index: usize) {
+        if self.recording {
+            self.events.lock().unwrap().push(TranscriptEvent::Dequeue {
+                offset: self.read_index,
+                bytes: self.transcript[self.read_index..new_index].to_vec(),
+            });
+        }
+        if self.mode == TranscriptMode::Incremental {
+            self.read_absorber
+                .update(&self.transcript[self.read_index..new_index]);
         }
+        self.read_index = new_index;
+    }
+
+    /// Record a derived challenge when in recording mode. `&self` because
+    /// all challenge derivation is `&self`; see `set_recording`.
+    fn record_challenge(&self, tag: &[u8], digest: &Digest) {
+        if self.recording {
+            self.events
+                .lock()
+                .unwrap()
+                .push(TranscriptEvent::Challenge {
+                    tag: tag.to_vec(),
+                    digest: *digest,
+                });
+        }
+    }
+
+    /// Switch transcript event recording on or off. While recording, every
+    /// enqueue, dequeue, and challenge derivation is logged; retrieve the
+    /// log with [`recorded_events`] and compare prover and verifier logs
+    /// with [`TranscriptEvent::first_divergence`]. Off by default; the log
+    /// clones every byte that passes through the stream, so leave it off
+    /// outside of debugging sessions.
+    ///
+    /// [`recorded_events`]: ProofStream::recorded_events
+    pub fn set_recording(&mut self, recording: bool) {
+        self.recording = recording;
+    }
+
+    /// The events recorded so far; see [`set_recording`].
+    ///
+    /// [`set_recording`]: ProofStream::set_recording
+    pub fn recorded_events(&self) -> Vec<TranscriptEvent> {
+        self.events.lock().unwrap().clone()
     }
 
     pub fn serialize(&self) -> Vec<u8> {
         self.transcript.clone()
     }
 
+    /// Write the transcript to `writer` as a sequence of length-prefixed
+    /// frames of at most [`STREAM_FRAME_SIZE`] bytes each, terminated by a
+    /// zero-length frame. Unlike [`serialize`], no second copy of the
+    /// transcript is materialized, so proofs can go straight to sockets or
+    /// files.
+    ///
+    /// [`serialize`]: ProofStream::serialize
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<(), Box<dyn Error>> {
+        for frame in self.transcript.chunks(STREAM_FRAME_SIZE) {
+            writer.write_all(&(frame.len() as u32).to_le_bytes())?;
+            writer.write_all(frame)?;
+        }
+        writer.write_all(&0u32.to_le_bytes())?;
+
+        Ok(())
+    }
+
+    /// Read a proof stream written by [`write_to`] from `reader`. The
+    /// transcript grows frame by frame, so a lying length field can demand
+    /// at most [`STREAM_FRAME_SIZE`] bytes at a time; anything claiming
+    /// more errors with [`ProofStreamError::MalformedFrame`].
+    ///
+    /// [`write_to`]: ProofStream::write_to
+    pub fn read_from<R: Read>(reader: &mut R) -> Result<Self, Box<dyn Error>> {
+        let mut transcript: Vec<u8> = vec![];
+        loop {
+            let mut length_bytes = [0u8; 4];
+            reader.read_exact(&mut length_bytes)?;
+            let frame_length = u32::from_le_bytes(length_bytes) as usize;
+            if frame_length == 0 {
+                break;
+            }
+            if frame_length > STREAM_FRAME_SIZE {
+                return Err(Box::new(ProofStreamError::MalformedFrame));
+            }
+
+            let transcript_length = transcript.len();
+            transcript.resize(transcript_length + frame_length, 0);
+            reader.read_exact(&mut transcript[transcript_length..])?;
+        }
+
+        Ok(transcript.into())
+    }
+
+    /// Append `other`'s transcript to this one, producing a single
+    /// composite proof -- e.g. a FRI proof plus auxiliary openings. The
+    /// boundary is re-bound deterministically: a Fiat-Shamir digest of
+    /// everything enqueued so far is written as a marker item before
+    /// `other`'s bytes, so neither proof can be swapped out or reordered
+    /// without the marker changing. The reader checks it with
+    /// [`cross_boundary`].
+    ///
+    /// [`cross_boundary`]: ProofStream::cross_boundary
+    pub fn append(&mut self, other: &ProofStream) -> Result<(), Box<dyn Error>> {
+        let boundary = self.prover_fiat_shamir();
+        self.enqueue_length_prepended(&boundary)?;
+        self.extend_transcript(&other.transcript);
+
+        self.stats.merkle_root_bytes += other.stats.merkle_root_bytes;
+        self.stats.authentication_structure_bytes += other.stats.authentication_structure_bytes;
+        self.stats.codeword_bytes += other.stats.codeword_bytes;
+
+        Ok(())
+    }
+
+    /// Read past a boundary written by [`append`]: dequeue the marker
+    /// digest and check it against the Fiat-Shamir digest of everything
+    /// read so far, erroring with [`ProofStreamError::BoundaryMismatch`]
+    /// if the two proofs were not combined exactly as claimed.
+    ///
+    /// [`append`]: ProofStream::append
+    pub fn cross_boundary(&mut self) -> Result<(), Box<dyn Error>> {
+        let expected_boundary = self.verifier_fiat_shamir();
+        let read_boundary: Digest = self.dequeue_length_prepended()?;
+        if read_boundary != expected_boundary {
+            return Err(Box::new(ProofStreamError::BoundaryMismatch));
+        }
+
+        Ok(())
+    }
+
+    /// How many prover-side challenges have been derived from this stream.
+    pub fn prover_challenge_count(&self) -> usize {
+        self.prover_challenge_count.load(Ordering::Relaxed)
+    }
+
+    /// How many verifier-side challenges have been derived from this
+    /// stream.
+    pub fn verifier_challenge_count(&self) -> usize {
+        self.verifier_challenge_count.load(Ordering::Relaxed)
+    }
+
+    /// Write the prover's challenge counter into the transcript. A
+    /// verifier that derives a different number of challenges than the
+    /// prover did would otherwise silently compute different challenges
+    /// downstream; with the counter sealed, [`check_challenge_count`]
+    /// turns that into a detected error.
+    ///
+    /// [`check_challenge_count`]: ProofStream::check_challenge_count
+    pub fn seal_challenge_count(&mut self) -> Result<(), Box<dyn Error>> {
+        let count = self.prover_challenge_count() as u64;
+        self.enqueue_length_prepended(&count)
+    }
+
+    /// Read a counter written by [`seal_challenge_count`] and check it
+    /// against the number of challenges derived on this side so far,
+    /// erroring with [`ProofStreamError::ChallengeCountMismatch`] on
+    /// disagreement.
+    ///
+    /// [`seal_challenge_count`]: ProofStream::seal_challenge_count
+    pub fn check_challenge_count(&mut self) -> Result<(), Box<dyn Error>> {
+        let actual = self.verifier_challenge_count();
+        let expected: u64 = self.dequeue_length_prepended()?;
+        if expected as usize != actual {
+            return Err(Box::new(ProofStreamError::ChallengeCountMismatch {
+                expected: expected as usize,
+                actual,
+            }));
+        }
+
+        Ok(())
+    }
+
     pub fn len(&self) -> usize {
         self.transcript.len()
     }
@@ -62,6 +619,11 @@ impl ProofStream {
             new_index <= self.transcript.len(),
             "new_index cannot exceed transcript length"
         );
+        if self.mode == TranscriptMode::Incremental {
+            // The dequeue-side sponge cannot rewind; rebuild it
+            self.read_absorber = blake3::Hasher::new();
+            self.read_absorber.update(&self.transcript[0..new_index]);
+        }
         self.read_index = new_index;
     }
 
@@ -69,8 +631,8 @@ impl ProofStream {
     where
         T: Serialize,
     {
-        let mut serialization_result = bincode::serialize(item)?;
-        self.transcript.append(&mut serialization_result);
+        let serialization_result = bincode::serialize(item)?;
+        self.extend_transcript(&serialization_result);
 
         Ok(())
     }
@@ -79,11 +641,10 @@ impl ProofStream {
     where
         T: Serialize,
     {
-        let mut serialization_result: Vec<u8> = bincode::serialize(item)?;
+        let serialization_result: Vec<u8> = bincode::serialize(item)?;
         let serialization_result_length: u32 = serialization_result.len() as u32;
-        self.transcript
-            .append(&mut bincode::serialize(&serialization_result_length).unwrap());
-        self.transcript.append(&mut serialization_result);
+        self.extend_transcript(&bincode::serialize(&serialization_result_length).unwrap());
+        self.extend_transcript(&serialization_result);
 
         Ok(())
     }
@@ -95,10 +656,11 @@ impl ProofStream {
         if byte_length + self.read_index > self.transcript.len() {
             return Err(Box::new(ProofStreamError::TranscriptLengthExceeded));
         }
+        self.charge_item(byte_length)?;
 
         let item: T =
             bincode::deserialize(&self.transcript[self.read_index..self.read_index + byte_length])?;
-        self.read_index += byte_length;
+        self.advance_read_index(self.read_index + byte_length);
 
         Ok(item)
     }
@@ -106,7 +668,7 @@ impl ProofStream {
     /// A package on a `ProofStream` consist of a `u32` containing the `item_length` of the payload (`item`)
     /// followed by the payload.  This is similar to _pascal style strings_.
     /// Corresponds to `pull` in [AoaS](https://aszepieniec.github.io/stark-anatomy/basic-tools#the-fiat-shamir-transform).
-
+    ///
     /// # Arguments
     ///
     /// * `item` - The payload we want to dequeue and deserialize.
@@ -130,20 +692,531 @@ impl ProofStream {
         if self.len() < item_end {
             return Err(Box::new(ProofStreamError::TranscriptLengthExceeded));
         }
+        self.charge_item(item_length as usize)?;
 
         let item: T = bincode::deserialize(&self.transcript[item_start..item_end])?;
 
-        self.read_index = item_end;
+        self.advance_read_index(item_end);
 
         Ok(item)
     }
 
+    /// Enqueue a typed [`ProofItem`], including its kind in the transcript.
+    pub fn enqueue_item(&mut self, item: &ProofItem) -> Result<(), Box<dyn Error>> {
+        let length_before = self.transcript.len();
+        self.enqueue_length_prepended(item)?;
+        let bytes_written = self.transcript.len() - length_before;
+        match item {
+            ProofItem::MerkleRoot(_) => self.stats.merkle_root_bytes += bytes_written,
+            ProofItem::AuthenticationStructure(_) => {
+                self.stats.authentication_structure_bytes += bytes_written
+            }
+            ProofItem::Codeword(_) => self.stats.codeword_bytes += bytes_written,
+            // Masked values and grinding nonces are rare and small; they
+            // are reported under `misc_bytes`.
+            ProofItem::MaskedValues(_) | ProofItem::GrindingNonce(_) => (),
+        }
+
+        Ok(())
+    }
+
+    /// Dequeue the next typed [`ProofItem`]. Callers match on the variant
+    /// or use the `as_*` accessors, which report a mismatch as a
+    /// [`ProofStreamError::UnexpectedItem`].
+    pub fn dequeue_item(&mut self) -> Result<ProofItem, Box<dyn Error>> {
+        self.dequeue_length_prepended()
+    }
+
+    /// Serialize the transcript into the canonical, self-describing binary
+    /// format: magic bytes, a format version, and a hasher identifier in
+    /// front of the raw transcript. Use this instead of [`serialize`] when
+    /// proofs cross crate-version or service boundaries, so that a reader
+    /// with mismatched expectations gets an explicit error from
+    /// [`from_versioned_bytes`] instead of garbage challenges.
+    ///
+    /// The hasher identifier is free-form; both sides must agree on it,
+    /// e.g. `"blake3"`. It must be shorter than 256 bytes.
+    ///
+    /// [`serialize`]: ProofStream::serialize
+    /// [`from_versioned_bytes`]: ProofStream::from_versioned_bytes
+    pub fn to_versioned_bytes(&self, hasher_id: &str) -> Vec<u8> {
+        assert!(
+            hasher_id.len() < 256,
+            "Hasher identifier must be shorter than 256 bytes"
+        );
+        let mut bytes =
+            Vec::with_capacity(PROOF_FORMAT_MAGIC.len() + 2 + hasher_id.len() + self.len());
+        bytes.extend_from_slice(PROOF_FORMAT_MAGIC);
+        bytes.push(PROOF_FORMAT_VERSION);
+        bytes.push(hasher_id.len() as u8);
+        bytes.extend_from_slice(hasher_id.as_bytes());
+        bytes.extend_from_slice(&self.transcript);
+
+        bytes
+    }
+
+    /// The inverse of [`to_versioned_bytes`]: check magic, format version,
+    /// and hasher identifier, and reconstruct the proof stream from the
+    /// remaining bytes.
+    ///
+    /// [`to_versioned_bytes`]: ProofStream::to_versioned_bytes
+    pub fn from_versioned_bytes(
+        bytes: &[u8],
+        expected_hasher_id: &str,
+    ) -> Result<Self, ProofStreamError> {
+        let header_length = PROOF_FORMAT_MAGIC.len() + 2;
+        if bytes.len() < header_length {
+            return Err(ProofStreamError::MalformedHeader);
+        }
+        if &bytes[0..PROOF_FORMAT_MAGIC.len()] != PROOF_FORMAT_MAGIC {
+            return Err(ProofStreamError::BadMagic);
+        }
+        let version = bytes[PROOF_FORMAT_MAGIC.len()];
+        if version != PROOF_FORMAT_VERSION {
+            return Err(ProofStreamError::UnsupportedFormatVersion(version));
+        }
+        let hasher_id_length = bytes[PROOF_FORMAT_MAGIC.len() + 1] as usize;
+        if bytes.len() < header_length + hasher_id_length {
+            return Err(ProofStreamError::MalformedHeader);
+        }
+        let hasher_id = &bytes[header_length..header_length + hasher_id_length];
+        if hasher_id != expected_hasher_id.as_bytes() {
+            return Err(ProofStreamError::HasherMismatch {
+                expected: expected_hasher_id.to_string(),
+                actual: String::from_utf8_lossy(hasher_id).to_string(),
+            });
+        }
+
+        Ok(Self::from(
+            bytes[header_length + hasher_id_length..].to_vec(),
+        ))
+    }
+
+    /// Serialize the transcript with a simple dictionary compressor that
+    /// replaces repeated byte runs -- FRI proofs are full of structurally
+    /// similar digests and field elements -- by backreferences. The inverse
+    /// is [`from_compressed_bytes`]; the decompressed stream is
+    /// byte-identical to [`serialize`], so Fiat-Shamir challenges are
+    /// unaffected and the verifier needs no changes beyond calling the
+    /// decompressing constructor.
+    ///
+    /// [`from_compressed_bytes`]: ProofStream::from_compressed_bytes
+    /// [`serialize`]: ProofStream::serialize
+    pub fn to_compressed_bytes(&self) -> Vec<u8> {
+        let data = &self.transcript;
+        let mut bytes: Vec<u8> = Vec::with_capacity(data.len() / 2 + 16);
+        bytes.extend_from_slice(&(data.len() as u32).to_le_bytes());
+
+        let mut positions: HashMap<&[u8], usize> = HashMap::new();
+        let mut literal_start = 0;
+        let mut i = 0;
+        while i + COMPRESSION_MIN_MATCH <= data.len() {
+            let key = &data[i..i + COMPRESSION_MIN_MATCH];
+            match positions.get(key) {
+                Some(&j) => {
+                    let mut length = COMPRESSION_MIN_MATCH;
+                    while i + length < data.len() && data[j + length] == data[i + length] {
+                        length += 1;
+                    }
+                    Self::push_literal_run(&mut bytes, &data[literal_start..i]);
+                    bytes.push(1);
+                    bytes.extend_from_slice(&((i - j) as u32).to_le_bytes());
+                    bytes.extend_from_slice(&(length as u32).to_le_bytes());
+                    for k in i..(i + length).min(data.len() - COMPRESSION_MIN_MATCH + 1) {
+                        positions.insert(&data[k..k + COMPRESSION_MIN_MATCH], k);
+                    }
+                    i += length;
+                    literal_start = i;
+                }
+                None => {
+                    positions.insert(key, i);
+                    i += 1;
+                }
+            }
+        }
+        Self::push_literal_run(&mut bytes, &data[literal_start..]);
+
+        bytes
+    }
+
+    fn push_literal_run(bytes: &mut Vec<u8>, literals: &[u8]) {
+        if !literals.is_empty() {
+            bytes.push(0);
+            bytes.extend_from_slice(&(literals.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(literals);
+        }
+    }
+
+    /// The inverse of [`to_compressed_bytes`].
+    ///
+    /// [`to_compressed_bytes`]: ProofStream::to_compressed_bytes
+    pub fn from_compressed_bytes(bytes: &[u8]) -> Result<Self, ProofStreamError> {
+        let read_u32 = |idx: usize| -> Result<u32, ProofStreamError> {
+            let word = bytes
+                .get(idx..idx + 4)
+                .ok_or(ProofStreamError::MalformedCompressedData)?;
+            Ok(u32::from_le_bytes(word.try_into().unwrap()))
+        };
+
+        // The declared length is attacker-controlled, so it must not drive
+        // any allocation on its own: the initial capacity is additionally
+        // capped by what the input could plausibly decompress to, and every
+        // token is checked against the declared length before it is applied,
+        // so both work and memory stay bounded even for malicious inputs.
+        let transcript_length = read_u32(0)? as usize;
+        let plausible_length = bytes
+            .len()
+            .saturating_mul(COMPRESSION_MAX_INITIAL_EXPANSION);
+        let mut transcript: Vec<u8> = Vec::with_capacity(transcript_length.min(plausible_length));
+        let mut idx = 4;
+        while idx < bytes.len() {
+            let tag = bytes[idx];
+            idx += 1;
+            match tag {
+                0 => {
+                    let length = read_u32(idx)? as usize;
+                    idx += 4;
+                    let literals = bytes
+                        .get(idx..idx + length)
+                        .ok_or(ProofStreamError::MalformedCompressedData)?;
+                    if transcript.len() + length > transcript_length {
+                        return Err(ProofStreamError::MalformedCompressedData);
+                    }
+                    transcript.extend_from_slice(literals);
+                    idx += length;
+                }
+                1 => {
+                    let offset = read_u32(idx)? as usize;
+                    let length = read_u32(idx + 4)? as usize;
+                    idx += 8;
+                    if offset == 0 || offset > transcript.len() {
+                        return Err(ProofStreamError::MalformedCompressedData);
+                    }
+                    if transcript.len() + length > transcript_length {
+                        return Err(ProofStreamError::MalformedCompressedData);
+                    }
+                    // Copy byte by byte; a match may overlap its own output
+                    for _ in 0..length {
+                        transcript.push(transcript[transcript.len() - offset]);
+                    }
+                }
+                _ => return Err(ProofStreamError::MalformedCompressedData),
+            }
+        }
+        if transcript.len() != transcript_length {
+            return Err(ProofStreamError::MalformedCompressedData);
+        }
+
+        Ok(Self::from(transcript))
+    }
+
+    /// Deserialize the next `byte_length` bytes without advancing the read
+    /// index. The target type may borrow from the transcript buffer, so
+    /// large items can be inspected without an owned copy.
+    pub fn peek<'a, T>(&'a self, byte_length: usize) -> Result<T, Box<dyn Error>>
+    where
+        T: Deserialize<'a>,
+    {
+        if byte_length + self.read_index > self.transcript.len() {
+            return Err(Box::new(ProofStreamError::TranscriptLengthExceeded));
+        }
+
+        let item: T =
+            bincode::deserialize(&self.transcript[self.read_index..self.read_index + byte_length])?;
+
+        Ok(item)
+    }
+
+    /// Like [`peek`], for a length-prepended item.
+    ///
+    /// [`peek`]: ProofStream::peek
+    pub fn peek_length_prepended<'a, T>(&'a self) -> Result<T, Box<dyn Error>>
+    where
+        T: Deserialize<'a>,
+    {
+        let (item_start, item_end) = self.length_prepended_bounds()?;
+        let item: T = bincode::deserialize(&self.transcript[item_start..item_end])?;
+
+        Ok(item)
+    }
+
+    /// Like [`dequeue`], but the target type may borrow from the transcript
+    /// buffer -- e.g. `&[u8]` or `&str` -- so large items are read without
+    /// an intermediate copy.
+    ///
+    /// [`dequeue`]: ProofStream::dequeue
+    pub fn dequeue_ref<'a, T>(&'a mut self, byte_length: usize) -> Result<T, Box<dyn Error>>
+    where
+        T: Deserialize<'a>,
+    {
+        if byte_length + self.read_index > self.transcript.len() {
+            return Err(Box::new(ProofStreamError::TranscriptLengthExceeded));
+        }
+        self.charge_item(byte_length)?;
+
+        let item_start = self.read_index;
+        self.advance_read_index(item_start + byte_length);
+        let item: T = bincode::deserialize(&self.transcript[item_start..item_start + byte_length])?;
+
+        Ok(item)
+    }
+
+    /// Like [`dequeue_ref`], for a length-prepended item.
+    ///
+    /// [`dequeue_ref`]: ProofStream::dequeue_ref
+    pub fn dequeue_ref_length_prepended<'a, T>(&'a mut self) -> Result<T, Box<dyn Error>>
+    where
+        T: Deserialize<'a>,
+    {
+        let (item_start, item_end) = self.length_prepended_bounds()?;
+        self.charge_item(item_end - item_start)?;
+        self.advance_read_index(item_end);
+        let item: T = bincode::deserialize(&self.transcript[item_start..item_end])?;
+
+        Ok(item)
+    }
+
+    /// The byte range of the length-prepended item at the read index.
+    fn length_prepended_bounds(&self) -> Result<(usize, usize), Box<dyn Error>> {
+        let sizeof_item_length = std::mem::size_of::<u32>();
+        let item_length_end = self.read_index + sizeof_item_length;
+        if self.transcript.len() < item_length_end {
+            return Err(Box::new(ProofStreamError::TranscriptLengthExceeded));
+        }
+        let item_length: u32 =
+            bincode::deserialize(&self.transcript[self.read_index..item_length_end])?;
+        let item_end = item_length_end + item_length as usize;
+        if self.transcript.len() < item_end {
+            return Err(Box::new(ProofStreamError::TranscriptLengthExceeded));
+        }
+
+        Ok((item_length_end, item_end))
+    }
+
+    /// Enqueue an item under a label. The label goes into the transcript
+    /// ahead of the item -- and thereby into the Fiat-Shamir state -- so
+    /// protocols using distinct labels can never produce colliding
+    /// transcripts, and a reader expecting a different item fails loudly
+    /// in [`dequeue_labeled`] instead of misinterpreting bytes.
+    ///
+    /// [`dequeue_labeled`]: ProofStream::dequeue_labeled
+    pub fn enqueue_labeled<T>(&mut self, label: &str, item: &T) -> Result<(), Box<dyn Error>>
+    where
+        T: Serialize,
+    {
+        self.enqueue_length_prepended(&label)?;
+        self.enqueue_length_prepended(item)
+    }
+
+    /// Dequeue an item enqueued under the given label, erroring with
+    /// [`ProofStreamError::LabelMismatch`] if the transcript holds a
+    /// different label at this position.
+    pub fn dequeue_labeled<T>(&mut self, label: &str) -> Result<T, Box<dyn Error>>
+    where
+        T: DeserializeOwned,
+    {
+        let actual: String = self.dequeue_length_prepended()?;
+        if actual != label {
+            return Err(Box::new(ProofStreamError::LabelMismatch {
+                expected: label.to_string(),
+                actual,
+            }));
+        }
+        self.dequeue_length_prepended()
+    }
+
+    /// A prover-side challenge bound to everything enqueued so far, with
+    /// the label mixed in as a domain-separation tag; the counterpart of
+    /// [`verifier_challenge`].
+    ///
+    /// [`verifier_challenge`]: ProofStream::verifier_challenge
+    pub fn prover_challenge(&self, label: &str) -> Digest {
+        self.prover_fiat_shamir_tagged(label.as_bytes())
+    }
+
+    /// The verifier-side counterpart of [`prover_challenge`]: the same
+    /// challenge, derived from the bytes read so far.
+    ///
+    /// [`prover_challenge`]: ProofStream::prover_challenge
+    pub fn verifier_challenge(&self, label: &str) -> Digest {
+        self.verifier_fiat_shamir_tagged(label.as_bytes())
+    }
+
     pub fn prover_fiat_shamir(&self) -> Digest {
-        from_blake3_digest(&blake3::hash(&self.transcript))
+        self.prover_challenge_count.fetch_add(1, Ordering::Relaxed);
+        let digest = match self.mode {
+            TranscriptMode::Rehash => from_blake3_digest(&blake3::hash(&self.transcript)),
+            TranscriptMode::Incremental => from_blake3_digest(&self.absorber.finalize()),
+        };
+        self.record_challenge(b"", &digest);
+
+        digest
     }
 
     pub fn verifier_fiat_shamir(&self) -> Digest {
-        from_blake3_digest(&blake3::hash(&self.transcript[0..self.read_index]))
+        self.verifier_challenge_count
+            .fetch_add(1, Ordering::Relaxed);
+        let digest = match self.mode {
+            TranscriptMode::Rehash => {
+                from_blake3_digest(&blake3::hash(&self.transcript[0..self.read_index]))
+            }
+            TranscriptMode::Incremental => from_blake3_digest(&self.read_absorber.finalize()),
+        };
+        self.record_challenge(b"", &digest);
+
+        digest
+    }
+
+    /// Like [`prover_fiat_shamir`], with a domain-separation tag hashed in
+    /// front of the transcript, so that different protocols -- or different
+    /// challenge kinds within one protocol -- derive distinct challenges
+    /// from identical byte streams.
+    ///
+    /// [`prover_fiat_shamir`]: ProofStream::prover_fiat_shamir
+    pub fn prover_fiat_shamir_tagged(&self, tag: &[u8]) -> Digest {
+        self.prover_challenge_count.fetch_add(1, Ordering::Relaxed);
+        let digest = match self.mode {
+            TranscriptMode::Rehash => Self::tagged_digest(tag, &self.transcript),
+            TranscriptMode::Incremental => Self::squeeze_tagged(&self.absorber, tag),
+        };
+        self.record_challenge(tag, &digest);
+
+        digest
+    }
+
+    /// The tagged prover challenge as it would be after additionally
+    /// enqueueing the given bytes, without mutating the stream. Proof-of-work
+    /// grinding uses this to evaluate candidate nonces through the same
+    /// mode-aware derivation the verifier replays; candidates are neither
+    /// counted nor recorded as challenges, since the real derivation follows
+    /// once the winning candidate is enqueued.
+    pub fn prover_fiat_shamir_tagged_after(&self, tag: &[u8], bytes: &[u8]) -> Digest {
+        match self.mode {
+            TranscriptMode::Rehash => {
+                let mut hasher = blake3::Hasher::new();
+                hasher.update(tag);
+                hasher.update(&self.transcript);
+                hasher.update(bytes);
+                from_blake3_digest(&hasher.finalize())
+            }
+            TranscriptMode::Incremental => {
+                let mut absorber = self.absorber.clone();
+                absorber.update(bytes);
+                Self::squeeze_tagged(&absorber, tag)
+            }
+        }
+    }
+
+    /// The verifier-side counterpart of [`prover_fiat_shamir_tagged`]: the
+    /// tagged digest over the part of the transcript read so far.
+    ///
+    /// [`prover_fiat_shamir_tagged`]: ProofStream::prover_fiat_shamir_tagged
+    pub fn verifier_fiat_shamir_tagged(&self, tag: &[u8]) -> Digest {
+        self.verifier_challenge_count
+            .fetch_add(1, Ordering::Relaxed);
+        let digest = match self.mode {
+            TranscriptMode::Rehash => {
+                Self::tagged_digest(tag, &self.transcript[0..self.read_index])
+            }
+            TranscriptMode::Incremental => Self::squeeze_tagged(&self.read_absorber, tag),
+        };
+        self.record_challenge(tag, &digest);
+
+        digest
+    }
+
+    fn tagged_digest(tag: &[u8], transcript: &[u8]) -> Digest {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(tag);
+        hasher.update(transcript);
+        from_blake3_digest(&hasher.finalize())
+    }
+
+    /// Squeeze a tagged challenge out of a running sponge: finalize a
+    /// clone with the tag absorbed last, leaving the sponge itself ready
+    /// for further absorption.
+    fn squeeze_tagged(absorber: &blake3::Hasher, tag: &[u8]) -> Digest {
+        let mut hasher = absorber.clone();
+        hasher.update(tag);
+        from_blake3_digest(&hasher.finalize())
+    }
+
+    /// Like [`prover_fiat_shamir`], but with the challenge derived through
+    /// an arbitrary [`AlgebraicHasher`] instead of the built-in blake3
+    /// transcript hash. This lets the transcript hasher match the `H` a
+    /// protocol is instantiated with -- Keccak when targeting EVM
+    /// verification, Rescue when targeting recursion. The transcript is
+    /// rehashed on every call, independent of [`TranscriptMode`].
+    ///
+    /// [`prover_fiat_shamir`]: ProofStream::prover_fiat_shamir
+    pub fn prover_fiat_shamir_with_hasher<H: AlgebraicHasher>(&self) -> Digest {
+        self.prover_challenge_count.fetch_add(1, Ordering::Relaxed);
+        H::hash_slice(&Self::bytes_to_elements(&self.transcript))
+    }
+
+    /// The verifier-side counterpart of [`prover_fiat_shamir_with_hasher`]:
+    /// the same challenge, derived from the bytes read so far.
+    ///
+    /// [`prover_fiat_shamir_with_hasher`]: ProofStream::prover_fiat_shamir_with_hasher
+    pub fn verifier_fiat_shamir_with_hasher<H: AlgebraicHasher>(&self) -> Digest {
+        self.verifier_challenge_count
+            .fetch_add(1, Ordering::Relaxed);
+        H::hash_slice(&Self::bytes_to_elements(
+            &self.transcript[0..self.read_index],
+        ))
+    }
+
+    /// Like [`prover_fiat_shamir_tagged`], derived through an arbitrary
+    /// [`AlgebraicHasher`]; see [`prover_fiat_shamir_with_hasher`].
+    ///
+    /// [`prover_fiat_shamir_tagged`]: ProofStream::prover_fiat_shamir_tagged
+    /// [`prover_fiat_shamir_with_hasher`]: ProofStream::prover_fiat_shamir_with_hasher
+    pub fn prover_fiat_shamir_tagged_with_hasher<H: AlgebraicHasher>(&self, tag: &[u8]) -> Digest {
+        self.prover_challenge_count.fetch_add(1, Ordering::Relaxed);
+        H::hash_slice(
+            &[
+                Self::bytes_to_elements(tag),
+                Self::bytes_to_elements(&self.transcript),
+            ]
+            .concat(),
+        )
+    }
+
+    /// The verifier-side counterpart of
+    /// [`prover_fiat_shamir_tagged_with_hasher`].
+    ///
+    /// [`prover_fiat_shamir_tagged_with_hasher`]: ProofStream::prover_fiat_shamir_tagged_with_hasher
+    pub fn verifier_fiat_shamir_tagged_with_hasher<H: AlgebraicHasher>(
+        &self,
+        tag: &[u8],
+    ) -> Digest {
+        self.verifier_challenge_count
+            .fetch_add(1, Ordering::Relaxed);
+        H::hash_slice(
+            &[
+                Self::bytes_to_elements(tag),
+                Self::bytes_to_elements(&self.transcript[0..self.read_index]),
+            ]
+            .concat(),
+        )
+    }
+
+    /// Pack transcript bytes into field elements for an
+    /// [`AlgebraicHasher`]: four little-endian bytes per element, with the
+    /// byte length appended as a final element so that inputs differing
+    /// only in trailing zero bytes pack differently.
+    fn bytes_to_elements(bytes: &[u8]) -> Vec<BFieldElement> {
+        let mut elements: Vec<BFieldElement> = bytes
+            .chunks(4)
+            .map(|chunk| {
+                let mut word = [0u8; 4];
+                word[..chunk.len()].copy_from_slice(chunk);
+                BFieldElement::new(u32::from_le_bytes(word) as u64)
+            })
+            .collect();
+        elements.push(BFieldElement::new(bytes.len() as u64));
+
+        elements
     }
 }
 
@@ -215,6 +1288,535 @@ pub mod test_proof_stream {
         );
     }
 
+    #[test]
+    fn ps_typed_items_test() {
+        let items = [
+            ProofItem::MerkleRoot(Digest::default()),
+            ProofItem::AuthenticationStructure(vec![PartialAuthenticationPath(vec![
+                Some(Digest::default()),
+                None,
+            ])]),
+            ProofItem::Codeword(vec![XFieldElement::new_const(BFieldElement::new(17))]),
+            ProofItem::MaskedValues(vec![XFieldElement::new_const(BFieldElement::new(4))]),
+            ProofItem::GrindingNonce(42),
+        ];
+
+        let mut ps = ProofStream::default();
+        for item in items.iter() {
+            ps.enqueue_item(item).unwrap();
+        }
+        for item in items.iter() {
+            assert_eq!(*item, ps.dequeue_item().unwrap());
+        }
+    }
+
+    #[test]
+    fn ps_typed_item_mismatch_test() {
+        let mut ps = ProofStream::default();
+        ps.enqueue_item(&ProofItem::GrindingNonce(99)).unwrap();
+
+        let item = ps.dequeue_item().unwrap();
+        assert_eq!(
+            ProofStreamError::UnexpectedItem {
+                expected: "MerkleRoot",
+                actual: "GrindingNonce",
+            },
+            item.as_merkle_root().unwrap_err()
+        );
+        assert_eq!(99, item.as_grinding_nonce().unwrap());
+    }
+
+    #[test]
+    fn ps_versioned_bytes_roundtrip_test() {
+        let mut ps = ProofStream::default();
+        ps.enqueue_length_prepended(&BFieldElement::new(42))
+            .unwrap();
+
+        let bytes = ps.to_versioned_bytes("blake3");
+        let recovered = ProofStream::from_versioned_bytes(&bytes, "blake3").unwrap();
+        assert_eq!(ps, recovered);
+    }
+
+    #[test]
+    fn ps_versioned_bytes_rejection_test() {
+        let ps = ProofStream::default();
+        let bytes = ps.to_versioned_bytes("blake3");
+
+        // Truncated header
+        assert_eq!(
+            Err(ProofStreamError::MalformedHeader),
+            ProofStream::from_versioned_bytes(&bytes[0..3], "blake3")
+        );
+
+        // Wrong magic
+        let mut bad_magic = bytes.clone();
+        bad_magic[0] ^= 0xff;
+        assert_eq!(
+            Err(ProofStreamError::BadMagic),
+            ProofStream::from_versioned_bytes(&bad_magic, "blake3")
+        );
+
+        // Unsupported version
+        let mut bad_version = bytes.clone();
+        bad_version[4] = 99;
+        assert_eq!(
+            Err(ProofStreamError::UnsupportedFormatVersion(99)),
+            ProofStream::from_versioned_bytes(&bad_version, "blake3")
+        );
+
+        // Mismatched hasher
+        assert_eq!(
+            Err(ProofStreamError::HasherMismatch {
+                expected: "rescue-prime".to_string(),
+                actual: "blake3".to_string(),
+            }),
+            ProofStream::from_versioned_bytes(&bytes, "rescue-prime")
+        );
+    }
+
+    #[test]
+    fn ps_compressed_bytes_roundtrip_test() {
+        // A transcript with plenty of repetition compresses and roundtrips
+        let mut ps = ProofStream::default();
+        for i in 0..100u64 {
+            ps.enqueue_length_prepended(&Digest::default()).unwrap();
+            ps.enqueue_length_prepended(&BFieldElement::new(i % 3))
+                .unwrap();
+        }
+        let compressed = ps.to_compressed_bytes();
+        assert!(compressed.len() < ps.len());
+        assert_eq!(ps, ProofStream::from_compressed_bytes(&compressed).unwrap());
+
+        // An incompressible transcript still roundtrips
+        let short: ProofStream = vec![1, 2, 3].into();
+        let short_compressed = short.to_compressed_bytes();
+        assert_eq!(
+            short,
+            ProofStream::from_compressed_bytes(&short_compressed).unwrap()
+        );
+
+        // Corrupted data is rejected rather than misread
+        assert_eq!(
+            Err(ProofStreamError::MalformedCompressedData),
+            ProofStream::from_compressed_bytes(&compressed[0..compressed.len() - 1])
+        );
+    }
+
+    #[test]
+    fn ps_decompression_bomb_test() {
+        // A tiny input whose backreference token claims a 4 GiB expansion
+        // must fail as soon as the declared length is exceeded, not after
+        // materializing the expansion
+        let mut bomb: Vec<u8> = vec![];
+        bomb.extend_from_slice(&16u32.to_le_bytes()); // declared length
+        bomb.push(0); // literal run of one byte
+        bomb.extend_from_slice(&1u32.to_le_bytes());
+        bomb.push(42);
+        bomb.push(1); // backreference: offset 1, length u32::MAX
+        bomb.extend_from_slice(&1u32.to_le_bytes());
+        bomb.extend_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(
+            Err(ProofStreamError::MalformedCompressedData),
+            ProofStream::from_compressed_bytes(&bomb)
+        );
+
+        // The same token stream under a maximal declared length must not be
+        // trusted with a matching allocation either; the token still
+        // overshoots the declared length and is rejected up front
+        bomb[0..4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(
+            Err(ProofStreamError::MalformedCompressedData),
+            ProofStream::from_compressed_bytes(&bomb)
+        );
+
+        // An oversized literal run is cut off by the declared length as well
+        let mut oversized_literals: Vec<u8> = vec![];
+        oversized_literals.extend_from_slice(&2u32.to_le_bytes());
+        oversized_literals.push(0);
+        oversized_literals.extend_from_slice(&4u32.to_le_bytes());
+        oversized_literals.extend_from_slice(&[1, 2, 3, 4]);
+        assert_eq!(
+            Err(ProofStreamError::MalformedCompressedData),
+            ProofStream::from_compressed_bytes(&oversized_literals)
+        );
+    }
+
+    #[test]
+    fn ps_incremental_transcript_test() {
+        // Prover and verifier sponges agree once the same bytes have been
+        // absorbed, for tagged and untagged challenges alike
+        let mut prover = ProofStream::new_incremental();
+        prover
+            .enqueue_length_prepended(&BFieldElement::new(42))
+            .unwrap();
+        prover
+            .enqueue_length_prepended(&BFieldElement::new(17))
+            .unwrap();
+
+        let mut verifier = ProofStream::incremental_from(prover.serialize());
+        let _: BFieldElement = verifier.dequeue_length_prepended().unwrap();
+        let _: BFieldElement = verifier.dequeue_length_prepended().unwrap();
+        assert_eq!(prover.prover_fiat_shamir(), verifier.verifier_fiat_shamir());
+        assert_eq!(
+            prover.prover_fiat_shamir_tagged(b"alpha"),
+            verifier.verifier_fiat_shamir_tagged(b"alpha")
+        );
+
+        // The incremental challenges differ from the rehashing ones -- the
+        // mode is part of the protocol agreement
+        let mut rehashing = ProofStream::default();
+        rehashing
+            .enqueue_length_prepended(&BFieldElement::new(42))
+            .unwrap();
+        rehashing
+            .enqueue_length_prepended(&BFieldElement::new(17))
+            .unwrap();
+        assert_ne!(
+            rehashing.prover_fiat_shamir_tagged(b"alpha"),
+            prover.prover_fiat_shamir_tagged(b"alpha")
+        );
+
+        // Rewinding the read index rebuilds the dequeue-side sponge
+        verifier.set_index(0);
+        let _: BFieldElement = verifier.dequeue_length_prepended().unwrap();
+        let _: BFieldElement = verifier.dequeue_length_prepended().unwrap();
+        assert_eq!(prover.prover_fiat_shamir(), verifier.verifier_fiat_shamir());
+    }
+
+    #[test]
+    fn ps_labeled_items_test() {
+        let mut ps = ProofStream::default();
+        ps.enqueue_labeled("merkle-root", &BFieldElement::new(42))
+            .unwrap();
+        ps.enqueue_labeled("last-codeword", &BFieldElement::new(17))
+            .unwrap();
+
+        // Challenges are bound to the labeled content and agree across sides
+        let challenge = ps.prover_challenge("alpha");
+        assert_ne!(challenge, ps.prover_challenge("beta"));
+
+        let root: BFieldElement = ps.dequeue_labeled("merkle-root").unwrap();
+        assert_eq!(BFieldElement::new(42), root);
+
+        // Reading under the wrong label fails loudly
+        let err = ps
+            .dequeue_labeled::<BFieldElement>("merkle-root")
+            .unwrap_err();
+        assert_eq!(
+            ProofStreamError::LabelMismatch {
+                expected: "merkle-root".to_string(),
+                actual: "last-codeword".to_string(),
+            },
+            *err.downcast::<ProofStreamError>().unwrap()
+        );
+
+        // After reading everything, the verifier derives the same challenge
+        let mut verifier: ProofStream = ps.serialize().into();
+        let _: BFieldElement = verifier.dequeue_labeled("merkle-root").unwrap();
+        let _: BFieldElement = verifier.dequeue_labeled("last-codeword").unwrap();
+        assert_eq!(challenge, verifier.verifier_challenge("alpha"));
+    }
+
+    #[test]
+    fn ps_zero_copy_dequeue_test() {
+        let mut ps = ProofStream::default();
+        ps.enqueue_length_prepended(&vec![1u8, 2, 3]).unwrap();
+        ps.enqueue_length_prepended(&"hello".to_string()).unwrap();
+
+        // Peeking does not advance the read index
+        let peeked: &[u8] = ps.peek_length_prepended().unwrap();
+        assert_eq!(&[1u8, 2, 3], peeked);
+        assert_eq!(0, ps.get_read_index());
+
+        // Borrowed dequeues advance and read the same bytes
+        let bytes: &[u8] = ps.dequeue_ref_length_prepended().unwrap();
+        assert_eq!(&[1u8, 2, 3], bytes);
+        let text: &str = ps.dequeue_ref_length_prepended().unwrap();
+        assert_eq!("hello", text);
+
+        // Exhausted streams error instead of panicking
+        assert!(ps.dequeue_ref_length_prepended::<&[u8]>().is_err());
+    }
+
+    #[test]
+    fn ps_transcript_recorder_test() {
+        let mut prover_ps = ProofStream::default();
+        prover_ps.set_recording(true);
+        prover_ps.enqueue(&BFieldElement::new(3)).unwrap();
+        let _ = prover_ps.prover_challenge("alpha");
+        prover_ps.enqueue(&BFieldElement::new(4)).unwrap();
+        let prover_events = prover_ps.recorded_events();
+        assert_eq!(3, prover_events.len());
+
+        // A faithful verifier's log corresponds event for event
+        let mut verifier_ps: ProofStream = prover_ps.serialize().into();
+        verifier_ps.set_recording(true);
+        let _: BFieldElement = verifier_ps.dequeue(8).unwrap();
+        let _ = verifier_ps.verifier_challenge("alpha");
+        let _: BFieldElement = verifier_ps.dequeue(8).unwrap();
+        assert_eq!(
+            None,
+            TranscriptEvent::first_divergence(&prover_events, &verifier_ps.recorded_events())
+        );
+
+        // A verifier using the wrong challenge label diverges at event 1
+        let mut diverging_ps: ProofStream = prover_ps.serialize().into();
+        diverging_ps.set_recording(true);
+        let _: BFieldElement = diverging_ps.dequeue(8).unwrap();
+        let _ = diverging_ps.verifier_challenge("beta");
+        let _: BFieldElement = diverging_ps.dequeue(8).unwrap();
+        assert_eq!(
+            Some(1),
+            TranscriptEvent::first_divergence(&prover_events, &diverging_ps.recorded_events())
+        );
+
+        // A verifier that stops early diverges at the end of its log
+        let mut short_ps: ProofStream = prover_ps.serialize().into();
+        short_ps.set_recording(true);
+        let _: BFieldElement = short_ps.dequeue(8).unwrap();
+        assert_eq!(
+            Some(1),
+            TranscriptEvent::first_divergence(&prover_events, &short_ps.recorded_events())
+        );
+    }
+
+    #[test]
+    fn ps_challenge_count_test() {
+        let mut prover_ps = ProofStream::default();
+        prover_ps.enqueue(&BFieldElement::new(7)).unwrap();
+        let _ = prover_ps.prover_challenge("alpha");
+        let _ = prover_ps.prover_fiat_shamir();
+        assert_eq!(2, prover_ps.prover_challenge_count());
+        prover_ps.seal_challenge_count().unwrap();
+
+        // A verifier deriving the same number of challenges passes
+        let mut verifier_ps: ProofStream = prover_ps.serialize().into();
+        let _: BFieldElement = verifier_ps.dequeue(8).unwrap();
+        let _ = verifier_ps.verifier_challenge("alpha");
+        let _ = verifier_ps.verifier_fiat_shamir();
+        verifier_ps.check_challenge_count().unwrap();
+
+        // A verifier that skipped a challenge is caught
+        let mut sloppy_ps: ProofStream = prover_ps.serialize().into();
+        let _: BFieldElement = sloppy_ps.dequeue(8).unwrap();
+        let _ = sloppy_ps.verifier_challenge("alpha");
+        let err = sloppy_ps.check_challenge_count().unwrap_err();
+        assert_eq!(
+            ProofStreamError::ChallengeCountMismatch {
+                expected: 2,
+                actual: 1,
+            },
+            *err.downcast::<ProofStreamError>().unwrap()
+        );
+    }
+
+    #[test]
+    fn ps_append_test() {
+        let mut first = ProofStream::default();
+        first.enqueue(&BFieldElement::new(101)).unwrap();
+        let mut second = ProofStream::default();
+        second.enqueue(&BFieldElement::new(202)).unwrap();
+
+        let mut composite = first;
+        composite.append(&second).unwrap();
+
+        // The reader walks the first proof, crosses the boundary, and
+        // continues into the second proof
+        let mut reader: ProofStream = composite.serialize().into();
+        let first_element: BFieldElement = reader.dequeue(8).unwrap();
+        assert_eq!(BFieldElement::new(101), first_element);
+        reader.cross_boundary().unwrap();
+        let second_element: BFieldElement = reader.dequeue(8).unwrap();
+        assert_eq!(BFieldElement::new(202), second_element);
+
+        // A tampered first proof no longer matches the boundary marker
+        let mut tampered_bytes = composite.serialize();
+        tampered_bytes[0] ^= 1;
+        let mut tampered: ProofStream = tampered_bytes.into();
+        let _: BFieldElement = tampered.dequeue(8).unwrap();
+        let err = tampered.cross_boundary().unwrap_err();
+        assert_eq!(
+            ProofStreamError::BoundaryMismatch,
+            *err.downcast::<ProofStreamError>().unwrap()
+        );
+    }
+
+    #[test]
+    fn ps_read_write_roundtrip_test() {
+        // Large enough to span multiple frames
+        let mut ps = ProofStream::default();
+        ps.enqueue_length_prepended(&vec![BFieldElement::new(17); 20_000])
+            .unwrap();
+
+        let mut buffer: Vec<u8> = vec![];
+        ps.write_to(&mut buffer).unwrap();
+        assert!(buffer.len() > ps.len());
+
+        let recovered = ProofStream::read_from(&mut buffer.as_slice()).unwrap();
+        assert_eq!(ps, recovered);
+
+        // Truncated input is rejected
+        assert!(ProofStream::read_from(&mut &buffer[0..buffer.len() / 2]).is_err());
+
+        // A frame claiming more than the frame size is rejected
+        let mut oversized: Vec<u8> = (u32::MAX).to_le_bytes().to_vec();
+        oversized.extend_from_slice(&[0u8; 64]);
+        let err = ProofStream::read_from(&mut oversized.as_slice()).unwrap_err();
+        assert_eq!(
+            ProofStreamError::MalformedFrame,
+            *err.downcast::<ProofStreamError>().unwrap()
+        );
+    }
+
+    #[test]
+    fn ps_generic_hasher_fiat_shamir_test() {
+        use crate::shared_math::rescue_prime_regular::RescuePrimeRegular;
+
+        let mut prover_ps = ProofStream::default();
+        prover_ps.enqueue(&BFieldElement::new(42)).unwrap();
+        prover_ps
+            .enqueue_length_prepended(&vec![BFieldElement::new(4); 7])
+            .unwrap();
+
+        let mut verifier_ps: ProofStream = prover_ps.serialize().into();
+        let _: BFieldElement = verifier_ps.dequeue(8).unwrap();
+        let _: Vec<BFieldElement> = verifier_ps.dequeue_length_prepended().unwrap();
+
+        // Prover and verifier agree, for any choice of transcript hasher
+        assert_eq!(
+            prover_ps.prover_fiat_shamir_with_hasher::<blake3::Hasher>(),
+            verifier_ps.verifier_fiat_shamir_with_hasher::<blake3::Hasher>(),
+        );
+        assert_eq!(
+            prover_ps.prover_fiat_shamir_with_hasher::<RescuePrimeRegular>(),
+            verifier_ps.verifier_fiat_shamir_with_hasher::<RescuePrimeRegular>(),
+        );
+        assert_eq!(
+            prover_ps.prover_fiat_shamir_tagged_with_hasher::<RescuePrimeRegular>(b"alpha"),
+            verifier_ps.verifier_fiat_shamir_tagged_with_hasher::<RescuePrimeRegular>(b"alpha"),
+        );
+
+        // Different hashers and different tags derive distinct challenges
+        assert_ne!(
+            prover_ps.prover_fiat_shamir_with_hasher::<blake3::Hasher>(),
+            prover_ps.prover_fiat_shamir_with_hasher::<RescuePrimeRegular>(),
+        );
+        assert_ne!(
+            prover_ps.prover_fiat_shamir_tagged_with_hasher::<RescuePrimeRegular>(b"alpha"),
+            prover_ps.prover_fiat_shamir_tagged_with_hasher::<RescuePrimeRegular>(b"beta"),
+        );
+    }
+
+    #[test]
+    fn ps_stats_test() {
+        let mut ps = ProofStream::default();
+        assert_eq!(ProofStreamStats::default(), ps.stats());
+
+        ps.enqueue_item(&ProofItem::MerkleRoot(Digest::default()))
+            .unwrap();
+        ps.enqueue_item(&ProofItem::Codeword(vec![
+            XFieldElement::new_const(
+                BFieldElement::new(1)
+            );
+            16
+        ]))
+        .unwrap();
+        ps.enqueue_item(&ProofItem::GrindingNonce(17)).unwrap();
+        ps.enqueue_length_prepended(&vec![0u8; 10]).unwrap();
+
+        let stats = ps.stats();
+        assert_eq!(ps.len(), stats.total_bytes);
+        assert_eq!(
+            stats.total_bytes,
+            stats.merkle_root_bytes
+                + stats.authentication_structure_bytes
+                + stats.codeword_bytes
+                + stats.misc_bytes
+        );
+        assert!(stats.codeword_bytes > stats.merkle_root_bytes);
+        assert_eq!(0, stats.authentication_structure_bytes);
+        assert!(stats.misc_bytes > 0);
+
+        // A stream restored from raw bytes has no type information
+        let restored: ProofStream = ps.serialize().into();
+        assert_eq!(ps.len(), restored.stats().misc_bytes);
+    }
+
+    #[test]
+    fn ps_limits_test() {
+        let mut ps = ProofStream::default();
+        for i in 0..4u64 {
+            ps.enqueue_length_prepended(&BFieldElement::new(i)).unwrap();
+        }
+        ps.enqueue_length_prepended(&vec![0u8; 100]).unwrap();
+
+        // Items within the limits read fine
+        ps.set_limits(ProofStreamLimits {
+            max_item_count: 4,
+            ..ProofStreamLimits::default()
+        });
+        for i in 0..3u64 {
+            let element: BFieldElement = ps.dequeue_length_prepended().unwrap();
+            assert_eq!(BFieldElement::new(i), element);
+        }
+
+        // The oversized item is rejected before it is deserialized
+        let mut oversized: ProofStream = ps.serialize().into();
+        oversized.set_limits(ProofStreamLimits {
+            max_item_size: 64,
+            ..ProofStreamLimits::default()
+        });
+        oversized.set_index(ps.get_read_index());
+        let _: BFieldElement = oversized.dequeue_length_prepended().unwrap();
+        let size_err = oversized.dequeue_length_prepended::<Vec<u8>>().unwrap_err();
+        assert_eq!(
+            ProofStreamError::ItemSizeLimitExceeded {
+                limit: 64,
+                actual: 108,
+            },
+            *size_err.downcast::<ProofStreamError>().unwrap()
+        );
+
+        // The item count limit caps how many dequeues a proof may demand
+        let _: BFieldElement = ps.dequeue_length_prepended().unwrap();
+        let count_err = ps.dequeue_length_prepended::<Vec<u8>>().unwrap_err();
+        assert_eq!(
+            ProofStreamError::ItemCountLimitExceeded { limit: 4 },
+            *count_err.downcast::<ProofStreamError>().unwrap()
+        );
+
+        // The total size limit rejects outright oversized proofs
+        let mut undersized: ProofStream = ps.serialize().into();
+        undersized.set_limits(ProofStreamLimits {
+            max_total_size: 16,
+            ..ProofStreamLimits::default()
+        });
+        let total_err = undersized
+            .dequeue_length_prepended::<BFieldElement>()
+            .unwrap_err();
+        assert!(matches!(
+            *total_err.downcast::<ProofStreamError>().unwrap(),
+            ProofStreamError::TotalSizeLimitExceeded { limit: 16, .. }
+        ));
+    }
+
+    #[test]
+    fn ps_tagged_fiat_shamir_test() {
+        let mut ps = ProofStream::default();
+        ps.enqueue_length_prepended(&BFieldElement::new(42))
+            .unwrap();
+
+        let untagged = ps.prover_fiat_shamir();
+        let tagged_a = ps.prover_fiat_shamir_tagged(b"protocol-a");
+        let tagged_b = ps.prover_fiat_shamir_tagged(b"protocol-b");
+        assert_ne!(tagged_a, tagged_b, "Different tags must separate domains");
+        assert_ne!(untagged, tagged_a, "A tag must change the digest");
+
+        // Once the verifier has read the same bytes, both sides agree
+        let _: BFieldElement = ps.dequeue_length_prepended().unwrap();
+        assert_eq!(tagged_a, ps.verifier_fiat_shamir_tagged(b"protocol-a"));
+    }
+
     #[test]
     fn ps_is_fifo_no_lifo() {
         let bfe1_before = BFieldElement::new(213);
@@ -4,7 +4,7 @@ use crate::shared_math::x_field_element::XFieldElement;
 use crate::util_types::blake3_wrapper::Blake3Hash;
 use itertools::Itertools;
 use num_traits::Zero;
-use rayon::iter::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
+use crate::parallel::prelude::*;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 